@@ -1,12 +1,15 @@
 #![allow(unused_crate_dependencies)]
 #![allow(missing_docs)]
+#![allow(clippy::unwrap_used)] // If a benchmark can't run, panicking is the only sensible option
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use hex_literal::hex;
 
 use cretrit::aes128v1::ore;
-use cretrit::SerializableCipherText;
+use cretrit::kbkdf::{KBKDFInit, CMACAES256, KBKDF};
+use cretrit::{CipherTextPool, PlainText, SerializableCipherText};
 
+/// The cipher that all the benchmarks share
 #[inline]
 fn create_ore_cipher() -> ore::Cipher<8, 256> {
     let k = hex!["adfd30251dfc5f6cfe240febf43970dd b1c8053580207d781d6d762d19177b01"];
@@ -14,49 +17,221 @@ fn create_ore_cipher() -> ore::Cipher<8, 256> {
     ore::Cipher::<8, 256>::new(black_box(&k)).unwrap()
 }
 
+/// Produce a full ciphertext of the given value
 #[inline]
 fn encrypt_u64(c: &ore::Cipher<8, 256>, u: u64) -> ore::CipherText<8, 256> {
     c.full_encrypt(&u.try_into().unwrap()).unwrap()
 }
 
+/// Produce a right-only ciphertext of the given value
+#[inline]
+fn right_encrypt_u64(c: &ore::Cipher<8, 256>, u: u64) -> ore::CipherText<8, 256> {
+    c.right_encrypt(&u.try_into().unwrap()).unwrap()
+}
+
+/// Produce a serialized right-only ciphertext of the given value, straight into a buffer
+#[inline]
+fn right_encrypt_u64_into(c: &ore::Cipher<8, 256>, u: u64, buf: &mut [u8]) -> usize {
+    c.right_encrypt_into(&u.try_into().unwrap(), buf).unwrap()
+}
+
+/// Turn a ciphertext into bytes
 fn serialise_ciphertext(ct: &ore::CipherText<8, 256>) -> Vec<u8> {
     ct.to_vec().unwrap()
 }
 
+/// Turn bytes back into a ciphertext
 fn deserialise_ciphertext(v: &[u8]) -> ore::CipherText<8, 256> {
     ore::CipherText::<8, 256>::from_slice(v).unwrap()
 }
 
+/// Turn bytes back into a ciphertext, reusing memory from the pool
+fn deserialise_pooled(v: &[u8], pool: &mut CipherTextPool) -> ore::CipherText<8, 256> {
+    ore::CipherText::<8, 256>::from_slice_pooled(v, pool).unwrap()
+}
+
+/// Compare two ciphertexts the way users would
 fn compare_ciphertexts(
     a: &ore::CipherText<8, 256>,
     b: &ore::CipherText<8, 256>,
 ) -> std::cmp::Ordering {
-    a.cmp(b)
+    a.try_cmp(b).unwrap()
 }
 
 pub fn benchmarks(c: &mut Criterion) {
-    c.bench_function("create ORE cipher", |b| b.iter(|| create_ore_cipher()));
+    c.bench_function("create ORE cipher", |b| b.iter(create_ore_cipher));
     c.bench_function("encrypt u64", |b| {
-        let c = create_ore_cipher();
-        b.iter(|| encrypt_u64(&c, 42))
+        let cipher = create_ore_cipher();
+        b.iter(|| encrypt_u64(&cipher, 42));
+    });
+    c.bench_function("right encrypt u64", |b| {
+        let cipher = create_ore_cipher();
+        b.iter(|| right_encrypt_u64(&cipher, 42));
+    });
+    c.bench_function("right encrypt u64 into buffer", |b| {
+        let cipher = create_ore_cipher();
+        let mut buf = vec![0u8; ore::Cipher::<8, 256>::max_right_serialized_len()];
+        b.iter(|| right_encrypt_u64_into(&cipher, 42, &mut buf));
     });
     c.bench_function("serialise", |b| {
-        let c = create_ore_cipher();
-        let ct = encrypt_u64(&c, 42);
-        b.iter(|| serialise_ciphertext(&ct))
+        let cipher = create_ore_cipher();
+        let ct = encrypt_u64(&cipher, 42);
+        b.iter(|| serialise_ciphertext(&ct));
     });
     c.bench_function("deserialise", |b| {
-        let c = create_ore_cipher();
-        let sct = serialise_ciphertext(&encrypt_u64(&c, 42));
-        b.iter(|| deserialise_ciphertext(&sct))
+        let cipher = create_ore_cipher();
+        let sct = serialise_ciphertext(&encrypt_u64(&cipher, 42));
+        b.iter(|| deserialise_ciphertext(&sct));
     });
     c.bench_function("compare", |b| {
-        let c = create_ore_cipher();
-        let ct1 = encrypt_u64(&c, 42);
-        let ct2 = encrypt_u64(&c, 420);
-        b.iter(|| compare_ciphertexts(&ct1, &ct2))
+        let cipher = create_ore_cipher();
+        let ct1 = encrypt_u64(&cipher, 42);
+        let ct2 = encrypt_u64(&cipher, 420);
+        b.iter(|| compare_ciphertexts(&ct1, &ct2));
+    });
+}
+
+/// Encrypt a bulk load's worth of values, one at a time and all at once
+fn batch_benchmarks(c: &mut Criterion) {
+    c.bench_function("right encrypt 100 u64s one at a time", |b| {
+        let cipher = create_ore_cipher();
+        b.iter(|| {
+            (0..100u64)
+                .map(|u| right_encrypt_u64(&cipher, u))
+                .collect::<Vec<_>>()
+        });
+    });
+    c.bench_function("right encrypt batch of 100 u64s", |b| {
+        let cipher = create_ore_cipher();
+        let values: Vec<PlainText<8, 256>> = (0..100u64).map(|u| u.try_into().unwrap()).collect();
+        b.iter(|| cipher.right_encrypt_batch(black_box(&values)).unwrap());
+    });
+}
+
+/// Compare one query against a whole table's worth of stored values, the way a scan would
+fn scan_benchmarks(c: &mut Criterion) {
+    let cipher = create_ore_cipher();
+    let stored: Vec<_> = (0..1000u64)
+        .map(|i| right_encrypt_u64(&cipher, i.wrapping_mul(7919)))
+        .collect();
+    let query = encrypt_u64(&cipher, 4_000_000);
+
+    c.bench_function("scan 1000", |b| {
+        b.iter(|| {
+            stored
+                .iter()
+                .filter(|s| compare_ciphertexts(black_box(&query), s).is_gt())
+                .count()
+        });
+    });
+
+    let prepared = ore::PreparedQuery::<8, 256>::new(query.left().unwrap()).unwrap();
+    c.bench_function("prepared scan 1000", |b| {
+        b.iter(|| {
+            stored
+                .iter()
+                .filter(|s| black_box(&prepared).compare_with_right(s).unwrap().is_gt())
+                .count()
+        });
+    });
+
+    // The same again, except that the stored values have to be parsed first, as they would be
+    // when they've come out of a database
+    let serialised: Vec<_> = stored.iter().map(serialise_ciphertext).collect();
+    c.bench_function("parse and scan 1000", |b| {
+        b.iter(|| {
+            serialised
+                .iter()
+                .filter(|v| {
+                    let s = deserialise_ciphertext(v);
+                    black_box(&prepared).compare_with_right(&s).unwrap().is_gt()
+                })
+                .count()
+        });
+    });
+
+    let mut pool = CipherTextPool::new();
+    c.bench_function("pooled parse and scan 1000", |b| {
+        b.iter(|| {
+            serialised
+                .iter()
+                .filter(|v| {
+                    let s = deserialise_pooled(v, &mut pool);
+                    let gt = black_box(&prepared).compare_with_right(&s).unwrap().is_gt();
+                    pool.recycle(s);
+                    gt
+                })
+                .count()
+        });
+    });
+}
+
+/// Encrypt, round-trip, and compare values laid out as `N` blocks of width `W`
+///
+/// Wider blocks mean fewer of them, and so less leakage per comparison, at the cost of bigger
+/// right ciphertexts that take longer to make.
+///
+fn block_width_benchmark<const N: usize, const W: u16>(c: &mut Criterion) {
+    let k = hex!["adfd30251dfc5f6cfe240febf43970dd b1c8053580207d781d6d762d19177b01"];
+    let cipher = ore::Cipher::<N, W>::new(&k).unwrap();
+    let value = PlainText::<N, W>::new([W.saturating_sub(1); N]);
+    let ct = cipher.full_encrypt(&value).unwrap();
+    let v = ct.to_vec().unwrap();
+
+    let mut group = c.benchmark_group(format!("{N} blocks of {W}"));
+    group.sample_size(10);
+    group.bench_function("encrypt", |b| {
+        b.iter(|| cipher.full_encrypt(black_box(&value)).unwrap());
+    });
+    group.bench_function("serialise", |b| b.iter(|| black_box(&ct).to_vec().unwrap()));
+    group.bench_function("deserialise", |b| {
+        b.iter(|| ore::CipherText::<N, W>::from_slice(black_box(&v)).unwrap());
+    });
+    group.bench_function("compare", |b| {
+        b.iter(|| black_box(&ct).try_cmp(&ct).unwrap());
+    });
+    group.finish();
+}
+
+/// Roughly 32 bits' worth of value, split up a few different ways
+fn block_width_benchmarks(c: &mut Criterion) {
+    block_width_benchmark::<4, 256>(c);
+    block_width_benchmark::<3, 4096>(c);
+    block_width_benchmark::<2, 0xFFFF>(c);
+}
+
+/// Derive a handful of subkeys one at a time, and all at once
+fn kdf_benchmarks(c: &mut Criterion) {
+    let kdf = CMACAES256::new(&[0u8; 32]).unwrap();
+    let ids: [&[u8]; 8] = [b"a", b"b", b"c", b"d", b"e", b"f", b"g", b"h"];
+
+    c.bench_function("derive 8 keys", |b| {
+        let mut keys = [[0u8; 32]; 8];
+        b.iter(|| {
+            for (k, id) in keys.iter_mut().zip(ids) {
+                black_box(&kdf).derive_key(k, id).unwrap();
+            }
+        });
+    });
+    c.bench_function("batch derive 8 keys", |b| {
+        let mut keys = [[0u8; 32]; 8];
+        b.iter(|| {
+            let mut batch: Vec<(&mut [u8], &[u8])> = keys
+                .iter_mut()
+                .zip(ids)
+                .map(|(k, id)| (k.as_mut_slice(), id))
+                .collect();
+            black_box(&kdf).derive_keys(&mut batch).unwrap();
+        });
     });
 }
 
-criterion_group!(benches, benchmarks);
+criterion_group!(
+    benches,
+    benchmarks,
+    scan_benchmarks,
+    batch_benchmarks,
+    block_width_benchmarks,
+    kdf_benchmarks
+);
 criterion_main!(benches);
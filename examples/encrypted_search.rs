@@ -0,0 +1,120 @@
+//! Encrypted search over an in-memory store.
+//!
+//! Each record has an account number, which only ever needs to be looked up exactly, and a
+//! balance, which gets range queries.  The store only ever sees right-only ciphertexts (plus a
+//! filter tag for the account number), so it learns nothing about either field until a query
+//! comes along; queries are made from left ciphertexts, which never get stored.
+//!
+//! Run it with `cargo run --example encrypted_search`.
+//!
+
+// Examples get all the dev-dependencies, whether they use them or not
+#![allow(unused_crate_dependencies)]
+// Printing things out is the whole point of an example
+#![allow(clippy::print_stdout)]
+
+use std::ops::Bound;
+
+use cretrit::aes128v1::{ere, ore};
+use cretrit::SerializableCipherText;
+
+/// What the store keeps for each record; everything in here is safe to hand to the store
+struct StoredRecord {
+    /// The record's (unencrypted) name, for showing results
+    name: &'static str,
+    /// The serialized right-only ERE ciphertext of the account number
+    account: Vec<u8>,
+    /// The filter tag for the account number, so that most records can be skipped cheaply
+    account_tag: u8,
+    /// The serialized right-only ORE ciphertext of the balance, in cents
+    balance: Vec<u8>,
+}
+
+/// A very simple, very trusting, datastore
+struct Store {
+    /// Everything that has been stored, in no particular order
+    records: Vec<StoredRecord>,
+}
+
+impl Store {
+    /// Find the names of the records whose account number matches the query
+    fn find_account(
+        &self,
+        query: &ere::LeftCipherText<8, 256>,
+    ) -> Result<Vec<&str>, cretrit::Error> {
+        let filter = ere::EqualityFilter::<8, 256>::new(query)?;
+        let prepared = ere::PreparedQuery::<8, 256>::new(query)?;
+        let mut found = vec![];
+
+        for record in &self.records {
+            // The filter rules out most records without deserializing them at all
+            if !filter.may_equal_serialized(&record.account, record.account_tag)? {
+                continue;
+            }
+
+            let account = ere::CipherText::<8, 256>::from_slice(&record.account)?;
+            if prepared.compare_with_right(&account)? {
+                found.push(record.name);
+            }
+        }
+
+        Ok(found)
+    }
+
+    /// Find the names of the records whose balance is within the query's range
+    fn find_balances(&self, query: &ore::RangeQuery<4, 256>) -> Result<Vec<&str>, cretrit::Error> {
+        let mut found = vec![];
+
+        for record in &self.records {
+            if query.contains(&ore::CipherText::<4, 256>::from_slice(&record.balance)?)? {
+                found.push(record.name);
+            }
+        }
+
+        Ok(found)
+    }
+}
+
+fn main() -> Result<(), cretrit::Error> {
+    // A real key comes from a KMS, or at least from a CSPRNG; see the crate documentation
+    let key = [42u8; 32];
+
+    // The writer and the querier both have the key; the store doesn't
+    let accounts = ere::Cipher::<8, 256>::new(&key)?;
+    let balances = ore::Cipher::<4, 256>::new(&key)?;
+
+    let mut store = Store { records: vec![] };
+
+    for (name, account, cents) in [
+        ("alice", 1_000_001u64, 125_000u32),
+        ("bob", 1_000_002, 1_799),
+        ("carol", 1_000_003, 98_000),
+        ("dave", 1_000_004, 300),
+    ] {
+        let (account_ct, account_tag) =
+            accounts.right_encrypt_with_filter_tag(&account.try_into()?)?;
+
+        store.records.push(StoredRecord {
+            name,
+            account: account_ct.to_vec()?,
+            account_tag,
+            balance: balances.right_encrypt(&cents.try_into()?)?.to_vec()?,
+        });
+    }
+
+    let owners = store.find_account(&accounts.left_encrypt(&1_000_003u64.try_into()?)?)?;
+    println!("account 1000003 belongs to {}", owners.join(", "));
+
+    let query = ore::RangeQuery::<4, 256>::new(
+        &balances,
+        Bound::Included(&1_000u32.try_into()?),
+        Bound::Excluded(&100_000u32.try_into()?),
+    )?;
+    let in_range = store.find_balances(&query)?;
+    println!(
+        "balances from $10 up to (but not including) $1000: {}",
+        in_range.join(", ")
+    );
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+//! Moving stored ciphertexts from an old key to a new one.
+//!
+//! Rotating keys means re-encrypting everything under the new key, which needs the plaintext.
+//! Ciphertexts with a left part can be decrypted by the cipher that made them, so as long as the
+//! stored values were kept as full ciphertexts somewhere trusted (such as an offline archive),
+//! they can be rotated, with [`rekey`](cretrit::rekey), without going back to the original data.  Each stored value records the
+//! [key check value](cretrit::Cipher::key_check_value) of the key it was encrypted with, so the
+//! rotation job can tell which values still need doing, and can be stopped and restarted safely.
+//!
+//! Run it with `cargo run --example key_rotation`.
+//!
+
+// Examples get all the dev-dependencies, whether they use them or not
+#![allow(unused_crate_dependencies)]
+// Printing things out is the whole point of an example
+#![allow(clippy::print_stdout)]
+
+use std::cmp::Ordering;
+
+use cretrit::aes128v1::ore;
+use cretrit::SerializableCipherText;
+
+/// A stored value, along with a note of which key it was encrypted with
+struct Stored {
+    /// The key check value of the key that encrypted `ciphertext`
+    kcv: [u8; 3],
+    /// A serialized full ciphertext
+    ciphertext: Vec<u8>,
+}
+
+/// Re-encrypt, under `new`, everything that was encrypted under `old`, returning how many values
+/// were rotated
+fn rotate(
+    store: &mut [Stored],
+    old: &ore::Cipher<8, 256>,
+    new: &ore::Cipher<8, 256>,
+) -> Result<usize, cretrit::Error> {
+    let mut rotated = 0usize;
+
+    for stored in store.iter_mut().filter(|s| s.kcv == old.key_check_value()) {
+        stored.ciphertext = cretrit::rekey(old, new, &stored.ciphertext)?;
+        stored.kcv = new.key_check_value();
+        rotated = rotated.saturating_add(1);
+    }
+
+    Ok(rotated)
+}
+
+fn main() -> Result<(), cretrit::Error> {
+    // Real keys come from a KMS, or at least from a CSPRNG; see the crate documentation
+    let old = ore::Cipher::<8, 256>::new(&[1u8; 32])?;
+    let new = ore::Cipher::<8, 256>::new(&[2u8; 32])?;
+
+    let mut store = [3u64, 1_000, 42, 9_001]
+        .into_iter()
+        .map(|v| {
+            Ok(Stored {
+                kcv: old.key_check_value(),
+                ciphertext: old.full_encrypt(&v.try_into()?)?.to_vec()?,
+            })
+        })
+        .collect::<Result<Vec<_>, cretrit::Error>>()?;
+
+    println!("rotated {} values", rotate(&mut store, &old, &new)?);
+    println!(
+        "rotated {} values the second time",
+        rotate(&mut store, &old, &new)?
+    );
+
+    // Queries made with the new key now work against everything in the store
+    let query = new.left_encrypt(&100u64.try_into()?)?;
+    let mut bigger = 0usize;
+
+    for stored in &store {
+        let ciphertext = ore::CipherText::<8, 256>::from_slice(&stored.ciphertext)?;
+        if query.compare_with_right(&ciphertext)? == Ordering::Less {
+            bigger = bigger.saturating_add(1);
+        }
+    }
+    println!("{bigger} stored values are bigger than 100");
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+//! Range queries over ciphertexts stored in Postgres.
+//!
+//! Postgres doesn't know how to compare cretrit ciphertexts, so on its own it can only hand
+//! back every row for the client to check.  To give the database something to work with, each
+//! row also stores which shard of a [`Partition`](cretrit::Partition) its value falls in.  A
+//! range query works out which shards its bounds are in, has the database return only the rows
+//! in those shards (which it can do with an ordinary index), and then checks the candidates
+//! properly.  The shard number reveals roughly where a value lies, so the fewer shards there are,
+//! the less it gives away, and the more rows each query has to check.
+//!
+//! Run it with `DATABASE_URL=postgres://... cargo run --example postgres`.  It only creates a
+//! temporary table, which goes away when the example finishes.
+//!
+
+// Examples get all the dev-dependencies, whether they use them or not
+#![allow(unused_crate_dependencies)]
+// Printing things out is the whole point of an example
+#![allow(clippy::print_stdout)]
+// The postgres crate's dependencies aren't ours to tidy up
+#![allow(clippy::multiple_crate_versions)]
+
+use std::error::Error;
+use std::ops::Bound;
+
+use cretrit::aes128v1::ore;
+use cretrit::SerializableCipherText;
+use postgres::{Client, NoTls};
+
+/// How many shards to divide the domain into
+const SHARDS: usize = 16;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        println!("set DATABASE_URL to the Postgres database to use");
+        return Ok(());
+    };
+
+    // A real key comes from a KMS, or at least from a CSPRNG; see the crate documentation
+    let cipher = ore::Cipher::<4, 256>::new(&[42u8; 32])?;
+
+    // The partition is made once, and its tokens handed to everything that writes rows
+    let tokens = ore::Partition::<4, 256>::uniform(&cipher, SHARDS)?.tokens()?;
+    let partition = ore::Partition::<4, 256>::from_tokens(&tokens)?;
+
+    let mut db = Client::connect(&url, NoTls)?;
+    db.batch_execute(
+        "CREATE TEMPORARY TABLE readings (
+            id SERIAL PRIMARY KEY,
+            value BYTEA NOT NULL,
+            shard INTEGER NOT NULL
+        );
+        CREATE INDEX ON readings (shard);",
+    )?;
+
+    for v in (0..1_000u32).map(|i| i.wrapping_mul(4_294_967)) {
+        let value = cipher.right_encrypt(&v.try_into()?)?;
+        let shard = i32::try_from(partition.shard_of(&value)?)?;
+
+        db.execute(
+            "INSERT INTO readings (value, shard) VALUES ($1, $2)",
+            &[&value.to_vec()?, &shard],
+        )?;
+    }
+
+    let (lower, upper) = (1_000_000_000u32, 1_300_000_000u32);
+    let query = ore::RangeQuery::<4, 256>::new(
+        &cipher,
+        Bound::Included(&lower.try_into()?),
+        Bound::Included(&upper.try_into()?),
+    )?;
+
+    // The shards that the bounds are in, and everything in between, are the only ones that can
+    // hold matching rows
+    let first_shard =
+        i32::try_from(partition.shard_of(&cipher.full_encrypt(&lower.try_into()?)?)?)?;
+    let last_shard = i32::try_from(partition.shard_of(&cipher.full_encrypt(&upper.try_into()?)?)?)?;
+
+    let candidates = db.query(
+        "SELECT id, value FROM readings WHERE shard BETWEEN $1 AND $2",
+        &[&first_shard, &last_shard],
+    )?;
+
+    let mut matches = vec![];
+    for row in &candidates {
+        let value: Vec<u8> = row.try_get("value")?;
+
+        if query.contains(&ore::CipherText::<4, 256>::from_slice(&value)?)? {
+            matches.push(row.try_get::<_, i32>("id")?);
+        }
+    }
+
+    println!(
+        "{} of {} candidate rows (from shards {first_shard} to {last_shard}) are in range",
+        matches.len(),
+        candidates.len(),
+    );
+
+    Ok(())
+}
@@ -26,8 +26,8 @@
 //! # }
 //! ```
 //!
-//! Comparing two encrypted ciphertexts is trivial, because Cretrit ciphertexts implement
-//! `Eq`:
+//! Comparing two encrypted ciphertexts is trivial, with [`try_eq()`](CipherText::try_eq).  With
+//! the (default) `comparison-traits` feature, `==` and `!=` work too.
 //!
 //! ```rust
 //! # use cretrit::aes128v1::ere;
@@ -39,8 +39,8 @@
 //! let forty_two = cipher.full_encrypt(&42u32.try_into()?)?;
 //! let over_nine_thousand = cipher.full_encrypt(&9001u32.try_into()?)?;
 //!
-//! assert!(forty_two == forty_two);
-//! assert!(forty_two != over_nine_thousand);
+//! assert!(forty_two.try_eq(&forty_two)?);
+//! assert!(!forty_two.try_eq(&over_nine_thousand)?);
 //! # Ok(())
 //! # }
 //! ```
@@ -78,44 +78,37 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! Matching just the leading part of values (a phone number's country code, say, or a date's year
+//! and month) can be done with [`prefix_matches()`](CipherText::prefix_matches), for "begins
+//! with" queries.  Bear in mind that a comparison reveals which blocks of the two values are
+//! equal, not just whether the leading ones are; there's nothing about a ciphertext that limits
+//! what can be learnt from it to prefixes.
+//!
+//! ```rust
+//! use cretrit::aes128v1::ere;
+//! use cretrit::PlainText;
+//!
+//! # fn main() -> Result<(), cretrit::Error> {
+//! # let key = [0u8; 32];
+//! let cipher = ere::Cipher::<12, 256>::new(&key)?;
+//! let sydney = cipher.right_encrypt(&PlainText::from_bytes("+61291234567", 0)?)?;
+//! let london = cipher.right_encrypt(&PlainText::from_bytes("+44201234567", 0)?)?;
+//!
+//! // The rest of the query value doesn't matter, as only the first three blocks are compared
+//! let australia = cipher.left_encrypt(&PlainText::from_bytes("+61", 0)?)?;
+//!
+//! assert!(australia.prefix_matches(&sydney, 3)?);
+//! assert!(!australia.prefix_matches(&london, 3)?);
+//! # Ok(())
+//! # }
+//! ```
 
-use super::CipherSuite;
-use crate::cipher::Cipher as C;
-use crate::ciphertext::CipherText as CT;
-use crate::cmp::EqualityCMP;
-
-/// [`Cipher`](crate::Cipher) specialisation for the [`aes128v1`](super) ciphersuite.
-///
-/// See the documentation for [`Cipher`](crate::Cipher) for usage information.
-///
-pub type Cipher<const N: usize, const W: u16> = C<CipherSuite<W, 2>, EqualityCMP, N, W, 2>;
-
-/// [`CipherText`](crate::ciphertext::CipherText) specialisation for the [`aes128v1`](super) ciphersuite.
-///
-/// See the documentation for [`CipherText`](crate::CipherText) for usage information.
-///
-pub type CipherText<const N: usize, const W: u16> = CT<CipherSuite<W, 2>, EqualityCMP, N, W, 2>;
-
-impl<const N: usize, const W: u16> PartialEq for CipherText<N, W> {
-    #[allow(clippy::panic, clippy::expect_used)] // No way to return error in impl Ord
-    fn eq(&self, other: &CipherText<N, W>) -> bool {
-        match self.left {
-            None => match other.left {
-                None => panic!("Neither ciphertext in comparison has a left component"),
-                Some(_) => other.eq(self),
-            },
-            Some(_) => EqualityCMP::invert(self.compare(other).expect("comparison failed"))
-                .expect("could not invert comparison value"),
-        }
-    }
-}
-
-impl<const N: usize, const W: u16> Eq for CipherText<N, W> {}
+crate::suite_modules::ere_module!("aes128v1");
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::PlainText;
     use rand::Rng;
 
     fn key() -> [u8; 32] {
@@ -130,6 +123,64 @@ mod tests {
         k
     }
 
+    #[test]
+    fn try_eq_reports_incomparable_ciphertexts() {
+        let cipher = Cipher::<4, 256>::new(&key()).unwrap();
+        let a = cipher.full_encrypt(&1u32.try_into().unwrap()).unwrap();
+        let b = cipher.right_encrypt(&1u32.try_into().unwrap()).unwrap();
+
+        assert!(a.try_eq(&b).unwrap());
+        assert!(b.try_eq(&a).unwrap());
+        assert!(matches!(b.try_eq(&b), Err(Error::Incomparable { .. })));
+        #[cfg(feature = "comparison-traits")]
+        assert!(b != b);
+    }
+
+    #[test]
+    fn date_prefixes() {
+        let cipher = Cipher::<3, 256>::new(&key()).unwrap();
+
+        // Years since 2000, month, and day
+        let stored = cipher.right_encrypt(&PlainText::new([24, 3, 17])).unwrap();
+        let same_month = cipher.full_encrypt(&PlainText::new([24, 3, 1])).unwrap();
+        let same_day_next_year = cipher.full_encrypt(&PlainText::new([25, 3, 17])).unwrap();
+
+        assert!(same_month.prefix_matches(&stored, 0).unwrap());
+        assert!(same_month.prefix_matches(&stored, 1).unwrap());
+        assert!(same_month.prefix_matches(&stored, 2).unwrap());
+        assert!(!same_month.prefix_matches(&stored, 3).unwrap());
+        assert!(!same_day_next_year.prefix_matches(&stored, 1).unwrap());
+        assert!(!same_day_next_year.prefix_matches(&stored, 3).unwrap());
+
+        // It doesn't matter which side the left part is on
+        assert!(stored.prefix_matches(&same_month, 2).unwrap());
+    }
+
+    #[test]
+    fn prefix_longer_than_value() {
+        let cipher = Cipher::<3, 256>::new(&key()).unwrap();
+
+        let a = cipher.full_encrypt(&PlainText::new([1, 2, 3])).unwrap();
+
+        assert!(matches!(a.prefix_matches(&a, 4), Err(Error::RangeError(_))));
+        assert!(matches!(
+            a.left().unwrap().prefix_matches(&a, 4),
+            Err(Error::RangeError(_))
+        ));
+    }
+
+    #[test]
+    fn right_only_prefixes_are_incomparable() {
+        let cipher = Cipher::<3, 256>::new(&key()).unwrap();
+
+        let a = cipher.right_encrypt(&PlainText::new([1, 2, 3])).unwrap();
+
+        assert!(matches!(
+            a.prefix_matches(&a, 1),
+            Err(Error::Incomparable { .. })
+        ));
+    }
+
     #[test]
     fn tiny_self_equality() {
         let cipher = Cipher::<1, 4>::new(&key()).unwrap();
@@ -225,7 +276,118 @@ mod tests {
         assert_eq!(1, n2.compare(&n1).unwrap());
     }
 
+    #[test]
+    fn left_ciphertext_as_query_token() {
+        let cipher = Cipher::<4, 256>::new(&key()).unwrap();
+        let stored = cipher.right_encrypt(&9001u32.try_into().unwrap()).unwrap();
+
+        let token = |v: u32| {
+            LeftCipherText::<4, 256>::from_slice(
+                &cipher
+                    .full_encrypt(&v.try_into().unwrap())
+                    .unwrap()
+                    .left()
+                    .unwrap()
+                    .to_vec()
+                    .unwrap(),
+            )
+            .unwrap()
+        };
+
+        assert!(token(9001).compare_with_right(&stored).unwrap());
+        assert!(!token(42).compare_with_right(&stored).unwrap());
+    }
+
+    #[test]
+    fn serialized_comparison_through_trait_object() {
+        let encryptor: Box<dyn ComparisonEncryptor<4, 256, Comparison = bool>> =
+            Box::new(Cipher::<4, 256>::new(&key()).unwrap());
+
+        let n1 = encryptor
+            .full_encrypt_to_vec(&42u32.try_into().unwrap())
+            .unwrap();
+        let n2 = encryptor
+            .right_encrypt_to_vec(&9001u32.try_into().unwrap())
+            .unwrap();
+        let n3 = encryptor
+            .right_encrypt_to_vec(&42u32.try_into().unwrap())
+            .unwrap();
+
+        assert!(!encryptor.compare_serialized(&n1, &n2).unwrap());
+        assert!(!encryptor.compare_serialized(&n2, &n1).unwrap());
+        assert!(encryptor.compare_serialized(&n1, &n3).unwrap());
+        assert!(encryptor.compare_serialized(&n3, &n1).unwrap());
+    }
+
+    #[test]
+    fn query_keys() {
+        let k = key();
+        let c1 = Cipher::<4, 256>::new(&k).unwrap();
+        let c2 = Cipher::<4, 256>::new(&k).unwrap();
+        let other = Cipher::<4, 256>::new(&key()).unwrap();
+
+        let q = c1.query_key(&42u32.try_into().unwrap()).unwrap();
+
+        assert_eq!(q, c1.query_key(&42u32.try_into().unwrap()).unwrap());
+        assert_eq!(q, c2.query_key(&42u32.try_into().unwrap()).unwrap());
+        assert_ne!(q, c1.query_key(&43u32.try_into().unwrap()).unwrap());
+        assert_ne!(q, other.query_key(&42u32.try_into().unwrap()).unwrap());
+    }
+
+    #[test]
+    fn query_keys_are_not_left_ciphertexts() {
+        let cipher = Cipher::<1, 256>::new(&key()).unwrap();
+        let value = 42u8.try_into().unwrap();
+        let left = cipher.full_encrypt(&value).unwrap().to_vec().unwrap();
+        let q = cipher.query_key(&value).unwrap();
+
+        assert!(!left.windows(q.len()).any(|w| w == q));
+    }
+
+    #[cfg(feature = "test-vectors")]
+    #[test]
+    fn streamed_right_encryption_is_byte_identical() {
+        let k = key();
+        let c1 = Cipher::<5, 16>::new_with_insecure_seed(&k, 42).unwrap();
+        let c2 = Cipher::<5, 16>::new_with_insecure_seed(&k, 42).unwrap();
+        let mut buf = vec![0u8; Cipher::<5, 16>::max_right_serialized_len()];
+
+        for v in [0u32, 1, 0x12345, 0xFFFFF] {
+            let value: PlainText<5, 16> = v.try_into().unwrap();
+            let len = c2.right_encrypt_into(&value, &mut buf).unwrap();
+
+            assert_eq!(
+                c1.right_encrypt(&value).unwrap().to_vec().unwrap(),
+                &buf[..len]
+            );
+        }
+    }
+
     quickcheck! {
+        fn streamed_right_encryption(a: u32, b: u32) -> bool {
+            let cipher = Cipher::<4, 256>::new(&key()).unwrap();
+            let mut buf = vec![0u8; Cipher::<4, 256>::max_right_serialized_len()];
+
+            let len = cipher.right_encrypt_into(&a.try_into().unwrap(), &mut buf).unwrap();
+            let stored = CipherText::<4, 256>::from_slice(&buf[..len]).unwrap();
+            let query = cipher.full_encrypt(&b.try_into().unwrap()).unwrap();
+
+            (a == b) == query.try_eq(&stored).unwrap()
+        }
+        fn prefix_matches_plaintext_prefix(a: u32, b: u32, blocks: u8) -> bool {
+            let cipher = Cipher::<4, 256>::new(&key()).unwrap();
+            let blocks = usize::from(blocks % 5);
+
+            let pa: PlainText<4, 256> = a.try_into().unwrap();
+            let pb: PlainText<4, 256> = b.try_into().unwrap();
+            let expected = pa.blocks()[..blocks] == pb.blocks()[..blocks];
+
+            let left = cipher.left_encrypt(&pa).unwrap();
+            let right = cipher.right_encrypt(&pb).unwrap();
+
+            left.prefix_matches(&right, blocks).unwrap() == expected
+        }
+
         fn u64_compare(a: u64, b: u64) -> bool {
             let cipher = Cipher::<8, 256>::new(&key()).unwrap();
 
@@ -258,11 +420,7 @@ mod tests {
             let ca = cipher.full_encrypt(&a.try_into().unwrap()).unwrap();
             let cb = cipher.full_encrypt(&b.try_into().unwrap()).unwrap();
 
-            if a == b {
-                ca == cb
-            } else {
-                ca != cb
-            }
+            (a == b) == ca.try_eq(&cb).unwrap()
         }
 
         fn u32_eq(a: u32, b: u32) -> bool {
@@ -271,11 +429,7 @@ mod tests {
             let ca = cipher.full_encrypt(&a.try_into().unwrap()).unwrap();
             let cb = cipher.full_encrypt(&b.try_into().unwrap()).unwrap();
 
-            if a == b {
-                ca == cb
-            } else {
-                ca != cb
-            }
+            (a == b) == ca.try_eq(&cb).unwrap()
         }
     }
 }
@@ -1,8 +1,8 @@
 //! Comparison-Revealing Encryption using AES128 as the Pseudo-Random Function and Hash Function.
 //!
-//! The module provides two comparison functions, one for orderable ciphertexts (in the [`ore`]
-//! module) and one for ciphertexts that only have to be compared for equality (in the [`ere`]
-//! module).
+//! The module provides three comparison functions, one for orderable ciphertexts (in the [`ore`]
+//! module), one for ciphertexts that only have to be compared for equality (in the [`ere`]
+//! module), and one for values that are only partially ordered (in the [`pore`] module).
 //!
 //! Order-revealing encryption (ORE) is more versatile, but produces ciphertexts which are around
 //! 60% larger than those produced by equality-revealing encryption (ERE).  Thus, if you know you
@@ -10,10 +10,11 @@
 
 pub mod ere;
 pub mod ore;
+pub mod pore;
 
 use rand_chacha::ChaCha20Rng;
 
-use crate::ciphersuite::CipherSuite as SuperSweet;
+use crate::ciphersuite::{CipherSuite as SuperSweet, ListedCipherSuite};
 use crate::{hash, kbkdf, prf, prp};
 
 /// The full set of parameters that make up the [`aes128v1`](super) ciphersuite.
@@ -28,9 +29,13 @@ use crate::{hash, kbkdf, prf, prp};
 pub struct CipherSuite<const W: u16, const M: u8> {}
 
 impl<const W: u16, const M: u8> SuperSweet<W, M> for CipherSuite<W, M> {
+    const NAME: &'static str = "aes128v1";
+
     type RNG = ChaCha20Rng;
     type PRF = prf::AES128PRF;
     type HF = hash::CMACAES128HF<M>;
     type PRP = prp::RandShufflePRP<W>;
     type KBKDF = kbkdf::CMACAES256;
 }
+
+impl<const W: u16, const M: u8> ListedCipherSuite<W, M> for CipherSuite<W, M> {}
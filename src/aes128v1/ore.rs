@@ -25,11 +25,13 @@
 //! # }
 //! ```
 //!
-//! Comparing two encrypted ciphertexts is trivial, because Cretrit ciphertexts implement
-//! `Eq`, `Ord`, etc as appropriate:
+//! Comparing two encrypted ciphertexts is trivial, with [`try_cmp()`](CipherText::try_cmp) and
+//! [`try_eq()`](CipherText::try_eq).  With the (default) `comparison-traits` feature, the usual
+//! comparison operators work too.
 //!
 //! ```rust
 //! # use cretrit::aes128v1::ore;
+//! use std::cmp::Ordering;
 //! #
 //! # fn main() -> Result<(), cretrit::Error> {
 //! # let key = [0u8; 32];
@@ -38,9 +40,9 @@
 //! let forty_two = cipher.full_encrypt(&42u32.try_into()?)?;
 //! let over_nine_thousand = cipher.full_encrypt(&9001u32.try_into()?)?;
 //!
-//! assert!(forty_two == forty_two);
-//! assert!(forty_two != over_nine_thousand);
-//! assert!(forty_two < over_nine_thousand);
+//! assert!(forty_two.try_eq(&forty_two)?);
+//! assert!(!forty_two.try_eq(&over_nine_thousand)?);
+//! assert_eq!(Ordering::Less, forty_two.try_cmp(&over_nine_thousand)?);
 //! # Ok(())
 //! # }
 //! ```
@@ -80,64 +82,13 @@
 //! # Ok(())
 //! # }
 //! ```
-use std::cmp::Ordering;
-
-use super::CipherSuite;
-use crate::cipher::Cipher as C;
-use crate::ciphertext::CipherText as CT;
-use crate::cmp::OrderingCMP;
-
-/// [`Cipher`](crate::Cipher) specialisation for the [`aes128v1`](super) ciphersuite.
-///
-/// See the documentation for [`Cipher`](crate::Cipher) for usage information.
-///
-pub type Cipher<const N: usize, const W: u16> = C<CipherSuite<W, 3>, OrderingCMP, N, W, 3>;
-
-/// [`CipherText`](crate::ciphertext::CipherText) specialisation for the [`aes128v1`](super) ciphersuite.
-///
-/// See the documentation for [`CipherText`](crate::CipherText) for usage information.
-///
-pub type CipherText<const N: usize, const W: u16> = CT<CipherSuite<W, 3>, OrderingCMP, N, W, 3>;
-
-impl<const N: usize, const W: u16> Ord for CipherText<N, W> {
-    fn cmp(&self, other: &CipherText<N, W>) -> Ordering {
-        match self.left {
-            None => match other.left {
-                #[allow(clippy::panic)] // No way to return an error when implementing Ord
-                None => panic!("Neither ciphertext in comparison has a left component"),
-                Some(_) => match other.cmp(self) {
-                    Ordering::Equal => Ordering::Equal,
-                    Ordering::Less => Ordering::Greater,
-                    Ordering::Greater => Ordering::Less,
-                },
-            },
-            #[allow(clippy::expect_used)] // No way to return an error when implementing Ord
-            Some(_) => OrderingCMP::invert(self.compare(other).expect("comparison failed"))
-                .expect("could not invert comparison value"),
-        }
-    }
-}
-
-impl<const N: usize, const W: u16> PartialOrd for CipherText<N, W> {
-    fn partial_cmp(&self, other: &CipherText<N, W>) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
 
-impl<const N: usize, const W: u16> PartialEq for CipherText<N, W> {
-    fn eq(&self, other: &CipherText<N, W>) -> bool {
-        self.cmp(other) == Ordering::Equal
-    }
-}
-
-impl<const N: usize, const W: u16> Eq for CipherText<N, W> {}
+crate::suite_modules::ore_module!("aes128v1");
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::PlainText;
     use rand::Rng;
-    use std::cmp::Ordering;
 
     fn key() -> [u8; 32] {
         let mut k: [u8; 32] = Default::default();
@@ -246,7 +197,883 @@ mod tests {
         assert_eq!(2, n2.compare(&n1).unwrap());
     }
 
+    #[test]
+    fn serialized_comparison_through_trait_object() {
+        let encryptor: Box<dyn ComparisonEncryptor<4, 256, Comparison = Ordering>> =
+            Box::new(Cipher::<4, 256>::new(&key()).unwrap());
+
+        let n1 = encryptor
+            .full_encrypt_to_vec(&42u32.try_into().unwrap())
+            .unwrap();
+        let n2 = encryptor
+            .right_encrypt_to_vec(&9001u32.try_into().unwrap())
+            .unwrap();
+
+        assert_eq!(
+            Ordering::Less,
+            encryptor.compare_serialized(&n1, &n2).unwrap()
+        );
+        assert_eq!(
+            Ordering::Greater,
+            encryptor.compare_serialized(&n2, &n1).unwrap()
+        );
+        assert_eq!(
+            Ordering::Equal,
+            encryptor.compare_serialized(&n1, &n1).unwrap()
+        );
+    }
+
+    #[test]
+    fn serialized_comparison_of_right_ciphertexts_fails() {
+        let cipher = Cipher::<4, 256>::new(&key()).unwrap();
+
+        let n1 = cipher
+            .right_encrypt_to_vec(&42u32.try_into().unwrap())
+            .unwrap();
+        let n2 = cipher
+            .right_encrypt_to_vec(&9001u32.try_into().unwrap())
+            .unwrap();
+
+        assert!(cipher.compare_serialized(&n1, &n2).is_err());
+    }
+
+    #[test]
+    fn left_ciphertext_as_query_token() {
+        let cipher = Cipher::<4, 256>::new(&key()).unwrap();
+        let stored = cipher.right_encrypt(&9001u32.try_into().unwrap()).unwrap();
+
+        assert!(stored.left().is_none());
+
+        for (v, expected) in [
+            (42u32, Ordering::Less),
+            (9001, Ordering::Equal),
+            (31_337, Ordering::Greater),
+        ] {
+            let query = cipher.full_encrypt(&v.try_into().unwrap()).unwrap();
+            let token = query.left().unwrap().to_vec().unwrap();
+            let left = LeftCipherText::<4, 256>::from_slice(&token).unwrap();
+
+            assert_eq!(4, left.block_count());
+            assert_eq!(expected, left.compare_with_right(&stored).unwrap());
+            assert_eq!(
+                query.try_cmp(&stored).unwrap(),
+                left.compare_with_right(&stored).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn left_ciphertext_serialized_len() {
+        let value = 42u32.try_into().unwrap();
+
+        assert_eq!(
+            LeftCipherText::<4, 256>::serialized_len(),
+            Cipher::<4, 256>::new(&key())
+                .unwrap()
+                .full_encrypt(&value)
+                .unwrap()
+                .left()
+                .unwrap()
+                .to_vec()
+                .unwrap()
+                .len()
+        );
+        assert_eq!(
+            LeftCipherText::<2, 1024>::serialized_len(),
+            Cipher::<2, 1024>::new(&key())
+                .unwrap()
+                .full_encrypt(&1000u16.try_into().unwrap())
+                .unwrap()
+                .left()
+                .unwrap()
+                .to_vec()
+                .unwrap()
+                .len()
+        );
+    }
+
+    #[test]
+    fn left_ciphertext_concat_and_split() {
+        let k = key();
+        let cipher_64 = Cipher::<8, 256>::new(&k).unwrap();
+        let cipher_32 = Cipher::<4, 256>::new(&k).unwrap();
+        let cipher_96 = Cipher::<12, 256>::new(&k).unwrap();
+
+        let hi = cipher_64.full_encrypt(&42u64.try_into().unwrap()).unwrap();
+        let lo = cipher_32
+            .full_encrypt(&9001u32.try_into().unwrap())
+            .unwrap();
+        let whole = cipher_96
+            .full_encrypt(&(42u128 << 32).saturating_add(9001).try_into().unwrap())
+            .unwrap();
+
+        let joined =
+            LeftCipherText::<12, 256>::concat(hi.left().unwrap(), lo.left().unwrap()).unwrap();
+        assert!(joined.same_as(whole.left().unwrap()));
+
+        let (split_hi, split_lo) = whole.left().unwrap().split::<8, 4>().unwrap();
+        assert!(split_hi.same_as(hi.left().unwrap()));
+        assert!(split_lo.same_as(lo.left().unwrap()));
+
+        for v in [0u32, 9000, 9001, 9002, u32::MAX] {
+            let r = cipher_32.right_encrypt(&v.try_into().unwrap()).unwrap();
+            assert_eq!(9001.cmp(&v), split_lo.compare_with_right(&r).unwrap());
+        }
+    }
+
+    #[test]
+    fn left_ciphertext_concat_and_split_check_sizes() {
+        let cipher = Cipher::<4, 256>::new(&key()).unwrap();
+        let ct = cipher.full_encrypt(&42u32.try_into().unwrap()).unwrap();
+        let left = ct.left().unwrap();
+
+        assert!(LeftCipherText::<7, 256>::concat(left, left).is_err());
+        assert!(LeftCipherText::<9, 256>::concat(left, left).is_err());
+        assert!(left.split::<2, 1>().is_err());
+        assert!(left.split::<3, 3>().is_err());
+        assert!(left.split::<0, 4>().is_ok());
+    }
+
+    #[test]
+    fn mismatched_parameters_are_detected() {
+        let cipher = Cipher::<8, 256>::new(&key()).unwrap();
+        // Every block different, so that each p(x) is different, too
+        let ct = cipher
+            .full_encrypt(&0x0102_0304_0506_0708u64.try_into().unwrap())
+            .unwrap();
+        let left = ct.left().unwrap().to_vec().unwrap();
+
+        // Wrong block count
+        assert!(matches!(
+            CipherText::<4, 256>::from_slice(&ct.to_vec().unwrap()),
+            Err(Error::ParamsError(_))
+        ));
+        assert!(matches!(
+            LeftCipherText::<4, 256>::from_slice(&left),
+            Err(Error::ParamsError(_))
+        ));
+
+        // Wrong block width, but the same size of left ciphertext; there's only about a 1 in 2^32
+        // chance that all of the p(x) are small enough to get past the check
+        assert!(matches!(
+            LeftCipherText::<8, 16>::from_slice(&left),
+            Err(Error::ParamsError(_))
+        ));
+
+        // Trailing junk
+        let mut long = left.clone();
+        long.push(0);
+        assert!(LeftCipherText::<8, 256>::from_slice(&long).is_err());
+        assert!(LeftCipherText::<8, 256>::from_slice(&left).is_ok());
+    }
+
+    #[test]
+    fn mismatched_keys_are_detected() {
+        let cipher = Cipher::<4, 256>::new(&key()).unwrap();
+        let other = Cipher::<4, 256>::new(&key()).unwrap();
+        let query = cipher.full_encrypt(&1u32.try_into().unwrap()).unwrap();
+        let stored = other.right_encrypt(&2u32.try_into().unwrap()).unwrap();
+
+        assert!(matches!(query.try_cmp(&stored), Err(Error::ParamsError(_))));
+        assert!(matches!(stored.try_eq(&query), Err(Error::ParamsError(_))));
+        assert!(matches!(
+            query.left().unwrap().compare_with_right(&stored),
+            Err(Error::ParamsError(_))
+        ));
+        assert!(matches!(
+            PreparedQuery::new(query.left().unwrap())
+                .unwrap()
+                .with_cache(4)
+                .compare_with_right(&stored),
+            Err(Error::ParamsError(_))
+        ));
+
+        // A header records the key, too
+        let headered = CipherText::<4, 256>::from_slice_with_header(
+            &stored.to_vec_with_header(other.key_check_value()).unwrap(),
+            other.key_check_value(),
+        )
+        .unwrap();
+        assert!(matches!(
+            query.try_cmp(&headered),
+            Err(Error::ParamsError(_))
+        ));
+
+        // Without one, there's nothing to go on, so the comparison goes ahead
+        let headerless = CipherText::<4, 256>::from_slice(&stored.to_vec().unwrap()).unwrap();
+        assert!(query.try_cmp(&headerless).is_ok());
+    }
+
+    #[test]
+    fn tri_state_serialized_comparison() {
+        let cipher = Cipher::<4, 256>::new(&key()).unwrap();
+
+        let n1 = cipher
+            .full_encrypt_to_vec(&42u32.try_into().unwrap())
+            .unwrap();
+        let n2 = cipher
+            .right_encrypt_to_vec(&9001u32.try_into().unwrap())
+            .unwrap();
+        let n3 = cipher
+            .right_encrypt_to_vec(&42u32.try_into().unwrap())
+            .unwrap();
+
+        assert_eq!(-1, cipher.compare_serialized_i32(&n1, &n2).unwrap());
+        assert_eq!(1, cipher.compare_serialized_i32(&n2, &n1).unwrap());
+        assert_eq!(0, cipher.compare_serialized_i32(&n1, &n3).unwrap());
+        assert_eq!(0, cipher.compare_serialized_i32(&n3, &n1).unwrap());
+
+        assert!(matches!(
+            cipher.compare_serialized_i32(&n2, &n3),
+            Err(Error::Incomparable { .. })
+        ));
+        assert!(matches!(
+            cipher.compare_serialized_i32(&n1, &[1, 0]),
+            Err(Error::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn right_only_cipher_cannot_make_lefts() {
+        let k = key();
+        let cipher = Cipher::<4, 256>::new_right_only(&k).unwrap();
+        let value = 42u32.try_into().unwrap();
+
+        assert!(cipher.is_right_only());
+        assert!(matches!(
+            cipher.full_encrypt(&value),
+            Err(Error::NotPermitted(_))
+        ));
+        assert!(cipher.full_encrypt_to_vec(&value).is_err());
+        assert!(matches!(
+            cipher.query_key(&value),
+            Err(Error::NotPermitted(_))
+        ));
+        assert_eq!(
+            Cipher::<4, 256>::new(&k).unwrap().key_check_value(),
+            cipher.key_check_value()
+        );
+        assert!(matches!(
+            cipher.left_encrypt(&value),
+            Err(Error::NotPermitted(_))
+        ));
+        assert!(!Cipher::<4, 256>::new(&k).unwrap().is_right_only());
+    }
+
+    #[test]
+    fn left_only_cipher_cannot_make_rights() {
+        let k = key();
+        let cipher = Cipher::<4, 256>::new_left_only(&k).unwrap();
+        let value: PlainText<4, 256> = 42u32.try_into().unwrap();
+
+        assert!(cipher.is_left_only());
+        assert!(!cipher.is_right_only());
+        assert!(matches!(
+            cipher.right_encrypt(&value),
+            Err(Error::NotPermitted(_))
+        ));
+        assert!(matches!(
+            cipher.full_encrypt(&value),
+            Err(Error::NotPermitted(_))
+        ));
+        assert!(cipher
+            .right_encrypt_into(
+                &value,
+                &mut vec![0u8; Cipher::<4, 256>::max_right_serialized_len()]
+            )
+            .is_err());
+        assert!(cipher.right_encrypt_with_filter_tag(&value).is_err());
+        assert!(!Cipher::<4, 256>::new(&k).unwrap().is_left_only());
+    }
+
+    #[test]
+    fn left_only_cipher_can_query() {
+        let k = key();
+        let client = Cipher::<4, 256>::new_left_only(&k).unwrap();
+        let storage = Cipher::<4, 256>::new_right_only(&k).unwrap();
+
+        let stored = storage.right_encrypt(&9001u32.try_into().unwrap()).unwrap();
+        let query = client.left_encrypt(&42u32.try_into().unwrap()).unwrap();
+
+        assert_eq!(Ordering::Less, query.compare_with_right(&stored).unwrap());
+        assert_eq!(
+            Cipher::<4, 256>::new(&k)
+                .unwrap()
+                .query_key(&42u32.try_into().unwrap())
+                .unwrap(),
+            client.query_key(&42u32.try_into().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn left_only_encryption() {
+        let cipher = Cipher::<4, 256>::new(&key()).unwrap();
+        let value: PlainText<4, 256> = 42u32.try_into().unwrap();
+        let left = cipher.left_encrypt(&value).unwrap();
+
+        assert_eq!(
+            cipher
+                .full_encrypt(&value)
+                .unwrap()
+                .left()
+                .unwrap()
+                .to_vec()
+                .unwrap(),
+            left.to_vec().unwrap()
+        );
+        for (v, expected) in [
+            (41u32, Ordering::Greater),
+            (42, Ordering::Equal),
+            (43, Ordering::Less),
+        ] {
+            let stored = cipher.right_encrypt(&v.try_into().unwrap()).unwrap();
+            assert_eq!(expected, left.compare_with_right(&stored).unwrap());
+        }
+    }
+
+    #[test]
+    fn right_only_cipher_can_still_compare() {
+        let k = key();
+        let client = Cipher::<4, 256>::new(&k).unwrap();
+        let storage = Cipher::<4, 256>::new_right_only(&k).unwrap();
+
+        let stored = storage
+            .right_encrypt_to_vec(&9001u32.try_into().unwrap())
+            .unwrap();
+        let query = client
+            .full_encrypt_to_vec(&42u32.try_into().unwrap())
+            .unwrap();
+
+        assert_eq!(
+            Ordering::Less,
+            storage.compare_serialized(&query, &stored).unwrap()
+        );
+    }
+
+    /// Encrypt a value with a cipher that only lives as long as this function does
+    fn encrypt_with_local_cipher(k: &[u8; 32], v: u32) -> CipherText<4, 256> {
+        let cipher = Cipher::<4, 256>::new(k).unwrap();
+
+        cipher.full_encrypt(&v.try_into().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn ciphertexts_outlive_their_cipher() {
+        fn owned<T: 'static>(t: T) -> T {
+            t
+        }
+
+        let k = key();
+        let stored = owned(vec![
+            encrypt_with_local_cipher(&k, 42),
+            encrypt_with_local_cipher(&k, 9001),
+        ]);
+
+        assert_eq!(Ordering::Less, stored[0].try_cmp(&stored[1]).unwrap());
+    }
+
+    #[test]
+    fn key_check_values() {
+        let k = key();
+        let cipher = Cipher::<4, 256>::new(&k).unwrap();
+
+        assert_eq!(
+            cipher.key_check_value(),
+            Cipher::<4, 256>::new(&k).unwrap().key_check_value()
+        );
+        assert_eq!(
+            cipher.key_check_value(),
+            Cipher::<8, 16>::new_right_only(&k)
+                .unwrap()
+                .key_check_value()
+        );
+        assert_eq!(
+            cipher.key_check_value(),
+            crate::aes128v1::ere::Cipher::<4, 256>::new(&k)
+                .unwrap()
+                .key_check_value()
+        );
+
+        assert_ne!(
+            Cipher::<4, 256>::new(&[0u8; 32]).unwrap().key_check_value(),
+            Cipher::<4, 256>::new(&[1u8; 32]).unwrap().key_check_value()
+        );
+    }
+
+    #[test]
+    fn random_bytes() {
+        let cipher = Cipher::<4, 256>::new(&key()).unwrap();
+
+        let mut b1 = [0u8; 32];
+        let mut b2 = [0u8; 32];
+        cipher.random_bytes(&mut b1).unwrap();
+        cipher.random_bytes(&mut b2).unwrap();
+
+        assert_ne!([0u8; 32], b1);
+        assert_ne!(b1, b2);
+    }
+
+    /// Long-running check that nonce bases don't repeat; run with `cargo test -- --ignored`
+    #[test]
+    #[ignore = "takes a long time"]
+    fn nonce_bases_are_unique_soak() {
+        let cipher = Cipher::<1, 4>::new(&key()).unwrap();
+        let value: PlainText<1, 4> = 1u8.try_into().unwrap();
+        let mut seen = std::collections::HashSet::new();
+
+        for i in 0..2_000_000 {
+            let ct = cipher.right_encrypt(&value).unwrap();
+            assert!(
+                seen.insert(*ct.right.nonce_base()),
+                "nonce base repeated after {i} encryptions"
+            );
+        }
+    }
+
+    #[cfg(all(feature = "nonce-tracking", feature = "test-vectors"))]
+    #[test]
+    fn repeated_nonce_bases_are_caught() {
+        let k = key();
+        let value: PlainText<4, 256> = 42u32.try_into().unwrap();
+
+        let c1 = Cipher::<4, 256>::new_with_insecure_seed(&k, 42).unwrap();
+        let c2 = Cipher::<4, 256>::new_with_insecure_seed(&k, 42).unwrap();
+
+        // Two ciphers seeded the same will produce the same nonces, so if c2 knows about c1's
+        // nonces, it'll catch its own first nonce as a repeat
+        let first = c1.right_encrypt(&value).unwrap();
+        c2.seen_nonces
+            .borrow_mut()
+            .insert(*first.right.nonce_base());
+
+        assert!(matches!(
+            c2.right_encrypt(&value),
+            Err(Error::CryptoError(_))
+        ));
+    }
+
+    #[test]
+    fn ciphers_with_the_same_rng_seed_are_reproducible() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha20Rng;
+
+        let k = key();
+        let value: PlainText<4, 256> = 9001u32.try_into().unwrap();
+
+        let c1 = Cipher::<4, 256>::new_with_rng(&k, &mut ChaCha20Rng::seed_from_u64(42)).unwrap();
+        let c2 = Cipher::<4, 256>::new_with_rng(&k, &mut ChaCha20Rng::seed_from_u64(42)).unwrap();
+        let c3 = Cipher::<4, 256>::new_with_rng(&k, &mut ChaCha20Rng::seed_from_u64(43)).unwrap();
+
+        let v1 = c1.full_encrypt(&value).unwrap().to_vec().unwrap();
+
+        assert_eq!(v1, c2.full_encrypt(&value).unwrap().to_vec().unwrap());
+        assert_ne!(v1, c3.full_encrypt(&value).unwrap().to_vec().unwrap());
+        assert!(Cipher::<4, 256>::new(&k)
+            .unwrap()
+            .full_encrypt(&value)
+            .unwrap()
+            .try_eq(&c1.full_encrypt(&value).unwrap())
+            .unwrap());
+    }
+
+    #[cfg(feature = "test-vectors")]
+    #[test]
+    fn seeded_ciphers_are_reproducible() {
+        let k = key();
+        let value: PlainText<4, 256> = 9001u32.try_into().unwrap();
+
+        let c1 = Cipher::<4, 256>::new_with_insecure_seed(&k, 42).unwrap();
+        let c2 = Cipher::<4, 256>::new_with_insecure_seed(&k, 42).unwrap();
+        let c3 = Cipher::<4, 256>::new_with_insecure_seed(&k, 43).unwrap();
+
+        let v1 = c1.full_encrypt(&value).unwrap().to_vec().unwrap();
+
+        assert_eq!(v1, c2.full_encrypt(&value).unwrap().to_vec().unwrap());
+        assert_ne!(v1, c3.full_encrypt(&value).unwrap().to_vec().unwrap());
+
+        // Later encryptions get different nonces, but still the same ones each time
+        assert_ne!(v1, c1.full_encrypt(&value).unwrap().to_vec().unwrap());
+        assert_eq!(
+            c1.right_encrypt(&value).unwrap().to_vec().unwrap(),
+            c2.full_encrypt(&value)
+                .and_then(|_| c2.right_encrypt(&value))
+                .unwrap()
+                .to_vec()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn streamed_right_encryption() {
+        let cipher = Cipher::<3, 257>::new(&key()).unwrap();
+        let mut buf = vec![0u8; Cipher::<3, 257>::max_right_serialized_len()];
+
+        for (a, b) in [(0u16, 1u16), (100, 100), (256, 3), (256, 256)] {
+            let len = cipher
+                .right_encrypt_into(&PlainText::new([a, b, a]), &mut buf)
+                .unwrap();
+            let stored = CipherText::<3, 257>::from_slice(&buf[..len]).unwrap();
+
+            for probe in [a, b] {
+                assert_eq!(
+                    a.cmp(&probe).then(b.cmp(&probe)).then(a.cmp(&probe)),
+                    stored
+                        .try_cmp(
+                            &cipher
+                                .full_encrypt(&PlainText::new([probe, probe, probe]))
+                                .unwrap()
+                        )
+                        .unwrap(),
+                    "{a} {b} vs {probe}"
+                );
+            }
+        }
+
+        assert!(matches!(
+            cipher.right_encrypt_into(&PlainText::new([1, 2, 3]), &mut buf[1..]),
+            Err(Error::RangeError(_))
+        ));
+    }
+
+    #[test]
+    fn batch_encryption() {
+        let cipher = Cipher::<3, 257>::new(&key()).unwrap();
+        let values: Vec<_> = [(0u16, 1u16), (100, 100), (256, 3), (256, 256)]
+            .iter()
+            .map(|(a, b)| PlainText::new([*a, *b, *a]))
+            .collect();
+
+        let full = cipher.encrypt_batch(&values).unwrap();
+        let right = cipher.right_encrypt_batch(&values).unwrap();
+
+        assert_eq!(values.len(), full.len());
+        assert_eq!(values.len(), right.len());
+
+        for (i, (f, value)) in full.iter().zip(values.iter()).enumerate() {
+            assert_eq!(value, &cipher.decrypt(f).unwrap());
+            assert_eq!(
+                f.left().unwrap().to_vec().unwrap(),
+                cipher.left_encrypt(value).unwrap().to_vec().unwrap(),
+                "{i}"
+            );
+
+            for (j, r) in right.iter().enumerate() {
+                assert!(r.left().is_none());
+                assert_eq!(i.cmp(&j), f.try_cmp(r).unwrap(), "{i} vs {j}");
+            }
+        }
+
+        assert!(cipher.encrypt_batch(&[]).unwrap().is_empty());
+        assert!(matches!(
+            cipher.encrypt_batch(&[PlainText::new([1, 257, 1])]),
+            Err(Error::RangeError(_))
+        ));
+        assert!(matches!(
+            Cipher::<3, 257>::new_right_only(&key())
+                .unwrap()
+                .encrypt_batch(&values),
+            Err(Error::NotPermitted(_))
+        ));
+    }
+
+    #[cfg(feature = "test-vectors")]
+    #[test]
+    fn batch_encryption_is_byte_identical() {
+        let k = key();
+        let c1 = Cipher::<4, 256>::new_with_insecure_seed(&k, 42).unwrap();
+        let c2 = Cipher::<4, 256>::new_with_insecure_seed(&k, 42).unwrap();
+        let values: Vec<PlainText<4, 256>> = [0u32, 1, 256, 9001, u32::MAX]
+            .iter()
+            .map(|v| (*v).try_into().unwrap())
+            .collect();
+
+        let full = c2.encrypt_batch(&values).unwrap();
+
+        for (value, ct) in values.iter().zip(full.iter()) {
+            assert_eq!(
+                c1.full_encrypt(value).unwrap().to_vec().unwrap(),
+                ct.to_vec().unwrap()
+            );
+        }
+
+        let right = c2.right_encrypt_batch(&values).unwrap();
+
+        for (value, ct) in values.iter().zip(right.iter()) {
+            assert_eq!(
+                c1.right_encrypt(value).unwrap().to_vec().unwrap(),
+                ct.to_vec().unwrap()
+            );
+        }
+    }
+
+    #[cfg(feature = "test-vectors")]
+    #[test]
+    fn streamed_right_encryption_is_byte_identical() {
+        let k = key();
+        let c1 = Cipher::<3, 257>::new_with_insecure_seed(&k, 42).unwrap();
+        let c2 = Cipher::<3, 257>::new_with_insecure_seed(&k, 42).unwrap();
+        let mut buf = vec![0u8; Cipher::<3, 257>::max_right_serialized_len()];
+
+        for v in [0u32, 1, 256, 9001, 16_974_592] {
+            let value: PlainText<3, 257> = v.try_into().unwrap();
+            let len = c2.right_encrypt_into(&value, &mut buf).unwrap();
+
+            assert_eq!(
+                c1.right_encrypt(&value).unwrap().to_vec().unwrap(),
+                &buf[..len]
+            );
+        }
+    }
+
+    #[test]
+    fn decryption() {
+        let k = key();
+        let cipher = Cipher::<3, 257>::new(&k).unwrap();
+        let value = PlainText::<3, 257>::new([256, 0, 17]);
+        let ct = cipher.full_encrypt(&value).unwrap();
+
+        assert_eq!(value, cipher.decrypt(&ct).unwrap());
+
+        let mut other_key = k;
+        other_key[0] ^= 1;
+        let e = Cipher::<3, 257>::new(&other_key)
+            .unwrap()
+            .decrypt(&ct)
+            .unwrap_err();
+        assert!(matches!(e, Error::CryptoError(_)), "{e}");
+        // Which block gave it away is nobody's business
+        assert!(!e.to_string().contains("block"), "{e}");
+
+        assert!(matches!(
+            cipher.decrypt(&cipher.right_encrypt(&value).unwrap()),
+            Err(Error::CryptoError(_))
+        ));
+    }
+
+    #[test]
+    fn presets_hold_their_whole_domain() {
+        let k = key();
+
+        let small = SmallCipher::new(&k).unwrap();
+        assert_eq!(
+            Ordering::Less,
+            small
+                .full_encrypt(&0u8.try_into().unwrap())
+                .unwrap()
+                .try_cmp(&small.full_encrypt(&u8::MAX.try_into().unwrap()).unwrap())
+                .unwrap()
+        );
+        assert!(PlainText::<1, 256>::try_from(256u16).is_err());
+
+        let medium = MediumCipher::new(&k).unwrap();
+        assert_eq!(
+            Ordering::Less,
+            medium
+                .full_encrypt(&0u32.try_into().unwrap())
+                .unwrap()
+                .try_cmp(&medium.full_encrypt(&u32::MAX.try_into().unwrap()).unwrap())
+                .unwrap()
+        );
+        assert!(PlainText::<4, 256>::try_from(u64::from(u32::MAX) + 1).is_err());
+
+        let large = LargeCipher::new(&k).unwrap();
+        assert_eq!(
+            Ordering::Less,
+            large
+                .full_encrypt(&0u64.try_into().unwrap())
+                .unwrap()
+                .try_cmp(&large.full_encrypt(&u64::MAX.try_into().unwrap()).unwrap())
+                .unwrap()
+        );
+    }
+
+    /// Check that comparing ciphertexts gives the same answers as comparing the plaintexts, for a
+    /// cipher of the given shape
+    macro_rules! shape_tests {
+        ($name:ident, $n:literal, $w:literal) => {
+            mod $name {
+                use super::*;
+
+                /// The block width, as a value rather than a literal
+                const W: u16 = $w;
+
+                /// How many distinct values the plaintext can hold
+                fn domain() -> u128 {
+                    u128::from(W).pow($n)
+                }
+
+                fn plaintext(v: u128) -> PlainText<$n, $w> {
+                    v.checked_rem(domain()).unwrap().try_into().unwrap()
+                }
+
+                fn check(cipher: &Cipher<$n, $w>, a: u128, b: u128) {
+                    let pa = plaintext(a);
+                    let pb = plaintext(b);
+                    let expected = a.checked_rem(domain()).cmp(&b.checked_rem(domain()));
+
+                    let ca = cipher.full_encrypt(&pa).unwrap();
+                    let cb = cipher.full_encrypt(&pb).unwrap();
+                    let rb = cipher.right_encrypt(&pb).unwrap();
+
+                    assert_eq!(expected, ca.try_cmp(&cb).unwrap(), "{a} <=> {b}");
+                    assert_eq!(expected, cb.try_cmp(&ca).unwrap().reverse(), "{b} <=> {a}");
+                    assert_eq!(
+                        expected,
+                        ca.left().unwrap().compare_with_right(&rb).unwrap(),
+                        "{a} <=> right({b})"
+                    );
+                }
+
+                #[test]
+                fn boundaries() {
+                    let cipher = Cipher::<$n, $w>::new(&key()).unwrap();
+                    let max = domain().checked_sub(1).unwrap();
+                    let edges = [
+                        0,
+                        1,
+                        u128::from(W).saturating_sub(1),
+                        u128::from(W),
+                        max.saturating_sub(1),
+                        max,
+                    ];
+
+                    for a in edges {
+                        for b in edges {
+                            check(&cipher, a, b);
+                        }
+                    }
+                }
+
+                quickcheck! {
+                    fn matches_plaintext_ordering(a: u128, b: u128) -> bool {
+                        check(&Cipher::<$n, $w>::new(&key()).unwrap(), a, b);
+                        true
+                    }
+                }
+            }
+        };
+    }
+
+    shape_tests!(shape_1x4, 1, 4);
+    shape_tests!(shape_8x4, 8, 4);
+    shape_tests!(shape_2x16, 2, 16);
+    shape_tests!(shape_5x16, 5, 16);
+    shape_tests!(shape_3x100, 3, 100);
+    shape_tests!(shape_1x257, 1, 257);
+    shape_tests!(shape_3x257, 3, 257);
+    shape_tests!(shape_1x4096, 1, 4096);
+    shape_tests!(shape_2x4096, 2, 4096);
+
+    #[test]
+    fn block_comparisons_need_a_left_part() {
+        let cipher = Cipher::<4, 256>::new(&key()).unwrap();
+        let a = cipher.right_encrypt(&1u32.try_into().unwrap()).unwrap();
+        let b = cipher.right_encrypt(&2u32.try_into().unwrap()).unwrap();
+
+        assert!(matches!(
+            a.compare_blocks(&b),
+            Err(Error::Incomparable { .. })
+        ));
+    }
+
+    #[test]
+    fn stable_cmp_sorts_the_same_whatever_the_starting_order() {
+        use rand::seq::SliceRandom;
+
+        let cipher = Cipher::<4, 256>::new(&key()).unwrap();
+        let mut cts = [3u32, 1, 3, 2, 3, 1]
+            .iter()
+            .map(|v| cipher.full_encrypt(&(*v).try_into().unwrap()).unwrap())
+            .collect::<Vec<_>>();
+
+        cts.sort_by(|a, b| a.stable_cmp(b).unwrap());
+        let sorted = cts
+            .iter()
+            .map(|ct| ct.to_vec().unwrap())
+            .collect::<Vec<_>>();
+
+        for _ in 0..10 {
+            cts.shuffle(&mut rand::thread_rng());
+            cts.sort_by(|a, b| a.stable_cmp(b).unwrap());
+
+            assert_eq!(
+                sorted,
+                cts.iter()
+                    .map(|ct| ct.to_vec().unwrap())
+                    .collect::<Vec<_>>()
+            );
+        }
+
+        // Ties are only broken between equal values
+        assert!(cts
+            .windows(2)
+            .all(|w| w[0].try_cmp(&w[1]).unwrap() != Ordering::Greater));
+    }
+
+    #[cfg(feature = "comparison-traits")]
+    #[test]
+    fn operators_do_not_panic_on_incomparable_ciphertexts() {
+        let cipher = Cipher::<4, 256>::new(&key()).unwrap();
+        let a = cipher.right_encrypt(&1u32.try_into().unwrap()).unwrap();
+        let b = cipher.right_encrypt(&1u32.try_into().unwrap()).unwrap();
+
+        assert_eq!(None, a.partial_cmp(&b));
+        assert!(a != b);
+        assert!(a != a);
+        for op in [
+            PartialOrd::lt,
+            PartialOrd::gt,
+            PartialOrd::le,
+            PartialOrd::ge,
+        ] {
+            assert!(!op(&a, &b));
+        }
+    }
+
+    #[test]
+    fn try_cmp_reports_incomparable_ciphertexts() {
+        let cipher = Cipher::<4, 256>::new(&key()).unwrap();
+        let a = cipher.full_encrypt(&1u32.try_into().unwrap()).unwrap();
+        let b = cipher.right_encrypt(&2u32.try_into().unwrap()).unwrap();
+
+        assert_eq!(Ordering::Less, a.try_cmp(&b).unwrap());
+        assert_eq!(Ordering::Greater, b.try_cmp(&a).unwrap());
+        assert!(!a.try_eq(&b).unwrap());
+        assert!(a.try_eq(&a).unwrap());
+        assert!(matches!(b.try_cmp(&b), Err(Error::Incomparable { .. })));
+        assert!(matches!(b.try_eq(&b), Err(Error::Incomparable { .. })));
+    }
+
     quickcheck! {
+        fn block_comparisons_match_plaintext_blocks(a: u32, b: u32) -> bool {
+            let cipher = Cipher::<4, 256>::new(&key()).unwrap();
+            let expected: Vec<Ordering> = a
+                .to_be_bytes()
+                .iter()
+                .zip(b.to_be_bytes().iter())
+                .map(|(x, y)| x.cmp(y))
+                .collect();
+
+            let full_a = cipher.full_encrypt(&a.try_into().unwrap()).unwrap();
+            let right_a = cipher.right_encrypt(&a.try_into().unwrap()).unwrap();
+            let full_b = cipher.full_encrypt(&b.try_into().unwrap()).unwrap();
+            let right_b = cipher.right_encrypt(&b.try_into().unwrap()).unwrap();
+
+            [(&full_a, &right_b), (&right_a, &full_b), (&full_a, &full_b)]
+                .iter()
+                .all(|(x, y)| {
+                    x.compare_blocks(y).unwrap().collect::<Result<Vec<_>, _>>().unwrap()
+                        == expected
+                })
+        }
+
+        fn u64_decrypt(v: u64) -> bool {
+            let cipher = Cipher::<8, 256>::new(&key()).unwrap();
+            let ct = cipher.full_encrypt(&v.try_into().unwrap()).unwrap();
+
+            u64::try_from(&cipher.decrypt(&ct).unwrap()).unwrap() == v
+        }
+
         fn u64_compare(a: u64, b: u64) -> bool {
             let cipher = Cipher::<8, 256>::new(&key()).unwrap();
 
@@ -260,6 +1087,7 @@ mod tests {
             }
         }
 
+        #[cfg(feature = "comparison-traits")]
         fn u64_cmp(a: u64, b: u64) -> bool {
             let cipher = Cipher::<8, 256>::new(&key()).unwrap();
 
@@ -286,6 +1114,7 @@ mod tests {
             }
         }
 
+        #[cfg(feature = "comparison-traits")]
         fn u32_cmp(a: u32, b: u32) -> bool {
             let cipher = Cipher::<4, 256>::new(&key()).unwrap();
 
@@ -0,0 +1,159 @@
+//! Partial-Order-Revealing Encryption using AES128 as the Pseudo-Random Function and Hash Function.
+//!
+//! Some things can't be put into a single line from smallest to largest: a set of permissions, a
+//! position in a version history with branches, or a node in a hierarchy.  Two such values might
+//! be equal, one might come before the other, or neither might come before the other at all, in
+//! which case they're *incomparable*.  Ciphertexts in this module reveal which of those is the
+//! case, and nothing else.
+//!
+//! Each block of a plaintext is treated as a set of bits, and one block comes before another if
+//! all of its bits are also set in the other one.  Any finite partial order can be represented
+//! that way, by giving each element the set of bits belonging to it and everything below it; the
+//! width `W` needs to be large enough to hold all of the bits that get used.  Values with more
+//! than one block are compared from the most significant block, and the first block that isn't
+//! equal decides the result.
+//!
+//! # Examples
+//!
+//! Encrypting and comparing a few sets of permissions:
+//!
+//! ```rust
+//! use cretrit::aes128v1::pore;
+//! use cretrit::PlainText;
+//! use std::cmp::Ordering;
+//!
+//! # fn main() -> Result<(), cretrit::Error> {
+//! # let key = [0u8; 32];
+//! const READ: u16 = 0b001;
+//! const WRITE: u16 = 0b010;
+//! const ADMIN: u16 = 0b100;
+//!
+//! let cipher = pore::Cipher::<1, 8>::new(&key)?;
+//! let reader = cipher.full_encrypt(&PlainText::new([READ]))?;
+//! let writer = cipher.full_encrypt(&PlainText::new([WRITE]))?;
+//! let editor = cipher.full_encrypt(&PlainText::new([READ | WRITE]))?;
+//! let everything = cipher.full_encrypt(&PlainText::new([READ | WRITE | ADMIN]))?;
+//!
+//! assert_eq!(Some(Ordering::Less), reader.try_partial_cmp(&editor)?);
+//! assert_eq!(Some(Ordering::Greater), everything.try_partial_cmp(&writer)?);
+//! assert_eq!(None, reader.try_partial_cmp(&writer)?);
+//! assert_eq!(Some(Ordering::Equal), editor.try_partial_cmp(&editor)?);
+//! # Ok(())
+//! # }
+//! ```
+
+crate::suite_modules::pore_module!("aes128v1");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmp::Comparator;
+    use crate::util::random_key;
+
+    #[test]
+    fn tiny_partial_order() {
+        let cipher = Cipher::<1, 8>::new(&random_key()).unwrap();
+
+        let a = cipher.full_encrypt(&PlainText::new([0b001])).unwrap();
+        let b = cipher.full_encrypt(&PlainText::new([0b010])).unwrap();
+        let ab = cipher.full_encrypt(&PlainText::new([0b011])).unwrap();
+
+        assert_eq!(Some(Ordering::Less), a.try_partial_cmp(&ab).unwrap());
+        assert_eq!(Some(Ordering::Greater), ab.try_partial_cmp(&b).unwrap());
+        assert_eq!(None, a.try_partial_cmp(&b).unwrap());
+        assert_eq!(None, b.try_partial_cmp(&a).unwrap());
+        assert!(a.try_eq(&a).unwrap());
+        assert!(!a.try_eq(&b).unwrap());
+    }
+
+    #[test]
+    fn try_partial_cmp_tells_incomparable_values_from_incomparable_ciphertexts() {
+        let cipher = Cipher::<1, 8>::new(&random_key()).unwrap();
+
+        let a = cipher.full_encrypt(&PlainText::new([0b001])).unwrap();
+        let b = cipher.right_encrypt(&PlainText::new([0b010])).unwrap();
+
+        assert_eq!(None, a.try_partial_cmp(&b).unwrap());
+        assert!(!a.try_eq(&b).unwrap());
+        assert!(matches!(
+            b.try_partial_cmp(&b),
+            Err(Error::Incomparable { .. })
+        ));
+    }
+
+    #[test]
+    fn first_unequal_block_decides() {
+        let cipher = Cipher::<2, 4>::new(&random_key()).unwrap();
+
+        let a = cipher.full_encrypt(&PlainText::new([0b01, 0b10])).unwrap();
+        let b = cipher.right_encrypt(&PlainText::new([0b11, 0b01])).unwrap();
+        let c = cipher.right_encrypt(&PlainText::new([0b10, 0b00])).unwrap();
+        let d = cipher.right_encrypt(&PlainText::new([0b01, 0b00])).unwrap();
+
+        assert_eq!(Some(Ordering::Less), a.try_partial_cmp(&b).unwrap());
+        assert_eq!(Some(Ordering::Greater), b.try_partial_cmp(&a).unwrap());
+        assert_eq!(None, a.try_partial_cmp(&c).unwrap());
+        assert_eq!(Some(Ordering::Greater), a.try_partial_cmp(&d).unwrap());
+    }
+
+    #[test]
+    fn serialized_comparisons() {
+        let cipher = Cipher::<1, 16>::new(&random_key()).unwrap();
+
+        let a = cipher
+            .full_encrypt_to_vec(&PlainText::new([0b0101]))
+            .unwrap();
+        let b = cipher
+            .right_encrypt_to_vec(&PlainText::new([0b1010]))
+            .unwrap();
+        let c = cipher
+            .right_encrypt_to_vec(&PlainText::new([0b1101]))
+            .unwrap();
+
+        assert_eq!(None, cipher.compare_serialized(&a, &b).unwrap());
+        assert_eq!(
+            Some(Ordering::Less),
+            cipher.compare_serialized(&a, &c).unwrap()
+        );
+        assert_eq!(
+            Some(Ordering::Greater),
+            cipher.compare_serialized(&c, &a).unwrap()
+        );
+    }
+
+    #[test]
+    fn left_comparisons() {
+        let cipher = Cipher::<1, 16>::new(&random_key()).unwrap();
+
+        let left = cipher.left_encrypt(&PlainText::new([0b0110])).unwrap();
+        let right = cipher.right_encrypt(&PlainText::new([0b0011])).unwrap();
+        let bigger = cipher.right_encrypt(&PlainText::new([0b1110])).unwrap();
+
+        assert_eq!(None, left.compare_with_right(&right).unwrap());
+        assert_eq!(
+            Some(Ordering::Less),
+            left.compare_with_right(&bigger).unwrap()
+        );
+    }
+
+    quickcheck! {
+        fn u16_subset_order(a: u16, b: u16) -> bool {
+            let cipher = Cipher::<2, 256>::new(&random_key()).unwrap();
+
+            let pa = PlainText::<2, 256>::try_from(a).unwrap();
+            let pb = PlainText::<2, 256>::try_from(b).unwrap();
+            let expected = pa
+                .blocks()
+                .iter()
+                .zip(pb.blocks())
+                .map(|(x, y)| PartialOrderCMP::invert(PartialOrderCMP::compare(*x, *y)).unwrap())
+                .find(|o| *o != Some(Ordering::Equal))
+                .unwrap_or(Some(Ordering::Equal));
+
+            let ca = cipher.full_encrypt(&pa).unwrap();
+            let cb = cipher.right_encrypt(&pb).unwrap();
+
+            ca.try_partial_cmp(&cb).unwrap() == expected
+        }
+    }
+}
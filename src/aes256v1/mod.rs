@@ -0,0 +1,46 @@
+//! Comparison-Revealing Encryption using AES256 as the Pseudo-Random Function and Hash Function.
+//!
+//! This is the same as the [`aes128v1`](crate::aes128v1) ciphersuite in every respect except
+//! the size of the keys, for when policy (or paranoia) requires 256 bit symmetric primitives
+//! throughout.  Every subkey is already derived with CMAC-AES256, and the nonces and block
+//! permutations come from `ChaCha20`, so the only changes are the Pseudo-Random Function, which is
+//! AES256, and the Hash Function, which is CMAC-AES256.  Since the Hash Function is keyed with
+//! the output of the Pseudo-Random Function, each block of a left ciphertext is 32 bytes, rather
+//! than 16; right ciphertexts are the same size as in `aes128v1`.
+//!
+//! As with `aes128v1`, there are modules for orderable ciphertexts ([`ore`]), for ciphertexts
+//! that only have to be compared for equality ([`ere`]), and for partially ordered values
+//! ([`pore`]).  Ciphertexts from the two ciphersuites can't be compared with each other, even if
+//! they were encrypted with the same key.
+
+pub mod ere;
+pub mod ore;
+pub mod pore;
+
+use rand_chacha::ChaCha20Rng;
+
+use crate::ciphersuite::{CipherSuite as SuperSweet, ListedCipherSuite};
+use crate::{hash, kbkdf, prf, prp};
+
+/// The full set of parameters that make up the [`aes256v1`](super) ciphersuite.
+///
+/// This struct simply represents the concrete choices about which cryptographic operators to use
+/// for the various parts of the Comparison-Revealing Encryption system.  These can *never* change;
+/// if anything needs to change, for any reason, a new ciphersuite is defined with the different
+/// parameters.
+///
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CipherSuite<const W: u16, const M: u8> {}
+
+impl<const W: u16, const M: u8> SuperSweet<W, M> for CipherSuite<W, M> {
+    const NAME: &'static str = "aes256v1";
+
+    type RNG = ChaCha20Rng;
+    type PRF = prf::AES256PRF;
+    type HF = hash::CMACAES256HF<M>;
+    type PRP = prp::RandShufflePRP<W>;
+    type KBKDF = kbkdf::CMACAES256;
+}
+
+impl<const W: u16, const M: u8> ListedCipherSuite<W, M> for CipherSuite<W, M> {}
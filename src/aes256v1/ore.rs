@@ -0,0 +1,118 @@
+//! Order-Revealing Encryption using AES256 as the primary cryptographic primitive.
+//!
+//! AES is usually high-performance (because hardware acceleration is widely available) and
+//! generally considered secure.
+//!
+//! Apart from the primitives, everything works just as it does in
+//! [`aes128v1::ore`](crate::aes128v1::ore), which has more examples, although ciphertexts from the
+//! two can't be compared with each other.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use cretrit::aes256v1::ore;
+//! use std::cmp::Ordering;
+//!
+//! # fn main() -> Result<(), cretrit::Error> {
+//! # let key = [0u8; 32];
+//! let cipher = ore::Cipher::<4, 256>::new(&key)?;
+//! let forty_two = cipher.full_encrypt(&42u32.try_into()?)?;
+//! let over_nine_thousand = cipher.right_encrypt(&9001u32.try_into()?)?;
+//!
+//! assert_eq!(Ordering::Less, forty_two.try_cmp(&over_nine_thousand)?);
+//! # Ok(())
+//! # }
+//! ```
+
+crate::suite_modules::ore_module!("aes256v1");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes128v1;
+    use crate::util::random_key;
+
+    #[test]
+    fn tiny_comparisons() {
+        let cipher = Cipher::<1, 4>::new(&random_key()).unwrap();
+
+        let n1 = cipher
+            .full_encrypt(&PlainText::<1, 4>::new([1u16]))
+            .unwrap();
+        let n2 = cipher
+            .full_encrypt(&PlainText::<1, 4>::new([2u16]))
+            .unwrap();
+
+        assert_eq!(0, n1.compare(&n1).unwrap());
+        assert_eq!(1, n1.compare(&n2).unwrap());
+        assert_eq!(2, n2.compare(&n1).unwrap());
+    }
+
+    #[test]
+    fn left_blocks_are_256_bits() {
+        assert_eq!(
+            aes128v1::ore::LeftCipherText::<4, 256>::serialized_len() + 4 * 16,
+            LeftCipherText::<4, 256>::serialized_len()
+        );
+
+        let cipher = Cipher::<4, 256>::new(&random_key()).unwrap();
+        let left = cipher.left_encrypt(&42u32.try_into().unwrap()).unwrap();
+
+        assert_eq!(
+            LeftCipherText::<4, 256>::serialized_len(),
+            left.to_vec().unwrap().len()
+        );
+    }
+
+    #[test]
+    fn aes128v1_ciphertexts_are_rejected() {
+        let k = random_key();
+        let cipher128 = aes128v1::ore::Cipher::<4, 256>::new(&k).unwrap();
+        let ct128 = cipher128.full_encrypt(&42u32.try_into().unwrap()).unwrap();
+
+        assert!(CipherText::<4, 256>::from_slice(&ct128.to_vec().unwrap()).is_err());
+    }
+
+    #[test]
+    fn decryption() {
+        let cipher = Cipher::<4, 256>::new(&random_key()).unwrap();
+        let ct = cipher.full_encrypt(&9001u32.try_into().unwrap()).unwrap();
+
+        assert_eq!(
+            9001u32,
+            u32::try_from(&cipher.decrypt(&ct).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn equality_filter() {
+        let cipher = Cipher::<4, 256>::new(&random_key()).unwrap();
+        let (ct, tag) = cipher
+            .right_encrypt_with_filter_tag(&42u32.try_into().unwrap())
+            .unwrap();
+        let filter =
+            EqualityFilter::new(&cipher.left_encrypt(&42u32.try_into().unwrap()).unwrap()).unwrap();
+
+        assert!(filter.may_equal(&ct, tag).unwrap());
+    }
+
+    quickcheck! {
+        fn u64_compare(a: u64, b: u64) -> bool {
+            let cipher = Cipher::<8, 256>::new(&random_key()).unwrap();
+
+            let ca = cipher.full_encrypt(&a.try_into().unwrap()).unwrap();
+            let cb = cipher.right_encrypt(&b.try_into().unwrap()).unwrap();
+
+            a.cmp(&b) == ca.compare_either(&cb).and_then(OrderingCMP::invert).unwrap()
+        }
+
+        fn u32_cmp(a: u32, b: u32) -> bool {
+            let cipher = Cipher::<4, 256>::new(&random_key()).unwrap();
+
+            let ca = cipher.full_encrypt(&a.try_into().unwrap()).unwrap();
+            let cb = cipher.full_encrypt(&b.try_into().unwrap()).unwrap();
+
+            a.cmp(&b) == ca.try_cmp(&cb).unwrap()
+        }
+    }
+}
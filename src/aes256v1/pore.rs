@@ -0,0 +1,43 @@
+//! Partial-Order-Revealing Encryption using AES256 as the Pseudo-Random Function and Hash Function.
+//!
+//! Apart from the primitives, everything works just as it does in
+//! [`aes128v1::pore`](crate::aes128v1::pore), which has more examples, although ciphertexts from the
+//! two can't be compared with each other.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use cretrit::aes256v1::pore;
+//! use cretrit::PlainText;
+//! use std::cmp::Ordering;
+//!
+//! # fn main() -> Result<(), cretrit::Error> {
+//! # let key = [0u8; 32];
+//! let cipher = pore::Cipher::<1, 8>::new(&key)?;
+//! let reader = cipher.full_encrypt(&PlainText::new([0b001]))?;
+//! let writer = cipher.right_encrypt(&PlainText::new([0b010]))?;
+//!
+//! assert_eq!(None, reader.try_partial_cmp(&writer)?);
+//! # Ok(())
+//! # }
+//! ```
+
+crate::suite_modules::pore_module!("aes256v1");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::random_key;
+
+    #[test]
+    fn tiny_partial_order() {
+        let cipher = Cipher::<1, 8>::new(&random_key()).unwrap();
+
+        let a = cipher.full_encrypt(&PlainText::new([0b001])).unwrap();
+        let b = cipher.right_encrypt(&PlainText::new([0b010])).unwrap();
+        let ab = cipher.right_encrypt(&PlainText::new([0b011])).unwrap();
+
+        assert_eq!(Some(Ordering::Less), a.try_partial_cmp(&ab).unwrap());
+        assert_eq!(None, a.try_partial_cmp(&b).unwrap());
+    }
+}
@@ -0,0 +1,244 @@
+//! Counting of heap allocations, to keep an eye on the comparison hot path.
+//!
+//! Comparing ciphertexts is supposed to happen without touching the heap at all, and parsing them
+//! should only need to allocate the storage for the parsed ciphertext.  This module provides a
+//! global allocator that counts what it's asked to do, so those properties can be checked by tests
+//! (both ours and yours) rather than being left to chance.
+//!
+//! Counts are kept per-thread, so that tests running in parallel don't trip over each other's
+//! allocations.  Nothing is counted unless [`CountingAllocator`] is installed as the global
+//! allocator:
+//!
+//! ```rust
+//! use cretrit::alloc_tracking::{count_allocations, CountingAllocator};
+//! use cretrit::aes128v1::ore;
+//!
+//! #[global_allocator]
+//! static ALLOCATOR: CountingAllocator = CountingAllocator::new();
+//!
+//! # fn main() -> Result<(), cretrit::Error> {
+//! # let key = [0u8; 32];
+//! let cipher = ore::Cipher::<4, 256>::new(&key)?;
+//! let a = cipher.full_encrypt(&42u32.try_into()?)?;
+//! let b = cipher.right_encrypt(&9001u32.try_into()?)?;
+//!
+//! let (result, counts) = count_allocations(|| a < b);
+//!
+//! assert!(result);
+//! assert_eq!(0, counts.allocations);
+//! # Ok(())
+//! # }
+//! ```
+//!
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+thread_local! {
+    /// What's been allocated on this thread so far
+    static COUNTS: Cell<AllocationCounts> = const { Cell::new(AllocationCounts::ZERO) };
+}
+
+/// A tally of calls to the global allocator
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AllocationCounts {
+    /// The number of fresh allocations made
+    pub allocations: usize,
+    /// The number of times an existing allocation was resized
+    pub reallocations: usize,
+    /// The number of allocations that were freed
+    pub deallocations: usize,
+    /// The total number of bytes requested by allocations and reallocations
+    pub bytes: usize,
+}
+
+impl AllocationCounts {
+    /// Nothing at all
+    const ZERO: Self = AllocationCounts {
+        allocations: 0,
+        reallocations: 0,
+        deallocations: 0,
+        bytes: 0,
+    };
+
+    /// The difference between two sets of counts, taken at different times
+    fn since(&self, earlier: &Self) -> Self {
+        AllocationCounts {
+            allocations: self.allocations.saturating_sub(earlier.allocations),
+            reallocations: self.reallocations.saturating_sub(earlier.reallocations),
+            deallocations: self.deallocations.saturating_sub(earlier.deallocations),
+            bytes: self.bytes.saturating_sub(earlier.bytes),
+        }
+    }
+}
+
+/// Update this thread's allocation counts
+fn tally(f: impl FnOnce(&mut AllocationCounts)) {
+    // If the thread is being torn down, there's nowhere to keep the counts, and nobody left to
+    // care about them, so we just don't bother
+    let _ignored = COUNTS.try_with(|c| {
+        let mut counts = c.get();
+        f(&mut counts);
+        c.set(counts);
+    });
+}
+
+/// A global allocator which hands everything off to the system allocator, counting as it goes
+///
+/// Install it with `#[global_allocator]`, and then use [`count_allocations`] to find out what a
+/// piece of code did to the heap.
+///
+#[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
+pub struct CountingAllocator;
+
+impl CountingAllocator {
+    /// Create a new counting allocator, suitable for use in a `static`
+    #[must_use]
+    pub const fn new() -> Self {
+        CountingAllocator
+    }
+}
+
+// Implementing GlobalAlloc can't be done without unsafe
+#[allow(unsafe_code)]
+// SAFETY: every method passes its arguments, unchanged, to the corresponding method of the system
+// allocator, so we uphold the GlobalAlloc contract exactly as well as it does.  Counting doesn't
+// allocate, because the thread-local is const-initialised and only holds plain integers.
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        tally(|c| {
+            c.allocations = c.allocations.saturating_add(1);
+            c.bytes = c.bytes.saturating_add(layout.size());
+        });
+        // SAFETY: our caller has promised to uphold the contract of GlobalAlloc::alloc
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        tally(|c| {
+            c.allocations = c.allocations.saturating_add(1);
+            c.bytes = c.bytes.saturating_add(layout.size());
+        });
+        // SAFETY: our caller has promised to uphold the contract of GlobalAlloc::alloc_zeroed
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        tally(|c| c.deallocations = c.deallocations.saturating_add(1));
+        // SAFETY: our caller has promised to uphold the contract of GlobalAlloc::dealloc
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        tally(|c| {
+            c.reallocations = c.reallocations.saturating_add(1);
+            c.bytes = c.bytes.saturating_add(new_size);
+        });
+        // SAFETY: our caller has promised to uphold the contract of GlobalAlloc::realloc
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+/// Everything that has been allocated on the current thread, since it started
+///
+/// Will be all zeroes if [`CountingAllocator`] isn't the global allocator.
+///
+#[must_use]
+pub fn current_counts() -> AllocationCounts {
+    COUNTS.try_with(Cell::get).unwrap_or_default()
+}
+
+/// Run the given function, and report what it allocated on the current thread
+///
+/// Allocations made by any other threads (including threads spawned by `f`) aren't counted.
+///
+pub fn count_allocations<R>(f: impl FnOnce() -> R) -> (R, AllocationCounts) {
+    let before = current_counts();
+    let result = f();
+
+    (result, current_counts().since(&before))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes128v1::{ere, ore};
+    use crate::util::random_key;
+    use crate::{CipherTextPool, SerializableCipherText};
+
+    #[test]
+    fn counting_works() {
+        let (v, counts) = count_allocations(|| vec![0u8; 42]);
+
+        assert_eq!(42, v.len());
+        assert_eq!(1, counts.allocations);
+        assert_eq!(42, counts.bytes);
+    }
+
+    #[test]
+    fn ore_comparison_does_not_allocate() {
+        let cipher = ore::Cipher::<8, 256>::new(&random_key()).unwrap();
+        let a = cipher.full_encrypt(&42u64.try_into().unwrap()).unwrap();
+        let b = cipher.right_encrypt(&9001u64.try_into().unwrap()).unwrap();
+
+        let (result, counts) = count_allocations(|| a.compare(&b));
+
+        assert_eq!(1, result.unwrap());
+        assert_eq!(AllocationCounts::default(), counts);
+    }
+
+    #[test]
+    fn ere_comparison_does_not_allocate() {
+        let cipher = ere::Cipher::<8, 256>::new(&random_key()).unwrap();
+        let a = cipher.full_encrypt(&42u64.try_into().unwrap()).unwrap();
+        let b = cipher.right_encrypt(&42u64.try_into().unwrap()).unwrap();
+
+        let (result, counts) = count_allocations(|| a.compare(&b));
+
+        assert_eq!(0, result.unwrap());
+        assert_eq!(AllocationCounts::default(), counts);
+    }
+
+    #[test]
+    fn parsing_only_allocates_storage() {
+        let cipher = ore::Cipher::<8, 256>::new(&random_key()).unwrap();
+        let bytes = cipher
+            .right_encrypt(&42u64.try_into().unwrap())
+            .unwrap()
+            .to_vec()
+            .unwrap();
+
+        let (ct, counts) = count_allocations(|| ore::CipherText::<8, 256>::from_slice(&bytes));
+
+        assert!(ct.is_ok());
+        // Just the one, for the packed values
+        assert_eq!(1, counts.allocations, "{counts:?}");
+        assert_eq!(0, counts.reallocations, "{counts:?}");
+    }
+
+    #[test]
+    fn pooled_parsing_reuses_storage() {
+        let cipher = ore::Cipher::<8, 256>::new(&random_key()).unwrap();
+        let bytes = cipher
+            .right_encrypt(&42u64.try_into().unwrap())
+            .unwrap()
+            .to_vec()
+            .unwrap();
+        let mut pool = CipherTextPool::new();
+
+        let warm_up = ore::CipherText::<8, 256>::from_slice_pooled(&bytes, &mut pool).unwrap();
+        pool.recycle(warm_up);
+
+        let ((), counts) = count_allocations(|| {
+            for _ in 0..10 {
+                let ct = ore::CipherText::<8, 256>::from_slice_pooled(&bytes, &mut pool).unwrap();
+                pool.recycle(ct);
+            }
+        });
+
+        assert_eq!(AllocationCounts::default(), counts);
+    }
+}
@@ -0,0 +1,280 @@
+//! Keeping a record of what a cipher has been used for.
+//!
+//! In some environments, it's not enough to protect data; you also have to be able to show who
+//! did what with it, and when.  Rather than requiring every caller to remember to log each
+//! encryption, a [`Cipher`](crate::Cipher) can be given an [`AuditSink`], which it will tell about
+//! every operation it performs.
+//!
+//! Nothing that could help an attacker -- plaintexts, ciphertexts, or key material -- is ever
+//! passed to the sink; only what was done, when, and the labels the cipher was given when the sink
+//! was attached.
+//!
+
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// The kinds of operation that get reported to an [`AuditSink`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum AuditOperation {
+    /// A value was encrypted into a ciphertext with both "left" and "right" parts
+    FullEncrypt,
+    /// A value was encrypted into a ciphertext with only a "right" part
+    RightEncrypt,
+    /// A value was encrypted into a "left" ciphertext on its own
+    LeftEncrypt,
+    /// Two (serialized) ciphertexts were compared
+    Compare,
+    /// A ciphertext was decrypted back into its plaintext
+    Decrypt,
+    /// A query key was derived for a value
+    QueryKey,
+    /// An equality token was derived for a value
+    EqualityToken,
+}
+
+/// Everything an [`AuditSink`] gets told about an operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AuditEvent<'a> {
+    /// What was done
+    pub operation: AuditOperation,
+    /// The identifier of the key in use, as given to
+    /// [`Cipher::with_audit_sink`](crate::Cipher::with_audit_sink)
+    pub key_id: &'a str,
+    /// What the cipher is being used for, as given to
+    /// [`Cipher::with_audit_sink`](crate::Cipher::with_audit_sink)
+    pub context: &'a str,
+    /// When the operation finished
+    pub timestamp: SystemTime,
+    /// Whether the operation completed successfully
+    pub succeeded: bool,
+}
+
+/// Somewhere to send a record of every operation a [`Cipher`](crate::Cipher) performs
+///
+/// Sinks are called synchronously, on the thread that performed the operation, so anything slow
+/// (like writing to a remote log store) should be handed off to something else, rather than done
+/// in [`record()`](Self::record) itself.
+///
+/// Comparisons made directly between [`CipherText`s](crate::CipherText) (with `<`, `==`, and so
+/// on) don't involve a cipher, and so can't be audited; only comparisons made through
+/// [`ComparisonEncryptor::compare_serialized`](crate::ComparisonEncryptor::compare_serialized) are
+/// reported.
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::aes128v1::ore;
+/// use cretrit::audit::{AuditEvent, AuditOperation, AuditSink};
+/// use std::sync::{Arc, Mutex};
+///
+/// #[derive(Default)]
+/// struct Recorder(Mutex<Vec<AuditOperation>>);
+///
+/// impl AuditSink for Recorder {
+///     fn record(&self, event: &AuditEvent<'_>) {
+///         self.0.lock().unwrap().push(event.operation);
+///     }
+/// }
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// # let key = [0u8; 32];
+/// let recorder = Arc::new(Recorder::default());
+/// let cipher = ore::Cipher::<4, 256>::new(&key)?
+///     .with_audit_sink(recorder.clone(), "key-2024-01", "customer ages");
+///
+/// cipher.right_encrypt(&42u32.try_into()?)?;
+///
+/// assert_eq!(vec![AuditOperation::RightEncrypt], *recorder.0.lock().unwrap());
+/// # Ok(())
+/// # }
+/// ```
+///
+pub trait AuditSink: Send + Sync {
+    /// Make a note of an operation that has been performed
+    fn record(&self, event: &AuditEvent<'_>);
+}
+
+/// An audit sink, along with the labels to pass to it
+#[derive(Clone)]
+pub(crate) struct Auditor {
+    /// Where the events go
+    sink: Arc<dyn AuditSink>,
+    /// Which key the cipher is using
+    key_id: String,
+    /// What the cipher is being used for
+    context: String,
+}
+
+impl Auditor {
+    /// Bundle up a sink and its labels
+    pub(crate) fn new(sink: Arc<dyn AuditSink>, key_id: &str, context: &str) -> Self {
+        Auditor {
+            sink,
+            key_id: key_id.to_owned(),
+            context: context.to_owned(),
+        }
+    }
+
+    /// Tell the sink about an operation
+    pub(crate) fn record(&self, operation: AuditOperation, succeeded: bool) {
+        self.sink.record(&AuditEvent {
+            operation,
+            key_id: &self.key_id,
+            context: &self.context,
+            timestamp: SystemTime::now(),
+            succeeded,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes128v1::{ere, ore};
+    use crate::util::random_key;
+    use crate::{ComparisonEncryptor, PlainText};
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<(AuditOperation, String, String, bool)>>);
+
+    impl AuditSink for Recorder {
+        fn record(&self, event: &AuditEvent<'_>) {
+            self.0.lock().unwrap().push((
+                event.operation,
+                event.key_id.to_string(),
+                event.context.to_string(),
+                event.succeeded,
+            ));
+        }
+    }
+
+    impl Recorder {
+        fn operations(&self) -> Vec<(AuditOperation, bool)> {
+            self.0
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(op, _, _, ok)| (*op, *ok))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn ore_operations_are_recorded() {
+        let recorder = Arc::new(Recorder::default());
+        let cipher = ore::Cipher::<4, 256>::new(&random_key())
+            .unwrap()
+            .with_audit_sink(Arc::<Recorder>::clone(&recorder), "k1", "ages");
+
+        let a = cipher
+            .full_encrypt_to_vec(&42u32.try_into().unwrap())
+            .unwrap();
+        let b = cipher
+            .right_encrypt_to_vec(&9001u32.try_into().unwrap())
+            .unwrap();
+        cipher.compare_serialized(&a, &b).unwrap();
+        assert!(cipher.compare_serialized(&b, &b).is_err());
+
+        assert_eq!(
+            vec![
+                (AuditOperation::FullEncrypt, true),
+                (AuditOperation::RightEncrypt, true),
+                (AuditOperation::Compare, true),
+                (AuditOperation::Compare, false),
+            ],
+            recorder.operations()
+        );
+        assert!(recorder
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|(_, k, c, _)| k == "k1" && c == "ages"));
+    }
+
+    #[test]
+    fn ere_operations_are_recorded() {
+        let recorder = Arc::new(Recorder::default());
+        let cipher = ere::Cipher::<4, 256>::new(&random_key())
+            .unwrap()
+            .with_audit_sink(Arc::<Recorder>::clone(&recorder), "k2", "names");
+
+        let a = cipher
+            .full_encrypt_to_vec(&42u32.try_into().unwrap())
+            .unwrap();
+        let b = cipher
+            .right_encrypt_to_vec(&42u32.try_into().unwrap())
+            .unwrap();
+        assert!(cipher.compare_serialized(&a, &b).unwrap());
+
+        assert_eq!(
+            vec![
+                (AuditOperation::FullEncrypt, true),
+                (AuditOperation::RightEncrypt, true),
+                (AuditOperation::Compare, true),
+            ],
+            recorder.operations()
+        );
+    }
+
+    #[test]
+    fn batches_are_recorded_per_value() {
+        let recorder = Arc::new(Recorder::default());
+        let cipher = ore::Cipher::<4, 256>::new(&random_key())
+            .unwrap()
+            .with_audit_sink(Arc::<Recorder>::clone(&recorder), "k3", "bulk");
+        let values = [PlainText::new([1, 2, 3, 4]), PlainText::new([5, 6, 7, 8])];
+
+        cipher.encrypt_batch(&values).unwrap();
+        cipher.right_encrypt_batch(&values[..1]).unwrap();
+
+        assert_eq!(
+            vec![
+                (AuditOperation::FullEncrypt, true),
+                (AuditOperation::FullEncrypt, true),
+                (AuditOperation::RightEncrypt, true),
+            ],
+            recorder.operations()
+        );
+    }
+
+    #[test]
+    fn query_keys_are_recorded() {
+        let recorder = Arc::new(Recorder::default());
+        let cipher = ere::Cipher::<4, 256>::new(&random_key())
+            .unwrap()
+            .with_audit_sink(Arc::<Recorder>::clone(&recorder), "k4", "lookups");
+
+        cipher.query_key(&42u32.try_into().unwrap()).unwrap();
+
+        assert_eq!(
+            vec![(AuditOperation::QueryKey, true)],
+            recorder.operations()
+        );
+    }
+
+    #[test]
+    fn equality_tokens_are_recorded() {
+        let recorder = Arc::new(Recorder::default());
+        let cipher = ore::Cipher::<4, 256>::new(&random_key())
+            .unwrap()
+            .with_audit_sink(Arc::<Recorder>::clone(&recorder), "k5", "index");
+
+        cipher.equality_token(&42u32.try_into().unwrap()).unwrap();
+
+        assert_eq!(
+            vec![(AuditOperation::EqualityToken, true)],
+            recorder.operations()
+        );
+    }
+
+    #[test]
+    fn no_sink_no_problem() {
+        let cipher = ore::Cipher::<4, 256>::new(&random_key()).unwrap();
+
+        assert!(cipher.full_encrypt(&42u32.try_into().unwrap()).is_ok());
+    }
+}
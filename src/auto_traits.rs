@@ -0,0 +1,101 @@
+//! Compile-time checks of which auto traits the public types implement.
+//!
+//! Whether a type is `Send`, `Sync`, or `Unpin` is decided by what's inside it, so an innocent
+//! looking change to a private field (an `Rc` here, a `Cell` there) can quietly take one of them
+//! away, and nobody finds out until some downstream code stops compiling.  Everything in here is
+//! checked when the tests are built, so that sort of change breaks our build instead.
+//!
+//! The negative assertions are just as deliberate: a `Cipher` keeps its random number generator in
+//! a `RefCell`, and a `PreparedQuery` does the same with its comparison cache, so neither can be
+//! shared between threads.  If that ever changes, it should be because someone meant it to, and
+//! they should update this file (and the documentation) to match.
+//!
+
+use static_assertions::{assert_impl_all, assert_not_impl_any};
+use std::cmp::Ordering;
+
+use crate::kbkdf::{BLAKE2S256, CMACAES256, HKDFSHA256};
+use crate::{
+    CipherTextType, ComparatorDescriptor, Error, ErrorKind, Matrix, Params, PlainText,
+    SuiteDescriptor,
+};
+
+/// The ciphersuites that the truncated types get checked with
+type TruncatedEre = crate::aes128v1::CipherSuite<256, 2>;
+/// See [`TruncatedEre`]
+type TruncatedOre = crate::aes128v1::CipherSuite<256, 3>;
+
+/// Check the types that every comparator of a ciphersuite provides
+macro_rules! assert_cipher_types {
+    ($($module:ident)::+ [$($arg:tt),+]) => {
+        assert_impl_all!($($module)::+::Cipher<$($arg),+>: Send, Unpin);
+        assert_not_impl_any!($($module)::+::Cipher<$($arg),+>: Sync);
+
+        assert_impl_all!($($module)::+::CipherText<$($arg),+>: Send, Sync, Unpin);
+        // Two right ciphertexts never compare equal, not even to themselves
+        assert_not_impl_any!($($module)::+::CipherText<$($arg),+>: Eq, Ord);
+        assert_impl_all!($($module)::+::LeftCipherText<$($arg),+>: Send, Sync, Unpin);
+
+        assert_impl_all!($($module)::+::PreparedQuery<$($arg),+>: Send, Unpin);
+        assert_not_impl_any!($($module)::+::PreparedQuery<$($arg),+>: Sync);
+    };
+}
+
+/// Check the types that only order-revealing ciphersuites provide
+macro_rules! assert_ore_types {
+    ($($module:ident)::+) => {
+        assert_impl_all!($($module)::+::RangeQuery<4, 256>: Send, Sync, Unpin);
+        assert_impl_all!($($module)::+::RangeIndex<4, 256, String>: Send, Sync, Unpin);
+        assert_impl_all!($($module)::+::Histogram<4, 256>: Send, Sync, Unpin);
+        assert_impl_all!($($module)::+::Partition<4, 256>: Send, Sync, Unpin);
+        assert_impl_all!($($module)::+::OrdBytesKey<4, 256>: Send, Sync, Unpin);
+        assert_impl_all!($($module)::+::SortableBytes<4, 256>: Send, Sync, Unpin);
+    };
+}
+
+assert_cipher_types!(crate::aes128v1::ere[4, 256]);
+assert_cipher_types!(crate::aes128v1::ore[4, 256]);
+assert_cipher_types!(crate::aes128v1::pore[4, 256]);
+assert_cipher_types!(crate::aes256v1::ere[4, 256]);
+assert_cipher_types!(crate::aes256v1::ore[4, 256]);
+assert_cipher_types!(crate::aes256v1::pore[4, 256]);
+assert_cipher_types!(crate::chacha20v1::ere[4, 256]);
+assert_cipher_types!(crate::chacha20v1::ore[4, 256]);
+assert_cipher_types!(crate::chacha20v1::pore[4, 256]);
+assert_cipher_types!(crate::truncated::ere[TruncatedEre, 8, 4, 256]);
+assert_cipher_types!(crate::truncated::ore[TruncatedOre, 8, 4, 256]);
+
+assert_ore_types!(crate::aes128v1::ore);
+assert_ore_types!(crate::aes256v1::ore);
+assert_ore_types!(crate::chacha20v1::ore);
+
+assert_impl_all!(crate::aes128v1::ere::EqualityFilter<4, 256>: Send, Sync, Unpin);
+assert_impl_all!(crate::aes128v1::ore::EqualityFilter<4, 256>: Send, Sync, Unpin);
+assert_impl_all!(crate::aes256v1::ere::EqualityFilter<4, 256>: Send, Sync, Unpin);
+assert_impl_all!(crate::aes256v1::ore::EqualityFilter<4, 256>: Send, Sync, Unpin);
+assert_impl_all!(crate::chacha20v1::ere::EqualityFilter<4, 256>: Send, Sync, Unpin);
+assert_impl_all!(crate::chacha20v1::ore::EqualityFilter<4, 256>: Send, Sync, Unpin);
+assert_impl_all!(crate::truncated::ere::EqualityFilter<TruncatedEre, 8, 4, 256>: Send, Sync, Unpin);
+assert_impl_all!(crate::truncated::ore::EqualityFilter<TruncatedOre, 8, 4, 256>: Send, Sync, Unpin);
+assert_impl_all!(crate::aes128v1::ere::FieldPack<4, 256>: Send, Sync, Unpin);
+assert_impl_all!(crate::aes256v1::ere::FieldPack<4, 256>: Send, Sync, Unpin);
+assert_impl_all!(crate::chacha20v1::ere::FieldPack<4, 256>: Send, Sync, Unpin);
+
+assert_impl_all!(PlainText<4, 256>: Send, Sync, Unpin);
+assert_impl_all!(Matrix<Ordering>: Send, Sync, Unpin);
+assert_impl_all!(Params: Send, Sync, Unpin);
+assert_impl_all!(SuiteDescriptor: Send, Sync, Unpin);
+assert_impl_all!(ComparatorDescriptor: Send, Sync, Unpin);
+assert_impl_all!(CipherTextType: Send, Sync, Unpin);
+assert_impl_all!(Error: Send, Sync, Unpin);
+assert_impl_all!(ErrorKind: Send, Sync, Unpin);
+assert_impl_all!(CMACAES256: Send, Sync, Unpin);
+assert_impl_all!(BLAKE2S256: Send, Sync, Unpin);
+assert_impl_all!(HKDFSHA256: Send, Sync, Unpin);
+assert_impl_all!(crate::audit::AuditEvent<'static>: Send, Sync, Unpin);
+
+#[cfg(feature = "serde")]
+assert_impl_all!(crate::FieldConfig: Send, Sync, Unpin);
+
+#[cfg(feature = "passphrase")]
+assert_impl_all!(crate::passphrase::PassphraseParams: Send, Sync, Unpin);
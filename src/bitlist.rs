@@ -4,39 +4,42 @@
 //! Storing each of those as separate bytes on disk would be hideously wasteful, so part of the
 //! serialisation process is to pack them into a stream of bits.
 //!
+//! Values whose range isn't a power of two can also be packed as base-`M` digits (see
+//! [`RadixGroup`]), which wastes less space than rounding each value up to a whole number of bits.
+//!
 
 use crate::Error;
 
-/// This seems, annoyingly enough, the easiest way to jam a common function into both structs
-macro_rules! fn_next_bit {
-    () => {
-        fn next_bit(&mut self) {
-            if self.bitmask == 128 {
-                self.curbyte = self.curbyte.saturating_add(1);
-                self.bitmask = 1;
-            } else {
-                self.bitmask = self.bitmask.wrapping_shl(1u32);
-            }
-        }
-    };
-}
-
 /// Construct a Vec<u8> of bits
 ///
-pub(crate) struct WritableBitList {
-    /// Where the bits get written
+/// Bits are accumulated into a 64-bit word, and only written out to the underlying byte vector
+/// once a whole word's worth has been collected, which is a *lot* faster than fiddling with
+/// individual bytes for every bit.
+///
+#[allow(unreachable_pub)] // I think this is a bug in the lint; see also https://github.com/rust-lang/rust/issues/110923
+#[derive(Debug)]
+pub struct WritableBitList {
+    /// Where the bits get written, once we've got a whole word of them
     list: Vec<u8>,
-    /// The byte that is currently being written to
-    curbyte: usize,
-    /// Which bit in the current byte is next to be written, represented as a "mask"
-    bitmask: u8,
+    /// The bits that haven't been written to `list` yet, least-significant bit first
+    word: u64,
+    /// How many bits of `word` are in use
+    used: u32,
+    /// The base-`M` digits that haven't made up a whole group yet, as a single number
+    digits: u64,
+    /// How many digits are in `digits`
+    digit_count: u32,
+    /// What the next digit pushed will be multiplied by, before being added to `digits`
+    place: u64,
+    /// How many bits the group `digits` is part of will take up, once it's written
+    group_bits: u32,
 }
 
 impl WritableBitList {
     /// Create a new `WritableBitList`
     ///
     /// The only thing you can do with a `WritableBitList` is add new bits to the end of the list with
-    /// `push()`, then read off the resulting sequence of bytes with `vec()`.
+    /// `push()` (or one of its bulk siblings), then read off the resulting sequence of bytes with `vec()`.
     ///
     /// The `capacity` parameter is the number of ***bits*** you expect to need to store; like the
     /// equivalent parameter of [`Vec::new`](std::vec::Vec::with_capacity), it is just a hint as to
@@ -45,73 +48,278 @@ impl WritableBitList {
     pub(crate) fn new(capacity: usize) -> Self {
         Self {
             list: Vec::with_capacity(num::Integer::div_ceil(&capacity, &8)),
-            curbyte: 0,
-            bitmask: 1,
+            word: 0,
+            used: 0,
+            digits: 0,
+            digit_count: 0,
+            place: 1,
+            group_bits: 0,
         }
     }
 
     /// Add another bit to the list
+    #[cfg(test)] // The packers all work in bulk, but this is handy for testing
     pub(crate) fn push(&mut self, b: bool) -> Result<(), Error> {
-        if self.bitmask == 1 {
-            self.list.push(0);
+        self.push_word(u64::from(b), 1)
+    }
+
+    /// Add the `n_bits` least-significant bits of `byte` to the list, least-significant bit first
+    pub(crate) fn push_byte_lsb(&mut self, n_bits: u8, byte: u8) -> Result<(), Error> {
+        if n_bits > 8 {
+            return Err(Error::InternalError(format!(
+                "cannot push {n_bits} bits from a byte"
+            )));
+        }
+
+        self.push_word(u64::from(byte), u32::from(n_bits))
+    }
+
+    /// Add the `count` least-significant bits of `bits` to the list, least-significant bit first
+    ///
+    /// Any bits above the `count`th are ignored.
+    ///
+    pub(crate) fn push_word(&mut self, bits: u64, count: u32) -> Result<(), Error> {
+        if self.digit_count > 0 {
+            return Err(Error::InternalError(
+                "cannot push bits while a group of digits is incomplete".to_string(),
+            ));
+        }
+
+        self.push_bits(bits, count)
+    }
+
+    /// Add a base-`group.radix` digit to the list
+    ///
+    /// Digits are collected until there's a whole group of them, which is then written out as a
+    /// single number, least-significant digit first.  If the list is turned into bytes with
+    /// `vec()` part-way through a group, the rest of the group is filled with zeroes; pushing
+    /// anything other than digits before the group is complete is an error.
+    ///
+    pub(crate) fn push_digit(&mut self, group: &RadixGroup, digit: u8) -> Result<(), Error> {
+        if digit >= group.radix {
+            return Err(Error::InternalError(format!(
+                "cannot push {digit} as a base-{} digit",
+                group.radix
+            )));
         }
 
-        if b {
-            let byte = self.list.get_mut(self.curbyte).ok_or_else(|| {
-                Error::InternalError(format!("Could not get byte {} of vector", self.curbyte))
-            })?;
-            *byte |= self.bitmask;
+        self.digits = self
+            .digits
+            .saturating_add(u64::from(digit).saturating_mul(self.place));
+        self.place = self.place.saturating_mul(u64::from(group.radix));
+        self.digit_count = self.digit_count.saturating_add(1);
+        self.group_bits = group.bits;
+
+        if self.digit_count >= group.digits {
+            self.flush_digits()?;
         }
 
-        self.next_bit();
         Ok(())
     }
 
+    /// Write out whatever digits have been collected as a complete group
+    fn flush_digits(&mut self) -> Result<(), Error> {
+        let digits = std::mem::take(&mut self.digits);
+        self.digit_count = 0;
+        self.place = 1;
+
+        self.push_bits(digits, self.group_bits)
+    }
+
+    /// The guts of `push_word()`, without the check for incomplete digit groups
+    fn push_bits(&mut self, bits: u64, count: u32) -> Result<(), Error> {
+        if count > u64::BITS {
+            return Err(Error::InternalError(format!(
+                "cannot push {count} bits from a {} bit word",
+                u64::BITS
+            )));
+        }
+
+        let masked = bits & low_bits(count);
+
+        self.word |= masked.checked_shl(self.used).unwrap_or(0);
+        let total = self.used.saturating_add(count);
+
+        if total >= u64::BITS {
+            self.list.extend_from_slice(&self.word.to_le_bytes());
+            // Whatever didn't fit in the word we just wrote out goes into the next one
+            self.word = masked
+                .checked_shr(u64::BITS.saturating_sub(self.used))
+                .unwrap_or(0);
+            self.used = total.saturating_sub(u64::BITS);
+        } else {
+            self.used = total;
+        }
+
+        Ok(())
+    }
+
+    /// Remove, and return, the bytes that have been completely filled so far
+    ///
+    /// The bits that don't yet make up a whole word stay where they are, so pushing can carry on
+    /// afterwards as though nothing had happened; `vec()` only returns what's been pushed since
+    /// the last time the bytes were taken.  This is what keeps memory use down when packing a
+    /// long sequence of bits in chunks.
+    ///
+    pub(crate) fn take_bytes(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.list)
+    }
+
     /// Get the sequence of bytes representing the pushed bits
     pub(crate) fn vec(&self) -> Vec<u8> {
-        let mut v = vec![0u8; self.list.len()];
-        v.clone_from_slice(&self.list);
+        if self.digit_count > 0 {
+            let mut finished = Self {
+                list: self.list.clone(),
+                word: self.word,
+                used: self.used,
+                digits: self.digits,
+                digit_count: self.digit_count,
+                place: self.place,
+                group_bits: self.group_bits,
+            };
+
+            // Can't fail, because group_bits is never more than 32
+            if finished.flush_digits().is_ok() {
+                return finished.vec();
+            }
+        }
+
+        let tail = num::Integer::div_ceil(&self.used, &8);
+        let mut v = Vec::with_capacity(self.list.len().saturating_add(8));
+
+        v.extend_from_slice(&self.list);
+        v.extend(
+            self.word
+                .to_le_bytes()
+                .iter()
+                .take(usize::try_from(tail).unwrap_or(8)),
+        );
         v
     }
+}
 
-    fn_next_bit!();
+/// How a list of base-`radix` digits is split into groups
+///
+/// Packing each digit into a whole number of bits wastes most of a bit per digit whenever the
+/// radix isn't a power of two (a trinary digit needs 2 bits, but only carries about 1.58 bits of
+/// information).  Instead, as many digits as will fit into 32 bits are treated as a single
+/// base-`radix` number, which is written out in just enough bits to hold the largest number that
+/// many digits could make.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RadixGroup {
+    /// The base of the digits
+    pub(crate) radix: u8,
+    /// How many digits make up a group
+    pub(crate) digits: u32,
+    /// How many bits each group takes up
+    pub(crate) bits: u32,
+    /// `radix` raised to the power of `digits`; every group is less than this
+    pub(crate) limit: u64,
+}
+
+impl RadixGroup {
+    /// Work out the grouping for digits of the given radix
+    ///
+    /// Radixes less than 2 don't make any sense, and are treated as 2.  Nothing in here can
+    /// overflow, because `limit` is never more than `2^32` before being multiplied by a `u8`.
+    ///
+    #[allow(clippy::arithmetic_side_effects)]
+    pub(crate) const fn new(radix: u8) -> Self {
+        let base = if radix < 2 { 2 } else { radix };
+        let mut digits = 0;
+        let mut limit = 1u64;
+
+        while limit * (base as u64) <= 1u64 << 32u32 {
+            limit *= base as u64;
+            digits += 1;
+        }
+
+        RadixGroup {
+            radix: base,
+            digits,
+            bits: u64::BITS - (limit - 1).leading_zeros(),
+            limit,
+        }
+    }
 }
 
 /// Read bits out of a packed slice o' bytes
-pub(crate) struct ReadableBitList {
+///
+/// The bytes are only ever read, so they're borrowed rather than copied; parsing a ciphertext
+/// shouldn't have to allocate anything more than the storage for the parsed values.
+///
+pub(crate) struct ReadableBitList<'a> {
     /// Where the bits are read from
-    list: Vec<u8>,
-    /// The byte that is currently being read from
-    curbyte: usize,
-    /// Which bit in the current byte is next to be read, represented as a "mask"
-    bitmask: u8,
+    list: &'a [u8],
+    /// The index of the next bit to be read
+    pos: usize,
 }
 
-impl ReadableBitList {
+impl<'a> ReadableBitList<'a> {
     /// Create a bitlist pre-filled with bits from the given slice
     ///
-    /// A pre-filled `ReadableBitList` can only have bits read off one-at-a-time from the front, with `shift()`.
+    /// A pre-filled `ReadableBitList` can only have bits read off from the front, with `shift()`
+    /// (or `shift_n()`, for more than one at a time).
     ///
-    pub(crate) fn from_slice(s: &[u8]) -> Self {
-        let mut v = vec![0u8; s.len()];
-        v.clone_from_slice(s);
-
-        Self {
-            list: v,
-            curbyte: 0,
-            bitmask: 1,
-        }
+    pub(crate) fn from_slice(s: &'a [u8]) -> Self {
+        Self { list: s, pos: 0 }
     }
 
     /// Read the next bit off the list
     ///
     /// Returns `None` if we've reached the end of the list.
     ///
+    #[cfg(test)] // The unpackers all work in bulk, but this is handy for testing
     pub(crate) fn shift(&mut self) -> Option<bool> {
-        let r = self.list.get(self.curbyte).map(|b| *b & self.bitmask > 0);
-        self.next_bit();
+        self.shift_n(1).map(|b| b == 1)
+    }
+
+    /// Read the next `n` bits off the list, with the first bit read in the least-significant
+    /// position of the returned word
+    ///
+    /// Returns `None`, and doesn't consume anything, if there aren't `n` bits left in the list,
+    /// or if `n` is more than will fit in a word.
+    ///
+    pub(crate) fn shift_n(&mut self, n: usize) -> Option<u64> {
+        let count = u32::try_from(n).ok()?;
+
+        if count > u64::BITS || n > self.remaining() {
+            return None;
+        }
+
+        let w = self.peek_word() & low_bits(count);
+        self.pos = self.pos.saturating_add(n);
+
+        Some(w)
+    }
+
+    /// Get the next 64 bits in the list, without consuming them
+    ///
+    /// If there are less than 64 bits left, the missing bits are zero.
+    ///
+    pub(crate) fn peek_word(&self) -> u64 {
+        let (byte, offset) = num::Integer::div_rem(&self.pos, &8);
+        let mut buf = [0u8; 9];
+
+        for (dst, src) in buf.iter_mut().zip(self.list.iter().skip(byte)) {
+            *dst = *src;
+        }
+
+        let (head, tail) = buf.split_at(8);
+        let mut lo = [0u8; 8];
+        lo.copy_from_slice(head);
 
-        r
+        let shift = u32::try_from(offset).unwrap_or(0);
+        let hi = u64::from(tail.first().copied().unwrap_or(0));
+
+        u64::from_le_bytes(lo).checked_shr(shift).unwrap_or(0)
+            | hi.checked_shl(u64::BITS.saturating_sub(shift)).unwrap_or(0)
+    }
+
+    /// How many bits are yet to be read
+    pub(crate) fn remaining(&self) -> usize {
+        self.list.len().saturating_mul(8).saturating_sub(self.pos)
     }
 
     /// Reports whether all bits in the list have been read
@@ -120,11 +328,16 @@ impl ReadableBitList {
     /// malformed, due to there being extra "garbage" data at the end.
     ///
     pub(crate) fn fully_consumed(&self) -> bool {
-        self.curbyte == self.list.len()
-            || (self.curbyte == self.list.len().saturating_sub(1) && self.bitmask > 1)
+        num::Integer::div_ceil(&self.pos, &8) >= self.list.len()
     }
+}
 
-    fn_next_bit!();
+/// A word with the `count` least-significant bits set
+fn low_bits(count: u32) -> u64 {
+    u64::MAX
+        .checked_shr(u64::BITS.saturating_sub(count))
+        .filter(|_| count > 0)
+        .unwrap_or(0)
 }
 
 #[cfg(test)]
@@ -169,7 +382,7 @@ mod tests {
 
     #[test]
     fn micro_shift() {
-        let mut bl = ReadableBitList::from_slice(&vec![0x15u8]);
+        let mut bl = ReadableBitList::from_slice(&[0x15u8]);
 
         assert_eq!(Some(true), bl.shift());
         assert_eq!(Some(false), bl.shift());
@@ -184,7 +397,7 @@ mod tests {
 
     #[test]
     fn smol_shift() {
-        let mut bl = ReadableBitList::from_slice(&vec![0x10u8, 0x01, 0x00]);
+        let mut bl = ReadableBitList::from_slice(&[0x10u8, 0x01, 0x00]);
 
         for _ in 0..4 {
             assert_eq!(Some(false), bl.shift());
@@ -202,4 +415,135 @@ mod tests {
         }
         assert_eq!(None, bl.shift());
     }
+
+    #[test]
+    fn word_push() {
+        let mut bl = WritableBitList::new(1);
+
+        bl.push_word(0b101, 3).unwrap();
+        bl.push_word(0xffff_ffff_ffff_ffff, 62).unwrap();
+        bl.push_word(0b1111_0000, 4).unwrap();
+
+        assert_eq!(
+            vec![0xfdu8, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01],
+            bl.vec()
+        );
+    }
+
+    #[test]
+    fn word_push_rejects_oversized_counts() {
+        let mut bl = WritableBitList::new(1);
+
+        assert!(bl.push_word(0, 65).is_err());
+    }
+
+    #[test]
+    fn radix_groups() {
+        assert_eq!(
+            (32, 32),
+            (RadixGroup::new(2).digits, RadixGroup::new(2).bits)
+        );
+        assert_eq!(
+            (20, 32),
+            (RadixGroup::new(3).digits, RadixGroup::new(3).bits)
+        );
+        assert_eq!(
+            (13, 31),
+            (RadixGroup::new(5).digits, RadixGroup::new(5).bits)
+        );
+        assert_eq!(
+            (4, 32),
+            (RadixGroup::new(255).digits, RadixGroup::new(255).bits)
+        );
+        assert_eq!(3_486_784_401, RadixGroup::new(3).limit);
+    }
+
+    #[test]
+    fn digit_push() {
+        let trinary = RadixGroup::new(3);
+        let mut bl = WritableBitList::new(1);
+
+        bl.push_digit(&trinary, 2).unwrap();
+        bl.push_digit(&trinary, 1).unwrap();
+
+        // An incomplete group gets padded out to a whole one
+        assert_eq!(vec![5u8, 0, 0, 0], bl.vec());
+        assert!(bl.push_word(0, 1).is_err());
+        assert!(bl.push_digit(&trinary, 3).is_err());
+
+        for _ in 2..20 {
+            bl.push_digit(&trinary, 0).unwrap();
+        }
+
+        // ... and now there's nothing in the way of pushing bits again
+        bl.push_word(1, 1).unwrap();
+        assert_eq!(vec![5u8, 0, 0, 0, 1], bl.vec());
+    }
+
+    #[test]
+    fn word_shift() {
+        let mut bl =
+            ReadableBitList::from_slice(&[0xfdu8, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]);
+
+        assert_eq!(Some(0b101), bl.shift_n(3));
+        assert_eq!(Some(0x3fff_ffff_ffff_ffff), bl.shift_n(62));
+        assert_eq!(None, bl.shift_n(8));
+        assert_eq!(Some(0), bl.shift_n(7));
+        assert!(bl.fully_consumed());
+        assert_eq!(None, bl.shift());
+    }
+
+    #[test]
+    fn bulk_push() {
+        let mut bl = WritableBitList::new(1);
+
+        bl.push_byte_lsb(3, 0b101).unwrap();
+        bl.push_byte_lsb(2, 0b11).unwrap();
+        bl.push_byte_lsb(8, 0x81).unwrap();
+        bl.push_byte_lsb(0, 0xff).unwrap();
+        for _ in 0..10 {
+            bl.push_byte_lsb(7, 0x7f).unwrap();
+        }
+
+        let mut expected = vec![0x3du8, 0xf0];
+        expected.extend([0xff; 8]);
+        expected.push(0x07);
+        assert_eq!(expected, bl.vec());
+
+        assert!(bl.push_byte_lsb(9, 0).is_err());
+    }
+
+    #[test]
+    fn bulk_shift() {
+        let mut bl = ReadableBitList::from_slice(&[0x3du8, 0xf0, 0xff]);
+
+        assert_eq!(Some(0b101), bl.shift_n(3));
+        assert_eq!(Some(0b11), bl.shift_n(2));
+        assert_eq!(Some(0x81), bl.shift_n(8));
+        assert_eq!(None, bl.shift_n(65));
+        assert_eq!(Some(0x7ff), bl.shift_n(11));
+        assert_eq!(None, bl.shift_n(1));
+    }
+
+    quickcheck! {
+        fn word_roundtrip(input: Vec<(u64, u8)>) -> bool {
+            let chunks: Vec<(u64, u32)> = input
+                .into_iter()
+                .map(|(bits, count)| (bits, u32::from(count % 65)))
+                .collect();
+            let mut w = WritableBitList::new(1);
+
+            for (bits, count) in &chunks {
+                w.push_word(*bits, *count).unwrap();
+            }
+
+            let packed = w.vec();
+            let mut r = ReadableBitList::from_slice(&packed);
+
+            chunks
+                .iter()
+                .all(|(bits, count)| r.shift_n(*count as usize) == Some(bits & low_bits(*count)))
+                && r.fully_consumed()
+        }
+    }
 }
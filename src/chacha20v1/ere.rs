@@ -0,0 +1,68 @@
+//! Equality-Revealing Encryption (ERE) using `ChaCha20` as the Pseudo-Random Function and BLAKE2s as the
+//! Hash Function.
+//!
+//! Apart from the primitives, everything works just as it does in
+//! [`aes128v1::ere`](crate::aes128v1::ere), which has more examples, although ciphertexts from the
+//! two can't be compared with each other.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use cretrit::chacha20v1::ere;
+//!
+//! # fn main() -> Result<(), cretrit::Error> {
+//! # let key = [0u8; 32];
+//! let cipher = ere::Cipher::<4, 256>::new(&key)?;
+//! let forty_two = cipher.full_encrypt(&42u32.try_into()?)?;
+//! let over_nine_thousand = cipher.right_encrypt(&9001u32.try_into()?)?;
+//!
+//! assert!(!forty_two.try_eq(&over_nine_thousand)?);
+//! # Ok(())
+//! # }
+//! ```
+
+crate::suite_modules::ere_module!("chacha20v1");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::random_key;
+
+    #[test]
+    fn tiny_comparisons() {
+        let cipher = Cipher::<1, 4>::new(&random_key()).unwrap();
+
+        let n1 = cipher
+            .full_encrypt(&PlainText::<1, 4>::new([1u16]))
+            .unwrap();
+        let n2 = cipher
+            .full_encrypt(&PlainText::<1, 4>::new([2u16]))
+            .unwrap();
+
+        assert_eq!(0, n1.compare(&n1).unwrap());
+        assert_eq!(1, n1.compare(&n2).unwrap());
+        assert_eq!(1, n2.compare(&n1).unwrap());
+    }
+
+    #[test]
+    fn serialization_roundtrip() {
+        let cipher = Cipher::<4, 16>::new(&random_key()).unwrap();
+        let ct = cipher.full_encrypt(&1234u16.try_into().unwrap()).unwrap();
+        let stored = cipher.right_encrypt(&1234u16.try_into().unwrap()).unwrap();
+
+        let parsed = CipherText::<4, 16>::from_slice(&ct.to_vec().unwrap()).unwrap();
+
+        assert!(parsed.try_eq(&stored).unwrap());
+    }
+
+    quickcheck! {
+        fn u32_eq(a: u32, b: u32) -> bool {
+            let cipher = Cipher::<4, 256>::new(&random_key()).unwrap();
+
+            let ca = cipher.full_encrypt(&a.try_into().unwrap()).unwrap();
+            let cb = cipher.right_encrypt(&b.try_into().unwrap()).unwrap();
+
+            (a == b) == ca.try_eq(&cb).unwrap()
+        }
+    }
+}
@@ -0,0 +1,48 @@
+//! Comparison-Revealing Encryption using `ChaCha20` as the Pseudo-Random Function and BLAKE2s as
+//! the Hash Function.
+//!
+//! On CPUs with AES instructions, the [`aes128v1`](crate::aes128v1) ciphersuite is hard to beat,
+//! but on CPUs without them (such as many embedded ARM cores), software AES and CMAC end up
+//! taking most of the time spent encrypting.  This ciphersuite uses primitives that are quick
+//! in software instead: `ChaCha20` (which is already what generates nonces and block permutations in
+//! every ciphersuite) as the Pseudo-Random Function, and keyed BLAKE2s for the Hash Function and
+//! the key derivation function.  BLAKE2s works on 32 bit words, which suits small CPUs.  As with
+//! [`aes256v1`](crate::aes256v1), each block of a left ciphertext is 32 bytes, while right
+//! ciphertexts are the same size as in every other ciphersuite.
+//!
+//! There are modules for orderable ciphertexts ([`ore`]), for ciphertexts that only have to be
+//! compared for equality ([`ere`]), and for partially ordered values ([`pore`]).  Ciphertexts
+//! from different ciphersuites can't be compared with each other, even if they were encrypted
+//! with the same key.
+
+pub mod ere;
+pub mod ore;
+pub mod pore;
+
+use rand_chacha::ChaCha20Rng;
+
+use crate::ciphersuite::{CipherSuite as SuperSweet, ListedCipherSuite};
+use crate::{hash, kbkdf, prf, prp};
+
+/// The full set of parameters that make up the [`chacha20v1`](super) ciphersuite.
+///
+/// This struct simply represents the concrete choices about which cryptographic operators to use
+/// for the various parts of the Comparison-Revealing Encryption system.  These can *never* change;
+/// if anything needs to change, for any reason, a new ciphersuite is defined with the different
+/// parameters.
+///
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CipherSuite<const W: u16, const M: u8> {}
+
+impl<const W: u16, const M: u8> SuperSweet<W, M> for CipherSuite<W, M> {
+    const NAME: &'static str = "chacha20v1";
+
+    type RNG = ChaCha20Rng;
+    type PRF = prf::ChaCha20PRF;
+    type HF = hash::BLAKE2SHF<M>;
+    type PRP = prp::RandShufflePRP<W>;
+    type KBKDF = kbkdf::BLAKE2S256;
+}
+
+impl<const W: u16, const M: u8> ListedCipherSuite<W, M> for CipherSuite<W, M> {}
@@ -2,18 +2,27 @@
 //!
 
 use core::fmt::Debug;
-use rand::{Rng, SeedableRng};
 use std::cell::RefCell;
+#[cfg(feature = "nonce-tracking")]
+use std::collections::HashSet;
 use std::marker::PhantomData;
+use std::sync::Arc;
 
-use crate::ciphersuite::CipherSuite;
-use crate::ciphertext::CipherText;
+use crate::audit::{AuditOperation, AuditSink, Auditor};
+use crate::ciphersuite::{CipherSuite, ListedCipherSuite};
+use crate::ciphertext::{CipherText, LeftCipherText, Serializable};
 use crate::cmp::Comparator;
+use crate::costs::CostEstimate;
+use crate::equality_token::EqualityToken;
+use crate::filter::EqualityFilter;
 use crate::kbkdf::{KBKDFInit, KBKDF};
+use crate::params::Params;
 use crate::plaintext::PlainText;
 use crate::prf::{PseudoRandomFunction, PseudoRandomFunctionInit};
 use crate::prp::{PseudoRandomPermutation, PseudoRandomPermutationInit};
+use crate::rng::{CryptoRng, Rng, RngCore, SeedableRng};
 use crate::Error;
+use zeroize::Zeroizing;
 
 /// Something capable of turning [`PlainText`s](crate::PlainText) into comparable
 /// [`CipherText`s](crate::CipherText) by means of encryption.
@@ -30,11 +39,15 @@ use crate::Error;
 /// ciphersuites.  At the moment, those are:
 ///
 /// * [`aes128v1`](crate::aes128v1) -- ciphersuite using AES128 as the primary cryptographic
-/// primitive, which provides
+///   primitive, which provides
 ///   * [`ere::Cipher`](crate::aes128v1::ere::Cipher) for equality comparisons (`==`, `!=`), and
 ///   * [`ore::Cipher`](crate::aes128v1::ore::Cipher) for ordering comparisons (`<`, `>`, `<=`,
-///   `>=`, `==`, `!=`).
+///     `>=`, `==`, `!=`).
+/// * [`aes256v1`](crate::aes256v1) -- the same, but with AES256 and 256 bit keys throughout.
+/// * [`chacha20v1`](crate::chacha20v1) -- ciphersuite using `ChaCha20` and BLAKE2s, for CPUs
+///   without AES acceleration.
 ///
+/// Each of them also has a `pore` module, for partial orders.
 ///
 /// These more-contrained Cipher types only require you to specify the block count and width (`N`
 /// and `W`) and the key to use for encryption, which is far more tractable.
@@ -56,10 +69,43 @@ pub struct Cipher<
     /// The instance of the PRP in use
     prp: S::PRP,
 
+    /// Where query keys come from
+    ///
+    /// This has its own key, derived from the root key, so that query keys have nothing in common
+    /// with any other part of a ciphertext.
+    ///
+    /// Right-only ciphers don't have one, since they can't make query keys anyway.
+    ///
+    query_kdf: Option<Arc<S::KBKDF>>,
+
+    /// A short, public fingerprint of the root key
+    key_check_value: [u8; 3],
+
+    /// Which kinds of ciphertext this cipher is allowed to produce
+    role: Role,
+
+    /// Who to tell about everything we do, if anyone
+    auditor: Option<Auditor>,
+
+    /// Every nonce base this cipher has handed out, so that a repeat can be caught
+    #[cfg(feature = "nonce-tracking")]
+    pub(crate) seen_nonces: RefCell<HashSet<[u8; 16]>>,
+
     /// Bumf to keep the compiler happy
     _ffs: PhantomData<CMP>,
 }
 
+/// Which kinds of ciphertext a [`Cipher`] is allowed to produce
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Role {
+    /// Anything at all
+    Full,
+    /// Left ciphertexts, for querying, but nothing that can be stored
+    LeftOnly,
+    /// Right ciphertexts, for storing, but nothing that can be used to query
+    RightOnly,
+}
+
 impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, const M: u8> Debug
     for Cipher<S, CMP, N, W, M>
 {
@@ -71,39 +117,453 @@ impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, con
 impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, const M: u8>
     Cipher<S, CMP, N, W, M>
 {
+    /// Refuse to compile a cipher whose parameters can't possibly encrypt anything useful
+    const VALID_PARAMS: () = assert!(
+        N >= 1 && W >= 2 && M >= 2,
+        "a Cipher needs at least one block (N), two values per block (W), and two comparison values (M)"
+    );
+
     /// Create a new Cipher.
     ///
     /// All ciphertexts produced with the same key (and all other parameters) can be compared
     /// against each other.  As such, it is just as important that the key used for these
     /// encryptions is as secure and secret as any other cryptographic key.
     ///
+    /// The key is borrowed, rather than copied, and every intermediate key derived from it while
+    /// setting up the cipher is zeroized once it's no longer needed.  Scrubbing the caller's own
+    /// copy of the key is, however, up to the caller.
+    ///
+    /// Degenerate parameters -- no blocks at all, or blocks that can only hold one value -- are
+    /// rejected when the program is compiled, rather than when the cipher is created:
+    ///
+    /// ```rust,compile_fail
+    /// # use cretrit::aes128v1::ore;
+    /// let cipher = ore::Cipher::<4, 1>::new(&[0u8; 32]);
+    /// ```
+    ///
     /// # Errors
     ///
     /// Can return an error if any of the underlying cryptographic operations can't complete, or if
     /// there's a bug somewhere.
     ///
     pub fn new(key: &[u8; 32]) -> Result<Self, Error>
+    where
+        <S as CipherSuite<W, M>>::PRF: PseudoRandomFunctionInit,
+        <S as CipherSuite<W, M>>::PRP: PseudoRandomPermutationInit<W>,
+        <S as CipherSuite<W, M>>::KBKDF: 'static,
+    {
+        Self::with_rng(key, SeedableRng::from_entropy(), Role::Full)
+    }
+
+    /// Create a new Cipher whose randomness comes from the given RNG, rather than the operating
+    /// system.
+    ///
+    /// The cipher's own RNG (the one its ciphersuite specifies) is seeded from `rng`,
+    /// once, when the cipher is created; after that, `rng` isn't used again.  This is for when
+    /// the operating system's entropy source isn't the one you want to trust, such as when a
+    /// hardware RNG is available.  Because `rng` has to be a [`CryptoRng`], a deterministic
+    /// CSPRNG seeded from a fixed value works too, and gives reproducible ciphertexts, but that
+    /// makes every right ciphertext predictable to anyone who knows the seed, so keep it to
+    /// tests.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::ore;
+    /// use rand::SeedableRng;
+    /// use rand_chacha::ChaCha20Rng;
+    ///
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let key = [0u8; 32];
+    /// let mut rng = ChaCha20Rng::from_entropy();
+    /// let cipher = ore::Cipher::<4, 256>::new_with_rng(&key, &mut rng)?;
+    /// let forty_two = cipher.full_encrypt(&42u32.try_into()?)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::CryptoError`] if a seed can't be read from `rng`.  Can also return an
+    /// error if any of the underlying cryptographic operations can't complete, or if there's a bug
+    /// somewhere.
+    ///
+    pub fn new_with_rng<R: RngCore + CryptoRng>(key: &[u8; 32], rng: &mut R) -> Result<Self, Error>
+    where
+        <S as CipherSuite<W, M>>::PRF: PseudoRandomFunctionInit,
+        <S as CipherSuite<W, M>>::PRP: PseudoRandomPermutationInit<W>,
+        <S as CipherSuite<W, M>>::KBKDF: 'static,
+    {
+        let seeded = S::RNG::from_rng(rng)
+            .map_err(|e| Error::CryptoError(format!("could not seed RNG: {e}")))?;
+
+        Self::with_rng(key, seeded, Role::Full)
+    }
+
+    /// Create a new Cipher whose "random" values all come from the given seed.
+    ///
+    /// **Never use this in production.**  Every ciphertext produced by a cipher created this way
+    /// is completely predictable to anyone who knows (or guesses) the seed, which throws away the
+    /// security of the right ciphertexts entirely.  It exists so that test suites can check
+    /// against fixed ciphertexts, and so that a bug report can include the exact ciphertexts that
+    /// caused the problem.  It is only available when the `test-vectors` feature is enabled, and
+    /// that feature should never be enabled in a release build.
+    ///
+    /// Two ciphers created with the same key and seed will produce identical ciphertexts for the
+    /// same sequence of encryptions.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::ore;
+    /// use cretrit::SerializableCipherText;
+    ///
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let key = [0u8; 32];
+    /// let c1 = ore::Cipher::<4, 256>::new_with_insecure_seed(&key, 42)?;
+    /// let c2 = ore::Cipher::<4, 256>::new_with_insecure_seed(&key, 42)?;
+    ///
+    /// assert_eq!(
+    ///     c1.full_encrypt(&9001u32.try_into()?)?.to_vec()?,
+    ///     c2.full_encrypt(&9001u32.try_into()?)?.to_vec()?,
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if any of the underlying cryptographic operations can't complete, or if
+    /// there's a bug somewhere.
+    ///
+    #[cfg(feature = "test-vectors")]
+    pub fn new_with_insecure_seed(key: &[u8; 32], seed: u64) -> Result<Self, Error>
+    where
+        <S as CipherSuite<W, M>>::PRF: PseudoRandomFunctionInit,
+        <S as CipherSuite<W, M>>::PRP: PseudoRandomPermutationInit<W>,
+        <S as CipherSuite<W, M>>::KBKDF: 'static,
+    {
+        Self::with_rng(key, SeedableRng::seed_from_u64(seed), Role::Full)
+    }
+
+    /// Do the actual work of creating a new Cipher, with the RNG provided, leaving out the query
+    /// key if the given role can't make left ciphertexts
+    fn with_rng(key: &[u8; 32], rng: S::RNG, role: Role) -> Result<Self, Error>
     where
         <S as CipherSuite<W, M>>::PRF: PseudoRandomFunctionInit,
         <S as CipherSuite<W, M>>::PRP: PseudoRandomPermutationInit<W>,
         <S as CipherSuite<W, M>>::KBKDF: 'static,
     {
         #![allow(clippy::similar_names)] // I think we can keep things clear in here, prf/prp is totes different
+        #[allow(clippy::let_unit_value)] // Referring to it is what makes the compiler check it
+        let () = Self::VALID_PARAMS;
+
         let kbkdf: Box<dyn KBKDF> = S::KBKDF::new(key)
             .map_err(|e| Error::KeyError(format!("failed to create KBKDF instance: {e}")))?;
 
         let prf: S::PRF = PseudoRandomFunctionInit::new(&*kbkdf)?;
         let prp: S::PRP = PseudoRandomPermutationInit::new(&*kbkdf)?;
-        let rng: S::RNG = SeedableRng::from_entropy();
+
+        let mut key_check_value = [0u8; 3];
+        kbkdf.derive_key(&mut key_check_value, b"Cipher.key_check_value")?;
+
+        // Query keys can only be made from left ciphertexts, so a cipher that can't make those
+        // has no business holding the key they're derived with
+        let query_kdf = if role == Role::RightOnly {
+            None
+        } else {
+            let mut query_key: Zeroizing<[u8; 32]> = Zeroizing::new(Default::default());
+            kbkdf.derive_key(&mut *query_key, b"Cipher.query_key")?;
+
+            Some(Arc::from(S::KBKDF::new(&*query_key).map_err(|e| {
+                Error::KeyError(format!("failed to create query KBKDF instance: {e}"))
+            })?))
+        };
 
         Ok(Cipher {
             rng: RefCell::new(rng),
             prf,
             prp,
+            query_kdf,
+            key_check_value,
+            role,
+            auditor: None,
+            #[cfg(feature = "nonce-tracking")]
+            seen_nonces: RefCell::new(HashSet::new()),
             _ffs: PhantomData,
         })
     }
 
+    /// Create a new Cipher which refuses to produce a "left" ciphertext.
+    ///
+    /// Left ciphertexts are deterministic, and so anything that can produce them can produce
+    /// correlatable ciphertexts.  It would be nice to give places (like storage nodes) that
+    /// should only ever be writing IND-CPA secure ciphertexts a key that can't make anything
+    /// else, but this scheme can't do that: right ciphertexts are made with the same PRF and PRP
+    /// keys as left ones, so any key that can make one can make the other.  What a right-only
+    /// cipher does is refuse to do anything that would involve generating a left ciphertext (such
+    /// as [`full_encrypt()`](Self::full_encrypt) or [`query_key()`](Self::query_key)), which stops
+    /// a service from producing them *accidentally*.  It can still compare ciphertexts that
+    /// already have a left part, such as those sent along with a query.
+    ///
+    /// A right-only cipher holds the same PRF and PRP keys as a regular one; the only thing it
+    /// leaves out is the key that [query keys](Self::query_key) are made with.  The key given to
+    /// a right-only cipher is just as able to produce left ciphertexts when given to a regular
+    /// one; see [`new_left_only()`](Self::new_left_only) for more on that.
+    ///
+    /// There's no way to turn a right-only cipher back into a regular one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::ore;
+    ///
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let key = [0u8; 32];
+    /// let cipher = ore::Cipher::<4, 256>::new_right_only(&key)?;
+    ///
+    /// assert!(cipher.right_encrypt(&42u32.try_into()?).is_ok());
+    /// assert!(cipher.full_encrypt(&42u32.try_into()?).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if any of the underlying cryptographic operations can't complete, or if
+    /// there's a bug somewhere.
+    ///
+    pub fn new_right_only(key: &[u8; 32]) -> Result<Self, Error>
+    where
+        <S as CipherSuite<W, M>>::PRF: PseudoRandomFunctionInit,
+        <S as CipherSuite<W, M>>::PRP: PseudoRandomPermutationInit<W>,
+        <S as CipherSuite<W, M>>::KBKDF: 'static,
+    {
+        Self::with_rng(key, SeedableRng::from_entropy(), Role::RightOnly)
+    }
+
+    /// Create a new Cipher which can never produce a "right" ciphertext.
+    ///
+    /// This is the counterpart to [`new_right_only()`](Self::new_right_only), for services that
+    /// issue queries but should never be writing anything to storage.  A left-only cipher can
+    /// make left ciphertexts ([`left_encrypt()`](Self::left_encrypt)) and query keys, which is
+    /// all that a [`PreparedQuery`](crate::PreparedQuery) or an
+    /// [`EqualityFilter`](crate::EqualityFilter) needs, but refuses to do anything that would
+    /// involve generating a right ciphertext (such as [`right_encrypt()`](Self::right_encrypt) or
+    /// [`full_encrypt()`](Self::full_encrypt)).
+    ///
+    /// Be clear about what this does and doesn't protect against.  Comparing a left ciphertext
+    /// with a right one works because both were made with the same PRF and PRP keys, so those
+    /// keys can't be split between "left" and "right" services: a left-only cipher holds exactly
+    /// the same key material as a regular one, and so does a right-only one.  Left-only and
+    /// right-only ciphers stop a service from *accidentally* producing the wrong kind of
+    /// ciphertext; anyone who steals the key can still produce both.
+    ///
+    /// There's no way to turn a left-only cipher back into a regular one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::ore;
+    ///
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let key = [0u8; 32];
+    /// let cipher = ore::Cipher::<4, 256>::new_left_only(&key)?;
+    ///
+    /// assert!(cipher.left_encrypt(&42u32.try_into()?).is_ok());
+    /// assert!(cipher.right_encrypt(&42u32.try_into()?).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if any of the underlying cryptographic operations can't complete, or if
+    /// there's a bug somewhere.
+    ///
+    pub fn new_left_only(key: &[u8; 32]) -> Result<Self, Error>
+    where
+        <S as CipherSuite<W, M>>::PRF: PseudoRandomFunctionInit,
+        <S as CipherSuite<W, M>>::PRP: PseudoRandomPermutationInit<W>,
+        <S as CipherSuite<W, M>>::KBKDF: 'static,
+    {
+        Self::with_rng(key, SeedableRng::from_entropy(), Role::LeftOnly)
+    }
+
+    /// Whether this cipher has been forbidden from producing left ciphertexts
+    ///
+    /// See [`new_right_only()`](Self::new_right_only) for details.
+    ///
+    #[must_use]
+    pub fn is_right_only(&self) -> bool {
+        self.role == Role::RightOnly
+    }
+
+    /// Whether this cipher has been forbidden from producing right ciphertexts
+    ///
+    /// See [`new_left_only()`](Self::new_left_only) for details.
+    ///
+    #[must_use]
+    pub fn is_left_only(&self) -> bool {
+        self.role == Role::LeftOnly
+    }
+
+    /// A short value which identifies the key this cipher was created with
+    ///
+    /// Key check values (KCVs) let you confirm that the right key has been loaded, without having to
+    /// encrypt (or decrypt) any real data.  Record the KCV when a key is generated, and then compare
+    /// it against the KCV of the cipher you've just created as part of a deployment check, or when a
+    /// service starts up.
+    ///
+    /// The KCV is derived from the key by the ciphersuite's KDF, under its own label, so it reveals
+    /// nothing useful about the key or any other value derived from it.  It only depends on the key
+    /// and the ciphersuite, so ciphers with different block counts, widths, or comparators (and
+    /// right-only ciphers) all have the same KCV if they were created with the same key.  At only
+    /// 24 bits, it's intended to catch mistakes, like loading the wrong key, not to authenticate
+    /// anything; two different keys will occasionally have the same KCV.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::{ere, ore};
+    ///
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let key = [0u8; 32];
+    /// let expected = ore::Cipher::<4, 256>::new(&key)?.key_check_value();
+    ///
+    /// // ... later, somewhere else ...
+    /// let cipher = ere::Cipher::<8, 16>::new(&key)?;
+    /// assert_eq!(expected, cipher.key_check_value());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    #[must_use]
+    pub fn key_check_value(&self) -> [u8; 3] {
+        self.key_check_value
+    }
+
+    /// Create a new Cipher, after making sure that the given parameters describe it.
+    ///
+    /// This is how a configuration (such as `aes128v1-ore/8x256`) gets resolved into a concrete
+    /// cipher type.  See [`Params`] for an example.  Only ciphers whose ciphersuite is listed in
+    /// [`suites()`](crate::suites) can be created this way.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the parameters don't describe this type of cipher, or if anything
+    /// goes wrong creating the cipher (see [`new()`](Self::new)).
+    ///
+    pub fn from_params(params: &Params, key: &[u8; 32]) -> Result<Self, Error>
+    where
+        S: ListedCipherSuite<W, M>,
+        <S as CipherSuite<W, M>>::PRF: PseudoRandomFunctionInit,
+        <S as CipherSuite<W, M>>::PRP: PseudoRandomPermutationInit<W>,
+        <S as CipherSuite<W, M>>::KBKDF: 'static,
+    {
+        params.check::<S, CMP, N, W, M>()?;
+
+        Self::new(key)
+    }
+
+    /// The parameters that describe this cipher
+    ///
+    /// Only ciphers whose ciphersuite is listed in [`suites()`](crate::suites) have parameters,
+    /// since they're the only ones whose parameters can be parsed again.
+    ///
+    #[must_use]
+    pub fn params(&self) -> Params
+    where
+        S: ListedCipherSuite<W, M>,
+    {
+        Params::of::<S, CMP, N, W, M>()
+    }
+
+    /// Report every operation this cipher performs to the given [`AuditSink`].
+    ///
+    /// The `key_id` and `context` labels are passed to the sink along with each operation, so
+    /// that the audit trail can say which key was used, and what for.  Since they're going into a
+    /// log, they shouldn't contain anything sensitive (like, say, the key itself).
+    ///
+    /// Attaching a new sink replaces any sink that was previously attached.
+    ///
+    #[must_use]
+    pub fn with_audit_sink(
+        mut self,
+        sink: Arc<dyn AuditSink>,
+        key_id: &str,
+        context: &str,
+    ) -> Self {
+        self.auditor = Some(Auditor::new(sink, key_id, context));
+        self
+    }
+
+    /// Run an operation, and tell the audit sink (if there is one) how it went
+    pub(crate) fn audited<T>(
+        &self,
+        operation: AuditOperation,
+        f: impl FnOnce() -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let result = f();
+
+        self.record(operation, result.is_ok());
+
+        result
+    }
+
+    /// Tell the audit sink, if there is one, about an operation
+    fn record(&self, operation: AuditOperation, succeeded: bool) {
+        if let Some(auditor) = &self.auditor {
+            auditor.record(operation, succeeded);
+        }
+    }
+
+    /// Create a new Cipher, with a key derived from a passphrase.
+    ///
+    /// The passphrase is run through Argon2id, with the given salt and parameters, to produce the
+    /// key passed to [`new()`](Self::new).  The salt must be at least
+    /// [`MIN_SALT_LENGTH`](crate::passphrase::MIN_SALT_LENGTH) bytes long, and should be randomly
+    /// generated, and then stored (along with the parameters) wherever you'll need to derive the
+    /// same key again.  Reusing a salt across different passphrases, or (worse) using a fixed salt
+    /// baked into your application, throws away much of the protection that salting provides.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::ore;
+    /// use cretrit::passphrase::PassphraseParams;
+    ///
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// // In real life, this would be random, and stored somewhere
+    /// let salt = b"sixteen byte salt";
+    /// let cipher = ore::Cipher::<4, 256>::from_passphrase(
+    ///     b"correct horse battery staple",
+    ///     salt,
+    ///     &PassphraseParams::default(),
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the salt is too short, if the parameters aren't acceptable to
+    /// Argon2, or if any of the underlying cryptographic operations can't complete.
+    ///
+    #[cfg(feature = "passphrase")]
+    pub fn from_passphrase(
+        passphrase: &[u8],
+        salt: &[u8],
+        params: &crate::passphrase::PassphraseParams,
+    ) -> Result<Self, Error>
+    where
+        <S as CipherSuite<W, M>>::PRF: PseudoRandomFunctionInit,
+        <S as CipherSuite<W, M>>::PRP: PseudoRandomPermutationInit<W>,
+        <S as CipherSuite<W, M>>::KBKDF: 'static,
+    {
+        Self::new(&*crate::passphrase::derive_key(passphrase, salt, params)?)
+    }
+
     /// Encrypt a value and produce a ciphertext that contains both "left" and "right" parts
     ///
     /// For details on ciphertexts and their components, see the struct-level documentation for
@@ -118,7 +578,9 @@ impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, con
         &self,
         value: &PlainText<N, W>,
     ) -> Result<CipherText<S, CMP, N, W, M>, Error> {
-        CipherText::<S, CMP, N, W, M>::new(self, value)
+        self.audited(AuditOperation::FullEncrypt, || {
+            CipherText::<S, CMP, N, W, M>::new(self, value)
+        })
     }
 
     /// Encrypt a value and produce a ciphertext that contains only a "right" part
@@ -135,7 +597,504 @@ impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, con
         &self,
         value: &PlainText<N, W>,
     ) -> Result<CipherText<S, CMP, N, W, M>, Error> {
-        CipherText::<S, CMP, N, W, M>::new_right(self, value)
+        self.audited(AuditOperation::RightEncrypt, || {
+            CipherText::<S, CMP, N, W, M>::new_right(self, value)
+        })
+    }
+
+    /// Encrypt a whole batch of values, producing ciphertexts that contain both "left" and
+    /// "right" parts
+    ///
+    /// The ciphertexts are exactly what [`full_encrypt()`](Self::full_encrypt) would have
+    /// produced for each value in turn, but the parts of the work that are the same for every
+    /// value are only done once.  With the `rayon` feature enabled, the right parts (which is
+    /// where nearly all the work is) are also made in parallel, which is the quickest way there
+    /// is to encrypt lots of values, for a data migration or other bulk load.
+    ///
+    /// Each value is recorded separately with the audit sink, if there is one, as if it had been
+    /// encrypted on its own.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::ore;
+    /// use cretrit::PlainText;
+    /// use std::cmp::Ordering;
+    ///
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let key = [0u8; 32];
+    /// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+    /// let values = (0..100u32)
+    ///     .map(PlainText::try_from)
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    ///
+    /// let ciphertexts = cipher.encrypt_batch(&values)?;
+    ///
+    /// assert_eq!(Ordering::Less, ciphertexts[41].try_cmp(&ciphertexts[42])?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if any of the values can't be encrypted, for any of the reasons that
+    /// [`full_encrypt()`](Self::full_encrypt) can fail; none of the ciphertexts are returned in
+    /// that case.
+    ///
+    pub fn encrypt_batch(
+        &self,
+        values: &[PlainText<N, W>],
+    ) -> Result<Vec<CipherText<S, CMP, N, W, M>>, Error> {
+        let result = CipherText::<S, CMP, N, W, M>::new_batch(self, values, true);
+
+        for _ in values {
+            self.record(AuditOperation::FullEncrypt, result.is_ok());
+        }
+
+        result
+    }
+
+    /// Encrypt a whole batch of values, producing ciphertexts that contain only a "right" part
+    ///
+    /// As with [`encrypt_batch()`](Self::encrypt_batch), the ciphertexts are exactly what
+    /// [`right_encrypt()`](Self::right_encrypt) would have produced for each value in turn, and
+    /// are made in parallel with the `rayon` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if any of the values can't be encrypted, for any of the reasons that
+    /// [`right_encrypt()`](Self::right_encrypt) can fail; none of the ciphertexts are returned in
+    /// that case.
+    ///
+    pub fn right_encrypt_batch(
+        &self,
+        values: &[PlainText<N, W>],
+    ) -> Result<Vec<CipherText<S, CMP, N, W, M>>, Error> {
+        let result = CipherText::<S, CMP, N, W, M>::new_batch(self, values, false);
+
+        for _ in values {
+            self.record(AuditOperation::RightEncrypt, result.is_ok());
+        }
+
+        result
+    }
+
+    /// Encrypt a value and produce a ciphertext that contains only a "right" part, along with a
+    /// one-byte filter tag to store next to it
+    ///
+    /// The tag lets an [`EqualityFilter`](crate::EqualityFilter) rule out most stored values that
+    /// aren't equal to a query without doing a full comparison; see its documentation for how,
+    /// and why storing the tag doesn't give anything away.  Right-only ciphers can produce tags,
+    /// too.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if any of the underlying cryptographic operations can't complete, or if
+    /// there's a bug somewhere.
+    ///
+    pub fn right_encrypt_with_filter_tag(
+        &self,
+        value: &PlainText<N, W>,
+    ) -> Result<(CipherText<S, CMP, N, W, M>, u8), Error> {
+        self.audited(AuditOperation::RightEncrypt, || {
+            let ciphertext = CipherText::<S, CMP, N, W, M>::new_right(self, value)?;
+            let tag = EqualityFilter::for_value(self, value)?.tag(&ciphertext)?;
+
+            Ok((ciphertext, tag))
+        })
+    }
+
+    /// Encrypt a value and produce only the "left" part of its ciphertext
+    ///
+    /// A left ciphertext is all that's needed to compare a value against stored right-only
+    /// ciphertexts, so this is the thing to use for producing query tokens.  It's the same as the
+    /// left part of a [`full_encrypt()`](Self::full_encrypt) ciphertext, but without the cost of
+    /// producing a right part that would only be thrown away.  Left ciphertexts are
+    /// deterministic, so they're for querying with, not storing; see the documentation for
+    /// [`LeftCipherText`](crate::LeftCipherText).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::ore;
+    /// use std::cmp::Ordering;
+    ///
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let key = [0u8; 32];
+    /// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+    /// let stored = cipher.right_encrypt(&9001u32.try_into()?)?;
+    ///
+    /// let query = cipher.left_encrypt(&42u32.try_into()?)?;
+    /// assert_eq!(Ordering::Less, query.compare_with_right(&stored)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// A left ciphertext serializes on its own, into a much smaller query token than a full
+    /// ciphertext, which can be parsed again wherever the comparisons get done:
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::ore;
+    /// use cretrit::SerializableCipherText;
+    /// use std::cmp::Ordering;
+    ///
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let key = [0u8; 32];
+    /// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+    /// let stored = cipher.right_encrypt(&9001u32.try_into()?)?.to_vec()?;
+    ///
+    /// // Where the query is made
+    /// let token = cipher.left_encrypt(&42u32.try_into()?)?.to_vec()?;
+    /// assert!(token.len() < cipher.full_encrypt(&42u32.try_into()?)?.to_vec()?.len());
+    ///
+    /// // Where the data lives, which only ever sees bytes, and doesn't need the key
+    /// let query = ore::LeftCipherText::<4, 256>::from_slice(&token)?;
+    /// let value = ore::CipherText::<4, 256>::from_slice(&stored)?;
+    /// assert_eq!(Ordering::Less, query.compare_with_right(&value)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::NotPermitted`] if this is a right-only cipher.  Can also return an
+    /// error if any of the underlying cryptographic operations can't complete, or if there's a bug
+    /// somewhere.
+    ///
+    pub fn left_encrypt(
+        &self,
+        value: &PlainText<N, W>,
+    ) -> Result<LeftCipherText<S, CMP, N, W, M>, Error> {
+        self.audited(AuditOperation::LeftEncrypt, || {
+            LeftCipherText::encrypt(self, value)
+        })
+    }
+
+    /// Turn a left ciphertext from a cipher with fewer blocks into one that can be compared with
+    /// this cipher's ciphertexts
+    ///
+    /// Changing a cipher's block count (say, from `<4, 256>` to `<8, 256>`, to make room for
+    /// bigger values) leaves old and new ciphertexts unable to be compared with each other.  As
+    /// long as both ciphers use the same key, ciphersuite, comparator, and block width, though,
+    /// the left ciphertext of a `K` block value is exactly the last `K` blocks of the left
+    /// ciphertext of the same value encrypted with `N` blocks, since the extra blocks at the front
+    /// are all zero.  This fills in those zero blocks, producing exactly what
+    /// [`left_encrypt()`](Self::left_encrypt) would have produced for the same value, so a query
+    /// that was encrypted for the old cipher can be compared against ciphertexts stored by the new
+    /// one.
+    ///
+    /// This only works when extending a value with zero blocks doesn't change it, which is the
+    /// case for the unsigned integer conversions and [`PlainText::parse_uint()`].  It doesn't hold
+    /// for encodings where the value depends on the block count, such as
+    /// [`PlainText::parse_int()`] (where the offset changes) or [`PlainText::from_bytes()`]
+    /// (where the padding goes at the end); for those, the query has to be encrypted again.
+    ///
+    /// A left ciphertext that came straight from a cipher with a different key is rejected, but
+    /// one that has been serialized and parsed again has no record of its key; if it wasn't made
+    /// with this cipher's key, the result won't compare correctly against anything.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::ore;
+    /// use std::cmp::Ordering;
+    ///
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let key = [0u8; 32];
+    /// let old_cipher = ore::Cipher::<4, 256>::new(&key)?;
+    /// let new_cipher = ore::Cipher::<8, 256>::new(&key)?;
+    ///
+    /// let old_query = old_cipher.left_encrypt(&42u32.try_into()?)?;
+    /// let stored = new_cipher.right_encrypt(&9001u64.try_into()?)?;
+    ///
+    /// let query = new_cipher.promote_left(&old_query)?;
+    /// assert_eq!(Ordering::Less, query.compare_with_right(&stored)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::RangeError`] if `K` is greater than `N`, [`Error::ParamsError`] if
+    /// `left` is known to have been made with a different key, and [`Error::NotPermitted`] if
+    /// this is a right-only cipher.  Can also return an error if any of
+    /// the underlying cryptographic operations can't complete, or if there's a bug somewhere.
+    ///
+    pub fn promote_left<const K: usize>(
+        &self,
+        left: &LeftCipherText<S, CMP, K, W, M>,
+    ) -> Result<LeftCipherText<S, CMP, N, W, M>, Error> {
+        self.audited(AuditOperation::LeftEncrypt, || {
+            LeftCipherText::zero_extend(self, left)
+        })
+    }
+
+    /// Encrypt a value straight into a serialized ciphertext that contains only a "right" part,
+    /// using no more memory than it takes to hold one block's worth of values
+    ///
+    /// [`right_encrypt()`](Self::right_encrypt) works out every value of every block before
+    /// any of them are packed, which is the fastest way to do it, but for wide blocks on small
+    /// devices the `N`×`W` values can be more than there's room for.  This writes each block's
+    /// values into `out` as soon as they're done, instead.  The result is exactly what
+    /// `right_encrypt(value)?.to_vec()` would produce, and can be parsed in all the usual ways,
+    /// but encryption is slower, because some of the work that `right_encrypt()` does once per
+    /// ciphertext has to be done once per block.
+    ///
+    /// `out` must be at least [`max_right_serialized_len()`](Self::max_right_serialized_len)
+    /// bytes long, even though the ciphertext usually turns out to be shorter than that; the
+    /// number of bytes actually used is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::ore;
+    /// use cretrit::SerializableCipherText;
+    ///
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let key = [0u8; 32];
+    /// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+    /// let mut buf = vec![0u8; ore::Cipher::<4, 256>::max_right_serialized_len()];
+    ///
+    /// let len = cipher.right_encrypt_into(&42u32.try_into()?, &mut buf)?;
+    /// let stored = ore::CipherText::<4, 256>::from_slice(&buf[..len])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::RangeError`] if `out` is too small, or any other error if any of the
+    /// underlying cryptographic operations can't complete, or if there's a bug somewhere.
+    ///
+    pub fn right_encrypt_into(
+        &self,
+        value: &PlainText<N, W>,
+        out: &mut [u8],
+    ) -> Result<usize, Error> {
+        self.audited(AuditOperation::RightEncrypt, || {
+            CipherText::<S, CMP, N, W, M>::right_encrypt_into(self, value, out)
+        })
+    }
+
+    /// How big a buffer [`right_encrypt_into()`](Self::right_encrypt_into) needs
+    #[must_use]
+    pub fn max_right_serialized_len() -> usize {
+        CipherText::<S, CMP, N, W, M>::max_right_serialized_len()
+    }
+
+    /// Roughly how long each of this cipher's operations takes
+    ///
+    /// The figures are worked out from the cipher's parameters, by counting the cryptographic
+    /// operations each one involves, and giving each of those a nominal cost that's about right
+    /// for a modern CPU with AES acceleration.  They're good for comparing one set of parameters
+    /// with another, or for back-of-the-envelope capacity planning, but they can easily be out by
+    /// a factor of two or more on any particular machine; use
+    /// [`measured_costs()`](Self::measured_costs) for figures from the machine you're on.
+    ///
+    #[must_use]
+    pub fn estimated_costs(&self) -> CostEstimate {
+        CostEstimate::modelled::<S, CMP, N, W, M>()
+    }
+
+    /// How long each of this cipher's operations takes on this machine, on average
+    ///
+    /// Every operation is run once to warm up, and then timed over `rounds` runs, so this takes a
+    /// while (and burns some CPU) if `rounds` is large, or the block width is.  It's best done
+    /// once, at startup, rather than on every request.  None of the operations are reported to
+    /// the cipher's audit sink.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::RangeError`] if `rounds` is zero, and [`Error::NotPermitted`] if this
+    /// cipher can't produce full ciphertexts (that is, it's right-only or left-only).  Can also
+    /// return an error if any of the underlying cryptographic operations can't complete.
+    ///
+    pub fn measured_costs(&self, rounds: u32) -> Result<CostEstimate, Error> {
+        if self.role != Role::Full {
+            return Err(Error::NotPermitted(
+                "only a cipher that can produce full ciphertexts can have its costs measured"
+                    .to_string(),
+            ));
+        }
+
+        CostEstimate::measure(self, rounds)
+    }
+
+    /// Recover the plaintext of a ciphertext that has a "left" part
+    ///
+    /// The left part of a ciphertext records where each block's value ended up after being
+    /// shuffled by the cipher's permutation, so the cipher that produced it can put the values back
+    /// where they came from.  The left part is also checked against what this cipher would have
+    /// produced for the recovered value, so a ciphertext from a different key is rejected, rather
+    /// than decrypting to garbage.
+    ///
+    /// This is **not** a substitute for authenticated encryption, such as AES-GCM.  Anyone can
+    /// swap one valid ciphertext for another, and nothing here will notice.  Also, only
+    /// ciphertexts with a left part can be decrypted, and left parts are deterministic, so storing
+    /// full ciphertexts in order to be able to decrypt them later gives away which stored values
+    /// are equal (see the documentation for [`CipherText`](crate::CipherText)).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::ore;
+    /// use cretrit::PlainText;
+    ///
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let key = [0u8; 32];
+    /// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+    /// let value: PlainText<4, 256> = 42u32.try_into()?;
+    ///
+    /// assert_eq!(value, cipher.decrypt(&cipher.full_encrypt(&value)?)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::CryptoError`] if the ciphertext has no left part, or if its left
+    /// part wasn't produced by a cipher with this key.
+    ///
+    pub fn decrypt(
+        &self,
+        ciphertext: &CipherText<S, CMP, N, W, M>,
+    ) -> Result<PlainText<N, W>, Error> {
+        self.audited(AuditOperation::Decrypt, || {
+            ciphertext
+                .left()
+                .ok_or_else(|| {
+                    Error::CryptoError(
+                        "only ciphertexts with a left part can be decrypted".to_string(),
+                    )
+                })?
+                .decrypt(self)
+        })
+    }
+
+    /// Derive a key, suitable for use in a `HashMap` or similar, which identifies the given value
+    ///
+    /// Query keys are derived from the (deterministic) left ciphertext of the value, and so two
+    /// values have the same query key if, and only if, they have the same left ciphertext: that is,
+    /// if they're the same value, encrypted by ciphers with the same key and parameters.  However,
+    /// the query key is a keyed digest of the left ciphertext, rather than the left ciphertext
+    /// itself, so it can't be compared against right ciphertexts, and hence reveals nothing beyond
+    /// equality.
+    ///
+    /// That's still enough to be getting on with, though; like left ciphertexts, query keys are
+    /// deterministic, and so anyone who sees a collection of them can tell which values are
+    /// repeated, and how often.  Use them for in-memory joins and lookups, not for storage.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::ere;
+    /// use std::collections::HashMap;
+    ///
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let key = [0u8; 32];
+    /// let cipher = ere::Cipher::<4, 256>::new(&key)?;
+    /// let mut customers = HashMap::new();
+    ///
+    /// customers.insert(cipher.query_key(&42u32.try_into()?)?, "Alice");
+    /// customers.insert(cipher.query_key(&9001u32.try_into()?)?, "Bob");
+    ///
+    /// assert_eq!(Some(&"Bob"), customers.get(&cipher.query_key(&9001u32.try_into()?)?));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if any of the underlying cryptographic operations can't complete, or if
+    /// there's a bug somewhere.
+    ///
+    pub fn query_key(&self, value: &PlainText<N, W>) -> Result<[u8; 16], Error> {
+        self.audited(AuditOperation::QueryKey, || {
+            let left = Zeroizing::new(LeftCipherText::encrypt(self, value)?.to_vec()?);
+            let mut key = [0u8; 16];
+
+            self.query_kdf
+                .as_ref()
+                .ok_or_else(|| {
+                    Error::NotPermitted("right-only ciphers cannot produce query keys".to_string())
+                })?
+                .derive_key(&mut key, &left)?;
+
+            Ok(key)
+        })
+    }
+
+    /// Produce a deterministic token for the given value, to use as the key of a hash index
+    ///
+    /// This works much like [`query_key()`](Self::query_key): the token is a keyed digest of the
+    /// value's left ciphertext, so equal values (encrypted by ciphers with the same key and
+    /// parameters) always have equal tokens, but a token can't be compared against right
+    /// ciphertexts, and so reveals nothing beyond equality.  Tokens are longer than query keys,
+    /// come wrapped in an [`EqualityToken`] that can be stored and loaded again, and have
+    /// nothing in common with the query key for the same value.
+    ///
+    /// See the documentation for [`EqualityToken`] for an example, and for what storing them
+    /// gives away.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::NotPermitted`] if this is a right-only cipher.  Can also return an
+    /// error if any of the underlying cryptographic operations can't complete, or if there's a bug
+    /// somewhere.
+    ///
+    pub fn equality_token(&self, value: &PlainText<N, W>) -> Result<EqualityToken, Error> {
+        self.audited(AuditOperation::EqualityToken, || {
+            let left = Zeroizing::new(LeftCipherText::encrypt(self, value)?.to_vec()?);
+            let mut token = [0u8; 32];
+
+            self.query_kdf
+                .as_ref()
+                .ok_or_else(|| {
+                    Error::NotPermitted(
+                        "right-only ciphers cannot produce equality tokens".to_string(),
+                    )
+                })?
+                .derive_labelled_key(&mut token, b"Cipher.equality_token", &left)?;
+
+            Ok(EqualityToken::from(token))
+        })
+    }
+
+    /// Fill the given slice with cryptographically secure random bytes
+    ///
+    /// The bytes come from the same RNG that the cipher uses for the nonces in right ciphertexts,
+    /// so anything that needs random values that are "as good as" cretrit's own (salts, nonces for
+    /// other encryption schemes, and so on) can get them from here.
+    ///
+    /// The RNG is the ciphersuite's CSPRNG (`ChaCha20`, for `aes128v1`), seeded from the operating
+    /// system's entropy source when the cipher is created.  It isn't periodically reseeded, and
+    /// there's no way to detect a process `fork()`, after which parent and child would generate
+    /// the same bytes; if your process forks, create new ciphers in the child.  Ciphers created with
+    /// `new_with_insecure_seed()` (behind the `test-vectors` feature) produce entirely predictable
+    /// bytes, as the name suggests.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::ore;
+    ///
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let key = [0u8; 32];
+    /// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+    ///
+    /// let mut salt = [0u8; 16];
+    /// cipher.random_bytes(&mut salt)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the RNG fails, which shouldn't happen with any of the RNGs that
+    /// cretrit uses.
+    ///
+    pub fn random_bytes(&self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_nonce(dest)
     }
 
     /// Write a random value into the given slice
@@ -154,6 +1113,40 @@ impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, con
         Ok(())
     }
 
+    /// Generate the nonce base for a new right ciphertext
+    ///
+    /// With the `nonce-tracking` feature enabled, every nonce base is remembered, and generating
+    /// one that has been seen before is an error.  A repeated nonce base would mean that the RNG
+    /// is badly broken (or has been seeded with something predictable), and so it's better to
+    /// stop than to keep producing correlatable ciphertexts.  Tracking uses 16 bytes (plus
+    /// overhead) for every right ciphertext ever produced, so it's meant for testing and
+    /// debugging, not for long-running production processes.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the RNG fails, or if a nonce base is repeated (when tracking).
+    ///
+    pub(crate) fn nonce_base(&self) -> Result<[u8; 16], Error> {
+        // Every right ciphertext needs a nonce base, so this is the one place that needs to check
+        if self.is_left_only() {
+            return Err(Error::NotPermitted(
+                "left-only ciphers cannot produce right ciphertexts".to_string(),
+            ));
+        }
+
+        let mut nonce_base = [0u8; 16];
+        self.fill_nonce(&mut nonce_base)?;
+
+        #[cfg(feature = "nonce-tracking")]
+        if !self.seen_nonces.borrow_mut().insert(nonce_base) {
+            return Err(Error::CryptoError(
+                "nonce base was generated twice; the RNG is not working properly".to_string(),
+            ));
+        }
+
+        Ok(nonce_base)
+    }
+
     /// Calculate the pseudo-random block corresponding to the given value
     ///
     /// Writes the result into the given block, rather than return by value, because the data can
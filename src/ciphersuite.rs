@@ -1,12 +1,11 @@
 //! The home of the `CipherSuite` trait.
 //!
 
-use rand::{CryptoRng, RngCore, SeedableRng};
-
 use crate::hash::HashFunction;
 use crate::kbkdf::{KBKDFInit, KBKDF};
 use crate::prf::PseudoRandomFunction;
 use crate::prp::PseudoRandomPermutation;
+use crate::rng::{CryptoRng, RngCore, SeedableRng};
 
 /// The collection of cryptographic primitives required to produce a comparable ciphertext.
 ///
@@ -16,6 +15,9 @@ use crate::prp::PseudoRandomPermutation;
 /// implementation in terms of its primitives.
 ///
 pub trait CipherSuite<const W: u16, const M: u8>: Clone {
+    /// The canonical name of the ciphersuite, as used in [`Params`](crate::Params)
+    const NAME: &'static str;
+
     /// The random-number generator
     ///
     /// A quality RNG is required both for generating random values (like nonces), but also as a
@@ -60,3 +62,12 @@ pub trait CipherSuite<const W: u16, const M: u8>: Clone {
     ///
     type KBKDF: KBKDF + KBKDFInit;
 }
+
+/// A ciphersuite that's listed in [`suites()`](crate::suites)
+///
+/// Only the names of listed ciphersuites can be parsed into a [`Params`](crate::Params), so only
+/// ciphers using one of them can be described by (or created from) a `Params`.  Ciphersuites put
+/// together from other ones, like those in the [`feistel`](crate::feistel) and
+/// [`truncated`](crate::truncated) modules, aren't listed, and don't implement this.
+///
+pub trait ListedCipherSuite<const W: u16, const M: u8>: CipherSuite<W, M> {}
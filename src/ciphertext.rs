@@ -1,18 +1,26 @@
 //! An encrypted, comparable data type.
 
-use rand::{RngCore, SeedableRng};
+use std::borrow::Cow;
 use std::convert::AsMut;
 use std::marker::PhantomData;
 
-use crate::bitlist::{ReadableBitList, WritableBitList};
+use crate::bitlist::WritableBitList;
 use crate::cipher::Cipher;
 use crate::ciphersuite::CipherSuite;
-use crate::cmp::Comparator;
+use crate::cmp::{Comparator, FirstDifference};
+use crate::encoding::ValueEncoding;
 use crate::error::Error;
 use crate::hash::HashFunction;
+use crate::packed::PackedValues;
 use crate::plaintext::PlainText;
+use crate::pool::CipherTextPool;
 use crate::prf::PseudoRandomFunction;
-use crate::util::check_overflow;
+use crate::rng::{RngCore, SeedableRng};
+use crate::stats::CipherTextStats;
+use crate::type_byte::CipherTextType;
+use crate::util::{check_overflow, fnv1a_64};
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::{Zeroize, Zeroizing};
 
 /// Provide the ability to serialise/deserialise a ciphertext
 ///
@@ -65,6 +73,196 @@ pub trait Serializable<const N: usize, const W: u16, const M: u8> {
     /// serialisation implementation.
     ///
     fn to_vec(&self) -> Result<Vec<u8>, Error>;
+
+    /// Serialise a [`CipherText`](super::CipherText) into an array of bytes, for storing in a
+    /// fixed-width field.
+    ///
+    /// `SIZE` has to be exactly the length of the serialized ciphertext.  Left ciphertexts are
+    /// always [`LeftCipherText::serialized_len()`](super::LeftCipherText::serialized_len) bytes;
+    /// whether other ciphertexts are always the same size depends on the comparator, as described
+    /// for [`CipherText::right_serialized_len()`](super::CipherText::right_serialized_len).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::ere;
+    /// use cretrit::SerializableCipherText;
+    ///
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let key = [0u8; 32];
+    /// const SIZE: usize = match ere::CipherText::<4, 256>::right_serialized_len() {
+    ///     Some(len) => len,
+    ///     None => panic!("ERE ciphertexts are always the same size"),
+    /// };
+    ///
+    /// let cipher = ere::Cipher::<4, 256>::new(&key)?;
+    /// let stored: [u8; SIZE] = cipher.right_encrypt(&42u32.try_into()?)?.to_array()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::RangeError`] if the serialized ciphertext isn't exactly `SIZE` bytes
+    /// long.
+    ///
+    fn to_array<const SIZE: usize>(&self) -> Result<[u8; SIZE], Error> {
+        let v = self.to_vec()?;
+
+        v.as_slice().try_into().map_err(|e| {
+            Error::RangeError(format!(
+                "serialized ciphertext is {} bytes long, not {SIZE} ({e})",
+                v.len()
+            ))
+        })
+    }
+}
+
+/// The version of the serialized ciphertext format that this release reads and writes
+///
+/// The format is defined byte-for-byte, and doesn't depend on the endianness or word size of the
+/// platform doing the serializing; see the [`CipherText`] documentation for the details.  This
+/// number only changes if a ciphertext serialized by one release could be rejected, or read
+/// differently, by another, so code that keeps ciphertexts around for a long time can check that
+/// it's still the number it expects.
+///
+/// # Examples
+///
+/// ```rust
+/// assert_eq!(1, cretrit::FORMAT_VERSION);
+/// ```
+///
+pub const FORMAT_VERSION: u8 = 1;
+
+/// Strip the "left" part from a serialized ciphertext, leaving only the "right" part.
+///
+/// This produces exactly the same bytes as deserialising the ciphertext, removing the left part,
+/// and serialising it again, but without all the work of parsing and re-packing the right
+/// ciphertext's values.  This makes it suitable for bulk "sanitisation" of stored ciphertexts
+/// that were (mistakenly or otherwise) written with their left parts attached.
+///
+/// Serialized ciphertexts which already don't have a left part are returned unchanged.
+///
+/// Since the ciphertext isn't fully parsed, this function doesn't know (or care) what the
+/// ciphersuite, block count, or block width of the ciphertext are, and will not detect corruption
+/// of the ciphertext, beyond the framing being invalid.
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::aes128v1::ore;
+/// use cretrit::SerializableCipherText;
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// # let key = [0u8; 32];
+/// # let cipher = ore::Cipher::<4, 256>::new(&key)?;
+/// let full = cipher.full_encrypt(&42u32.try_into()?)?.to_vec()?;
+/// let sanitised = cretrit::sanitize_serialized(&full)?;
+///
+/// assert!(!ore::CipherText::<4, 256>::from_slice(&sanitised)?.has_left());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Will return an error if the data provided isn't framed like a serialized ciphertext.
+///
+pub fn sanitize_serialized(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    let (_, right) = split_serialized(bytes)?;
+    let len = u16::try_from(right.len()).map_err(|e| {
+        Error::InternalError(format!(
+            "right ciphertext length {} doesn't fit in a u16, even though it came from one ({e})",
+            right.len()
+        ))
+    })?;
+
+    let mut v = Vec::with_capacity(right.len().saturating_add(3));
+    v.push(CipherTextType::RightOnly.byte());
+    v.extend_from_slice(&len.to_be_bytes());
+    v.extend_from_slice(right);
+    Ok(v)
+}
+
+/// Break a serialized ciphertext into its left part (if it has one) and its right part.
+///
+/// Only the framing is checked; the contents of each part are left for someone else to worry
+/// about.
+///
+pub(crate) fn split_serialized(bytes: &[u8]) -> Result<(Option<&[u8]>, &[u8]), Error> {
+    let (left, rest) = match CipherTextType::of_serialized(bytes)? {
+        CipherTextType::RightOnly => (None, bytes.get(1..).unwrap_or_default()),
+        CipherTextType::Full => {
+            let len_bytes = bytes.get(1..3).ok_or_else(|| {
+                Error::ParseError(
+                    "end-of-data while looking for left ciphertext length".to_string(),
+                )
+            })?;
+            let len = u16::from_be_bytes(len_bytes.try_into().map_err(|e| {
+                Error::ParseError(format!(
+                    "failed to convert {len_bytes:?} into u16 for left ciphertext length ({e})"
+                ))
+            })?);
+            let right_start = 3usize.saturating_add(usize::from(len));
+
+            (
+                Some(bytes.get(3..right_start).ok_or_else(|| {
+                    Error::ParseError("end-of-data while looking for left ciphertext".to_string())
+                })?),
+                bytes.get(right_start..).unwrap_or_default(),
+            )
+        }
+    };
+
+    let len_bytes = rest.get(..2).ok_or_else(|| {
+        Error::ParseError("end-of-data while looking for right ciphertext length".to_string())
+    })?;
+    let len = u16::from_be_bytes(len_bytes.try_into().map_err(|e| {
+        Error::ParseError(format!(
+            "failed to convert {len_bytes:?} into u16 for right ciphertext length ({e})"
+        ))
+    })?);
+    let right = rest.get(2..).unwrap_or_default();
+
+    if usize::from(len) == right.len() {
+        Ok((left, right))
+    } else {
+        Err(Error::ParseError(format!(
+            "length does not match size in right ciphertext (expected={len}, actual={})",
+            right.len()
+        )))
+    }
+}
+
+/// Copy `bytes` into `out`, starting at `pos`, and return the position just past them
+fn write_at(out: &mut [u8], pos: usize, bytes: &[u8]) -> Result<usize, Error> {
+    let end = pos.saturating_add(bytes.len());
+    let out_len = out.len();
+
+    out.get_mut(pos..end)
+        .ok_or_else(|| {
+            Error::RangeError(format!(
+                "output buffer too small for serialized ciphertext (need at least {end} bytes, got {out_len})"
+            ))
+        })?
+        .copy_from_slice(bytes);
+
+    Ok(end)
+}
+
+/// The key check value of the key that two ciphertexts (or parts of them) were encrypted with,
+/// as long as they weren't encrypted with keys known to be different
+///
+/// Ciphertexts parsed from their plain serialized form don't know which key they were encrypted
+/// with, so they're assumed to go with anything.
+///
+fn same_key(a: Option<[u8; 3]>, b: Option<[u8; 3]>) -> Result<Option<[u8; 3]>, Error> {
+    match (a, b) {
+        (Some(kcv_a), Some(kcv_b)) if kcv_a != kcv_b => Err(Error::ParamsError(format!(
+            "ciphertexts were encrypted with different keys (key check values {kcv_a:02x?} and {kcv_b:02x?})"
+        ))),
+        _ => Ok(a.or(b)),
+    }
 }
 
 /// Rust is weird sometimes.
@@ -78,9 +276,49 @@ where
     a
 }
 
-/// A generic large-domain left ciphertext for the Lewi-Wu comparison-revealing encryption scheme.
+/// The "left" part of a Comparison-Revealing Encrypted value, on its own.
+///
+/// A left ciphertext can't be compared against another left ciphertext, only against the right
+/// part of a [`CipherText`], so the left part is all that a query needs to carry.  It's a good
+/// deal smaller than a full ciphertext, too, which makes it a better choice of query token to
+/// send through a message queue or over the wire.  A cipher produces one with
+/// [`Cipher::left_encrypt()`], you can also get one from a full ciphertext with
+/// [`CipherText::left()`], and it can be (de)serialized with the
+/// [`SerializableCipherText`](crate::SerializableCipherText) trait, like any other ciphertext.
+///
+/// Left ciphertexts are deterministic, so the [caveats about left
+/// ciphertexts](CipherText#the-left-ciphertext) apply in full: these are for querying with, not
+/// for storing.
+///
+/// Like the other generic types, this isn't meant to be used directly; the ciphersuite-specific
+/// modules (such as [`aes128v1::ore`](crate::aes128v1::ore)) have aliases which fill in all the
+/// type parameters, and provide comparison functions that return something sensible.
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::aes128v1::ore;
+/// use cretrit::SerializableCipherText;
+/// use std::cmp::Ordering;
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// # let key = [0u8; 32];
+/// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+/// let stored = cipher.right_encrypt(&9001u32.try_into()?)?;
+///
+/// let token = cipher.left_encrypt(&42u32.try_into()?)?.to_vec()?;
+/// assert_eq!(ore::LeftCipherText::<4, 256>::serialized_len(), token.len());
+///
+/// // ... send the token to wherever the stored values are ...
+///
+/// let left = ore::LeftCipherText::<4, 256>::from_slice(&token)?;
+/// assert_eq!(Ordering::Less, left.compare_with_right(&stored)?);
+/// # Ok(())
+/// # }
+/// ```
+///
 #[derive(Debug, Clone)]
-pub(crate) struct LeftCipherText<
+pub struct LeftCipherText<
     S: CipherSuite<W, M>,
     CMP: Comparator<M>,
     const N: usize,
@@ -91,24 +329,42 @@ pub(crate) struct LeftCipherText<
     f: [<<S as CipherSuite<W, M>>::PRF as PseudoRandomFunction>::BlockType; N],
     /// The p(x) for each block in the large-domain left ciphertext
     px: [u16; N],
+    /// The key check value of the cipher that encrypted this, if it's known
+    key_check_value: Option<[u8; 3]>,
 
-    /// Compiler pacification
-    _mark: PhantomData<CMP>,
+    /// Compiler pacification; a function pointer, so that it doesn't affect auto traits
+    _mark: PhantomData<fn() -> CMP>,
 }
 
 impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, const M: u8>
     LeftCipherText<S, CMP, N, W, M>
 {
-    /// Create a new, blank left ciphertext, ready for writing a value into
-    pub(crate) fn new() -> Self {
+    /// Create a new, blank left ciphertext, ready for writing a value encrypted with a key with
+    /// the given KCV into
+    pub(crate) fn new(key_check_value: Option<[u8; 3]>) -> Self {
         LeftCipherText {
             f: [Default::default(); N],
             px: [0; N],
+            key_check_value,
 
             _mark: PhantomData,
         }
     }
 
+    /// Encrypt an entire plaintext into a left ciphertext
+    pub(crate) fn encrypt(
+        cipher: &Cipher<S, CMP, N, W, M>,
+        plaintext: &PlainText<N, W>,
+    ) -> Result<Self, Error> {
+        let mut left = Self::new(Some(cipher.key_check_value()));
+
+        for n in 0..N {
+            left.set_block(cipher, n, plaintext.block(n)?)?;
+        }
+
+        Ok(left)
+    }
+
     /// Encrypt the block value into the `n`th block of the left ciphertext
     pub(crate) fn set_block(
         &mut self,
@@ -124,6 +380,13 @@ impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, con
         if value >= W {
             return Err(Error::RangeError(format!("attempted to write a value {value} greater than the left ciphertext block width {W}")));
         }
+        // Every left ciphertext is made here, one block at a time, so this is the one place that
+        // needs to check
+        if cipher.is_right_only() {
+            return Err(Error::NotPermitted(
+                "right-only ciphers cannot produce left ciphertexts".to_string(),
+            ));
+        }
 
         let permuted_value = cipher.permuted_value(value)?;
 
@@ -141,6 +404,42 @@ impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, con
         Ok(())
     }
 
+    /// Recover the plaintext from this left ciphertext
+    ///
+    /// Each block's p(x) is run back through the inverse of the cipher's permutation, and then the
+    /// block's F(k, p(x)) is worked out afresh and checked against the one in the ciphertext, so
+    /// that a left ciphertext from a different key (or one that's been tampered with) is rejected,
+    /// rather than producing a plausible-looking wrong answer.  The checks are done in constant
+    /// time, and all of them are made before deciding, so neither the time taken nor the error
+    /// says which block (if any) matched.
+    ///
+    pub(crate) fn decrypt(
+        &self,
+        cipher: &Cipher<S, CMP, N, W, M>,
+    ) -> Result<PlainText<N, W>, Error> {
+        let mut blocks = Zeroizing::new([0u16; N]);
+        let mut f: Zeroizing<<<S as CipherSuite<W, M>>::PRF as PseudoRandomFunction>::BlockType> =
+            Zeroizing::new(Default::default());
+        let mut genuine = Choice::from(1);
+
+        for (n, block) in blocks.iter_mut().enumerate() {
+            let px = self.px(n)?;
+            cipher.pseudorandomise(px, &mut f);
+
+            genuine &= f.as_ref().ct_eq(self.f(n)?.as_ref());
+
+            *block = cipher.inverse_permuted_value(px)?;
+        }
+
+        if !bool::from(genuine) {
+            return Err(Error::CryptoError(
+                "left ciphertext was not produced by this cipher".to_string(),
+            ));
+        }
+
+        Ok(PlainText::new(*blocks))
+    }
+
     /// Retrieve the F(k, p(x)) value for the `n`th block of the left ciphertext
     pub(crate) fn f(
         &self,
@@ -167,12 +466,270 @@ impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, con
             })
             .copied()
     }
+
+    /// The number of blocks in the left ciphertext (`N`)
+    #[must_use]
+    pub fn block_count(&self) -> usize {
+        N
+    }
+
+    /// How many bytes each block's p(x) takes up when serialized
+    ///
+    /// A single byte is enough for any block width up to 256, and anything wider needs a
+    /// (big-endian) `u16`.
+    ///
+    const PX_SIZE: usize = if W <= 256 { 1 } else { 2 };
+
+    /// How many bytes a serialized left ciphertext of this type takes up
+    ///
+    /// Every left ciphertext with the same parameters is the same size, so this doesn't need a
+    /// ciphertext to work it out.
+    ///
+    #[must_use]
+    pub const fn serialized_len() -> usize {
+        let f_size = <<S as CipherSuite<W, M>>::PRF as PseudoRandomFunction>::BLOCK_SIZE;
+        N.saturating_mul(f_size.saturating_add(Self::PX_SIZE))
+    }
+
+    /// Join two left ciphertexts together, block-wise, to make a longer one
+    ///
+    /// The blocks of a left ciphertext don't depend on where in the ciphertext they are, so a left
+    /// ciphertext for a value made up of two smaller values (such as a 96 bit key made from a 64
+    /// bit value followed by a 32 bit value) can be put together from the left ciphertexts of the
+    /// two smaller values.  The result is exactly what encrypting the combined value would have
+    /// produced.
+    ///
+    /// The types make sure the ciphersuite, comparator, and block width all match, but there is no
+    /// way to tell whether the two left ciphertexts were produced with the same key; if they
+    /// weren't, the result won't compare correctly against anything.
+    ///
+    /// Right ciphertexts can't be joined like this, because every block's nonce is derived from
+    /// a single, random, nonce base, so the combined value has to be encrypted from scratch.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::ore;
+    /// use std::cmp::Ordering;
+    ///
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let key = [0u8; 32];
+    /// let cipher_64 = ore::Cipher::<8, 256>::new(&key)?;
+    /// let cipher_32 = ore::Cipher::<4, 256>::new(&key)?;
+    /// let cipher_96 = ore::Cipher::<12, 256>::new(&key)?;
+    ///
+    /// let hi = cipher_64.full_encrypt(&42u64.try_into()?)?;
+    /// let lo = cipher_32.full_encrypt(&9001u32.try_into()?)?;
+    /// let combined = ore::LeftCipherText::<12, 256>::concat(
+    ///     hi.left().expect("full ciphertext"),
+    ///     lo.left().expect("full ciphertext"),
+    /// )?;
+    ///
+    /// let stored = cipher_96.right_encrypt(&(42u128 << 32 | 9001).try_into()?)?;
+    /// assert_eq!(Ordering::Equal, combined.compare_with_right(&stored)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `A + B` isn't `N`, or if the two parts are known to have been
+    /// encrypted with different keys.
+    ///
+    pub fn concat<const A: usize, const B: usize>(
+        first: &LeftCipherText<S, CMP, A, W, M>,
+        second: &LeftCipherText<S, CMP, B, W, M>,
+    ) -> Result<Self, Error> {
+        if A.checked_add(B) != Some(N) {
+            return Err(Error::RangeError(format!(
+                "cannot join {A} and {B} block left ciphertexts into {N} blocks"
+            )));
+        }
+
+        let mut left = Self::new(same_key(first.key_check_value, second.key_check_value)?);
+
+        for (n, (f_n, px_n)) in left.f.iter_mut().zip(left.px.iter_mut()).enumerate() {
+            if let Some(b) = n.checked_sub(A) {
+                *f_n = second.f(b)?;
+                *px_n = second.px(b)?;
+            } else {
+                *f_n = first.f(n)?;
+                *px_n = first.px(n)?;
+            }
+        }
+
+        Ok(left)
+    }
+
+    /// Break a left ciphertext into two shorter ones, the first containing the first `A` blocks,
+    /// and the second containing the remaining `B` blocks
+    ///
+    /// This is the inverse of [`concat()`](Self::concat); each part is exactly what encrypting
+    /// the corresponding part of the value would have produced.  The first part can also be
+    /// obtained from a full ciphertext with [`CipherText::prefix()`], which can split the right
+    /// part too, but there's no way to produce the right part of the second half.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `A + B` isn't `N`.
+    ///
+    #[allow(clippy::type_complexity)] // It's a pair of left ciphertexts; it's not that complex
+    pub fn split<const A: usize, const B: usize>(
+        &self,
+    ) -> Result<
+        (
+            LeftCipherText<S, CMP, A, W, M>,
+            LeftCipherText<S, CMP, B, W, M>,
+        ),
+        Error,
+    > {
+        if A.checked_add(B) != Some(N) {
+            return Err(Error::RangeError(format!(
+                "cannot split a {N} block left ciphertext into {A} and {B} blocks"
+            )));
+        }
+
+        let mut first = LeftCipherText::<S, CMP, A, W, M>::new(self.key_check_value);
+        let mut second = LeftCipherText::<S, CMP, B, W, M>::new(self.key_check_value);
+
+        for (n, (f_n, px_n)) in first.f.iter_mut().zip(first.px.iter_mut()).enumerate() {
+            *f_n = self.f(n)?;
+            *px_n = self.px(n)?;
+        }
+        for (n, (f_n, px_n)) in second.f.iter_mut().zip(second.px.iter_mut()).enumerate() {
+            *f_n = self.f(n.saturating_add(A))?;
+            *px_n = self.px(n.saturating_add(A))?;
+        }
+
+        Ok((first, second))
+    }
+
+    /// Make an `N` block left ciphertext out of a shorter one, by putting left ciphertext blocks
+    /// for zero in front of it
+    pub(crate) fn zero_extend<const K: usize>(
+        cipher: &Cipher<S, CMP, N, W, M>,
+        shorter: &LeftCipherText<S, CMP, K, W, M>,
+    ) -> Result<Self, Error> {
+        let padding = N.checked_sub(K).ok_or_else(|| {
+            Error::RangeError(format!(
+                "cannot promote a {K} block left ciphertext to {N} blocks"
+            ))
+        })?;
+
+        let mut left = Self::new(same_key(
+            Some(cipher.key_check_value()),
+            shorter.key_check_value,
+        )?);
+
+        for n in 0..padding {
+            left.set_block(cipher, n, 0)?;
+        }
+
+        for (k, (f_n, px_n)) in left
+            .f
+            .iter_mut()
+            .zip(left.px.iter_mut())
+            .skip(padding)
+            .enumerate()
+        {
+            *f_n = shorter.f(k)?;
+            *px_n = shorter.px(k)?;
+        }
+
+        Ok(left)
+    }
+
+    /// Compare against the right part of a ciphertext
+    ///
+    /// Returns the numeric comparison value, which needs to be run through the comparator's invert
+    /// function in order to convert that into a "proper" logical comparison value.
+    ///
+    pub(crate) fn compare(&self, other: &CipherText<S, CMP, N, W, M>) -> Result<u8, Error> {
+        CipherText::compare_parts(self, &other.right)
+    }
+
+    /// Compare against the right part of a ciphertext one block at a time, most significant
+    /// block first
+    pub(crate) fn block_comparisons<'a>(
+        &'a self,
+        other: &'a CipherText<S, CMP, N, W, M>,
+    ) -> BlockComparisons<'a, S, CMP, N, W, M> {
+        BlockComparisons {
+            left: self,
+            right: &other.right,
+            reversed: false,
+            n: 0,
+        }
+    }
+
+    /// Feed the left ciphertext into a [`Hasher`](std::hash::Hasher)
+    ///
+    /// Left ciphertexts are deterministic, so this is consistent with [`same_as()`](Self::same_as):
+    /// identical left ciphertexts always hash the same.
+    ///
+    pub(crate) fn hash_into<H: std::hash::Hasher>(&self, state: &mut H) {
+        for f in &self.f {
+            state.write(f.as_ref());
+        }
+        for px in &self.px {
+            state.write_u16(*px);
+        }
+    }
+
+    /// Determine whether two left ciphertexts are identical
+    ///
+    /// Every block is compared in constant time, regardless of whether an earlier one differed, so
+    /// the time taken doesn't give away where the first difference is.
+    ///
+    pub(crate) fn same_as(&self, other: &Self) -> bool {
+        let mut same = Choice::from(1);
+
+        for (a, b) in self.f.iter().zip(other.f.iter()) {
+            same &= a.as_ref().ct_eq(b.as_ref());
+        }
+        for (a, b) in self.px.iter().zip(other.px.iter()) {
+            same &= a.ct_eq(b);
+        }
+
+        bool::from(same)
+    }
+
+    /// Produce a left ciphertext containing only the first `K` blocks of this one
+    pub(crate) fn prefix<const K: usize>(&self) -> Result<LeftCipherText<S, CMP, K, W, M>, Error> {
+        let mut left = LeftCipherText::<S, CMP, K, W, M>::new(self.key_check_value);
+
+        for k in 0..K {
+            let f_k = left
+                .f
+                .get_mut(k)
+                .ok_or_else(|| Error::InternalError(format!("failed to get f[{k}]")))?;
+            *f_k = self.f(k)?;
+            let px_k = left
+                .px
+                .get_mut(k)
+                .ok_or_else(|| Error::InternalError(format!("failed to get px[{k}]")))?;
+            *px_k = self.px(k)?;
+        }
+
+        Ok(left)
+    }
 }
 
 impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, const M: u8>
     Serializable<N, W, M> for LeftCipherText<S, CMP, N, W, M>
 {
     fn from_slice(bytes: &[u8]) -> Result<Self, Error> {
+        // Every left ciphertext with the same parameters is the same size, so this is the
+        // cheapest way to notice that we've been handed a left ciphertext made with some other
+        // block count or width
+        if bytes.len() != Self::serialized_len() {
+            return Err(Error::ParamsError(format!(
+                "left ciphertext is {} bytes, but one with {N} blocks of width {W} would be {}",
+                bytes.len(),
+                Self::serialized_len()
+            )));
+        }
+
         let mut f: [<<S as CipherSuite<W, M>>::PRF as PseudoRandomFunction>::BlockType; N] =
             [Default::default(); N];
         // Like I'm typing this out more often than I absolutely need to...
@@ -180,15 +737,17 @@ impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, con
         let mut px = [0u16; N];
         let px_start = check_overflow(
             N.overflowing_mul(f_size),
-            &format!("overflow while calculating px_start (N={N}*f_size={f_size})"),
+            format_args!("overflow while calculating px_start (N={N}*f_size={f_size})"),
         )?;
 
         for i in 0..N {
             let first_byte = check_overflow(
                 i.overflowing_mul(f_size),
-                &format!("overflow while calculating first byte of block (i={i}*f_size={f_size})"),
+                format_args!(
+                    "overflow while calculating first byte of block (i={i}*f_size={f_size})"
+                ),
             )?;
-            let last_byte = check_overflow(first_byte.overflowing_add(f_size), &format!("overflow while calculating last byte of block (first_byte={first_byte}+f_size={f_size})"))?;
+            let last_byte = check_overflow(first_byte.overflowing_add(f_size), format_args!("overflow while calculating last byte of block (first_byte={first_byte}+f_size={f_size})"))?;
             let block = bytes.get(first_byte..last_byte).ok_or_else(|| {
                 Error::ParseError(format!("end-of-data while looking for f[{i}]"))
             })?;
@@ -197,36 +756,35 @@ impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, con
             })?;
             *f_i_ref = clone_into_array(block);
 
-            let px_i = if W <= 256 {
-                u16::from(
-                    *bytes
-                        .get(check_overflow(
-                            px_start.overflowing_add(i),
-                            &format!("overflow while adding i={i} to px_start={px_start}"),
-                        )?)
-                        .ok_or_else(|| {
-                            Error::ParseError(format!("end-of-data while looking for px[{i}]"))
-                        })?,
-                )
-            } else {
-                let px_loc = check_overflow(
-                    px_start.overflowing_add(check_overflow(
-                        i.overflowing_add(2),
-                        &format!(
-                            "overflow while multiplying i={i} by 2 in LeftCipherText::from_slice"
-                        ),
-                    )?),
-                    &format!("overflow while adding px_start={px_start} to 2*{i}"),
-                )?;
-                let px_bytes = bytes.get(px_loc..=px_loc).ok_or_else(|| {
+            let px_loc = check_overflow(
+                px_start.overflowing_add(check_overflow(
+                    i.overflowing_mul(Self::PX_SIZE),
+                    format_args!(
+                        "overflow while multiplying i={i} by px size in LeftCipherText::from_slice"
+                    ),
+                )?),
+                format_args!("overflow while adding px_start={px_start} to px offset of block {i}"),
+            )?;
+            let px_bytes = bytes
+                .get(px_loc..px_loc.saturating_add(Self::PX_SIZE))
+                .ok_or_else(|| {
                     Error::ParseError(format!("end-of-data while looking for px[{i}]"))
                 })?;
-                u16::from_be_bytes(px_bytes.try_into().map_err(|e| {
-                    Error::ParseError(format!(
-                        "failed to convert {px_bytes:?} into u16 for px[{i}] ({e})"
-                    ))
-                })?)
+            let px_i = match *px_bytes {
+                [byte] => u16::from(byte),
+                [hi, lo] => u16::from_be_bytes([hi, lo]),
+                _ => {
+                    return Err(Error::InternalError(format!(
+                        "px[{i}] is {} bytes, which is not a valid p(x) size",
+                        px_bytes.len()
+                    )))
+                }
             };
+            if px_i >= W {
+                return Err(Error::ParamsError(format!(
+                    "p(x) for block {i} is {px_i}, which is too big for a block width of {W}"
+                )));
+            }
             let px_i_ref = px.get_mut(i).ok_or_else(|| Error::InternalError(format!("failed to get {i}th element of px array (which is supposed to have {N} elements)")))?;
             *px_i_ref = px_i;
         }
@@ -234,15 +792,14 @@ impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, con
         Ok(Self {
             f,
             px,
+            key_check_value: None,
 
             _mark: PhantomData,
         })
     }
 
     fn to_vec(&self) -> Result<Vec<u8>, Error> {
-        let f_size = <<S as CipherSuite<W, M>>::PRF as PseudoRandomFunction>::BLOCK_SIZE;
-
-        let mut v: Vec<u8> = Vec::with_capacity(N.saturating_mul(f_size.saturating_add(2)));
+        let mut v: Vec<u8> = Vec::with_capacity(Self::serialized_len());
 
         for n in 0..N {
             v.extend_from_slice(
@@ -267,136 +824,500 @@ impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, con
 
         Ok(v)
     }
+
+    /// Left ciphertexts are always the same size, so they're written straight into the array,
+    /// without serializing them into a vector first.
+    ///
+    fn to_array<const SIZE: usize>(&self) -> Result<[u8; SIZE], Error> {
+        if SIZE != Self::serialized_len() {
+            return Err(Error::RangeError(format!(
+                "serialized left ciphertext is {} bytes long, not {SIZE}",
+                Self::serialized_len()
+            )));
+        }
+
+        let mut out = [0u8; SIZE];
+        let mut pos = 0usize;
+
+        for f_n in &self.f {
+            pos = write_at(&mut out, pos, f_n.as_ref())?;
+        }
+        for px_n in &self.px {
+            pos = if W <= 256 {
+                write_at(&mut out, pos, &[u8::try_from(*px_n).map_err(|e| Error::InternalError(format!("failed to convert {px_n} to u8, even though it's supposed to be within range ({e})")))?])?
+            } else {
+                write_at(&mut out, pos, &px_n.to_be_bytes())?
+            };
+        }
+
+        Ok(out)
+    }
 }
 
-/// A generic large-domain right ciphertext for the Lewi-Wu comparison-revealing encryption scheme.
-#[derive(Debug, Clone)]
-pub(crate) struct RightCipherText<
+/// A left ciphertext which has been made ready to be compared against many right ciphertexts.
+///
+/// Every comparison hashes each right ciphertext block's nonce with a key taken from the
+/// corresponding block of the left ciphertext.  Since the left ciphertext doesn't change, the
+/// hash function can be keyed once for each block, rather than once for every comparison.
+///
+pub(crate) struct PreparedLeft<
     S: CipherSuite<W, M>,
     CMP: Comparator<M>,
     const N: usize,
     const W: u16,
     const M: u8,
 > {
-    /// The base nonce from which the per-block nonces are derived
-    nonce_base: [u8; 16],
-    /// Cached copies of the per-block nonces
-    nonce_cache: [[u8; 16]; N],
-    /// The `v_i` sequences for each block
-    values: Vec<Vec<u8>>,
+    /// The p(x) for each block in the large-domain left ciphertext
+    px: [u16; N],
+    /// The hash function for each block, keyed with F(k, p(x))
+    hashers: Vec<<<S as CipherSuite<W, M>>::HF as HashFunction<M>>::Keyed>,
+    /// The key check value of the cipher that encrypted the left ciphertext, if it's known
+    key_check_value: Option<[u8; 3]>,
 
     /// Compiler pacification
-    _mark: (PhantomData<S>, PhantomData<CMP>),
+    _mark: PhantomData<CMP>,
 }
 
 impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, const M: u8>
-    RightCipherText<S, CMP, N, W, M>
+    PreparedLeft<S, CMP, N, W, M>
 {
-    /// Spawn a new right ciphertext, ready to have its blocks written
-    pub(crate) fn new(cipher: &Cipher<S, CMP, N, W, M>) -> Result<Self, Error> {
-        let values: Vec<Vec<u8>> = (0..N).map(|_| vec![0u8; W as usize]).collect();
-        let mut rct = RightCipherText {
-            nonce_base: Default::default(),
-            nonce_cache: [Default::default(); N],
-            values,
-            _mark: (PhantomData, PhantomData),
-        };
+    /// Do all the per-left-ciphertext work that can be done in advance
+    pub(crate) fn new(left: &LeftCipherText<S, CMP, N, W, M>) -> Result<Self, Error> {
+        let mut hashers = Vec::with_capacity(N);
 
-        cipher.fill_nonce(&mut rct.nonce_base)?;
-
-        rct.cache_nonces()?;
+        for n in 0..N {
+            hashers.push(S::HF::keyed(left.f(n)?.as_ref())?);
+        }
 
-        Ok(rct)
+        Ok(PreparedLeft {
+            px: left.px,
+            hashers,
+            key_check_value: left.key_check_value,
+            _mark: PhantomData,
+        })
     }
 
-    /// Generate the per-block nonces and cache them so we don't have to generate them every time
-    /// we want to read them
-    fn cache_nonces(&mut self) -> Result<(), Error> {
-        let mut seed: <<S as CipherSuite<W, M>>::RNG as SeedableRng>::Seed = Default::default();
-        let seed_len = std::cmp::min(seed.as_mut().len(), self.nonce_base.len());
+    /// Compare against a right ciphertext
+    ///
+    /// Produces exactly the same result as [`CipherText::compare_parts`], just with less work.
+    ///
+    pub(crate) fn compare(&self, right: &RightCipherText<S, CMP, N, W, M>) -> Result<u8, Error> {
+        self.check_key(right)?;
 
-        seed.as_mut()
-            .get_mut(0..seed_len)
-            .ok_or_else(|| {
-                Error::InternalError(format!("could not get seed slice, seed_len={seed_len}"))
+        let mut result = FirstDifference::new();
+
+        for (n, ((px, hasher), nonce)) in self
+            .px
+            .iter()
+            .zip(self.hashers.iter())
+            .zip(right.nonces()?.iter())
+            .enumerate()
+        {
+            let v_h = check_overflow(
+                right.lookup(n, *px)?.overflowing_add(M),
+                format_args!("overflow while adding M to v_h"),
+            )?;
+            let h_k_r = S::HF::hash_keyed(hasher, nonce)?;
+
+            let res = check_overflow(
+                v_h.overflowing_sub(h_k_r),
+                format_args!("overflow on v_h - h_k_r"),
+            )?
+            .rem_euclid(M);
+
+            // Every block gets looked at, whether or not an earlier one differed, so that the
+            // time taken doesn't give away where the first difference is
+            result.update(res);
+        }
+
+        Ok(result.result())
+    }
+
+    /// Make sure that the right ciphertext wasn't encrypted with a different key
+    pub(crate) fn check_key(&self, right: &RightCipherText<S, CMP, N, W, M>) -> Result<(), Error> {
+        same_key(self.key_check_value, right.key_check_value).map(|_| ())
+    }
+}
+
+/// The PRF output and inverse permutation for each of the `W` values a block can take
+///
+/// These are the same for every block of every right ciphertext that a cipher produces, so
+/// anything that makes more than one block's worth of right ciphertext only needs to work them
+/// out once, and can then share them around (between threads, even, unlike the cipher itself).
+///
+pub(crate) struct BlockKeys<S: CipherSuite<W, M>, const W: u16, const M: u8> {
+    /// `F(k, i)` for each `i`
+    prf: Zeroizing<Vec<<<S as CipherSuite<W, M>>::PRF as PseudoRandomFunction>::BlockType>>,
+    /// The value which permutes to each `i`
+    inverses: Zeroizing<Vec<u16>>,
+}
+
+impl<S: CipherSuite<W, M>, const W: u16, const M: u8> BlockKeys<S, W, M> {
+    /// Work out the keys for every value
+    pub(crate) fn new<CMP: Comparator<M>, const N: usize>(
+        cipher: &Cipher<S, CMP, N, W, M>,
+    ) -> Result<Self, Error> {
+        let mut prf = Zeroizing::new(vec![Default::default(); usize::from(W)]);
+        let mut inverses = Zeroizing::new(Vec::with_capacity(usize::from(W)));
+
+        for (i, b) in (0..W).zip(prf.iter_mut()) {
+            cipher.pseudorandomise(i, b);
+            inverses.push(cipher.inverse_permuted_value(i)?);
+        }
+
+        Ok(BlockKeys { prf, inverses })
+    }
+
+    /// The PRF output and inverse permutation for each value, in order
+    fn iter(
+        &self,
+    ) -> impl Iterator<
+        Item = (
+            &<<S as CipherSuite<W, M>>::PRF as PseudoRandomFunction>::BlockType,
+            &u16,
+        ),
+    > {
+        self.prf.iter().zip(self.inverses.iter())
+    }
+}
+
+/// A generic large-domain right ciphertext for the Lewi-Wu comparison-revealing encryption scheme.
+#[derive(Debug, Clone)]
+pub(crate) struct RightCipherText<
+    S: CipherSuite<W, M>,
+    CMP: Comparator<M>,
+    const N: usize,
+    const W: u16,
+    const M: u8,
+> {
+    /// The base nonce from which the per-block nonces are derived
+    nonce_base: [u8; 16],
+    /// Cached copies of the per-block nonces
+    ///
+    /// With the `uncached-nonces` feature, they're derived from the nonce base whenever they're
+    /// needed instead, which saves 16 bytes per block for every ciphertext held in memory.
+    ///
+    #[cfg(not(feature = "uncached-nonces"))]
+    nonce_cache: [[u8; 16]; N],
+    /// The `v_i` sequences for each block, one after the other
+    values: PackedValues<M>,
+    /// The key check value of the cipher that encrypted this, if it's known
+    key_check_value: Option<[u8; 3]>,
+
+    /// Compiler pacification; a function pointer, so that it doesn't affect auto traits
+    _mark: PhantomData<fn() -> (S, CMP)>,
+}
+
+impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, const M: u8> Drop
+    for RightCipherText<S, CMP, N, W, M>
+{
+    fn drop(&mut self) {
+        // The nonces aren't secret, as such -- they're part of the serialized ciphertext -- but
+        // there's no reason to leave them lying around in memory once the ciphertext is gone
+        self.nonce_base.zeroize();
+        #[cfg(not(feature = "uncached-nonces"))]
+        self.nonce_cache.zeroize();
+    }
+}
+
+impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, const M: u8>
+    RightCipherText<S, CMP, N, W, M>
+{
+    /// Spawn a new right ciphertext, ready to have its blocks written
+    pub(crate) fn new(cipher: &Cipher<S, CMP, N, W, M>) -> Result<Self, Error> {
+        Self::assemble(
+            cipher.nonce_base()?,
+            PackedValues::new(N.saturating_mul(usize::from(W))),
+            Some(cipher.key_check_value()),
+        )
+    }
+
+    /// Put a right ciphertext together from its nonce base and values, generating the per-block
+    /// nonces and caching them so we don't have to generate them every time we want to read them
+    #[cfg(not(feature = "uncached-nonces"))]
+    fn assemble(
+        nonce_base: [u8; 16],
+        values: PackedValues<M>,
+        key_check_value: Option<[u8; 3]>,
+    ) -> Result<Self, Error> {
+        Ok(RightCipherText {
+            nonce_cache: Self::derive_nonces(&nonce_base)?,
+            nonce_base,
+            values,
+            key_check_value,
+            _mark: PhantomData,
+        })
+    }
+
+    /// Put a right ciphertext together from its nonce base and values
+    #[cfg(feature = "uncached-nonces")]
+    #[allow(clippy::unnecessary_wraps)] // Has to match the caching version
+    fn assemble(
+        nonce_base: [u8; 16],
+        values: PackedValues<M>,
+        key_check_value: Option<[u8; 3]>,
+    ) -> Result<Self, Error> {
+        Ok(RightCipherText {
+            nonce_base,
+            values,
+            key_check_value,
+            _mark: PhantomData,
+        })
+    }
+
+    /// All of the per-block nonces, from the cache if there is one
+    #[cfg(not(feature = "uncached-nonces"))]
+    #[allow(clippy::unnecessary_wraps)] // Has to match the uncached version
+    pub(crate) fn nonces(&self) -> Result<Cow<'_, [[u8; 16]; N]>, Error> {
+        Ok(Cow::Borrowed(&self.nonce_cache))
+    }
+
+    /// All of the per-block nonces, freshly derived from the nonce base
+    #[cfg(feature = "uncached-nonces")]
+    pub(crate) fn nonces(&self) -> Result<Cow<'_, [[u8; 16]; N]>, Error> {
+        Ok(Cow::Owned(Self::derive_nonces(&self.nonce_base)?))
+    }
+
+    /// Generate the per-block nonces from a nonce base
+    fn derive_nonces(nonce_base: &[u8; 16]) -> Result<[[u8; 16]; N], Error> {
+        let mut nonces = [[0u8; 16]; N];
+        let mut ndf = Self::nonce_generator(nonce_base)?;
+
+        for nonce in &mut nonces {
+            ndf.fill_bytes(nonce);
+        }
+
+        Ok(nonces)
+    }
+
+    /// Seed the generator that produces the per-block nonces, in order, from a nonce base
+    fn nonce_generator(nonce_base: &[u8; 16]) -> Result<<S as CipherSuite<W, M>>::RNG, Error> {
+        let mut seed: <<S as CipherSuite<W, M>>::RNG as SeedableRng>::Seed = Default::default();
+        let seed_len = std::cmp::min(seed.as_mut().len(), nonce_base.len());
+
+        seed.as_mut()
+            .get_mut(0..seed_len)
+            .ok_or_else(|| {
+                Error::InternalError(format!("could not get seed slice, seed_len={seed_len}"))
             })?
-            .copy_from_slice(self.nonce_base.get(0..seed_len).ok_or_else(|| {
+            .copy_from_slice(nonce_base.get(0..seed_len).ok_or_else(|| {
                 Error::InternalError(format!(
                     "could not get nonce base slice, seed_len={seed_len}"
                 ))
             })?);
-        let mut ndf: <S as CipherSuite<W, M>>::RNG = SeedableRng::from_seed(seed);
 
-        for i in 0..N {
-            ndf.fill_bytes(self.nonce_cache.get_mut(i).ok_or_else(|| {
-                Error::RangeError(format!("failed to get {i}th nonce from cache"))
-            })?);
+        Ok(SeedableRng::from_seed(seed))
+    }
+
+    /// Encrypt every block of the plaintext into the right ciphertext
+    pub(crate) fn set_blocks(
+        &mut self,
+        cipher: &Cipher<S, CMP, N, W, M>,
+        plaintext: &PlainText<N, W>,
+    ) -> Result<(), Error> {
+        self.set_blocks_with(&BlockKeys::new(cipher)?, plaintext)
+    }
+
+    /// Encrypt every block of the plaintext into the right ciphertext, using keys that have
+    /// already been worked out
+    ///
+    /// The hash function keyed with a given `i`'s PRF output is the same for every block, so the
+    /// work is done `i`-first, so that each of them only has to be set up once per ciphertext,
+    /// rather than once per block.  Keying the hash function is the expensive part.
+    ///
+    #[cfg(not(feature = "rayon"))]
+    pub(crate) fn set_blocks_with(
+        &mut self,
+        keys: &BlockKeys<S, W, M>,
+        plaintext: &PlainText<N, W>,
+    ) -> Result<(), Error> {
+        let blocks = Self::blocks(plaintext)?;
+        let nonces = self.nonces()?.into_owned();
+
+        for (i, (b, p_i)) in (0..W).zip(keys.iter()) {
+            let hasher = S::HF::keyed(b.as_ref())?;
+
+            for (n, (value, nonce)) in blocks.iter().zip(nonces.iter()).enumerate() {
+                self.values.set(
+                    Self::index(n, i),
+                    Self::value(&hasher, *p_i, *value, nonce)?,
+                )?;
+            }
         }
 
         Ok(())
     }
 
-    /// Encrypt the value provided into the `n`th block of the right ciphertext
-    pub(crate) fn set_block(
+    /// Encrypt every block of the plaintext into the right ciphertext, using keys that have
+    /// already been worked out
+    ///
+    /// Each `i` is independent of all the others, so they're shared out between threads, each of
+    /// which keys the hash function once for its `i`, and works out that `i`'s value for every
+    /// block.  The values are then packed away in one go at the end.
+    ///
+    #[cfg(feature = "rayon")]
+    pub(crate) fn set_blocks_with(
         &mut self,
-        cipher: &Cipher<S, CMP, N, W, M>,
-        n: usize,
-        value: u16,
+        keys: &BlockKeys<S, W, M>,
+        plaintext: &PlainText<N, W>,
     ) -> Result<(), Error> {
-        if n >= N {
-            return Err(Error::RangeError(format!(
-                "attempted to write to the {n}th block of {N} in right ciphertext"
-            )));
-        }
-        if value >= W {
-            return Err(Error::RangeError(format!("attempted to write a value {value} greater than the right ciphertext block width {W}")));
-        }
+        use rayon::prelude::*;
 
-        for i in 0..W {
-            let mut b: <<S as CipherSuite<W, M>>::PRF as PseudoRandomFunction>::BlockType =
-                Default::default();
+        let blocks = Self::blocks(plaintext)?;
+        let nonces = self.nonces()?.into_owned();
+        // The values for each i, for every block, one i after the other
+        let mut values = vec![0u8; N.saturating_mul(usize::from(W))];
 
-            cipher.pseudorandomise(i, &mut b);
+        values
+            .par_chunks_mut(N.max(1))
+            .zip(keys.prf.par_iter().zip(keys.inverses.par_iter()))
+            .try_for_each(|(out, (b, p_i))| -> Result<(), Error> {
+                let hasher = S::HF::keyed(b.as_ref())?;
 
-            let p_i_y = CMP::compare(cipher.inverse_permuted_value(i)?, value);
-            let nonce = self.nonce(n)?;
-            let h_f_r = <<S as CipherSuite<W, M>>::HF as HashFunction<M>>::hash(&b.into(), &nonce)?;
+                for (v, (value, nonce)) in out.iter_mut().zip(blocks.iter().zip(nonces.iter())) {
+                    *v = Self::value(&hasher, *p_i, *value, nonce)?;
+                }
 
-            // Absolutely *shits* me that we can't get this ref once at the top of the function;
-            // nope, gotta deref it on every loop to keep the borrow checker happy
-            let block_values = self.values.get_mut(n).ok_or_else(|| {
-                Error::RangeError(format!(
-                    "attempted to set_block on {n}th block of {N} of right ciphertext"
-                ))
-            })?;
-            let v_ref = block_values.get_mut(usize::from(i)).ok_or_else(|| {
-                Error::RangeError(format!("couldn't set {i}th value of {n}th block"))
+                Ok(())
             })?;
-            *v_ref = check_overflow(p_i_y.overflowing_add(h_f_r), &format!("overflow while attempting to add right ciphertext value components p_i_y={p_i_y}, h_f_r={h_f_r}"))?.rem_euclid(M);
+
+        for (i, out) in (0..W).zip(values.chunks(N.max(1))) {
+            for (n, v) in out.iter().enumerate() {
+                self.values.set(Self::index(n, i), *v)?;
+            }
         }
 
         Ok(())
     }
 
-    /// Fetch the value of the `px`th element in the `n`th block of the [`RightCipherText`].
+    /// Encrypt a plaintext and write the serialized right ciphertext straight into `out`, one
+    /// block at a time, returning how many bytes of `out` were used
     ///
-    pub(crate) fn value(&self, n: usize, px: u16) -> Result<u8, Error> {
-        self.values
-            .get(n)
-            .ok_or_else(|| {
-                Error::RangeError(format!(
-                    "attempted to get the values of the {n}th block of {N}"
-                ))
-            })?
-            .get(usize::from(px))
-            .ok_or_else(|| {
-                Error::RangeError(format!("couldn't get the {px}th value of the {n}th block"))
-            })
-            .copied()
+    /// Unlike [`set_blocks()`](Self::set_blocks), the work is done block-first, so that each
+    /// block's values can be packed onto the end of the output as soon as they're all known, and
+    /// only one block's worth of values is ever held in memory.  The PRF output and inverse
+    /// permutation for each `i` are still only worked out once per ciphertext, and kept (W of
+    /// each) until it's finished, but the price is that the hash function has to be keyed with
+    /// them once per block, rather than once per ciphertext.
+    ///
+    /// The caller is expected to have made sure that `out` is big enough; if it isn't, the
+    /// ciphertext is abandoned part-way through.
+    ///
+    pub(crate) fn encrypt_into(
+        cipher: &Cipher<S, CMP, N, W, M>,
+        plaintext: &PlainText<N, W>,
+        out: &mut [u8],
+    ) -> Result<usize, Error> {
+        let blocks = Self::blocks(plaintext)?;
+        let nonce_base = cipher.nonce_base()?;
+        let nonces = Zeroizing::new(Self::derive_nonces(&nonce_base)?);
+
+        let mut len = write_at(out, 0, &nonce_base)?;
+
+        // These are the same for every block, so there's no point working them out more than once
+        let keys = BlockKeys::new(cipher)?;
+
+        let mut block_values = PackedValues::<M>::new(usize::from(W));
+        let mut bits = WritableBitList::new(usize::from(W).saturating_mul(CMP::Encoding::MAX_BITS));
+
+        for (value, nonce) in blocks.iter().zip(nonces.iter()) {
+            for (i, (b, p_i)) in keys.iter().enumerate() {
+                let hasher = S::HF::keyed(b.as_ref())?;
+
+                block_values.set(i, Self::value(&hasher, *p_i, *value, nonce)?)?;
+            }
+
+            CMP::Encoding::pack_into(&block_values, &mut bits)?;
+            len = write_at(out, len, &bits.take_bytes())?;
+        }
+
+        write_at(out, len, &bits.vec())
+    }
+
+    /// The maximum size of a serialized right ciphertext of this type
+    pub(crate) const fn max_serialized_len() -> usize {
+        N.saturating_mul(W as usize)
+            .div_ceil(CMP::Encoding::GROUP_SIZE)
+            .saturating_mul(CMP::Encoding::GROUP_BITS)
+            .div_ceil(8)
+            .saturating_add(16)
+    }
+
+    /// Pull the blocks out of a plaintext, making sure each of them will fit in a block of the
+    /// right ciphertext
+    ///
+    /// Our copy of the plaintext gets scrubbed as soon as the caller is done with it.
+    ///
+    fn blocks(plaintext: &PlainText<N, W>) -> Result<Zeroizing<[u16; N]>, Error> {
+        let mut blocks: Zeroizing<[u16; N]> = Zeroizing::new([0u16; N]);
+        for (n, block) in blocks.iter_mut().enumerate() {
+            *block = plaintext.block(n)?;
+            if *block >= W {
+                return Err(Error::RangeError(format!(
+                    "attempted to write a value greater than the right ciphertext block width {W} to block {n}"
+                )));
+            }
+        }
+
+        Ok(blocks)
+    }
+
+    /// Work out the right ciphertext value for the `i`th element of a block, given the hash
+    /// function keyed with the PRF output for `i`, the permuted `i`, the block's plaintext value,
+    /// and the block's nonce
+    ///
+    /// The PRF output is as good as key material, so the hasher is the caller's to scrub.
+    ///
+    fn value(
+        hasher: &<<S as CipherSuite<W, M>>::HF as HashFunction<M>>::Keyed,
+        p_i: u16,
+        value: u16,
+        nonce: &[u8; 16],
+    ) -> Result<u8, Error> {
+        let p_i_y = CMP::compare(p_i, value);
+        let h_f_r = S::HF::hash_keyed(hasher, nonce)?;
+
+        // No values in the error message; they'd be left lying around on the heap, even
+        // when there isn't an error, which would rather defeat the purpose of scrubbing the PRF
+        // output
+        Ok(check_overflow(
+            p_i_y.overflowing_add(h_f_r),
+            format_args!("overflow while attempting to add right ciphertext value components"),
+        )?
+        .rem_euclid(M))
+    }
+
+    /// The base nonce from which the per-block nonces are derived
+    pub(crate) fn nonce_base(&self) -> &[u8; 16] {
+        &self.nonce_base
+    }
+
+    /// Fetch the value of the `px`th element in the `n`th block, for callers which have already
+    /// made sure that `n < N` and `px < W`
+    ///
+    /// This is what the comparison loops use, so it does the bare minimum: there's no
+    /// range-checking beyond what the packed values do anyway, and the error (which can only
+    /// happen if there's a bug) doesn't need anything formatted.
+    ///
+    #[inline]
+    fn lookup(&self, n: usize, px: u16) -> Result<u8, Error> {
+        self.values.get(Self::index(n, px)).ok_or_else(|| {
+            Error::InternalError("value lookup outside right ciphertext".to_string())
+        })
+    }
+
+    /// Where the `i`th value of the `n`th block lives in the packed values
+    ///
+    /// Values are stored block by block, so a block's values are all next to each other.
+    ///
+    fn index(n: usize, i: u16) -> usize {
+        n.saturating_mul(usize::from(W))
+            .saturating_add(usize::from(i))
     }
 
     /// Fetch the nonce for the `n`th block of the [`RightCipherText`].
     ///
+    #[cfg(not(feature = "uncached-nonces"))]
     pub(crate) fn nonce(&self, n: usize) -> Result<[u8; 16], Error> {
         self.nonce_cache
             .get(n)
@@ -406,134 +1327,70 @@ impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, con
             .copied()
     }
 
-    /// Decode a packed set of binary values into the nested vector-of-vectors that is the
-    /// in-memory representation of the values arrays in the right ciphertext.
-    fn unpack_binary_values(bytes: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
-        let mut v = ReadableBitList::from_slice(bytes);
-        let mut vals: Vec<Vec<u8>> = Vec::with_capacity(N);
-
-        for _n in 0..N {
-            let mut block_vals = Vec::with_capacity(W.into());
-            for _w in 0..W {
-                let b = u8::from(v.shift().ok_or_else(|| {
-                    Error::ParseError(
-                        "end-of-data reached while unpacking binary values".to_string(),
-                    )
-                })?);
-                block_vals.push(b);
-            }
-            vals.push(block_vals);
+    /// Derive the nonce for the `n`th block of the [`RightCipherText`].
+    ///
+    /// The nonces come out of the generator in order, so this has to generate (and throw away)
+    /// all of the nonces before the one that's wanted.  Anything that needs every nonce should use
+    /// [`nonces()`](Self::nonces) instead.
+    ///
+    #[cfg(feature = "uncached-nonces")]
+    pub(crate) fn nonce(&self, n: usize) -> Result<[u8; 16], Error> {
+        if n >= N {
+            return Err(Error::RangeError(format!(
+                "attempted to get the {n}th nonce of {N} blocks"
+            )));
         }
 
-        if v.fully_consumed() {
-            Ok(vals)
-        } else {
-            Err(Error::ParseError(
-                "bitlist longer than required number of entries".to_string(),
-            ))
+        let mut ndf = Self::nonce_generator(&self.nonce_base)?;
+        let mut nonce = [0u8; 16];
+
+        for _ in 0..=n {
+            ndf.fill_bytes(&mut nonce);
         }
-    }
 
-    /// Jam all of the binary values for this ciphertext into a byte vector, in such a way that
-    /// they take up a *lot* less space than they would if we just wrote out each value as a u8.
-    fn pack_binary_values(&self) -> Result<Vec<u8>, Error> {
-        let mut v = WritableBitList::new(N.saturating_mul(usize::from(W)));
+        Ok(nonce)
+    }
 
-        for n in 0..N {
-            for w in 0..W {
-                let val = self
-                    .values
-                    .get(n)
-                    .ok_or_else(|| {
-                        Error::RangeError(format!(
-                            "could not get value list for {n}th block because it wasn't there"
-                        ))
-                    })?
-                    .get(usize::from(w))
-                    .ok_or_else(|| {
-                        Error::RangeError(format!("could not get {w}th value from {n}th block"))
-                    })?;
-                v.push(*val > 0)?;
-            }
-        }
+    /// Parse a serialized right ciphertext, keeping its values in `storage` (reusing its
+    /// allocation, if it's big enough)
+    pub(crate) fn from_slice_in(bytes: &[u8], storage: Vec<u64>) -> Result<Self, Error> {
+        let nonce_base: [u8; 16] = clone_into_array(bytes.get(0..16).ok_or_else(|| {
+            Error::ParseError("end-of-data found while looking for nonce base".to_string())
+        })?);
 
-        Ok(v.vec())
-    }
-
-    /// Decode a packed set of trinary values into the nested vector-of-vectors that is the
-    /// in-memory representation of the values arrays in the right ciphertext.
-    fn unpack_trinary_values(bytes: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
-        let mut v = ReadableBitList::from_slice(bytes);
-        let mut vals: Vec<Vec<u8>> = Vec::with_capacity(N);
-
-        for _n in 0..N {
-            let mut block_vals = Vec::with_capacity(W.into());
-            for _w in 0..W {
-                let b = if v.shift().ok_or_else(|| {
-                    Error::ParseError(
-                        "end-of-data reached while unpacking trinary values".to_string(),
-                    )
-                })? {
-                    if v.shift().ok_or_else(|| {
-                        Error::ParseError(
-                            "end-of-data reached while unpacking trinary values".to_string(),
-                        )
-                    })? {
-                        2
-                    } else {
-                        1
-                    }
-                } else {
-                    0
-                };
-                block_vals.push(b);
-            }
-            vals.push(block_vals);
-        }
+        let value_slice = bytes.get(16..).ok_or_else(|| {
+            Error::ParseError("end-of-data found while looking for value bitlist".to_string())
+        })?;
+        let values =
+            CMP::Encoding::unpack_in(value_slice, N.saturating_mul(usize::from(W)), storage)?;
 
-        if v.fully_consumed() {
-            Ok(vals)
-        } else {
-            Err(Error::ParseError(
-                "bitlist longer than required number of entries".to_string(),
-            ))
-        }
+        Self::assemble(nonce_base, values, None)
     }
 
-    /// Jam all of the trinary values for this ciphertext into a byte vector, in such a way that
-    /// they take up a *lot* less space than they would if we just wrote out each value as a u8.
-    fn pack_trinary_values(&self) -> Result<Vec<u8>, Error> {
-        let mut v = WritableBitList::new(N.saturating_mul(usize::from(W).saturating_mul(2usize)));
+    /// Hand over the storage that the values are kept in, so it can be reused by
+    /// [`from_slice_in()`](Self::from_slice_in), leaving this ciphertext with no values
+    pub(crate) fn take_storage(&mut self) -> Vec<u64> {
+        self.values.take_storage()
+    }
 
-        for n in 0..N {
-            for w in 0..W {
-                let val = self
-                    .values
-                    .get(n)
-                    .ok_or_else(|| {
-                        Error::RangeError(format!(
-                            "could not get value list for {n}th block because it wasn't there"
-                        ))
-                    })?
-                    .get(usize::from(w))
-                    .ok_or_else(|| {
-                        Error::RangeError(format!("could not get {w}th value from {n}th block"))
-                    })?;
-
-                if *val == 0 {
-                    v.push(false)?;
-                } else {
-                    v.push(true)?;
-                    if *val > 1 {
-                        v.push(true)?;
-                    } else {
-                        v.push(false)?;
-                    }
-                }
-            }
-        }
+    /// Produce a right ciphertext containing only the first `K` blocks of this one
+    ///
+    /// Since the per-block nonces are generated in sequence from the nonce base, the first `K`
+    /// nonces of an `N` block ciphertext are the same as the nonces of a `K` block ciphertext with
+    /// the same nonce base, so the nonce base can be carried across unchanged.
+    ///
+    pub(crate) fn prefix<const K: usize>(&self) -> Result<RightCipherText<S, CMP, K, W, M>, Error> {
+        let values = self
+            .values
+            .prefix(K.saturating_mul(usize::from(W)))
+            .filter(|_| K <= N)
+            .ok_or_else(|| {
+                Error::RangeError(format!(
+                    "attempted to take the first {K} blocks of a {N} block right ciphertext"
+                ))
+            })?;
 
-        Ok(v.vec())
+        RightCipherText::assemble(self.nonce_base, values, self.key_check_value)
     }
 }
 
@@ -541,33 +1398,7 @@ impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, con
     Serializable<N, W, M> for RightCipherText<S, CMP, N, W, M>
 {
     fn from_slice(bytes: &[u8]) -> Result<Self, Error> {
-        let nonce_base: [u8; 16] = clone_into_array(bytes.get(0..16).ok_or_else(|| {
-            Error::ParseError("end-of-data found while looking for nonce base".to_string())
-        })?);
-
-        let value_slice = bytes.get(16..).ok_or_else(|| {
-            Error::ParseError("end-of-data found while looking for value bitlist".to_string())
-        })?;
-        let values = if M == 2 {
-            Self::unpack_binary_values(value_slice)
-        } else if M == 3 {
-            Self::unpack_trinary_values(value_slice)
-        } else {
-            Err(Error::RangeError(format!(
-                "don't know how to unpack bytes for M={M}"
-            )))
-        }?;
-
-        let mut rct = RightCipherText::<S, CMP, N, W, M> {
-            nonce_base,
-            values,
-            nonce_cache: [Default::default(); N],
-
-            _mark: (PhantomData, PhantomData),
-        };
-        rct.cache_nonces()?;
-
-        Ok(rct)
+        Self::from_slice_in(bytes, Vec::new())
     }
 
     fn to_vec(&self) -> Result<Vec<u8>, Error> {
@@ -577,17 +1408,7 @@ impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, con
 
         v.extend_from_slice(&self.nonce_base);
 
-        let value_slice = if M == 2 {
-            self.pack_binary_values()
-        } else if M == 3 {
-            self.pack_trinary_values()
-        } else {
-            Err(Error::RangeError(format!(
-                "don't know how to pack values for M={M}"
-            )))
-        }?;
-
-        v.extend_from_slice(&value_slice);
+        v.extend_from_slice(&CMP::Encoding::pack(&self.values)?);
 
         Ok(v)
     }
@@ -626,13 +1447,13 @@ impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, con
         cipher: &Cipher<S, CMP, N, W, M>,
         plaintext: &PlainText<N, W>,
     ) -> Result<Self, Error> {
-        let mut left = LeftCipherText::new();
+        let mut left = LeftCipherText::new(Some(cipher.key_check_value()));
         let mut right = RightCipherText::new(cipher)?;
 
         for n in 0..N {
             left.set_block(cipher, n, plaintext.block(n)?)?;
-            right.set_block(cipher, n, plaintext.block(n)?)?;
         }
+        right.set_blocks(cipher, plaintext)?;
 
         Ok(CipherText {
             left: Some(left),
@@ -654,74 +1475,382 @@ impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, con
     ) -> Result<Self, Error> {
         let mut right = RightCipherText::new(cipher)?;
 
-        for n in 0..N {
-            right.set_block(cipher, n, plaintext.block(n)?)?;
-        }
+        right.set_blocks(cipher, plaintext)?;
 
         Ok(CipherText { left: None, right })
     }
 
-    /// Generic comparison function between [`CipherText`]s.
+    /// Encrypt a whole batch of plaintexts, producing full ciphertexts if `full` is set, and
+    /// right-only ones otherwise
     ///
-    /// Comparison in the Lewi-Wu ORE scheme produces an integer result, and it is up to the
-    /// comparator to interpret that integer into something meaningful for the given comparator.
+    /// Everything that needs the cipher itself (the left parts, and the nonce bases of the right
+    /// parts) is done first, one plaintext at a time, since the cipher's RNG can't be shared.
+    /// The right parts, which are where nearly all the work is, only need the keys that are
+    /// common to every block, so with the `rayon` feature they're done in parallel.
     ///
-    pub(crate) fn compare(&self, other: &Self) -> Result<u8, Error> {
-        match &self.left {
-            None => Err(Error::ComparisonError(
-                "No left part in this ciphertext".to_string(),
-            )),
-            Some(v) => Self::compare_parts(v, &other.right),
+    pub(crate) fn new_batch(
+        cipher: &Cipher<S, CMP, N, W, M>,
+        plaintexts: &[PlainText<N, W>],
+        full: bool,
+    ) -> Result<Vec<Self>, Error> {
+        let keys = BlockKeys::new(cipher)?;
+        let mut ciphertexts = Vec::with_capacity(plaintexts.len());
+
+        for plaintext in plaintexts {
+            let left = if full {
+                let mut left = LeftCipherText::new(Some(cipher.key_check_value()));
+
+                for n in 0..N {
+                    left.set_block(cipher, n, plaintext.block(n)?)?;
+                }
+
+                Some(left)
+            } else {
+                None
+            };
+
+            ciphertexts.push(CipherText {
+                left,
+                right: RightCipherText::new(cipher)?,
+            });
+        }
+
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+
+            ciphertexts
+                .par_iter_mut()
+                .zip(plaintexts.par_iter())
+                .try_for_each(|(ct, plaintext)| ct.right.set_blocks_with(&keys, plaintext))?;
         }
+        #[cfg(not(feature = "rayon"))]
+        for (ct, plaintext) in ciphertexts.iter_mut().zip(plaintexts.iter()) {
+            ct.right.set_blocks_with(&keys, plaintext)?;
+        }
+
+        Ok(ciphertexts)
     }
 
-    /// Determine whether this ciphertext has a "left" ciphertext
+    /// Encrypt the plaintext straight into the serialized form of a right-only ciphertext,
+    /// without ever holding the whole ciphertext in memory
     ///
-    pub fn has_left(&self) -> bool {
-        self.left.is_some()
+    /// The output is exactly what `new_right(...).to_vec()` would have produced (for the same
+    /// nonce), but it is written into `out` a block at a time.  The right ciphertext length comes
+    /// before the right ciphertext, and isn't known until the last block has been packed, so it
+    /// gets filled in at the end.
+    ///
+    pub(crate) fn right_encrypt_into(
+        cipher: &Cipher<S, CMP, N, W, M>,
+        plaintext: &PlainText<N, W>,
+        out: &mut [u8],
+    ) -> Result<usize, Error> {
+        let max_len = Self::max_right_serialized_len();
+        if out.len() < max_len {
+            return Err(Error::RangeError(format!(
+                "output buffer too small for serialized right ciphertext (need {max_len} bytes, got {})",
+                out.len()
+            )));
+        }
+
+        let right_len = RightCipherText::<S, CMP, N, W, M>::encrypt_into(
+            cipher,
+            plaintext,
+            out.get_mut(3..).unwrap_or_default(),
+        )?;
+        let len_bytes = u16::try_from(right_len)
+            .map_err(|e| {
+                Error::RangeError(format!(
+                    "Couldn't represent length of right ciphertext ({right_len}) as u16 ({e})"
+                ))
+            })?
+            .to_be_bytes();
+
+        write_at(out, 0, &[CipherTextType::RightOnly.byte()])?;
+        write_at(out, 1, &len_bytes)?;
+
+        Ok(right_len.saturating_add(3))
     }
 
-    /// Compare two ciphertexts
+    /// The most space that a serialized right-only ciphertext of this type can take up
     ///
-    /// Returns the numeric comparison value, which needs to be run through the comparator's invert
-    /// function in order to convert that into a "proper" logical comparison value.
+    /// That's one type byte, two bytes of right ciphertext length, and the right ciphertext itself.
     ///
-    fn compare_parts(
-        left: &LeftCipherText<S, CMP, N, W, M>,
-        right: &RightCipherText<S, CMP, N, W, M>,
-    ) -> Result<u8, Error> {
-        let mut result: Option<u8> = None;
-
-        for n in 0..N {
-            let v_h = check_overflow(
-                right.value(n, left.px(n)?)?.overflowing_add(M),
-                "overflow while adding M to v_h",
-            )?;
-            let h_k_r = S::HF::hash(&left.f(n)?.into(), &right.nonce(n)?)?;
+    pub(crate) const fn max_right_serialized_len() -> usize {
+        RightCipherText::<S, CMP, N, W, M>::max_serialized_len().saturating_add(3)
+    }
 
-            let res = check_overflow(v_h.overflowing_sub(h_k_r), "overflow on v_h - h_k_r")?
-                .rem_euclid(M);
+    /// How many bytes a serialized right-only ciphertext of this type takes up, if they're all
+    /// the same size
+    ///
+    /// The values in the right ciphertexts of equality and partial order comparisons are packed
+    /// into a fixed number of bits apiece, so every right-only ciphertext with the same parameters
+    /// is the same size, which makes them suitable for storing in fixed-width columns (see
+    /// [`to_array()`](Serializable::to_array)).  Order-revealing ciphertexts use a
+    /// variable-length encoding, which saves a good deal of space on average, but means that their
+    /// size varies; for those, this is `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::{ere, ore};
+    ///
+    /// assert_eq!(Some(147), ere::CipherText::<4, 256>::right_serialized_len());
+    /// assert_eq!(None, ore::CipherText::<4, 256>::right_serialized_len());
+    /// ```
+    ///
+    #[must_use]
+    pub const fn right_serialized_len() -> Option<usize> {
+        if CMP::Encoding::FIXED_WIDTH {
+            Some(Self::max_right_serialized_len())
+        } else {
+            None
+        }
+    }
 
-            if res != 0 && result.is_none() {
-                // Returning early here would further damage our attempts to
-                // do constant-time comparisons
-                result = Some(res);
+    /// How many bytes a serialized full ciphertext of this type takes up, if they're all the same
+    /// size
+    ///
+    /// That's the right-only size (see [`right_serialized_len()`](Self::right_serialized_len)),
+    /// plus two bytes of left ciphertext length and the left ciphertext itself, so it's `None` for
+    /// exactly the same comparators.
+    ///
+    #[must_use]
+    pub const fn full_serialized_len() -> Option<usize> {
+        match Self::right_serialized_len() {
+            Some(right_len) => {
+                Some(
+                    right_len
+                        .saturating_add(2)
+                        .saturating_add(LeftCipherText::<S, CMP, N, W, M>::serialized_len()),
+                )
             }
+            None => None,
         }
+    }
 
-        Ok(result.unwrap_or(0))
+    /// Generic comparison function between [`CipherText`]s.
+    ///
+    /// Comparison in the Lewi-Wu ORE scheme produces an integer result, and it is up to the
+    /// comparator to interpret that integer into something meaningful for the given comparator.
+    ///
+    pub(crate) fn compare(&self, other: &Self) -> Result<u8, Error> {
+        match &self.left {
+            None => Err(Error::Incomparable {
+                reason: "No left part in this ciphertext".to_string(),
+            }),
+            Some(v) => Self::compare_parts(v, &other.right),
+        }
     }
-}
 
-impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, const M: u8>
-    Serializable<N, W, M> for CipherText<S, CMP, N, W, M>
-{
-    fn from_slice(bytes: &[u8]) -> Result<Self, Error> {
+    /// Compare the right part of this ciphertext against the left part of another
+    ///
+    /// This is the same comparison as [`compare()`](Self::compare), but with the ciphertexts'
+    /// roles swapped: the result is still from the perspective of this ciphertext (so "less" means
+    /// that the value in this ciphertext is less than the value in `other`), but it's `other`
+    /// that needs to have a left part.
+    ///
+    pub(crate) fn compare_reversed(&self, other: &Self) -> Result<u8, Error> {
+        match &other.left {
+            None => Err(Error::Incomparable {
+                reason: "No left part in the other ciphertext".to_string(),
+            }),
+            Some(v) => Ok(CMP::reverse(Self::compare_parts(v, &self.right)?)),
+        }
+    }
+
+    /// Compare this ciphertext against another, using whichever of them has a left part
+    ///
+    /// The result is always from the perspective of this ciphertext, regardless of which left part
+    /// gets used.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::Incomparable`] if neither ciphertext has a left part, and
+    /// [`Error::ParamsError`] if they're known to have been encrypted with different keys.
+    ///
+    pub(crate) fn compare_either(&self, other: &Self) -> Result<u8, Error> {
+        if self.left.is_some() {
+            self.compare(other)
+        } else if other.left.is_some() {
+            self.compare_reversed(other)
+        } else {
+            Err(Error::Incomparable {
+                reason: "neither ciphertext has a left part".to_string(),
+            })
+        }
+    }
+
+    /// Wrap this ciphertext in a self-describing JSON document, for handing to someone else
+    ///
+    /// The envelope is a JSON object with the following fields, all of which must be present:
+    ///
+    /// * `v` -- the version of the envelope format, currently always `1`;
+    /// * `suite` -- the name of the ciphersuite, such as `aes128v1`;
+    /// * `n`, `w` -- the number of blocks, and the block width;
+    /// * `m` -- the number of possible comparison results, which identifies the comparator (`3`
+    ///   for `ore`, `2` for `ere`, and `4` for `pore`);
+    /// * `key_id` -- the given key identifier, which is whatever the parties exchanging
+    ///   ciphertexts have agreed to call the key (such as the `key_id` in a
+    ///   [`FieldConfig`](crate::FieldConfig)); and
+    /// * `ct_b64` -- the serialized ciphertext, in standard, padded, base64.
+    ///
+    /// Only the `ct_b64` field is secret, in the sense that it reveals what the ciphertext
+    /// reveals.  The key ID is copied into the envelope as-is, so it shouldn't be, or contain,
+    /// anything sensitive.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::ore;
+    ///
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let key = [0u8; 32];
+    /// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+    /// let json = cipher.right_encrypt(&42u32.try_into()?)?.to_json_envelope("customers-2024")?;
+    ///
+    /// // ... send it somewhere, and at the other end ...
+    /// let value = ore::CipherText::<4, 256>::from_json_envelope(&json, "customers-2024")?;
+    /// assert!(cipher.full_encrypt(&42u32.try_into()?)?.try_eq(&value)?);
+    ///
+    /// // Anything other than an aes128v1-ore/4x256 ciphertext, for this key, is rejected
+    /// assert!(ore::CipherText::<4, 256>::from_json_envelope(&json, "customers-2023").is_err());
+    /// assert!(ore::CipherText::<8, 256>::from_json_envelope(&json, "customers-2024").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the ciphertext can't be serialized, which should only happen if
+    /// there's a bug somewhere.
+    ///
+    #[cfg(feature = "serde")]
+    pub fn to_json_envelope(&self, key_id: &str) -> Result<String, Error> {
+        crate::envelope::wrap(self, key_id)
+    }
+
+    /// Take a ciphertext out of a JSON envelope produced by
+    /// [`to_json_envelope()`](Self::to_json_envelope)
+    ///
+    /// Everything the envelope says about the ciphertext is checked against what's expected before
+    /// the ciphertext itself is even decoded.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::ParseError`] if the envelope isn't well-formed, or is of a version
+    /// this version of cretrit doesn't understand, [`Error::ParamsError`] if the envelope is for a
+    /// different ciphersuite, comparator, block count, or width, [`Error::KeyError`] if the key ID
+    /// doesn't match `key_id`, or any error that
+    /// [`from_slice()`](crate::SerializableCipherText::from_slice) can return if the ciphertext
+    /// itself is invalid.
+    ///
+    #[cfg(feature = "serde")]
+    pub fn from_json_envelope(json: &str, key_id: &str) -> Result<Self, Error> {
+        crate::envelope::unwrap(json, key_id)
+    }
+
+    /// Serialize this ciphertext with a header that describes it
+    ///
+    /// The header records the wire format version, the ciphersuite, the block count, width, and
+    /// comparator of the ciphertext, and the given [key check
+    /// value](crate::Cipher::key_check_value) (which should be that of the cipher that encrypted
+    /// it), so that
+    /// [`from_slice_with_header()`](Self::from_slice_with_header) can say exactly what's wrong
+    /// when it's given a ciphertext of the wrong sort, or one encrypted with a different key,
+    /// rather than failing to parse it, or (worse) parsing it successfully and comparing it as
+    /// nonsense.  That makes this the form to
+    /// use wherever ciphertexts of several types might get mixed up, or where they need to
+    /// survive changes to the format.  The header takes up twenty or so bytes, and (unlike the
+    /// plain [`to_vec()`](Serializable::to_vec) form) it isn't understood by
+    /// [`sanitize_serialized()`](crate::sanitize_serialized) or the other functions that work
+    /// directly on serialized ciphertexts.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::ore;
+    ///
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let key = [0u8; 32];
+    /// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+    /// let kcv = cipher.key_check_value();
+    /// let stored = cipher.right_encrypt(&42u32.try_into()?)?.to_vec_with_header(kcv)?;
+    ///
+    /// let value = ore::CipherText::<4, 256>::from_slice_with_header(&stored, kcv)?;
+    /// assert!(cipher.full_encrypt(&42u32.try_into()?)?.try_eq(&value)?);
+    ///
+    /// // Anything other than an aes128v1-ore/4x256 ciphertext is rejected
+    /// assert!(ore::CipherText::<8, 256>::from_slice_with_header(&stored, kcv).is_err());
+    ///
+    /// // As is one encrypted with a different key
+    /// let other = ore::Cipher::<4, 256>::new(&[1u8; 32])?.key_check_value();
+    /// assert!(ore::CipherText::<4, 256>::from_slice_with_header(&stored, other).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the ciphertext can't be serialized, which should only happen if
+    /// there's a bug somewhere.
+    ///
+    pub fn to_vec_with_header(&self, key_check_value: [u8; 3]) -> Result<Vec<u8>, Error> {
+        crate::wire_header::wrap(self, key_check_value)
+    }
+
+    /// Deserialize a ciphertext produced by [`to_vec_with_header()`](Self::to_vec_with_header)
+    ///
+    /// Everything the header says about the ciphertext is checked against what's expected before
+    /// the ciphertext itself is even looked at, including that its key check value is
+    /// `key_check_value` (which will usually come from the
+    /// [`key_check_value()`](crate::Cipher::key_check_value) of the cipher the ciphertext is going
+    /// to be compared or decrypted with).  The ciphertext remembers that key check value, so
+    /// comparing it against a ciphertext known to have been encrypted with a different key is an
+    /// error, rather than a meaningless result.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::ParseError`] if the header is missing, truncated, or of a version
+    /// this version of cretrit doesn't understand, [`Error::ParamsError`] if the header is for a
+    /// different ciphersuite, comparator, block count, width, or key, or any error that
+    /// [`from_slice()`](crate::SerializableCipherText::from_slice) can return if the ciphertext
+    /// itself is invalid.
+    ///
+    pub fn from_slice_with_header(bytes: &[u8], key_check_value: [u8; 3]) -> Result<Self, Error> {
+        crate::wire_header::unwrap(bytes, key_check_value)
+    }
+
+    /// Record that this ciphertext was encrypted with a key with the given KCV, so that comparing
+    /// it against one encrypted with a different key fails
+    pub(crate) fn with_key_check_value(mut self, key_check_value: [u8; 3]) -> Self {
+        if let Some(left) = self.left.as_mut() {
+            left.key_check_value = Some(key_check_value);
+        }
+        self.right.key_check_value = Some(key_check_value);
+        self
+    }
+
+    /// Deserialize a ciphertext, as [`from_slice()`](crate::SerializableCipherText::from_slice)
+    /// does, but reusing memory from a ciphertext previously handed back to `pool`
+    ///
+    /// See [`CipherTextPool`] for when (and how) that's worth doing.
+    ///
+    /// # Errors
+    ///
+    /// Can return any error that [`from_slice()`](crate::SerializableCipherText::from_slice) can.
+    ///
+    pub fn from_slice_pooled(bytes: &[u8], pool: &mut CipherTextPool) -> Result<Self, Error> {
+        Self::from_slice_in(bytes, pool.take())
+    }
+
+    /// Deserialize a ciphertext, keeping the values of its right part in `storage` (reusing its
+    /// allocation, if it's big enough)
+    pub(crate) fn from_slice_in(bytes: &[u8], storage: Vec<u64>) -> Result<Self, Error> {
         let mut v = bytes;
 
-        let t = v.first().ok_or_else(|| {
-            Error::ParseError("end-of-data while looking for ciphertext type marker".to_string())
-        })?;
+        let t = CipherTextType::of_serialized(v)?;
+
         v = v.get(1..).ok_or_else(|| {
             Error::ParseError(
                 "end-of-data while looking for rest of ciphertext after ciphertext type marker"
@@ -729,9 +1858,7 @@ impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, con
             )
         })?;
 
-        let left: Option<LeftCipherText<S, CMP, N, W, M>> = if *t == 0 {
-            None
-        } else if *t == 1 {
+        let left: Option<LeftCipherText<S, CMP, N, W, M>> = if t.has_left() {
             let len_bytes = v.get(..2).ok_or_else(|| {
                 Error::ParseError(
                     "end-of-data while looking for left ciphertext length".to_string(),
@@ -756,7 +1883,7 @@ impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, con
             })?;
             Some(LeftCipherText::<S, CMP, N, W, M>::from_slice(left_bytes)?)
         } else {
-            return Err(Error::ParseError(format!("unrecognised type byte {t}")));
+            None
         };
 
         let len_bytes = v.get(..2).ok_or_else(|| {
@@ -775,7 +1902,7 @@ impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, con
             let right_bytes = v.get(..len).ok_or_else(|| {
                 Error::ParseError("end-of-data while looking for right ciphertext".to_string())
             })?;
-            let right = RightCipherText::<S, CMP, N, W, M>::from_slice(right_bytes)?;
+            let right = RightCipherText::<S, CMP, N, W, M>::from_slice_in(right_bytes, storage)?;
 
             Ok(CipherText::<S, CMP, N, W, M> { left, right })
         } else {
@@ -786,6 +1913,404 @@ impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, con
         }
     }
 
+    /// Hand over the memory this ciphertext keeps its right part's values in, so it can be
+    /// reused, leaving the ciphertext with no values
+    pub(crate) fn take_storage(&mut self) -> Vec<u64> {
+        self.right.take_storage()
+    }
+
+    /// Determine whether this ciphertext has a "left" ciphertext
+    ///
+    pub fn has_left(&self) -> bool {
+        self.left.is_some()
+    }
+
+    /// The type of ciphertext this is, as recorded in the type byte when it's serialized
+    #[must_use]
+    pub fn cipher_text_type(&self) -> CipherTextType {
+        if self.left.is_some() {
+            CipherTextType::Full
+        } else {
+            CipherTextType::RightOnly
+        }
+    }
+
+    /// The "left" part of this ciphertext, if it has one
+    ///
+    /// See [`LeftCipherText`] for what you might want to do with it.
+    ///
+    #[must_use]
+    pub fn left(&self) -> Option<&LeftCipherText<S, CMP, N, W, M>> {
+        self.left.as_ref()
+    }
+
+    /// Determine whether two ciphertexts encrypt the same value, by comparing their left parts
+    ///
+    /// Left ciphertexts are deterministic, so two left ciphertexts produced by the same cipher
+    /// (same key, same block count and width) are identical if, and only if, they encrypt the same
+    /// value.  Checking that is a lot cheaper than a full comparison, which has to hash every
+    /// block of the right ciphertext.
+    ///
+    /// This only works when *both* ciphertexts have a left part, which means that both of them
+    /// carry the deterministic, and therefore frequency-revealing, part of the ciphertext; see
+    /// [the left ciphertext caveats](Self#the-left-ciphertext) before storing left parts just so
+    /// you can use this.  Also, ciphertexts produced by different keys will simply compare as
+    /// unequal, rather than producing an error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::ere;
+    ///
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let key = [0u8; 32];
+    /// let cipher = ere::Cipher::<4, 256>::new(&key)?;
+    /// let a = cipher.full_encrypt(&42u32.try_into()?)?;
+    /// let b = cipher.full_encrypt(&42u32.try_into()?)?;
+    /// let c = cipher.full_encrypt(&9001u32.try_into()?)?;
+    ///
+    /// assert!(a.left_eq(&b)?);
+    /// assert!(!a.left_eq(&c)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::Incomparable`] if either ciphertext doesn't have a left part.
+    ///
+    pub fn left_eq(&self, other: &Self) -> Result<bool, Error> {
+        match (&self.left, &other.left) {
+            (Some(a), Some(b)) => Ok(a.same_as(b)),
+            _ => Err(Error::Incomparable {
+                reason: "both ciphertexts need a left part to compare left parts".to_string(),
+            }),
+        }
+    }
+
+    /// A fast, unkeyed digest of this ciphertext's serialized form, for spotting duplicates
+    ///
+    /// Two ciphertexts have the same digest if they serialize to the same bytes, which, because
+    /// every right ciphertext has its own random nonce, only happens when the same ciphertext has
+    /// been stored twice (such as by an accidental double-write), not when two ciphertexts happen
+    /// to encrypt the same value.  The digest is the 64-bit FNV-1a hash of
+    /// [`to_vec()`](Serializable::to_vec), so storage that only has the serialized bytes can
+    /// compute the same value without cretrit.
+    ///
+    /// This is *not* a cryptographic hash: it's easy to find different ciphertexts with the same
+    /// digest, so a match means "probably a duplicate; now compare the bytes", not "definitely the
+    /// same".  Nor is it a blind index, as it takes no key and says nothing about the plaintext;
+    /// for finding ciphertexts that encrypt the same value, see [`left_eq()`](Self::left_eq).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::ore;
+    /// use cretrit::SerializableCipherText;
+    ///
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let key = [0u8; 32];
+    /// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+    /// let a = cipher.right_encrypt(&42u32.try_into()?)?;
+    /// let copy = ore::CipherText::<4, 256>::from_slice(&a.to_vec()?)?;
+    /// let b = cipher.right_encrypt(&42u32.try_into()?)?;
+    ///
+    /// assert_eq!(a.storage_digest()?, copy.storage_digest()?);
+    /// assert_ne!(a.storage_digest()?, b.storage_digest()?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the ciphertext can't be serialized, which should only happen if
+    /// there's a bug somewhere.
+    ///
+    pub fn storage_digest(&self) -> Result<u64, Error> {
+        Ok(fnv1a_64(&self.to_vec()?))
+    }
+
+    /// Produce a ciphertext containing only the `K` most significant blocks of this ciphertext
+    ///
+    /// The result is exactly the ciphertext that a `K` block cipher with the same key would have
+    /// produced (with the same nonce) from the leading `K` blocks of the plaintext, so it can be
+    /// compared against other ciphertexts produced by such a cipher, or against other prefixes of
+    /// the same length.  This is useful for maintaining a coarse-grained secondary index, with the
+    /// full-precision ciphertext stored elsewhere.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::ore;
+    ///
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let key = [0u8; 32];
+    /// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+    /// let coarse_cipher = ore::Cipher::<2, 256>::new(&key)?;
+    ///
+    /// let value = cipher.right_encrypt(&0x0102_0304u32.try_into()?)?;
+    /// let coarse_value: ore::CipherText<2, 256> = value.prefix()?;
+    ///
+    /// assert!(coarse_cipher
+    ///     .full_encrypt(&0x0102u16.try_into()?)?
+    ///     .try_eq(&coarse_value)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `K` is larger than the number of blocks in this ciphertext.
+    ///
+    pub fn prefix<const K: usize>(&self) -> Result<CipherText<S, CMP, K, W, M>, Error> {
+        if K > N {
+            return Err(Error::RangeError(format!(
+                "cannot take a {K} block prefix of a {N} block ciphertext"
+            )));
+        }
+
+        Ok(CipherText {
+            left: self
+                .left
+                .as_ref()
+                .map(LeftCipherText::prefix::<K>)
+                .transpose()?,
+            right: self.right.prefix::<K>()?,
+        })
+    }
+
+    /// Gather some statistics about a serialized ciphertext, to check that it looks as random as
+    /// it ought to
+    ///
+    /// The ciphertext is parsed in full, so anything malformed is reported as an error.  None of
+    /// the key material is needed, and nothing about the encrypted value can be learnt from the
+    /// result; see [`CipherTextStats`] for what's collected, and an example of how to use it.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `bytes` isn't a valid serialized ciphertext of this type.
+    ///
+    pub fn inspect_serialized(bytes: &[u8]) -> Result<CipherTextStats, Error> {
+        let ct = Self::from_slice(bytes)?;
+        let (_, right) = split_serialized(bytes)?;
+
+        Ok(CipherTextStats::new(
+            ct.cipher_text_type(),
+            *ct.right.nonce_base(),
+            &ct.right.values,
+            right.get(16..).unwrap_or_default(),
+        ))
+    }
+
+    /// Compare two serialized ciphertexts, without deserializing either of them in full
+    ///
+    /// Only the left part of `a`, and the nonce base and the handful of values from the right part
+    /// of `b` that are actually needed, are parsed.  The result is the same as that of
+    /// [`compare()`](Self::compare) on the deserialized ciphertexts, as long as both of them are
+    /// well-formed; if they aren't, the result may be an error, or it may just be wrong.
+    ///
+    pub(crate) fn lazy_compare(a: &[u8], b: &[u8]) -> Result<u8, Error> {
+        let left = LeftCipherText::<S, CMP, N, W, M>::from_slice(
+            split_serialized(a)?.0.ok_or_else(|| Error::Incomparable {
+                reason: "No left part in this ciphertext".to_string(),
+            })?,
+        )?;
+        let (_, right) = split_serialized(b)?;
+
+        let nonce_base: [u8; 16] = clone_into_array(right.get(0..16).ok_or_else(|| {
+            Error::ParseError("end-of-data found while looking for nonce base".to_string())
+        })?);
+        let nonces = RightCipherText::<S, CMP, N, W, M>::derive_nonces(&nonce_base)?;
+
+        // Parsing the left ciphertext has already made sure that every p(x) is less than W
+        let mut indices = [0usize; N];
+        for (n, (idx, px)) in indices.iter_mut().zip(left.px.iter()).enumerate() {
+            *idx = RightCipherText::<S, CMP, N, W, M>::index(n, *px);
+        }
+        let values = CMP::Encoding::peek(
+            right.get(16..).ok_or_else(|| {
+                Error::ParseError("end-of-data found while looking for value bitlist".to_string())
+            })?,
+            &indices,
+        )?;
+
+        let mut result = FirstDifference::new();
+
+        for ((value, nonce), f) in values.iter().zip(nonces.iter()).zip(left.f.iter()) {
+            let v_h = check_overflow(
+                value.overflowing_add(M),
+                format_args!("overflow while adding M to v_h"),
+            )?;
+            let h_k_r = S::HF::hash(f.as_ref(), nonce)?;
+
+            let res = check_overflow(
+                v_h.overflowing_sub(h_k_r),
+                format_args!("overflow on v_h - h_k_r"),
+            )?
+            .rem_euclid(M);
+
+            // Every block gets looked at, whether or not an earlier one differed, so that the
+            // time taken doesn't give away where the first difference is
+            result.update(res);
+        }
+
+        Ok(result.result())
+    }
+
+    /// Compare two ciphertexts
+    ///
+    /// Returns the numeric comparison value, which needs to be run through the comparator's invert
+    /// function in order to convert that into a "proper" logical comparison value.
+    ///
+    fn compare_parts(
+        left: &LeftCipherText<S, CMP, N, W, M>,
+        right: &RightCipherText<S, CMP, N, W, M>,
+    ) -> Result<u8, Error> {
+        let mut result = FirstDifference::new();
+        let nonces = right.nonces()?;
+
+        for (n, nonce) in nonces.iter().enumerate() {
+            let res = Self::compare_block(left, right, n, nonce)?;
+
+            // Every block gets looked at, whether or not an earlier one differed, so that the
+            // time taken doesn't give away where the first difference is
+            result.update(res);
+        }
+
+        Ok(result.result())
+    }
+
+    /// Compare the `n`th block of a left ciphertext against the `n`th block of a right ciphertext
+    ///
+    /// Returns the numeric comparison value for just that block, which is zero if the blocks are
+    /// equal.
+    ///
+    fn compare_block(
+        left: &LeftCipherText<S, CMP, N, W, M>,
+        right: &RightCipherText<S, CMP, N, W, M>,
+        n: usize,
+        nonce: &[u8; 16],
+    ) -> Result<u8, Error> {
+        same_key(left.key_check_value, right.key_check_value)?;
+
+        // Every p(x) has already been checked against W, whether it was encrypted or parsed, so
+        // the only way a lookup can miss is a bug
+        let v_h = check_overflow(
+            right.lookup(n, left.px(n)?)?.overflowing_add(M),
+            format_args!("overflow while adding M to v_h"),
+        )?;
+        let h_k_r = S::HF::hash(left.f(n)?.as_ref(), nonce)?;
+
+        Ok(check_overflow(
+            v_h.overflowing_sub(h_k_r),
+            format_args!("overflow on v_h - h_k_r"),
+        )?
+        .rem_euclid(M))
+    }
+
+    /// Compare this ciphertext against another one block at a time, using whichever of them has a
+    /// left part
+    ///
+    /// As with [`compare_either()`](Self::compare_either), the results are from the perspective of
+    /// this ciphertext.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::Incomparable`] if neither ciphertext has a left part, and
+    /// [`Error::ParamsError`] if they're known to have been encrypted with different keys.
+    ///
+    pub(crate) fn block_comparisons<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> Result<BlockComparisons<'a, S, CMP, N, W, M>, Error> {
+        let (left, right, reversed) = match (&self.left, &other.left) {
+            (Some(l), _) => (l, &other.right, false),
+            (None, Some(l)) => (l, &self.right, true),
+            (None, None) => {
+                return Err(Error::Incomparable {
+                    reason: "neither ciphertext has a left part".to_string(),
+                })
+            }
+        };
+
+        Ok(BlockComparisons {
+            left,
+            right,
+            reversed,
+            n: 0,
+        })
+    }
+}
+
+/// The results of comparing two ciphertexts block by block, most significant block first
+///
+/// Each block is compared on its own, without regard to the blocks before it, and only when it's
+/// asked for.  The overall result of a comparison is the first result that isn't zero (or zero,
+/// if they all are), so stopping early gives the answer for a less precise version of the
+/// values.
+///
+pub(crate) struct BlockComparisons<
+    'a,
+    S: CipherSuite<W, M>,
+    CMP: Comparator<M>,
+    const N: usize,
+    const W: u16,
+    const M: u8,
+> {
+    /// Whichever of the ciphertexts had a left part
+    left: &'a LeftCipherText<S, CMP, N, W, M>,
+    /// The right part of the other ciphertext
+    right: &'a RightCipherText<S, CMP, N, W, M>,
+    /// Whether the left part came from the second ciphertext, so that the results need to be
+    /// turned around
+    reversed: bool,
+    /// The next block to compare
+    n: usize,
+}
+
+impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, const M: u8> Iterator
+    for BlockComparisons<'_, S, CMP, N, W, M>
+{
+    type Item = Result<u8, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.n >= N {
+            return None;
+        }
+
+        let res = self
+            .right
+            .nonce(self.n)
+            .and_then(|nonce| CipherText::compare_block(self.left, self.right, self.n, &nonce));
+        self.n = self.n.saturating_add(1);
+
+        Some(res.map(|r| if self.reversed { CMP::reverse(r) } else { r }))
+    }
+
+    fn nth(&mut self, skip: usize) -> Option<Self::Item> {
+        // Skipped blocks don't need comparing at all
+        self.n = self.n.saturating_add(skip);
+        self.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = N.saturating_sub(self.n);
+        (remaining, Some(remaining))
+    }
+}
+
+impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, const M: u8>
+    ExactSizeIterator for BlockComparisons<'_, S, CMP, N, W, M>
+{
+}
+
+impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, const M: u8>
+    Serializable<N, W, M> for CipherText<S, CMP, N, W, M>
+{
+    fn from_slice(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_slice_in(bytes, Vec::new())
+    }
+
     fn to_vec(&self) -> Result<Vec<u8>, Error> {
         let f_size = <<S as CipherSuite<W, M>>::PRF as PseudoRandomFunction>::BLOCK_SIZE;
 
@@ -803,26 +2328,22 @@ impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, con
         let vec_len: usize = meta_len.saturating_add(left_len).saturating_add(right_len);
         let mut v: Vec<u8> = Vec::with_capacity(vec_len);
 
-        // Type byte -- 0 is just a right CT, 1 is left+right
-        // other values to be worried about later
-        match &self.left {
-            Some(l) => {
-                v.push(1);
-                let left_bytes = l.to_vec()?;
-                v.extend_from_slice(
-                    &u16::try_from(left_bytes.len())
-                        .map_err(|e| {
-                            Error::RangeError(format!(
-                                "Couldn't represent length left_bytes ({}) as u16 ({e})",
-                                left_bytes.len()
-                            ))
-                        })?
-                        .to_be_bytes(),
-                );
-                v.extend_from_slice(&left_bytes);
-            }
-            None => v.push(0),
-        };
+        v.push(self.cipher_text_type().byte());
+
+        if let Some(l) = &self.left {
+            let left_bytes = l.to_vec()?;
+            v.extend_from_slice(
+                &u16::try_from(left_bytes.len())
+                    .map_err(|e| {
+                        Error::RangeError(format!(
+                            "Couldn't represent length left_bytes ({}) as u16 ({e})",
+                            left_bytes.len()
+                        ))
+                    })?
+                    .to_be_bytes(),
+            );
+            v.extend_from_slice(&left_bytes);
+        }
 
         let right_bytes = self.right.to_vec()?;
         v.extend_from_slice(
@@ -853,17 +2374,131 @@ mod tests {
         // for testing purposes, it's not going to break anything
         let mut rng = rand::thread_rng();
 
-        rng.try_fill(&mut k).unwrap();
+        rng.try_fill(&mut k).unwrap();
+
+        k
+    }
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    // Deliberately generic, so that this fails to compile if any ciphersuite or comparator could
+    // make a ciphertext lose either trait
+    #[allow(dead_code)]
+    fn ciphertexts_are_always_send_sync<
+        S: CipherSuite<W, M>,
+        CMP: Comparator<M>,
+        const N: usize,
+        const W: u16,
+        const M: u8,
+    >() {
+        assert_send_sync::<CipherText<S, CMP, N, W, M>>();
+        assert_send_sync::<LeftCipherText<S, CMP, N, W, M>>();
+    }
+
+    #[test]
+    fn ciphertexts_can_be_sent_to_other_threads() {
+        use crate::aes128v1::ore;
+        use std::cmp::Ordering;
+
+        let cipher = ore::Cipher::<4, 256>::new(&key()).unwrap();
+        let stored = cipher.right_encrypt(&42u32.try_into().unwrap()).unwrap();
+        let query = cipher.full_encrypt(&9001u32.try_into().unwrap()).unwrap();
+        let left = query.left().unwrap().clone();
+
+        let result = std::thread::spawn(move || {
+            (
+                query.try_cmp(&stored).unwrap(),
+                left.compare_with_right(&stored).unwrap(),
+            )
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!((Ordering::Greater, Ordering::Greater), result);
+    }
+
+    mod radix {
+        use super::*;
+        use crate::aes128v1;
+        use crate::encoding::RadixEncoding;
+        use std::cmp::Ordering;
+
+        /// A comparator that also reveals whether two values are next to each other, so it needs
+        /// five values, which none of the hand-tuned encodings can store
+        struct AdjacencyCMP {}
+
+        impl Comparator<5> for AdjacencyCMP {
+            const NAME: &'static str = "adjacency";
+            type Encoding = RadixEncoding;
+
+            fn compare(a: u16, b: u16) -> u8 {
+                match (a.cmp(&b), a.abs_diff(b)) {
+                    (Ordering::Equal, _) => 0,
+                    (Ordering::Less, 1) => 1,
+                    (Ordering::Less, _) => 2,
+                    (Ordering::Greater, 1) => 3,
+                    (Ordering::Greater, _) => 4,
+                }
+            }
+
+            fn reverse(value: u8) -> u8 {
+                match value {
+                    1 => 3,
+                    2 => 4,
+                    3 => 1,
+                    4 => 2,
+                    v => v,
+                }
+            }
+        }
+
+        type Cipher = crate::Cipher<aes128v1::CipherSuite<16, 5>, AdjacencyCMP, 3, 16, 5>;
+        type CT = CipherText<aes128v1::CipherSuite<16, 5>, AdjacencyCMP, 3, 16, 5>;
 
-        k
+        #[test]
+        fn new_comparators_just_work() {
+            let cipher = Cipher::new(&key()).unwrap();
+            let a = CT::new(&cipher, &PlainText::new([1, 5, 9])).unwrap();
+            let b = CT::from_slice(
+                &CT::new_right(&cipher, &PlainText::new([1, 6, 2]))
+                    .unwrap()
+                    .to_vec()
+                    .unwrap(),
+            )
+            .unwrap();
+
+            assert_eq!(
+                vec![0, 1, 4],
+                a.block_comparisons(&b)
+                    .unwrap()
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap()
+            );
+            assert_eq!(
+                Some(3 + 16 + 16),
+                CT::right_serialized_len(),
+                "48 values take 4 groups of 13, at 31 bits each"
+            );
+            assert_eq!(CT::right_serialized_len(), Some(b.to_vec().unwrap().len()));
+        }
     }
 
     mod ere {
         use super::*;
         use crate::aes128v1::ere;
 
-        #[cfg(feature = "serde")]
-        use serde_json;
+        #[test]
+        fn reversed_comparison() {
+            let cipher = ere::Cipher::<4, 256>::new(&key()).unwrap();
+
+            let full = cipher.full_encrypt(&42u32.try_into().unwrap()).unwrap();
+            let same = cipher.right_encrypt(&42u32.try_into().unwrap()).unwrap();
+            let different = cipher.right_encrypt(&9001u32.try_into().unwrap()).unwrap();
+
+            assert_eq!(0, same.compare_reversed(&full).unwrap());
+            assert_eq!(1, different.compare_reversed(&full).unwrap());
+            assert_eq!(1, different.compare_either(&full).unwrap());
+        }
 
         #[test]
         fn full_ciphertext_has_left() {
@@ -895,8 +2530,8 @@ mod tests {
 
             let n_rt = ere::CipherText::<8, 256>::from_slice(&v).unwrap();
 
-            assert_eq!(n, n_rt);
-            assert_eq!(n_rt, n);
+            assert!(n.try_eq(&n_rt).unwrap());
+            assert!(n_rt.try_eq(&n).unwrap());
         }
 
         #[test]
@@ -912,7 +2547,29 @@ mod tests {
 
             let n2_rt = ere::CipherText::<8, 256>::from_slice(&v).unwrap();
 
-            assert_eq!(n1, n2_rt);
+            assert!(n1.try_eq(&n2_rt).unwrap());
+        }
+
+        #[test]
+        fn lazy_comparison_of_binary_values() {
+            let cipher = ere::Cipher::<8, 256>::new(&key()).unwrap();
+
+            let a = cipher.full_encrypt(&31_337u64.try_into().unwrap()).unwrap();
+            let b = cipher
+                .right_encrypt(&31_337u64.try_into().unwrap())
+                .unwrap();
+            let c = cipher.right_encrypt(&42u64.try_into().unwrap()).unwrap();
+
+            let v_a = a.to_vec().unwrap();
+
+            assert_eq!(
+                a.compare(&b).unwrap(),
+                ere::CipherText::<8, 256>::lazy_compare(&v_a, &b.to_vec().unwrap()).unwrap()
+            );
+            assert_eq!(
+                a.compare(&c).unwrap(),
+                ere::CipherText::<8, 256>::lazy_compare(&v_a, &c.to_vec().unwrap()).unwrap()
+            );
         }
 
         #[test]
@@ -926,8 +2583,8 @@ mod tests {
 
             let n_rt: ere::CipherText<8, 256> = serde_json::from_str(&s).unwrap();
 
-            assert_eq!(n, n_rt);
-            assert_eq!(n_rt, n);
+            assert!(n.try_eq(&n_rt).unwrap());
+            assert!(n_rt.try_eq(&n).unwrap());
         }
 
         #[test]
@@ -945,7 +2602,7 @@ mod tests {
 
             let n2_rt: ere::CipherText<8, 256> = serde_json::from_str(&s).unwrap();
 
-            assert_eq!(n1, n2_rt);
+            assert!(n1.try_eq(&n2_rt).unwrap());
         }
 
         #[test]
@@ -1031,11 +2688,316 @@ mod tests {
 
             assert!(ere::CipherText::<4, 16>::from_slice(&v).is_err());
         }
+
+        #[test]
+        fn serialized_sizes_are_fixed() {
+            const RIGHT: usize = 147;
+            const FULL: usize = 147 + 2 + 68;
+
+            let cipher = ere::Cipher::<4, 256>::new(&key()).unwrap();
+            assert_eq!(
+                Some(RIGHT),
+                ere::CipherText::<4, 256>::right_serialized_len()
+            );
+            assert_eq!(Some(FULL), ere::CipherText::<4, 256>::full_serialized_len());
+
+            for v in [0u32, 42, u32::MAX] {
+                let right = cipher.right_encrypt(&v.try_into().unwrap()).unwrap();
+                let full = cipher.full_encrypt(&v.try_into().unwrap()).unwrap();
+
+                assert_eq!(right.to_vec().unwrap(), right.to_array::<RIGHT>().unwrap());
+                assert_eq!(full.to_vec().unwrap(), full.to_array::<FULL>().unwrap());
+
+                let left = full.left().unwrap();
+                assert_eq!(left.to_vec().unwrap(), left.to_array::<68>().unwrap());
+            }
+        }
+
+        #[test]
+        fn storage_digest_spots_duplicates() {
+            let cipher = ere::Cipher::<4, 256>::new(&key()).unwrap();
+            let ct = cipher.full_encrypt(&42u32.try_into().unwrap()).unwrap();
+            let bytes = ct.to_vec().unwrap();
+
+            let copy = ere::CipherText::<4, 256>::from_slice(&bytes).unwrap();
+            let again = cipher.full_encrypt(&42u32.try_into().unwrap()).unwrap();
+
+            assert_eq!(ct.storage_digest().unwrap(), copy.storage_digest().unwrap());
+            assert_ne!(
+                ct.storage_digest().unwrap(),
+                again.storage_digest().unwrap()
+            );
+            assert_eq!(fnv1a_64(&bytes), ct.storage_digest().unwrap());
+        }
+
+        #[test]
+        fn wrong_array_size_is_an_error() {
+            let cipher = ere::Cipher::<4, 256>::new(&key()).unwrap();
+            let ct = cipher.full_encrypt(&42u32.try_into().unwrap()).unwrap();
+
+            assert!(matches!(ct.to_array::<146>(), Err(Error::RangeError(_))));
+            assert!(matches!(
+                ct.left().unwrap().to_array::<69>(),
+                Err(Error::RangeError(_))
+            ));
+        }
     }
 
     mod ore {
         use super::*;
         use crate::aes128v1::ore;
+        use std::cmp::Ordering;
+
+        #[test]
+        fn serialized_sizes_vary() {
+            assert_eq!(None, ore::CipherText::<4, 256>::right_serialized_len());
+            assert_eq!(None, ore::CipherText::<4, 256>::full_serialized_len());
+        }
+
+        #[test]
+        fn reserved_type_bytes_are_rejected() {
+            let cipher = ore::Cipher::<4, 256>::new(&key()).unwrap();
+
+            let n = cipher.full_encrypt(&42u32.try_into().unwrap()).unwrap();
+            assert_eq!(CipherTextType::Full, n.cipher_text_type());
+
+            let mut v = n.to_vec().unwrap();
+            v[0] = 0x21;
+
+            assert!(matches!(
+                ore::CipherText::<4, 256>::from_slice(&v),
+                Err(Error::UnknownCipherTextType(0x21))
+            ));
+            assert!(matches!(
+                sanitize_serialized(&v),
+                Err(Error::UnknownCipherTextType(0x21))
+            ));
+        }
+
+        #[test]
+        fn reversed_comparison() {
+            let cipher = ore::Cipher::<4, 256>::new(&key()).unwrap();
+
+            let small = cipher.full_encrypt(&1u32.try_into().unwrap()).unwrap();
+            let big = cipher.right_encrypt(&9001u32.try_into().unwrap()).unwrap();
+
+            // 2 is "greater", 1 is "less"
+            assert_eq!(2, big.compare_reversed(&small).unwrap());
+            assert_eq!(1, small.compare(&big).unwrap());
+            assert_eq!(2, big.compare_either(&small).unwrap());
+            assert_eq!(1, small.compare_either(&big).unwrap());
+            assert!(big.compare_reversed(&big).is_err());
+        }
+
+        #[test]
+        fn neither_side_comparable() {
+            let cipher = ore::Cipher::<4, 256>::new(&key()).unwrap();
+
+            let a = cipher.right_encrypt(&1u32.try_into().unwrap()).unwrap();
+            let b = cipher.right_encrypt(&1u32.try_into().unwrap()).unwrap();
+
+            assert!(matches!(
+                a.compare_either(&b),
+                Err(Error::Incomparable { .. })
+            ));
+        }
+
+        #[test]
+        fn missing_left_parts_are_always_incomparable() {
+            use crate::ComparisonEncryptor;
+
+            let cipher = ore::Cipher::<4, 256>::new(&key()).unwrap();
+
+            let full = cipher.full_encrypt(&1u32.try_into().unwrap()).unwrap();
+            let right = cipher.right_encrypt(&1u32.try_into().unwrap()).unwrap();
+            let right_bytes = right.to_vec().unwrap();
+
+            for e in [
+                right.compare(&full).unwrap_err(),
+                full.compare_reversed(&right).unwrap_err(),
+                full.left_eq(&right).unwrap_err(),
+                ore::CipherText::<4, 256>::lazy_compare(&right_bytes, &right_bytes).unwrap_err(),
+                cipher
+                    .compare_serialized(&right_bytes, &right_bytes)
+                    .unwrap_err(),
+                crate::compare_matrix(std::slice::from_ref(&right), std::slice::from_ref(&full))
+                    .unwrap_err(),
+                ore::Histogram::<4, 256>::new(vec![right.clone()]).unwrap_err(),
+                ore::OrdBytesKey::<4, 256>::new(right_bytes.clone()).unwrap_err(),
+                ore::SortableBytes::<4, 256>::new(right_bytes.clone()).unwrap_err(),
+            ] {
+                assert!(matches!(e, Error::Incomparable { .. }), "{e}");
+            }
+        }
+
+        /// Serialization round-trip tests for a block width too big to fit p(x) in a byte
+        macro_rules! wide_block_tests {
+            ($name:ident, $w:literal) => {
+                mod $name {
+                    use super::*;
+                    use std::cmp::Ordering;
+
+                    /// Every block as big as it can be, so p(x) needs every bit it's got
+                    fn biggest() -> PlainText<2, $w> {
+                        PlainText::new([$w - 1; 2])
+                    }
+
+                    #[test]
+                    fn left_roundtrips() {
+                        let cipher = ore::Cipher::<2, $w>::new(&key()).unwrap();
+
+                        for value in [PlainText::new([0, 0]), PlainText::new([1, 256]), biggest()] {
+                            let left = LeftCipherText::encrypt(&cipher, &value).unwrap();
+                            let v = left.to_vec().unwrap();
+
+                            assert_eq!(ore::LeftCipherText::<2, $w>::serialized_len(), v.len());
+                            assert!(left
+                                .same_as(&ore::LeftCipherText::<2, $w>::from_slice(&v).unwrap()));
+                        }
+                    }
+
+                    #[test]
+                    fn full_roundtrips_and_compares() {
+                        let cipher = ore::Cipher::<2, $w>::new(&key()).unwrap();
+
+                        let big = cipher.full_encrypt(&biggest()).unwrap();
+                        let small = cipher.full_encrypt(&PlainText::new([1, 256])).unwrap();
+
+                        let big_rt =
+                            ore::CipherText::<2, $w>::from_slice(&big.to_vec().unwrap()).unwrap();
+                        let small_rt =
+                            ore::CipherText::<2, $w>::from_slice(&small.to_vec().unwrap()).unwrap();
+
+                        assert_eq!(big.to_vec().unwrap(), big_rt.to_vec().unwrap());
+                        assert_eq!(Ordering::Equal, big_rt.try_cmp(&big).unwrap());
+                        assert_eq!(Ordering::Greater, big_rt.try_cmp(&small_rt).unwrap());
+                        assert_eq!(Ordering::Less, small_rt.try_cmp(&big).unwrap());
+                    }
+                }
+            };
+        }
+
+        wide_block_tests!(w257, 257);
+        wide_block_tests!(w1000, 1000);
+        wide_block_tests!(w4096, 4096);
+        wide_block_tests!(w65535, 0xFFFF);
+
+        #[test]
+        fn ciphertexts_are_rejected_at_the_wrong_width() {
+            let narrow = ore::Cipher::<2, 256>::new(&key())
+                .unwrap()
+                .full_encrypt(&PlainText::new([1, 200]))
+                .unwrap()
+                .to_vec()
+                .unwrap();
+            let wide = ore::Cipher::<2, 257>::new(&key())
+                .unwrap()
+                .full_encrypt(&PlainText::new([1, 200]))
+                .unwrap()
+                .to_vec()
+                .unwrap();
+            let wider = ore::Cipher::<2, 4096>::new(&key())
+                .unwrap()
+                .full_encrypt(&PlainText::new([1, 200]))
+                .unwrap()
+                .to_vec()
+                .unwrap();
+            let widest = ore::Cipher::<2, 0xFFFF>::new(&key())
+                .unwrap()
+                .full_encrypt(&PlainText::new([1, 200]))
+                .unwrap()
+                .to_vec()
+                .unwrap();
+
+            // One byte of p(x) per block, or two
+            assert!(ore::CipherText::<2, 257>::from_slice(&narrow).is_err());
+            assert!(ore::CipherText::<2, 256>::from_slice(&wide).is_err());
+            // Same size p(x), but a different number of right ciphertext values
+            assert!(ore::CipherText::<2, 257>::from_slice(&wider).is_err());
+            assert!(ore::CipherText::<2, 4096>::from_slice(&wide).is_err());
+            assert!(ore::CipherText::<2, 4096>::from_slice(&widest).is_err());
+            assert!(ore::CipherText::<2, 0xFFFF>::from_slice(&wider).is_err());
+            // Left ciphertexts only differ in the size of p(x)
+            assert!(ore::LeftCipherText::<2, 257>::from_slice(
+                &ore::CipherText::<2, 256>::from_slice(&narrow)
+                    .unwrap()
+                    .left()
+                    .unwrap()
+                    .to_vec()
+                    .unwrap()
+            )
+            .is_err());
+        }
+
+        #[test]
+        fn right_ciphertexts_must_fit_their_length_field() {
+            use crate::aes128v1::ere;
+
+            // 8 * 65535 one-bit values, plus the nonce base, is more than 65535 bytes
+            let cipher = ere::Cipher::<8, 0xFFFF>::new(&key()).unwrap();
+            let ct = cipher.right_encrypt(&PlainText::new([0; 8])).unwrap();
+
+            assert!(ere::CipherText::<8, 0xFFFF>::right_serialized_len().unwrap() > 0xFFFF);
+            assert!(matches!(ct.to_vec(), Err(Error::RangeError(_))));
+        }
+
+        #[test]
+        fn sanitized_full_ciphertext_matches_reserialized_right() {
+            let cipher = ore::Cipher::<8, 256>::new(&key()).unwrap();
+
+            let n = cipher.full_encrypt(&42u64.try_into().unwrap()).unwrap();
+            let v = n.to_vec().unwrap();
+
+            let mut n_r = n.clone();
+            n_r.left = None;
+
+            assert_eq!(n_r.to_vec().unwrap(), sanitize_serialized(&v).unwrap());
+        }
+
+        #[test]
+        fn sanitized_full_ciphertext_still_compares() {
+            let cipher = ore::Cipher::<8, 256>::new(&key()).unwrap();
+
+            let n1 = cipher.full_encrypt(&42u64.try_into().unwrap()).unwrap();
+            let n2 = cipher.full_encrypt(&31_337u64.try_into().unwrap()).unwrap();
+
+            let n2_s = ore::CipherText::<8, 256>::from_slice(
+                &sanitize_serialized(&n2.to_vec().unwrap()).unwrap(),
+            )
+            .unwrap();
+
+            assert!(!n2_s.has_left());
+            assert_eq!(Ordering::Less, n1.try_cmp(&n2_s).unwrap());
+        }
+
+        #[test]
+        fn sanitizing_right_ciphertext_is_a_no_op() {
+            let cipher = ore::Cipher::<8, 256>::new(&key()).unwrap();
+
+            let v = cipher
+                .right_encrypt(&42u64.try_into().unwrap())
+                .unwrap()
+                .to_vec()
+                .unwrap();
+
+            assert_eq!(v, sanitize_serialized(&v).unwrap());
+        }
+
+        #[test]
+        fn sanitizing_garbage_fails() {
+            let cipher = ore::Cipher::<8, 256>::new(&key()).unwrap();
+
+            let v = cipher
+                .full_encrypt(&42u64.try_into().unwrap())
+                .unwrap()
+                .to_vec()
+                .unwrap();
+
+            assert!(sanitize_serialized(&[]).is_err());
+            assert!(sanitize_serialized(&[2, 0, 0]).is_err());
+            assert!(sanitize_serialized(&v[..v.len() - 1]).is_err());
+            assert!(sanitize_serialized(&v[..100]).is_err());
+        }
 
         #[test]
         fn trinary_full_ciphertext_roundtrips_correctly() {
@@ -1050,15 +3012,15 @@ mod tests {
             let n1_rt = ore::CipherText::<8, 256>::from_slice(&v1).unwrap();
             let n2_rt = ore::CipherText::<8, 256>::from_slice(&v2).unwrap();
 
-            assert!(n1 == n1_rt);
-            assert!(n2 == n2_rt);
-            assert!(n1 < n2_rt);
-            assert!(n2 > n1_rt);
+            assert!(n1.try_eq(&n1_rt).unwrap());
+            assert!(n2.try_eq(&n2_rt).unwrap());
+            assert_eq!(Ordering::Less, n1.try_cmp(&n2_rt).unwrap());
+            assert_eq!(Ordering::Greater, n2.try_cmp(&n1_rt).unwrap());
 
-            assert!(n1_rt == n1);
-            assert!(n2_rt == n2);
-            assert!(n1_rt < n2);
-            assert!(n2_rt > n1);
+            assert!(n1_rt.try_eq(&n1).unwrap());
+            assert!(n2_rt.try_eq(&n2).unwrap());
+            assert_eq!(Ordering::Less, n1_rt.try_cmp(&n2).unwrap());
+            assert_eq!(Ordering::Greater, n2_rt.try_cmp(&n1).unwrap());
         }
 
         #[test]
@@ -1081,10 +3043,217 @@ mod tests {
 
             let n2r_rt = ore::CipherText::<8, 256>::from_slice(&v2r).unwrap();
 
-            assert!(n1f == n1r_rt);
-            assert!(n2f == n2r_rt);
-            assert!(n1f < n2r_rt);
-            assert!(n2f > n1r_rt);
+            assert!(n1f.try_eq(&n1r_rt).unwrap());
+            assert!(n2f.try_eq(&n2r_rt).unwrap());
+            assert_eq!(Ordering::Less, n1f.try_cmp(&n2r_rt).unwrap());
+            assert_eq!(Ordering::Greater, n2f.try_cmp(&n1r_rt).unwrap());
+        }
+
+        #[test]
+        fn lazy_comparison_of_trinary_values() {
+            let cipher = ore::Cipher::<8, 256>::new(&key()).unwrap();
+
+            let a = cipher.full_encrypt(&31_337u64.try_into().unwrap()).unwrap();
+            let v_a = a.to_vec().unwrap();
+
+            for v in [0u64, 42, 31_336, 31_337, 31_338, u64::MAX] {
+                let b = cipher.right_encrypt(&v.try_into().unwrap()).unwrap();
+
+                assert_eq!(
+                    a.compare(&b).unwrap(),
+                    ore::CipherText::<8, 256>::lazy_compare(&v_a, &b.to_vec().unwrap()).unwrap(),
+                    "value {v}"
+                );
+            }
+        }
+
+        #[test]
+        fn left_eq() {
+            let cipher = ore::Cipher::<8, 256>::new(&key()).unwrap();
+
+            let a = cipher.full_encrypt(&42u64.try_into().unwrap()).unwrap();
+            let b = cipher.full_encrypt(&42u64.try_into().unwrap()).unwrap();
+            let c = cipher.full_encrypt(&43u64.try_into().unwrap()).unwrap();
+            let r = cipher.right_encrypt(&42u64.try_into().unwrap()).unwrap();
+
+            assert!(a.left_eq(&b).unwrap());
+            assert!(!a.left_eq(&c).unwrap());
+            assert!(a.left_eq(&r).is_err());
+            assert!(r.left_eq(&a).is_err());
+        }
+
+        #[test]
+        fn left_eq_across_keys() {
+            let c1 = ore::Cipher::<8, 256>::new(&key()).unwrap();
+            let c2 = ore::Cipher::<8, 256>::new(&key()).unwrap();
+
+            let a = c1.full_encrypt(&42u64.try_into().unwrap()).unwrap();
+            let b = c2.full_encrypt(&42u64.try_into().unwrap()).unwrap();
+
+            assert!(!a.left_eq(&b).unwrap());
+        }
+
+        #[test]
+        fn lazy_comparison_needs_a_left() {
+            let cipher = ore::Cipher::<8, 256>::new(&key()).unwrap();
+
+            let v = cipher
+                .right_encrypt(&42u64.try_into().unwrap())
+                .unwrap()
+                .to_vec()
+                .unwrap();
+
+            assert!(ore::CipherText::<8, 256>::lazy_compare(&v, &v).is_err());
+        }
+
+        #[test]
+        fn lazy_comparison_of_truncated_values_fails() {
+            let cipher = ore::Cipher::<8, 256>::new(&key()).unwrap();
+
+            let a = cipher
+                .full_encrypt(&42u64.try_into().unwrap())
+                .unwrap()
+                .to_vec()
+                .unwrap();
+            let b = cipher
+                .right_encrypt(&u64::MAX.try_into().unwrap())
+                .unwrap()
+                .to_vec()
+                .unwrap();
+            let len = u16::try_from(b.len() - 3 - 200).unwrap().to_be_bytes();
+            let mut truncated = vec![0, len[0], len[1]];
+            truncated.extend_from_slice(&b[3..b.len() - 200]);
+
+            assert!(ore::CipherText::<8, 256>::lazy_compare(&a, &truncated).is_err());
+        }
+
+        #[test]
+        fn prefix_compares_against_shorter_cipher() {
+            let k = key();
+            let cipher = ore::Cipher::<4, 256>::new(&k).unwrap();
+            let coarse_cipher = ore::Cipher::<2, 256>::new(&k).unwrap();
+
+            let n = cipher
+                .right_encrypt(&0x1234_5678u32.try_into().unwrap())
+                .unwrap();
+            let p: ore::CipherText<2, 256> = n.prefix().unwrap();
+
+            assert!(!p.has_left());
+            assert!(
+                coarse_cipher
+                    .full_encrypt(&0x1234u16.try_into().unwrap())
+                    .unwrap()
+                    .try_cmp(&p)
+                    .unwrap()
+                    == Ordering::Equal
+            );
+            assert!(
+                coarse_cipher
+                    .full_encrypt(&0x1233u16.try_into().unwrap())
+                    .unwrap()
+                    .try_cmp(&p)
+                    .unwrap()
+                    == Ordering::Less
+            );
+            assert!(
+                coarse_cipher
+                    .full_encrypt(&0x1235u16.try_into().unwrap())
+                    .unwrap()
+                    .try_cmp(&p)
+                    .unwrap()
+                    == Ordering::Greater
+            );
+        }
+
+        #[test]
+        fn prefixes_compare_against_each_other() {
+            let cipher = ore::Cipher::<4, 256>::new(&key()).unwrap();
+
+            let n1 = cipher
+                .full_encrypt(&0x1234_5678u32.try_into().unwrap())
+                .unwrap();
+            let n2 = cipher
+                .right_encrypt(&0x1234_0000u32.try_into().unwrap())
+                .unwrap();
+            let n3 = cipher
+                .right_encrypt(&0x1235_0000u32.try_into().unwrap())
+                .unwrap();
+
+            let p1: ore::CipherText<2, 256> = n1.prefix().unwrap();
+            let p2: ore::CipherText<2, 256> = n2.prefix().unwrap();
+            let p3: ore::CipherText<2, 256> = n3.prefix().unwrap();
+
+            assert_eq!(Ordering::Greater, n1.try_cmp(&n2).unwrap());
+            assert!(p1.try_eq(&p2).unwrap());
+            assert_eq!(Ordering::Less, p1.try_cmp(&p3).unwrap());
+        }
+
+        #[test]
+        fn prefix_roundtrips_correctly() {
+            let cipher = ore::Cipher::<4, 256>::new(&key()).unwrap();
+
+            let n = cipher
+                .full_encrypt(&0x1234_5678u32.try_into().unwrap())
+                .unwrap();
+            let p: ore::CipherText<3, 256> = n.prefix().unwrap();
+
+            let p_rt = ore::CipherText::<3, 256>::from_slice(&p.to_vec().unwrap()).unwrap();
+
+            assert!(p.try_eq(&p_rt).unwrap());
+        }
+
+        #[test]
+        fn cannot_take_oversized_prefix() {
+            let cipher = ore::Cipher::<4, 256>::new(&key()).unwrap();
+
+            let n = cipher.full_encrypt(&42u32.try_into().unwrap()).unwrap();
+
+            assert!(n.prefix::<5>().is_err());
+        }
+
+        #[test]
+        fn promoted_left_matches_longer_encryption() {
+            let k = key();
+            let old_cipher = ore::Cipher::<4, 256>::new(&k).unwrap();
+            let new_cipher = ore::Cipher::<8, 256>::new(&k).unwrap();
+
+            let old = old_cipher
+                .left_encrypt(&0x1234_5678u32.try_into().unwrap())
+                .unwrap();
+            let promoted = new_cipher.promote_left(&old).unwrap();
+
+            assert!(promoted.same_as(
+                &new_cipher
+                    .left_encrypt(&0x1234_5678u64.try_into().unwrap())
+                    .unwrap()
+            ));
+
+            for (v, expected) in [
+                (42u64, Ordering::Greater),
+                (0x1234_5678, Ordering::Equal),
+                (u64::from(u32::MAX) + 1, Ordering::Less),
+            ] {
+                let stored = new_cipher.right_encrypt(&v.try_into().unwrap()).unwrap();
+                assert_eq!(expected, promoted.compare_with_right(&stored).unwrap());
+            }
+        }
+
+        #[test]
+        fn cannot_promote_to_fewer_blocks() {
+            let k = key();
+            let cipher = ore::Cipher::<8, 256>::new(&k).unwrap();
+            let left = cipher.left_encrypt(&42u64.try_into().unwrap()).unwrap();
+
+            assert!(matches!(
+                ore::Cipher::<4, 256>::new(&k).unwrap().promote_left(&left),
+                Err(Error::RangeError(_))
+            ));
+            assert!(matches!(
+                ore::Cipher::<16, 256>::new_right_only(&k)
+                    .unwrap()
+                    .promote_left(&left),
+                Err(Error::NotPermitted(_))
+            ));
         }
 
         #[test]
@@ -1171,4 +3340,123 @@ mod tests {
             assert!(ore::CipherText::<4, 16>::from_slice(&v).is_err());
         }
     }
+
+    /// Fixed byte-for-byte expectations for serialized ciphertexts
+    ///
+    /// Everything in the wire format is defined in terms of bytes (or explicitly big- or
+    /// little-endian integers), so these must pass unchanged on every platform, regardless of its
+    /// endianness or word size.
+    ///
+    #[test]
+    fn nonces_are_derived_in_order() {
+        use crate::aes128v1::ore;
+
+        let cipher = ore::Cipher::<8, 16>::new(&[1u8; 32]).unwrap();
+        let ct = cipher.right_encrypt(&42u32.try_into().unwrap()).unwrap();
+        let nonces = ct.right.nonces().unwrap();
+        let short = ct.prefix::<3>().unwrap();
+
+        for (n, nonce) in nonces.iter().enumerate() {
+            assert_eq!(*nonce, ct.right.nonce(n).unwrap());
+        }
+        assert_eq!(nonces[..3], short.right.nonces().unwrap()[..]);
+        assert!(matches!(ct.right.nonce(8), Err(Error::RangeError(_))));
+    }
+
+    mod wire_format {
+        use super::*;
+        use crate::aes128v1::{ere, ore};
+        use hex_literal::hex;
+
+        fn fixed_key() -> [u8; 32] {
+            core::array::from_fn(|i| u8::try_from(i).unwrap())
+        }
+
+        const ORE_LEFT: [u8; 68] = hex![
+            "806645f5 775c4262 972b43cd 70f82f24 f7d674f0 e415f263 2d98436b a3e85fbe
+             1e02bb38 595e787f 880adf8f ac931020 a608b13f 8023c86a 768fb902 2567be27
+             950bf22c"
+        ];
+
+        const ERE_LEFT: [u8; 34] = hex![
+            "727cf17d 73e47bad b506448f 991f42ff 28b2d878 f2570f3d c4a7e2bf 168c2286
+             080a"
+        ];
+
+        #[test]
+        fn ore_left() {
+            let cipher = ore::Cipher::<4, 256>::new(&fixed_key()).unwrap();
+            let ct = cipher
+                .full_encrypt(&0x1234_5678u32.try_into().unwrap())
+                .unwrap();
+
+            assert_eq!(ORE_LEFT.to_vec(), ct.left().unwrap().to_vec().unwrap());
+            assert_eq!(
+                ORE_LEFT.to_vec(),
+                ore::LeftCipherText::<4, 256>::from_slice(&ORE_LEFT)
+                    .unwrap()
+                    .to_vec()
+                    .unwrap()
+            );
+        }
+
+        #[test]
+        fn ere_left() {
+            let cipher = ere::Cipher::<2, 16>::new(&fixed_key()).unwrap();
+            let ct = cipher.full_encrypt(&0x42u32.try_into().unwrap()).unwrap();
+
+            assert_eq!(ERE_LEFT.to_vec(), ct.left().unwrap().to_vec().unwrap());
+        }
+
+        #[test]
+        fn wide_left_is_big_endian() {
+            // Each p(x) is a big-endian u16, after both of the F(k, p(x)) values
+            const WIDE_LEFT: [u8; 36] = hex![
+                "eb563726 8a2eddab 69f37e8d d7110045 4f3146cd be07ec2a ed7ceb58 50f158eb
+                 0d53 c238"
+            ];
+
+            let cipher = ore::Cipher::<2, 0xFFFF>::new(&fixed_key()).unwrap();
+            let ct = cipher
+                .full_encrypt(&PlainText::new([0x1234, 0xabcd]))
+                .unwrap();
+
+            assert_eq!(WIDE_LEFT.to_vec(), ct.left().unwrap().to_vec().unwrap());
+            assert_eq!(
+                WIDE_LEFT.to_vec(),
+                ore::LeftCipherText::<2, 0xFFFF>::from_slice(&WIDE_LEFT)
+                    .unwrap()
+                    .to_vec()
+                    .unwrap()
+            );
+        }
+
+        #[test]
+        fn format_version_is_pinned() {
+            // If this has to change, the fixtures in here almost certainly do too, and vice versa
+            assert_eq!(1, FORMAT_VERSION);
+        }
+
+        #[cfg(feature = "test-vectors")]
+        #[test]
+        fn ore_full() {
+            // Type, left length, left (f then p(x)), right length, right (nonce base then values)
+            const FULL: [u8; 57] = hex![
+                "01
+                 0022 5560d885 f585000b 822f6c48 35aec15a ea6cda07 ecf2b939 0e79c863 a1d203f9
+                      0102
+                 0012 7848b5d7 11bc9883 996317a3 f9c90269
+                      3b0f"
+            ];
+
+            let cipher = ore::Cipher::<2, 4>::new_with_insecure_seed(&fixed_key(), 42).unwrap();
+            let ct = cipher.full_encrypt(&9u32.try_into().unwrap()).unwrap();
+
+            assert_eq!(FULL.to_vec(), ct.to_vec().unwrap());
+
+            let parsed = ore::CipherText::<2, 4>::from_slice(&FULL).unwrap();
+            assert_eq!(FULL.to_vec(), parsed.to_vec().unwrap());
+            assert_eq!(0, parsed.compare(&ct).unwrap());
+        }
+    }
 }
@@ -11,12 +11,24 @@
 
 use std::cmp::Ordering;
 
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, ConstantTimeGreater};
+
+use crate::encoding::{BinaryEncoding, QuaternaryEncoding, TrinaryEncoding, ValueEncoding};
 use crate::Error;
 
 /// What you have to implement in order to be considered a comparator.
 pub trait Comparator<const M: u8> {
+    /// The canonical name of the kind of comparison, as used in [`Params`](crate::Params)
+    const NAME: &'static str;
+
+    /// How the values produced by [`compare()`](Self::compare) get serialised
+    type Encoding: ValueEncoding<M>;
+
     /// Compare two values, return the value that'll get encoded into the ciphertext
     fn compare(a: u16, b: u16) -> u8;
+
+    /// Turn the result of comparing `a` with `b` into the result of comparing `b` with `a`
+    fn reverse(value: u8) -> u8;
 }
 
 /// A comparator implementation that can do <, =, >
@@ -25,19 +37,29 @@ pub struct OrderingCMP {}
 
 impl OrderingCMP {
     /// Turn the return value from a CRE comparison into something that users will recognise
+    ///
+    /// The mapping is done without branching on `i`, so that the time it takes doesn't depend
+    /// on the result.
+    ///
     pub fn invert(i: u8) -> Result<Ordering, Error> {
-        match i {
-            0 => Ok(Ordering::Equal),
-            1 => Ok(Ordering::Less),
-            2 => Ok(Ordering::Greater),
-            _ => Err(Error::RangeError(format!(
+        if bool::from(i.ct_gt(&2)) {
+            return Err(Error::RangeError(format!(
                 "value passed to invert must be in the range 0..2 (got {i})"
-            ))),
+            )));
         }
+
+        let mut sign = i8::conditional_select(&0, &-1, i.ct_eq(&1));
+        sign.conditional_assign(&1, i.ct_eq(&2));
+
+        Ok(sign.cmp(&0))
     }
 }
 
 impl Comparator<3> for OrderingCMP {
+    const NAME: &'static str = "ore";
+
+    type Encoding = TrinaryEncoding;
+
     fn compare(a: u16, b: u16) -> u8 {
         match a.cmp(&b) {
             Ordering::Equal => 0,
@@ -45,9 +67,24 @@ impl Comparator<3> for OrderingCMP {
             Ordering::Greater => 2,
         }
     }
+
+    fn reverse(value: u8) -> u8 {
+        match value {
+            1 => 2,
+            2 => 1,
+            v => v,
+        }
+    }
 }
 
 /// A comparator implementation for strict equality
+///
+/// This is also the comparator for "begins with" queries, via the `prefix_matches()` methods of
+/// the `ere` modules.  There's no separate prefix comparator, because there'd be no point: a
+/// comparator only decides what each block's comparison reveals, and every block is compared
+/// independently of the others, so a comparator that reports whether blocks are equal reveals
+/// which blocks are equal, leading or not, just as this one does.
+///
 #[derive(Debug, Clone)]
 pub struct EqualityCMP {}
 
@@ -62,10 +99,182 @@ impl EqualityCMP {
             Ok(i == 0)
         }
     }
+
+    /// Whether the first `blocks` block comparisons were all equal
+    ///
+    /// Every one of those blocks gets compared, even once one has turned out to differ, so that
+    /// the time taken doesn't give away how long the common prefix is.
+    ///
+    pub(crate) fn prefix_matches(
+        blocks: usize,
+        comparisons: impl ExactSizeIterator<Item = Result<u8, Error>>,
+    ) -> Result<bool, Error> {
+        if blocks > comparisons.len() {
+            return Err(Error::RangeError(format!(
+                "cannot match a prefix of {blocks} blocks, as there are only {} blocks",
+                comparisons.len()
+            )));
+        }
+
+        let mut diff = 0u8;
+        for res in comparisons.take(blocks) {
+            diff |= res?;
+        }
+
+        Ok(diff == 0)
+    }
 }
 
 impl Comparator<2> for EqualityCMP {
+    const NAME: &'static str = "ere";
+
+    type Encoding = BinaryEncoding;
+
     fn compare(a: u16, b: u16) -> u8 {
         u8::from(a != b)
     }
+
+    fn reverse(value: u8) -> u8 {
+        // Equality doesn't care which way around you look at it
+        value
+    }
+}
+
+/// A comparator implementation for partial orders, which can do <, =, >, and "incomparable"
+///
+/// Each block value is treated as a set of bits, and one value is "less than" another if all of
+/// its bits are also set in the other one (that is, the order is set inclusion).  Any finite
+/// partial order can be embedded into set inclusion, so this covers the lot; it's up to the
+/// caller to pick the bit patterns that represent their elements.
+///
+/// Multi-block values are compared block-by-block from the most significant block, with the first
+/// block that isn't equal deciding the result.
+///
+#[derive(Debug, Clone)]
+pub struct PartialOrderCMP {}
+
+impl PartialOrderCMP {
+    /// Turn the return value from a CRE comparison into something that users will recognise
+    ///
+    /// Incomparable values come back as `None`, in the same manner as
+    /// [`PartialOrd::partial_cmp()`].
+    ///
+    pub fn invert(i: u8) -> Result<Option<Ordering>, Error> {
+        match i {
+            0 => Ok(Some(Ordering::Equal)),
+            1 => Ok(Some(Ordering::Less)),
+            2 => Ok(Some(Ordering::Greater)),
+            3 => Ok(None),
+            _ => Err(Error::RangeError(format!(
+                "value passed to invert must be in the range 0..3 (got {i})"
+            ))),
+        }
+    }
+}
+
+impl Comparator<4> for PartialOrderCMP {
+    const NAME: &'static str = "pore";
+
+    type Encoding = QuaternaryEncoding;
+
+    fn compare(a: u16, b: u16) -> u8 {
+        if a == b {
+            0
+        } else if a & b == a {
+            1
+        } else if a & b == b {
+            2
+        } else {
+            3
+        }
+    }
+
+    fn reverse(value: u8) -> u8 {
+        match value {
+            1 => 2,
+            2 => 1,
+            v => v,
+        }
+    }
+}
+
+/// The overall result of a block-by-block comparison, worked out in constant time
+///
+/// A comparison's result is that of the first block that isn't equal, or zero if they all are.
+/// Finding that with an ordinary loop means branching on each block's result, which gives away
+/// (through timing, and the branch predictor) which block differed first.  Every block result
+/// given to a `FirstDifference` is handled in exactly the same way, whatever it is.
+///
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct FirstDifference {
+    /// The first non-zero result seen so far, or zero
+    result: u8,
+
+    /// Whether a non-zero result has been seen yet
+    found: Choice,
+}
+
+impl FirstDifference {
+    /// Start a new comparison, with no blocks seen yet
+    pub(crate) fn new() -> Self {
+        FirstDifference {
+            result: 0,
+            found: Choice::from(0),
+        }
+    }
+
+    /// Take the result of comparing the next block into account
+    pub(crate) fn update(&mut self, block_result: u8) {
+        let differs = !block_result.ct_eq(&0);
+
+        self.result
+            .conditional_assign(&block_result, differs & !self.found);
+        self.found |= differs;
+    }
+
+    /// The result of the comparison, given all the blocks seen so far
+    pub(crate) fn result(self) -> u8 {
+        self.result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_order_comparisons() {
+        assert_eq!(0, PartialOrderCMP::compare(0b101, 0b101));
+        assert_eq!(1, PartialOrderCMP::compare(0b001, 0b101));
+        assert_eq!(1, PartialOrderCMP::compare(0, 0b101));
+        assert_eq!(2, PartialOrderCMP::compare(0b111, 0b101));
+        assert_eq!(3, PartialOrderCMP::compare(0b001, 0b010));
+        assert!(PartialOrderCMP::invert(4).is_err());
+    }
+
+    #[test]
+    fn ordering_invert_is_unchanged() {
+        assert_eq!(Ordering::Equal, OrderingCMP::invert(0).unwrap());
+        assert_eq!(Ordering::Less, OrderingCMP::invert(1).unwrap());
+        assert_eq!(Ordering::Greater, OrderingCMP::invert(2).unwrap());
+
+        for i in 3..=255 {
+            assert!(OrderingCMP::invert(i).is_err(), "{i}");
+        }
+    }
+
+    quickcheck! {
+        fn first_difference_is_the_first_non_zero(results: Vec<u8>) -> bool {
+            let mut diff = FirstDifference::new();
+            for r in &results {
+                diff.update(*r);
+            }
+
+            diff.result() == results.iter().copied().find(|r| *r != 0).unwrap_or(0)
+        }
+
+        fn partial_order_reverse(a: u16, b: u16) -> bool {
+            PartialOrderCMP::reverse(PartialOrderCMP::compare(a, b)) == PartialOrderCMP::compare(b, a)
+        }
+    }
 }
@@ -0,0 +1,260 @@
+//! Rough figures for how long a cipher's operations take.
+//!
+//! Capacity planning needs some idea of what each encryption or comparison is going to cost, and
+//! for a given cipher that's almost entirely determined by its parameters: the number of blocks
+//! (`N`) and the block width (`W`).  A right ciphertext, for instance, has `N * W` values in it,
+//! each of which needs a keyed hash, so doubling `W` roughly doubles the cost of a right
+//! encryption, while a comparison only ever hashes one value per block, regardless of `W`.
+//!
+//! [`Cipher::estimated_costs()`](crate::Cipher::estimated_costs) works the figures out from the
+//! parameters alone, by counting the cryptographic operations involved and multiplying by a
+//! nominal cost for each.  [`Cipher::measured_costs()`](crate::Cipher::measured_costs) runs the
+//! operations for real, on the current machine, for when a model isn't good enough.
+//!
+
+use std::time::{Duration, Instant};
+
+use crate::ciphersuite::CipherSuite;
+use crate::ciphertext::{CipherText, LeftCipherText, Serializable};
+use crate::cmp::Comparator;
+use crate::{Cipher, Error, PlainText};
+
+/// Nominal cost, in nanoseconds, of generating one PRF output block
+const PRF_NANOS: u64 = 40;
+
+/// Nominal cost, in nanoseconds, of keying the hash function
+const HASH_KEYING_NANOS: u64 = 150;
+
+/// Nominal cost, in nanoseconds, of hashing one nonce with an already-keyed hash function
+const KEYED_HASH_NANOS: u64 = 40;
+
+/// Nominal cost, in nanoseconds, of looking up one value in the permutation
+const PERMUTATION_NANOS: u64 = 2;
+
+/// Nominal cost, in nanoseconds, of deriving one block's nonce
+const NONCE_NANOS: u64 = 20;
+
+/// Nominal cost, in nanoseconds, of writing out one byte of serialized ciphertext
+const SERIALIZED_BYTE_NANOS: u64 = 1;
+
+/// How long each of a cipher's operations takes, on average
+///
+/// The figures are per operation, for a single value, on a single thread.
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::aes128v1::ore;
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// # let key = [0u8; 32];
+/// let narrow = ore::Cipher::<8, 16>::new(&key)?.estimated_costs();
+/// let wide = ore::Cipher::<4, 256>::new(&key)?.estimated_costs();
+///
+/// // Wide blocks make for expensive right ciphertexts...
+/// assert!(wide.right_encrypt > narrow.right_encrypt);
+/// // ... but fewer blocks make for cheaper comparisons
+/// assert!(wide.compare < narrow.compare);
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CostEstimate {
+    /// Producing a ciphertext with both "left" and "right" parts
+    pub full_encrypt: Duration,
+    /// Producing a ciphertext with only a "right" part
+    pub right_encrypt: Duration,
+    /// Producing a "left" ciphertext on its own
+    pub left_encrypt: Duration,
+    /// Comparing a left ciphertext against a right ciphertext
+    pub compare: Duration,
+    /// Serializing a full ciphertext
+    pub serialize: Duration,
+    /// Whether these figures were measured on this machine, rather than worked out from the
+    /// cipher's parameters
+    pub measured: bool,
+}
+
+impl CostEstimate {
+    /// Work out the costs for a cipher with the given parameters, without running anything
+    pub(crate) fn modelled<
+        S: CipherSuite<W, M>,
+        CMP: Comparator<M>,
+        const N: usize,
+        const W: u16,
+        const M: u8,
+    >() -> Self {
+        let n = u64::try_from(N).unwrap_or(u64::MAX);
+        let w = u64::from(W);
+
+        let left_nanos = n.saturating_mul(PRF_NANOS.saturating_add(PERMUTATION_NANOS));
+        let right_nanos = n
+            .saturating_mul(NONCE_NANOS)
+            .saturating_add(
+                w.saturating_mul(
+                    PRF_NANOS
+                        .saturating_add(HASH_KEYING_NANOS)
+                        .saturating_add(PERMUTATION_NANOS),
+                ),
+            )
+            .saturating_add(n.saturating_mul(w).saturating_mul(KEYED_HASH_NANOS));
+        let serialized_len = CipherText::<S, CMP, N, W, M>::max_right_serialized_len()
+            .saturating_add(2)
+            .saturating_add(LeftCipherText::<S, CMP, N, W, M>::serialized_len());
+
+        CostEstimate {
+            full_encrypt: Duration::from_nanos(left_nanos.saturating_add(right_nanos)),
+            right_encrypt: Duration::from_nanos(right_nanos),
+            left_encrypt: Duration::from_nanos(left_nanos),
+            compare: Duration::from_nanos(
+                n.saturating_mul(HASH_KEYING_NANOS.saturating_add(KEYED_HASH_NANOS)),
+            ),
+            serialize: Duration::from_nanos(
+                u64::try_from(serialized_len)
+                    .unwrap_or(u64::MAX)
+                    .saturating_mul(SERIALIZED_BYTE_NANOS),
+            ),
+            measured: false,
+        }
+    }
+
+    /// Time each operation `rounds` times with the given cipher, after one round to warm up
+    ///
+    /// Nothing done here is reported to the cipher's audit sink, since none of it is real work.
+    ///
+    pub(crate) fn measure<
+        S: CipherSuite<W, M>,
+        CMP: Comparator<M>,
+        const N: usize,
+        const W: u16,
+        const M: u8,
+    >(
+        cipher: &Cipher<S, CMP, N, W, M>,
+        rounds: u32,
+    ) -> Result<Self, Error> {
+        if rounds == 0 {
+            return Err(Error::RangeError(
+                "cannot measure costs over zero rounds".to_string(),
+            ));
+        }
+
+        let value = PlainText::<N, W>::new([0; N]);
+        let full = CipherText::<S, CMP, N, W, M>::new(cipher, &value)?;
+        let left = LeftCipherText::<S, CMP, N, W, M>::encrypt(cipher, &value)?;
+        left.compare(&full)?;
+        full.to_vec()?;
+
+        let average = |f: &mut dyn FnMut() -> Result<(), Error>| -> Result<Duration, Error> {
+            let start = Instant::now();
+            for _ in 0..rounds {
+                f()?;
+            }
+            start.elapsed().checked_div(rounds).ok_or_else(|| {
+                Error::InternalError(format!("could not average a duration over {rounds} rounds"))
+            })
+        };
+
+        Ok(CostEstimate {
+            full_encrypt: average(&mut || CipherText::new(cipher, &value).map(drop))?,
+            right_encrypt: average(&mut || CipherText::new_right(cipher, &value).map(drop))?,
+            left_encrypt: average(&mut || LeftCipherText::encrypt(cipher, &value).map(drop))?,
+            compare: average(&mut || left.compare(&full).map(drop))?,
+            serialize: average(&mut || full.to_vec().map(drop))?,
+            measured: true,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes128v1::{ere, ore};
+    use crate::audit::{AuditEvent, AuditSink};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn right_encryption_scales_with_block_width() {
+        let narrow = ore::Cipher::<4, 16>::new(&[0u8; 32])
+            .unwrap()
+            .estimated_costs();
+        let wide = ore::Cipher::<4, 256>::new(&[0u8; 32])
+            .unwrap()
+            .estimated_costs();
+
+        assert!(wide.right_encrypt > narrow.right_encrypt * 10);
+        assert_eq!(wide.left_encrypt, narrow.left_encrypt);
+        assert_eq!(wide.compare, narrow.compare);
+        assert!(!wide.measured);
+    }
+
+    #[test]
+    fn full_encryption_is_left_plus_right() {
+        let costs = ere::Cipher::<8, 256>::new(&[0u8; 32])
+            .unwrap()
+            .estimated_costs();
+
+        assert_eq!(costs.full_encrypt, costs.left_encrypt + costs.right_encrypt);
+    }
+
+    #[test]
+    fn measured_costs_are_measured() {
+        let costs = ore::Cipher::<4, 16>::new(&[0u8; 32])
+            .unwrap()
+            .measured_costs(3)
+            .unwrap();
+
+        assert!(costs.measured);
+        assert!(costs.right_encrypt > Duration::ZERO);
+    }
+
+    #[test]
+    fn measuring_needs_at_least_one_round() {
+        let cipher = ore::Cipher::<4, 16>::new(&[0u8; 32]).unwrap();
+
+        assert!(matches!(
+            cipher.measured_costs(0),
+            Err(Error::RangeError(_))
+        ));
+    }
+
+    #[test]
+    fn measuring_needs_a_full_cipher() {
+        let cipher = ore::Cipher::<4, 16>::new_right_only(&[0u8; 32]).unwrap();
+
+        assert!(matches!(
+            cipher.measured_costs(1),
+            Err(Error::NotPermitted(_))
+        ));
+        // Modelled costs don't involve the key at all, so anyone can have those
+        assert_eq!(
+            ore::Cipher::<4, 16>::new(&[0u8; 32])
+                .unwrap()
+                .estimated_costs(),
+            cipher.estimated_costs()
+        );
+    }
+
+    #[test]
+    fn measuring_is_not_audited() {
+        #[derive(Default)]
+        struct Counter(AtomicUsize);
+
+        impl AuditSink for Counter {
+            fn record(&self, _: &AuditEvent<'_>) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let counter = Arc::new(Counter::default());
+        let cipher = ore::Cipher::<4, 16>::new(&[0u8; 32])
+            .unwrap()
+            .with_audit_sink(Arc::<Counter>::clone(&counter), "k1", "test");
+
+        cipher.measured_costs(2).unwrap();
+
+        assert_eq!(0, counter.0.load(Ordering::Relaxed));
+    }
+}
@@ -0,0 +1,204 @@
+//! Conversions from the `chrono` and `time` crates' date and timestamp types.
+//!
+//! Dates are encoded as the number of days since 1970-01-01, and timestamps as the number of
+//! whole seconds since the Unix epoch (anything smaller than a second is truncated away).  Both
+//! are signed, so they use the same offset encoding as [`PlainText::parse_int()`], which keeps
+//! dates before the epoch sorting before those after it.
+//!
+//! Since the encoding doesn't depend on which crate the value came from, a date from `chrono` and
+//! the same date from `time` produce the same plaintext, as do timestamps.  Dates and timestamps
+//! aren't comparable with each other, though, nor with plaintexts made from integers by any means
+//! other than [`parse_int()`](PlainText::parse_int).
+//!
+//! A `PlainText<4, 256>` covers about 68 years either side of the epoch in seconds (and rather
+//! more than anyone will ever need in days); timestamps further out than that need a bigger
+//! plaintext, such as a `PlainText<8, 256>`.
+//!
+
+use std::convert::TryFrom;
+
+use crate::{Error, PlainText};
+
+#[cfg(feature = "chrono")]
+/// What `chrono::Datelike::num_days_from_ce()` returns for 1970-01-01
+const CHRONO_EPOCH_DAYS_FROM_CE: i128 = 719_163;
+
+#[cfg(feature = "time")]
+/// The Julian day number of 1970-01-01
+const UNIX_EPOCH_JULIAN_DAY: i128 = 2_440_588;
+
+#[cfg(feature = "chrono")]
+impl<const N: usize, const W: u16> TryFrom<chrono::NaiveDate> for PlainText<N, W> {
+    type Error = Error;
+
+    /// Encode the number of days since 1970-01-01
+    fn try_from(value: chrono::NaiveDate) -> Result<Self, Self::Error> {
+        PlainText::from_signed(
+            i128::from(chrono::Datelike::num_days_from_ce(&value))
+                .saturating_sub(CHRONO_EPOCH_DAYS_FROM_CE),
+        )
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<const N: usize, const W: u16> TryFrom<chrono::DateTime<chrono::Utc>> for PlainText<N, W> {
+    type Error = Error;
+
+    /// Encode the number of whole seconds since the Unix epoch
+    fn try_from(value: chrono::DateTime<chrono::Utc>) -> Result<Self, Self::Error> {
+        PlainText::from_signed(value.timestamp().into())
+    }
+}
+
+#[cfg(feature = "time")]
+impl<const N: usize, const W: u16> TryFrom<time::Date> for PlainText<N, W> {
+    type Error = Error;
+
+    /// Encode the number of days since 1970-01-01
+    fn try_from(value: time::Date) -> Result<Self, Self::Error> {
+        PlainText::from_signed(
+            i128::from(value.to_julian_day()).saturating_sub(UNIX_EPOCH_JULIAN_DAY),
+        )
+    }
+}
+
+#[cfg(feature = "time")]
+impl<const N: usize, const W: u16> TryFrom<time::OffsetDateTime> for PlainText<N, W> {
+    type Error = Error;
+
+    /// Encode the number of whole seconds since the Unix epoch
+    ///
+    /// The offset is taken into account, so two values that represent the same instant produce
+    /// the same plaintext, whatever their offsets.
+    ///
+    fn try_from(value: time::OffsetDateTime) -> Result<Self, Self::Error> {
+        PlainText::from_signed(value.unix_timestamp().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "chrono")]
+    mod chrono_conversions {
+        use super::*;
+        use chrono::{NaiveDate, TimeZone, Utc};
+
+        fn date(y: i32, m: u32, d: u32) -> PlainText<4, 256> {
+            NaiveDate::from_ymd_opt(y, m, d)
+                .unwrap()
+                .try_into()
+                .unwrap()
+        }
+
+        #[test]
+        fn dates_are_days_since_the_epoch() {
+            assert_eq!(PlainText::parse_int("0").unwrap(), date(1970, 1, 1));
+            assert_eq!(PlainText::parse_int("1").unwrap(), date(1970, 1, 2));
+            assert_eq!(PlainText::parse_int("-1").unwrap(), date(1969, 12, 31));
+            assert_eq!(PlainText::parse_int("19000").unwrap(), date(2022, 1, 8));
+        }
+
+        #[test]
+        fn dates_sort_in_order() {
+            assert!(date(1969, 12, 31).blocks() < date(1970, 1, 1).blocks());
+            assert!(date(1970, 1, 1).blocks() < date(2000, 2, 29).blocks());
+            assert!(date(1, 1, 1).blocks() < date(1969, 12, 31).blocks());
+        }
+
+        #[test]
+        fn timestamps_are_seconds_since_the_epoch() {
+            let ts = Utc.with_ymd_and_hms(2001, 9, 9, 1, 46, 40).unwrap();
+
+            assert_eq!(
+                PlainText::<8, 256>::parse_int("1000000000").unwrap(),
+                ts.try_into().unwrap()
+            );
+            assert_eq!(
+                PlainText::<8, 256>::parse_int("-1").unwrap(),
+                Utc.with_ymd_and_hms(1969, 12, 31, 23, 59, 59)
+                    .unwrap()
+                    .try_into()
+                    .unwrap()
+            );
+        }
+
+        #[test]
+        fn timestamps_out_of_range() {
+            let far_future = Utc.with_ymd_and_hms(2100, 1, 1, 0, 0, 0).unwrap();
+
+            assert!(matches!(
+                PlainText::<4, 256>::try_from(far_future),
+                Err(Error::RangeError(_))
+            ));
+            assert!(PlainText::<8, 256>::try_from(far_future).is_ok());
+        }
+    }
+
+    #[cfg(feature = "time")]
+    mod time_conversions {
+        use super::*;
+        use time::{Date, Month, OffsetDateTime, UtcOffset};
+
+        #[test]
+        fn dates_are_days_since_the_epoch() {
+            let date = |y, m, d| -> PlainText<4, 256> {
+                Date::from_calendar_date(y, m, d)
+                    .unwrap()
+                    .try_into()
+                    .unwrap()
+            };
+
+            assert_eq!(
+                PlainText::parse_int("0").unwrap(),
+                date(1970, Month::January, 1)
+            );
+            assert_eq!(
+                PlainText::parse_int("-1").unwrap(),
+                date(1969, Month::December, 31)
+            );
+            assert_eq!(
+                PlainText::parse_int("19000").unwrap(),
+                date(2022, Month::January, 8)
+            );
+        }
+
+        #[test]
+        fn timestamps_ignore_the_offset() {
+            let utc = OffsetDateTime::from_unix_timestamp(1_000_000_000).unwrap();
+            let elsewhere = utc.to_offset(UtcOffset::from_hms(10, 0, 0).unwrap());
+
+            assert_eq!(
+                PlainText::<8, 256>::parse_int("1000000000").unwrap(),
+                utc.try_into().unwrap()
+            );
+            assert_eq!(
+                PlainText::<8, 256>::try_from(utc).unwrap(),
+                PlainText::<8, 256>::try_from(elsewhere).unwrap()
+            );
+        }
+    }
+
+    #[cfg(all(feature = "chrono", feature = "time"))]
+    #[test]
+    fn chrono_and_time_agree() {
+        use chrono::{TimeZone, Utc};
+
+        assert_eq!(
+            PlainText::<4, 256>::try_from(chrono::NaiveDate::from_ymd_opt(1999, 12, 31).unwrap())
+                .unwrap(),
+            PlainText::<4, 256>::try_from(
+                time::Date::from_calendar_date(1999, time::Month::December, 31).unwrap()
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            PlainText::<8, 256>::try_from(Utc.timestamp_opt(-123_456_789, 0).unwrap()).unwrap(),
+            PlainText::<8, 256>::try_from(
+                time::OffsetDateTime::from_unix_timestamp(-123_456_789).unwrap()
+            )
+            .unwrap()
+        );
+    }
+}
@@ -0,0 +1,788 @@
+//! How the values in a right ciphertext are written out
+//!
+//! Each comparator produces values in a different range (`0..M`), and the most compact way to
+//! serialise those values depends on what `M` is.  Rather than having the ciphertext try to
+//! figure that out for itself at runtime, each comparator names the encoding its values use, and
+//! the ciphertext just does what it's told.
+//!
+//! A comparator with an `M` that doesn't have a hand-tuned encoding of its own can use
+//! [`RadixEncoding`], which works for any `M`.
+//!
+
+use crate::bitlist::{RadixGroup, ReadableBitList, WritableBitList};
+use crate::packed::PackedValues;
+use crate::Error;
+
+/// Defines what you need to do in order to be a right ciphertext value encoding
+#[allow(unreachable_pub)] // I think this is a bug in the lint; see also https://github.com/rust-lang/rust/issues/110923
+pub trait ValueEncoding<const M: u8> {
+    /// The most bits that any one value can take up once it's been packed
+    const MAX_BITS: usize;
+
+    /// Whether every value takes up exactly [`MAX_BITS`](Self::MAX_BITS), so that the packed size
+    /// only depends on how many values there are
+    const FIXED_WIDTH: bool;
+
+    /// How many values are packed together into one group, for encodings that don't give each
+    /// value its own bits
+    const GROUP_SIZE: usize = 1;
+
+    /// How many bits each group of [`GROUP_SIZE`](Self::GROUP_SIZE) values takes up, at most
+    ///
+    /// A partial group at the end of the values takes up the same space as a whole one.
+    ///
+    const GROUP_BITS: usize = Self::MAX_BITS;
+
+    /// Turn a set of values into a sequence of bytes
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if one of the values can't be encoded, which should only happen if
+    /// there's a bug somewhere.
+    ///
+    fn pack(values: &PackedValues<M>) -> Result<Vec<u8>, Error>;
+
+    /// Add the packed form of a set of values to the end of a list of bits
+    ///
+    /// Packing a set of values in several goes, one after the other, produces exactly the same
+    /// bits as packing them all at once with [`pack()`](Self::pack).
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if one of the values can't be encoded, which should only happen if
+    /// there's a bug somewhere.
+    ///
+    fn pack_into(values: &PackedValues<M>, bits: &mut WritableBitList) -> Result<(), Error>;
+
+    /// Turn a sequence of bytes, as produced by [`pack()`](Self::pack), back into `len` values
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the bytes don't encode exactly `len` values.
+    ///
+    fn unpack(bytes: &[u8], len: usize) -> Result<PackedValues<M>, Error> {
+        Self::unpack_in(bytes, len, Vec::new())
+    }
+
+    /// Turn a sequence of bytes back into `len` values, as [`unpack()`](Self::unpack) does, but
+    /// keeping them in `storage` (reusing its allocation, if it's big enough) rather than in
+    /// freshly-allocated memory
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the bytes don't encode exactly `len` values.
+    ///
+    fn unpack_in(bytes: &[u8], len: usize, storage: Vec<u64>) -> Result<PackedValues<M>, Error>;
+
+    /// Dig the values at the given (ascending) indices straight out of a sequence of bytes, as
+    /// produced by [`pack()`](Self::pack), without decoding any of the others
+    ///
+    /// Only as much of the bytes are looked at as is needed to find the requested values, so
+    /// corruption elsewhere goes unnoticed.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the bytes run out before all the values have been found, or if the
+    /// indices aren't ascending and the encoding can only be read in order.
+    ///
+    fn peek<const K: usize>(bytes: &[u8], indices: &[usize; K]) -> Result<[u8; K], Error>;
+}
+
+/// Values which are either 0 or 1, stored one bit apiece
+#[allow(unreachable_pub)] // I think this is a bug in the lint; see also https://github.com/rust-lang/rust/issues/110923
+#[derive(Debug, Clone)]
+pub struct BinaryEncoding {}
+
+impl ValueEncoding<2> for BinaryEncoding {
+    const MAX_BITS: usize = 1;
+    const FIXED_WIDTH: bool = true;
+
+    /// Since binary values are stored one bit apiece in memory, this is pretty much just a copy.
+    fn pack(values: &PackedValues<2>) -> Result<Vec<u8>, Error> {
+        Ok(values.to_le_bytes())
+    }
+
+    fn pack_into(values: &PackedValues<2>, bits: &mut WritableBitList) -> Result<(), Error> {
+        fixed_width_pack_into(values, Self::MAX_BITS, bits)
+    }
+
+    fn unpack_in(bytes: &[u8], len: usize, storage: Vec<u64>) -> Result<PackedValues<2>, Error> {
+        fixed_width_unpack(bytes, len, storage, Self::MAX_BITS, "binary")
+    }
+
+    fn peek<const K: usize>(bytes: &[u8], indices: &[usize; K]) -> Result<[u8; K], Error> {
+        fixed_width_peek(bytes, indices, Self::MAX_BITS, "binary")
+    }
+}
+
+/// Values which are 0, 1, 2, or 3, stored two bits apiece
+#[allow(unreachable_pub)] // I think this is a bug in the lint; see also https://github.com/rust-lang/rust/issues/110923
+#[derive(Debug, Clone)]
+pub struct QuaternaryEncoding {}
+
+impl ValueEncoding<4> for QuaternaryEncoding {
+    const MAX_BITS: usize = 2;
+    const FIXED_WIDTH: bool = true;
+
+    /// As with [`BinaryEncoding`], the in-memory layout is already what we want.
+    fn pack(values: &PackedValues<4>) -> Result<Vec<u8>, Error> {
+        Ok(values.to_le_bytes())
+    }
+
+    fn pack_into(values: &PackedValues<4>, bits: &mut WritableBitList) -> Result<(), Error> {
+        fixed_width_pack_into(values, Self::MAX_BITS, bits)
+    }
+
+    fn unpack_in(bytes: &[u8], len: usize, storage: Vec<u64>) -> Result<PackedValues<4>, Error> {
+        fixed_width_unpack(bytes, len, storage, Self::MAX_BITS, "quaternary")
+    }
+
+    fn peek<const K: usize>(bytes: &[u8], indices: &[usize; K]) -> Result<[u8; K], Error> {
+        fixed_width_peek(bytes, indices, Self::MAX_BITS, "quaternary")
+    }
+}
+
+/// Add values that are stored `width` bits apiece, in both memory and serialized form, to the end
+/// of a list of bits
+fn fixed_width_pack_into<const M: u8>(
+    values: &PackedValues<M>,
+    width: usize,
+    bits: &mut WritableBitList,
+) -> Result<(), Error> {
+    let mut remaining = values.len().saturating_mul(width);
+
+    for byte in values.to_le_bytes() {
+        let n_bits = remaining.min(8);
+        bits.push_byte_lsb(u8::try_from(n_bits).unwrap_or(8), byte)?;
+        remaining = remaining.saturating_sub(n_bits);
+    }
+
+    Ok(())
+}
+
+/// Turn the bytes of values stored `width` bits apiece back into `len` values
+///
+/// `name` is only used in error messages.
+///
+fn fixed_width_unpack<const M: u8>(
+    bytes: &[u8],
+    len: usize,
+    storage: Vec<u64>,
+    width: usize,
+    name: &str,
+) -> Result<PackedValues<M>, Error> {
+    let expected = num::Integer::div_ceil(&len.saturating_mul(width), &8);
+
+    match bytes.len().cmp(&expected) {
+        std::cmp::Ordering::Less => Err(Error::ParseError(format!(
+            "end-of-data reached while unpacking {name} values"
+        ))),
+        std::cmp::Ordering::Greater => Err(Error::ParseError(
+            "bitlist longer than required number of entries".to_string(),
+        )),
+        std::cmp::Ordering::Equal => PackedValues::from_le_bytes_in(bytes, len, storage),
+    }
+}
+
+/// Dig values stored `width` bits apiece out of a sequence of bytes
+///
+/// `width` has to divide 8, so that no value straddles two bytes.  `name` is only used in error
+/// messages.
+///
+fn fixed_width_peek<const K: usize>(
+    bytes: &[u8],
+    indices: &[usize; K],
+    width: usize,
+    name: &str,
+) -> Result<[u8; K], Error> {
+    let mask = u8::MAX
+        .checked_shr(u32::try_from(8usize.saturating_sub(width)).unwrap_or(0))
+        .unwrap_or(0);
+    let mut out = [0u8; K];
+
+    for (o, idx) in out.iter_mut().zip(indices) {
+        let (byte, bit) = num::Integer::div_rem(&idx.saturating_mul(width), &8);
+        let b = bytes.get(byte).ok_or_else(|| {
+            Error::ParseError(format!("end-of-data reached while peeking {name} values"))
+        })?;
+        *o = b.checked_shr(u32::try_from(bit).unwrap_or(0)).unwrap_or(0) & mask;
+    }
+
+    Ok(out)
+}
+
+/// Values in the range `0..M`, for any `M`, stored as groups of base-`M` digits
+///
+/// Each group of [`GROUP_SIZE`](ValueEncoding::GROUP_SIZE) values is treated as a single number,
+/// least-significant digit first, and takes up just enough bits to hold the biggest number that
+/// many digits could make.  The last group is padded out with zeroes, so the packed size only
+/// depends on how many values there are.  Trinary values come out at a little over 1.6 bits each,
+/// as opposed to [`TrinaryEncoding`]'s two bits for every non-zero value.
+///
+#[allow(unreachable_pub)] // I think this is a bug in the lint; see also https://github.com/rust-lang/rust/issues/110923
+#[allow(dead_code)] // None of the built-in comparators need it (yet)
+#[derive(Debug, Clone)]
+pub struct RadixEncoding {}
+
+impl RadixEncoding {
+    /// How many bits `len` base-`M` values take up, once packed
+    fn packed_bits<const M: u8>(len: usize) -> usize {
+        len.div_ceil(<Self as ValueEncoding<M>>::GROUP_SIZE)
+            .saturating_mul(<Self as ValueEncoding<M>>::GROUP_BITS)
+    }
+
+    /// Check that a group, as read from a packed sequence, is one that could have been packed
+    fn check_group(acc: u64, group: &RadixGroup) -> Result<u64, Error> {
+        if acc < group.limit {
+            Ok(acc)
+        } else {
+            Err(Error::ParseError(format!(
+                "packed group {acc} is too big for {} base-{} digits",
+                group.digits, group.radix
+            )))
+        }
+    }
+}
+
+impl<const M: u8> ValueEncoding<M> for RadixEncoding {
+    const MAX_BITS: usize =
+        (RadixGroup::new(M).bits as usize).div_ceil(RadixGroup::new(M).digits as usize);
+    const FIXED_WIDTH: bool = true;
+    const GROUP_SIZE: usize = RadixGroup::new(M).digits as usize;
+    const GROUP_BITS: usize = RadixGroup::new(M).bits as usize;
+
+    fn pack(values: &PackedValues<M>) -> Result<Vec<u8>, Error> {
+        let mut v = WritableBitList::new(Self::packed_bits::<M>(values.len()));
+
+        Self::pack_into(values, &mut v)?;
+
+        Ok(v.vec())
+    }
+
+    fn pack_into(values: &PackedValues<M>, bits: &mut WritableBitList) -> Result<(), Error> {
+        let group = RadixGroup::new(M);
+
+        for val in values.iter() {
+            bits.push_digit(&group, val)?;
+        }
+
+        Ok(())
+    }
+
+    fn unpack_in(bytes: &[u8], len: usize, storage: Vec<u64>) -> Result<PackedValues<M>, Error> {
+        let group = RadixGroup::new(M);
+        let group_bits = <Self as ValueEncoding<M>>::GROUP_BITS;
+        let groups = len.div_ceil(<Self as ValueEncoding<M>>::GROUP_SIZE);
+        let expected = Self::packed_bits::<M>(len).div_ceil(8);
+
+        match bytes.len().cmp(&expected) {
+            std::cmp::Ordering::Less => {
+                return Err(Error::ParseError(format!(
+                    "end-of-data reached while unpacking base-{M} values"
+                )))
+            }
+            std::cmp::Ordering::Greater => {
+                return Err(Error::ParseError(
+                    "bitlist longer than required number of entries".to_string(),
+                ))
+            }
+            std::cmp::Ordering::Equal => (),
+        }
+
+        let mut v = ReadableBitList::from_slice(bytes);
+        let mut vals = PackedValues::new_in(len, storage);
+        let mut i = 0usize;
+
+        for _ in 0..groups {
+            let mut acc = Self::check_group(
+                v.shift_n(group_bits).ok_or_else(|| {
+                    Error::ParseError(format!(
+                        "end-of-data reached while unpacking base-{M} values"
+                    ))
+                })?,
+                &group,
+            )?;
+
+            for _ in 0..group.digits {
+                let (rest, remainder) = num::Integer::div_rem(&acc, &u64::from(M));
+                let digit = u8::try_from(remainder).map_err(|e| {
+                    Error::InternalError(format!("base-{M} digit didn't fit in a byte ({e})"))
+                })?;
+
+                if i < len {
+                    vals.set(i, digit)?;
+                } else if digit != 0 {
+                    return Err(Error::ParseError(format!(
+                        "non-zero padding after the last base-{M} value"
+                    )));
+                } else {
+                    // Zero padding after the last value is exactly what should be there
+                }
+
+                acc = rest;
+                i = i.saturating_add(1);
+            }
+        }
+
+        Ok(vals)
+    }
+
+    fn peek<const K: usize>(bytes: &[u8], indices: &[usize; K]) -> Result<[u8; K], Error> {
+        let group = RadixGroup::new(M);
+        let group_bits = <Self as ValueEncoding<M>>::GROUP_BITS;
+        let mut out = [0u8; K];
+
+        for (o, idx) in out.iter_mut().zip(indices) {
+            let (g, d) = num::Integer::div_rem(idx, &<Self as ValueEncoding<M>>::GROUP_SIZE);
+            let (byte, bit) = num::Integer::div_rem(&g.saturating_mul(group_bits), &8);
+            // A group of up to 32 bits, starting up to 7 bits into a byte, fits in five bytes
+            let window = bytes.get(byte..).ok_or_else(|| {
+                Error::ParseError(format!("end-of-data reached while peeking base-{M} values"))
+            })?;
+            let needed = bit.saturating_add(group_bits).div_ceil(8);
+            let mut buf = [0u8; 8];
+
+            for (dst, src) in buf.iter_mut().zip(window.get(..needed).ok_or_else(|| {
+                Error::ParseError(format!("end-of-data reached while peeking base-{M} values"))
+            })?) {
+                *dst = *src;
+            }
+
+            let mut reader = ReadableBitList::from_slice(&buf);
+            reader.shift_n(bit);
+            let acc = Self::check_group(reader.shift_n(group_bits).unwrap_or(u64::MAX), &group)?;
+            let place = u64::from(M)
+                .checked_pow(u32::try_from(d).unwrap_or(u32::MAX))
+                .ok_or_else(|| {
+                    Error::InternalError(format!("digit {d} is past the end of a group"))
+                })?;
+
+            *o = u8::try_from(num::Integer::mod_floor(
+                &acc.checked_div(place).unwrap_or(0),
+                &u64::from(M),
+            ))
+            .map_err(|e| {
+                Error::InternalError(format!("base-{M} digit didn't fit in a byte ({e})"))
+            })?;
+        }
+
+        Ok(out)
+    }
+}
+
+/// Values which are 0, 1, or 2, stored with a variable-length encoding
+///
+/// Most values in a right ciphertext are zero, so zero gets a one-bit encoding, while the other
+/// two values get two bits each.
+///
+#[allow(unreachable_pub)] // I think this is a bug in the lint; see also https://github.com/rust-lang/rust/issues/110923
+#[derive(Debug, Clone)]
+pub struct TrinaryEncoding {}
+
+impl ValueEncoding<3> for TrinaryEncoding {
+    const MAX_BITS: usize = 2;
+    const FIXED_WIDTH: bool = false;
+
+    fn pack(values: &PackedValues<3>) -> Result<Vec<u8>, Error> {
+        let mut v = WritableBitList::new(values.len().saturating_mul(Self::MAX_BITS));
+
+        Self::pack_into(values, &mut v)?;
+
+        Ok(v.vec())
+    }
+
+    fn pack_into(values: &PackedValues<3>, bits: &mut WritableBitList) -> Result<(), Error> {
+        for val in values.iter() {
+            let (code, len) = TRINARY_ENCODE
+                .get(usize::from(val).min(2))
+                .copied()
+                .ok_or_else(|| Error::InternalError(format!("no trinary encoding for {val}")))?;
+
+            bits.push_byte_lsb(len, code)?;
+        }
+
+        Ok(())
+    }
+
+    /// Rather than reading the encoded values a bit at a time, this looks up each byte's worth of
+    /// bits in [`TRINARY_DECODE`], which says which values are (completely) encoded in that byte.
+    ///
+    fn unpack_in(bytes: &[u8], len: usize, storage: Vec<u64>) -> Result<PackedValues<3>, Error> {
+        let mut v = ReadableBitList::from_slice(bytes);
+        let mut vals = PackedValues::new_in(len, storage);
+        let mut i = 0usize;
+
+        while i < vals.len() {
+            let mut word = v.peek_word();
+            let mut avail = u64::BITS;
+            let mut consumed = 0usize;
+
+            // Leave a byte's worth of headroom, so that every lookup sees a full byte of real
+            // bits (or, at the end of the list, zero padding)
+            while avail >= 8 && i < vals.len() {
+                let run = TRINARY_DECODE
+                    .get(usize::from(word.to_le_bytes()[0]))
+                    .ok_or_else(|| {
+                        Error::InternalError("byte not found in decode table".to_string())
+                    })?;
+                let take = usize::from(run.count).min(vals.len().saturating_sub(i));
+                let used = take
+                    .checked_sub(1)
+                    .and_then(|last| run.ends.get(last))
+                    .copied()
+                    .unwrap_or(0);
+
+                for val in run.values.iter().take(take) {
+                    vals.set(i, *val)?;
+                    i = i.saturating_add(1);
+                }
+                word = word.wrapping_shr(u32::from(used));
+                avail = avail.saturating_sub(u32::from(used));
+                consumed = consumed.saturating_add(usize::from(used));
+            }
+
+            v.shift_n(consumed).ok_or_else(|| {
+                Error::ParseError("end-of-data reached while unpacking trinary values".to_string())
+            })?;
+        }
+
+        if v.fully_consumed() {
+            Ok(vals)
+        } else {
+            Err(Error::ParseError(
+                "bitlist longer than required number of entries".to_string(),
+            ))
+        }
+    }
+
+    fn peek<const K: usize>(bytes: &[u8], indices: &[usize; K]) -> Result<[u8; K], Error> {
+        if indices.windows(2).any(|w| w.first() > w.get(1)) {
+            return Err(Error::InternalError(format!(
+                "value indices must be ascending, but got {indices:?}"
+            )));
+        }
+
+        let mut out = [0u8; K];
+        // The bit position of the start of the `i`th value
+        let mut pos = 0usize;
+        let mut i = 0usize;
+
+        for (o, idx) in out.iter_mut().zip(indices) {
+            let mut wanted = idx.checked_sub(i).ok_or_else(|| {
+                Error::InternalError(format!("went past value {idx} while looking for it"))
+            })?;
+
+            loop {
+                let (byte, bit) = num::Integer::div_rem(&pos, &8);
+                let lo = bytes.get(byte).ok_or_else(|| {
+                    Error::ParseError(
+                        "end-of-data reached while peeking trinary values".to_string(),
+                    )
+                })?;
+                let hi = bytes.get(byte.saturating_add(1)).unwrap_or(&0);
+                let window = u16::from_le_bytes([*lo, *hi])
+                    .checked_shr(u32::try_from(bit).unwrap_or(0))
+                    .unwrap_or(0)
+                    .to_le_bytes()[0];
+                let run = TRINARY_DECODE.get(usize::from(window)).ok_or_else(|| {
+                    Error::InternalError("byte not found in decode table".to_string())
+                })?;
+
+                if let Some(v) = run.values.get(wanted).filter(|_| wanted < run.count.into()) {
+                    *o = *v;
+                    break;
+                }
+
+                let skipped = usize::from(run.count);
+                let used = skipped
+                    .checked_sub(1)
+                    .and_then(|last| run.ends.get(last))
+                    .copied()
+                    .ok_or_else(|| {
+                        Error::InternalError(format!("no values decoded from {window:#x}"))
+                    })?;
+
+                pos = pos.saturating_add(usize::from(used));
+                i = i.saturating_add(skipped);
+                wanted = wanted.saturating_sub(skipped);
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+/// The bits used to represent each trinary value, along with how many bits there are
+///
+/// Bits are written least-significant first, so `1` is encoded as a `1` followed by a `0`, and
+/// `2` as a pair of `1`s.
+///
+const TRINARY_ENCODE: [(u8, u8); 3] = [(0b0, 1), (0b01, 2), (0b11, 2)];
+
+/// All of the trinary values that are completely encoded within a single byte
+#[derive(Clone, Copy)]
+struct TrinaryRun {
+    /// How many values were found
+    count: u8,
+    /// The values themselves
+    values: [u8; 8],
+    /// The bit position just past the end of each value's encoding
+    ends: [u8; 8],
+}
+
+/// What every possible byte decodes to, when treated as a sequence of encoded trinary values
+const TRINARY_DECODE: [TrinaryRun; 256] = trinary_decode_table();
+
+/// Build the [`TRINARY_DECODE`] table
+///
+/// Being a `const fn`, none of the usual `get()` or `checked_*` niceties are available; however,
+/// any out-of-bounds access or overflow in here is a compile error, rather than a runtime one.
+/// Similarly, the casts can't truncate, because nothing is ever bigger than 8.
+///
+#[allow(
+    clippy::indexing_slicing,
+    clippy::arithmetic_side_effects,
+    clippy::cast_possible_truncation
+)]
+const fn trinary_decode_table() -> [TrinaryRun; 256] {
+    let mut table = [TrinaryRun {
+        count: 0,
+        values: [0; 8],
+        ends: [0; 8],
+    }; 256];
+    let mut byte = 0usize;
+
+    while byte < 256 {
+        let mut bit = 0usize;
+        let mut count = 0usize;
+
+        while bit < 8 {
+            let val = if (byte >> bit) & 1 == 0 {
+                bit += 1;
+                0
+            } else if bit < 7 {
+                let v = if (byte >> (bit + 1)) & 1 == 0 { 1 } else { 2 };
+                bit += 2;
+                v
+            } else {
+                // The last bit starts a value that finishes in the next byte
+                break;
+            };
+
+            table[byte].values[count] = val;
+            table[byte].ends[count] = bit as u8;
+            count += 1;
+        }
+
+        table[byte].count = count as u8;
+        byte += 1;
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values<const M: u8>(vals: &[u8]) -> PackedValues<M> {
+        let mut p = PackedValues::<M>::new(vals.len());
+
+        for (i, v) in vals.iter().enumerate() {
+            p.set(i, *v).unwrap();
+        }
+
+        p
+    }
+
+    #[test]
+    fn trinary_packing() {
+        let p = values::<3>(&[0, 1, 2, 0, 2]);
+
+        // 0 | 10 | 11 | 0 | 11, LSB first
+        assert_eq!(vec![0b1101_1010u8], TrinaryEncoding::pack(&p).unwrap());
+    }
+
+    #[test]
+    fn trinary_unpacking_checks_length() {
+        assert!(TrinaryEncoding::unpack(&[0b1101_1010u8], 5).is_ok());
+        assert!(TrinaryEncoding::unpack(&[0b1101_1010u8], 6).is_err());
+        assert!(TrinaryEncoding::unpack(&[0b1101_1010u8, 0b0], 5).is_err());
+    }
+
+    #[test]
+    fn peeking() {
+        let vals: Vec<u8> = (0..300u16)
+            .map(|i| u8::try_from(i * 7 % 3).unwrap())
+            .collect();
+        let t = TrinaryEncoding::pack(&values::<3>(&vals)).unwrap();
+        let b = BinaryEncoding::pack(&values::<2>(
+            &vals.iter().map(|v| v % 2).collect::<Vec<_>>(),
+        ))
+        .unwrap();
+        let indices = [0usize, 1, 7, 8, 9, 150, 299];
+
+        assert_eq!(
+            indices.map(|i| vals[i]),
+            TrinaryEncoding::peek(&t, &indices).unwrap()
+        );
+        assert_eq!(
+            indices.map(|i| vals[i] % 2),
+            BinaryEncoding::peek(&b, &indices).unwrap()
+        );
+        assert!(TrinaryEncoding::peek(&t, &[5usize, 4]).is_err());
+        assert!(TrinaryEncoding::peek(&t[..10], &[299usize]).is_err());
+        assert!(BinaryEncoding::peek(&b[..10], &[299usize]).is_err());
+    }
+
+    #[test]
+    fn quaternary_packing() {
+        let p = values::<4>(&[0, 1, 2, 3, 3]);
+
+        // 00 | 10 | 01 | 11 | 11, LSB first
+        assert_eq!(
+            vec![0b1110_0100u8, 0b0000_0011],
+            QuaternaryEncoding::pack(&p).unwrap()
+        );
+        assert!(QuaternaryEncoding::unpack(&[0b1110_0100u8, 0b11], 5).is_ok());
+        assert!(QuaternaryEncoding::unpack(&[0b1110_0100u8], 5).is_err());
+        assert!(QuaternaryEncoding::unpack(&[0b1110_0100u8, 0b11, 0], 5).is_err());
+        assert_eq!(
+            [3u8, 0, 2],
+            QuaternaryEncoding::peek(&[0b1110_0100u8, 0b11], &[4usize, 0, 2]).unwrap()
+        );
+        assert!(QuaternaryEncoding::peek(&[0b1110_0100u8], &[4usize]).is_err());
+    }
+
+    #[test]
+    fn radix_packing() {
+        let p = values::<5>(&[4, 0, 1]);
+
+        // 4 + 0 * 5 + 1 * 25, in a 31-bit group
+        assert_eq!(vec![29u8, 0, 0, 0], RadixEncoding::pack(&p).unwrap());
+        assert_eq!(p, RadixEncoding::unpack(&[29u8, 0, 0, 0], 3).unwrap());
+        assert_eq!(
+            [1u8, 4],
+            <RadixEncoding as ValueEncoding<5>>::peek(&[29u8, 0, 0, 0], &[2usize, 0]).unwrap()
+        );
+    }
+
+    #[test]
+    fn radix_unpacking_is_strict() {
+        // Wrong length
+        assert!(<RadixEncoding as ValueEncoding<5>>::unpack(&[29u8, 0, 0], 3).is_err());
+        assert!(<RadixEncoding as ValueEncoding<5>>::unpack(&[29u8, 0, 0, 0, 0], 3).is_err());
+        // 125 means there's a 1 in the (non-existent) fourth value
+        assert!(<RadixEncoding as ValueEncoding<5>>::unpack(&[125u8, 0, 0, 0], 3).is_err());
+        // 5^13 doesn't fit in 13 base-5 digits
+        assert!(
+            <RadixEncoding as ValueEncoding<5>>::unpack(&1_220_703_125u32.to_le_bytes(), 13)
+                .is_err()
+        );
+        assert!(<RadixEncoding as ValueEncoding<5>>::peek(&[29u8, 0, 0], &[0usize]).is_err());
+    }
+
+    #[test]
+    fn radix_is_denser_than_trinary() {
+        let vals: Vec<u8> = (0..300u16)
+            .map(|i| u8::try_from(i * 7 % 3).unwrap())
+            .collect();
+        let p = values::<3>(&vals);
+
+        let radix = RadixEncoding::pack(&p).unwrap();
+
+        assert!(radix.len() < TrinaryEncoding::pack(&p).unwrap().len());
+        assert_eq!(
+            <RadixEncoding as ValueEncoding<3>>::GROUP_BITS
+                * vals
+                    .len()
+                    .div_ceil(<RadixEncoding as ValueEncoding<3>>::GROUP_SIZE),
+            radix.len() * 8
+        );
+    }
+
+    /// Pack the values in chunks of `chunk`, taking the finished bytes after every chunk
+    fn pack_in_chunks<const M: u8, E: ValueEncoding<M>>(vals: &[u8], chunk: usize) -> Vec<u8> {
+        let mut bits = WritableBitList::new(0);
+        let mut out = vec![];
+
+        for c in vals.chunks(chunk.max(1)) {
+            E::pack_into(&values::<M>(c), &mut bits).unwrap();
+            out.extend(bits.take_bytes());
+        }
+        out.extend(bits.vec());
+
+        out
+    }
+
+    quickcheck! {
+        fn trinary_chunked_packing(vals: Vec<u8>, chunk: u8) -> bool {
+            let vals: Vec<u8> = vals.iter().map(|v| v % 3).collect();
+
+            pack_in_chunks::<3, TrinaryEncoding>(&vals, chunk.into())
+                == TrinaryEncoding::pack(&values::<3>(&vals)).unwrap()
+        }
+
+        fn binary_chunked_packing(vals: Vec<u8>, chunk: u8) -> bool {
+            let vals: Vec<u8> = vals.iter().map(|v| v % 2).collect();
+
+            pack_in_chunks::<2, BinaryEncoding>(&vals, chunk.into())
+                == BinaryEncoding::pack(&values::<2>(&vals)).unwrap()
+        }
+
+        fn quaternary_chunked_packing(vals: Vec<u8>, chunk: u8) -> bool {
+            let vals: Vec<u8> = vals.iter().map(|v| v % 4).collect();
+
+            pack_in_chunks::<4, QuaternaryEncoding>(&vals, chunk.into())
+                == QuaternaryEncoding::pack(&values::<4>(&vals)).unwrap()
+        }
+
+        fn radix_chunked_packing(vals: Vec<u8>, chunk: u8) -> bool {
+            let vals: Vec<u8> = vals.iter().map(|v| v % 3).collect();
+
+            pack_in_chunks::<3, RadixEncoding>(&vals, chunk.into())
+                == RadixEncoding::pack(&values::<3>(&vals)).unwrap()
+        }
+
+        fn radix_roundtrip(vals: Vec<u8>) -> bool {
+            fn roundtrip<const M: u8>(input: &[u8]) -> bool {
+                let vals: Vec<u8> = input.iter().map(|v| v.checked_rem(M).unwrap()).collect();
+                let p = values::<M>(&vals);
+                let packed = RadixEncoding::pack(&p).unwrap();
+                let indices = [0usize, 13, 40].map(|i| i.min(vals.len().saturating_sub(1)));
+
+                RadixEncoding::unpack(&packed, vals.len()).unwrap() == p
+                    && (vals.is_empty()
+                        || <RadixEncoding as ValueEncoding<M>>::peek(&packed, &indices).unwrap()
+                            == indices.map(|i| vals[i]))
+            }
+
+            roundtrip::<2>(&vals)
+                && roundtrip::<3>(&vals)
+                && roundtrip::<5>(&vals)
+                && roundtrip::<7>(&vals)
+                && roundtrip::<200>(&vals)
+        }
+
+        fn trinary_roundtrip(vals: Vec<u8>) -> bool {
+            let vals: Vec<u8> = vals.iter().map(|v| v % 3).collect();
+            let p = values::<3>(&vals);
+
+            TrinaryEncoding::unpack(&TrinaryEncoding::pack(&p).unwrap(), vals.len()).unwrap() == p
+        }
+
+        fn binary_roundtrip(vals: Vec<u8>) -> bool {
+            let vals: Vec<u8> = vals.iter().map(|v| v % 2).collect();
+            let p = values::<2>(&vals);
+
+            BinaryEncoding::unpack(&BinaryEncoding::pack(&p).unwrap(), vals.len()).unwrap() == p
+        }
+
+        fn quaternary_roundtrip(vals: Vec<u8>) -> bool {
+            let vals: Vec<u8> = vals.iter().map(|v| v % 4).collect();
+            let p = values::<4>(&vals);
+
+            QuaternaryEncoding::unpack(&QuaternaryEncoding::pack(&p).unwrap(), vals.len()).unwrap() == p
+        }
+    }
+}
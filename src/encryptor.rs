@@ -0,0 +1,82 @@
+//! A common interface over everything that can do comparison-revealing encryption.
+//!
+
+use crate::{Error, PlainText};
+
+/// Something that can encrypt [`PlainText`s](crate::PlainText) into serialized, comparable
+/// ciphertexts, and compare those ciphertexts.
+///
+/// The concrete [`Cipher`](crate::Cipher) types are the obvious implementations of this trait,
+/// but anything that wants to stand in for them (a mock in a test suite, a cipher whose key lives
+/// in an HSM, etc) can implement it too, and code that only cares about "encrypt this, compare
+/// that" doesn't need to know which one it's been handed.
+///
+/// Everything going in and out of the trait is in serialized form, because that's the one
+/// representation of a ciphertext that every implementation can agree on.  This also means the
+/// trait is object-safe, so you can use a `Box<dyn ComparisonEncryptor<N, W, Comparison = ...>>`
+/// if you need to decide which implementation to use at runtime.
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::aes128v1::ore;
+/// use cretrit::ComparisonEncryptor;
+/// use std::cmp::Ordering;
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// # let key = [0u8; 32];
+/// let encryptor: Box<dyn ComparisonEncryptor<4, 256, Comparison = Ordering>> =
+///     Box::new(ore::Cipher::<4, 256>::new(&key)?);
+///
+/// let forty_two = encryptor.full_encrypt_to_vec(&42u32.try_into()?)?;
+/// let over_nine_thousand = encryptor.right_encrypt_to_vec(&9001u32.try_into()?)?;
+///
+/// assert_eq!(
+///     Ordering::Less,
+///     encryptor.compare_serialized(&forty_two, &over_nine_thousand)?
+/// );
+/// # Ok(())
+/// # }
+/// ```
+///
+pub trait ComparisonEncryptor<const N: usize, const W: u16> {
+    /// What you get back from comparing two ciphertexts
+    ///
+    /// For order-revealing encryption, this is an [`Ordering`](std::cmp::Ordering), while for
+    /// equality-revealing encryption it's a `bool` that is `true` when the values are equal.
+    ///
+    type Comparison;
+
+    /// Encrypt a value and produce a serialized ciphertext that contains both "left" and "right"
+    /// parts
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if any of the underlying cryptographic operations can't complete, or if
+    /// there's a bug somewhere.
+    ///
+    fn full_encrypt_to_vec(&self, value: &PlainText<N, W>) -> Result<Vec<u8>, Error>;
+
+    /// Encrypt a value and produce a serialized ciphertext that contains only a "right" part
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if any of the underlying cryptographic operations can't complete, or if
+    /// there's a bug somewhere.
+    ///
+    fn right_encrypt_to_vec(&self, value: &PlainText<N, W>) -> Result<Vec<u8>, Error>;
+
+    /// Compare two serialized ciphertexts
+    ///
+    /// The result is from the perspective of `a`; that is, an order-revealing implementation
+    /// returns `Ordering::Less` if the value in `a` is less than the value in `b`.  At least one of
+    /// the two ciphertexts must have a "left" part.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::Incomparable`] if neither ciphertext has a "left" part, or another
+    /// error if either ciphertext can't be parsed, or if something goes wrong during the
+    /// comparison itself.
+    ///
+    fn compare_serialized(&self, a: &[u8], b: &[u8]) -> Result<Self::Comparison, Error>;
+}
@@ -0,0 +1,216 @@
+//! A self-describing JSON wrapper around a serialized ciphertext.
+//!
+
+use base64ct::{Base64, Encoding};
+use serde::{Deserialize, Serialize};
+
+use crate::ciphersuite::CipherSuite;
+use crate::ciphertext::{CipherText, Serializable};
+use crate::cmp::Comparator;
+use crate::Error;
+
+/// The only version of the envelope format that currently exists
+const ENVELOPE_VERSION: u8 = 1;
+
+/// What an envelope looks like on the wire
+///
+/// The field names are short, and fixed, because other people's code has to produce and consume
+/// these; don't rename them.
+///
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Envelope {
+    /// The version of the envelope format
+    v: u8,
+    /// The name of the ciphersuite
+    suite: String,
+    /// Number of blocks
+    n: usize,
+    /// Block width
+    w: u16,
+    /// Number of possible comparison results, which identifies the comparator
+    m: u8,
+    /// Which key the ciphertext was encrypted with, as agreed between the parties
+    key_id: String,
+    /// The serialized ciphertext, in standard (padded) base64
+    ct_b64: String,
+}
+
+/// Wrap a ciphertext up in an envelope
+pub(crate) fn wrap<
+    S: CipherSuite<W, M>,
+    CMP: Comparator<M>,
+    const N: usize,
+    const W: u16,
+    const M: u8,
+>(
+    ct: &CipherText<S, CMP, N, W, M>,
+    key_id: &str,
+) -> Result<String, Error> {
+    let envelope = Envelope {
+        v: ENVELOPE_VERSION,
+        suite: S::NAME.to_string(),
+        n: N,
+        w: W,
+        m: M,
+        key_id: key_id.to_string(),
+        ct_b64: Base64::encode_string(&ct.to_vec()?),
+    };
+
+    serde_json::to_string(&envelope)
+        .map_err(|e| Error::InternalError(format!("failed to serialize envelope: {e}")))
+}
+
+/// Take a ciphertext out of an envelope, as long as the envelope says it's the right sort of
+/// ciphertext, encrypted with the right key
+pub(crate) fn unwrap<
+    S: CipherSuite<W, M>,
+    CMP: Comparator<M>,
+    const N: usize,
+    const W: u16,
+    const M: u8,
+>(
+    json: &str,
+    key_id: &str,
+) -> Result<CipherText<S, CMP, N, W, M>, Error> {
+    let envelope: Envelope = serde_json::from_str(json)
+        .map_err(|e| Error::ParseError(format!("invalid ciphertext envelope: {e}")))?;
+
+    if envelope.v != ENVELOPE_VERSION {
+        return Err(Error::ParseError(format!(
+            "unsupported ciphertext envelope version {} (expected {ENVELOPE_VERSION})",
+            envelope.v
+        )));
+    }
+
+    if envelope.suite != S::NAME || envelope.n != N || envelope.w != W || envelope.m != M {
+        return Err(Error::ParamsError(format!(
+            "envelope contains a {}/{}x{} ciphertext with m={}, but a {}/{N}x{W} ciphertext with m={M} was expected",
+            envelope.suite, envelope.n, envelope.w, envelope.m, S::NAME
+        )));
+    }
+
+    if envelope.key_id != key_id {
+        return Err(Error::KeyError(format!(
+            "envelope contains a ciphertext for key {:?}, but key {key_id:?} was expected",
+            envelope.key_id
+        )));
+    }
+
+    let bytes = Base64::decode_vec(&envelope.ct_b64)
+        .map_err(|e| Error::ParseError(format!("envelope ciphertext is not valid base64: {e}")))?;
+
+    CipherText::<S, CMP, N, W, M>::from_slice(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes128v1::{ere, ore};
+    use crate::ErrorKind;
+
+    fn cipher() -> ore::Cipher<4, 256> {
+        ore::Cipher::<4, 256>::new(&[0u8; 32]).unwrap()
+    }
+
+    fn envelope(ct: &ore::CipherText<4, 256>) -> serde_json::Value {
+        serde_json::from_str(&ct.to_json_envelope("k1").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn envelope_has_the_agreed_fields() {
+        let ct = cipher().right_encrypt(&42u32.try_into().unwrap()).unwrap();
+        let e = envelope(&ct);
+
+        assert_eq!(1, e["v"]);
+        assert_eq!("aes128v1", e["suite"]);
+        assert_eq!(4, e["n"]);
+        assert_eq!(256, e["w"]);
+        assert_eq!(3, e["m"]);
+        assert_eq!("k1", e["key_id"]);
+        assert_eq!(
+            ct.to_vec().unwrap(),
+            Base64::decode_vec(e["ct_b64"].as_str().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        let cipher = cipher();
+        let ct = cipher.full_encrypt(&42u32.try_into().unwrap()).unwrap();
+
+        let rt = ore::CipherText::<4, 256>::from_json_envelope(
+            &ct.to_json_envelope("k1").unwrap(),
+            "k1",
+        )
+        .unwrap();
+
+        assert!(rt.has_left());
+        assert!(rt == cipher.right_encrypt(&42u32.try_into().unwrap()).unwrap());
+    }
+
+    #[test]
+    fn mismatched_metadata_is_rejected() {
+        let ct = cipher().right_encrypt(&42u32.try_into().unwrap()).unwrap();
+        let e = envelope(&ct);
+
+        for (field, value) in [
+            ("suite", serde_json::json!("aes256v1")),
+            ("n", serde_json::json!(8)),
+            ("w", serde_json::json!(16)),
+            ("m", serde_json::json!(2)),
+        ] {
+            let mut bad = e.clone();
+            bad[field] = value;
+
+            let err =
+                ore::CipherText::<4, 256>::from_json_envelope(&bad.to_string(), "k1").unwrap_err();
+            assert_eq!(ErrorKind::Params, err.kind(), "{field}");
+        }
+
+        let err = ore::CipherText::<4, 256>::from_json_envelope(&e.to_string(), "k2").unwrap_err();
+        assert_eq!(ErrorKind::Key, err.kind());
+    }
+
+    #[test]
+    fn other_comparators_are_rejected() {
+        let ere_cipher = ere::Cipher::<4, 256>::new(&[0u8; 32]).unwrap();
+        let json = ere_cipher
+            .right_encrypt(&42u32.try_into().unwrap())
+            .unwrap()
+            .to_json_envelope("k1")
+            .unwrap();
+
+        assert!(ore::CipherText::<4, 256>::from_json_envelope(&json, "k1").is_err());
+    }
+
+    #[test]
+    fn malformed_envelopes_are_rejected() {
+        let ct = cipher().right_encrypt(&42u32.try_into().unwrap()).unwrap();
+        let e = envelope(&ct);
+
+        let mut future = e.clone();
+        future["v"] = serde_json::json!(2);
+
+        let mut not_base64 = e.clone();
+        not_base64["ct_b64"] = serde_json::json!("not base64!");
+
+        let mut truncated = e.clone();
+        truncated["ct_b64"] = serde_json::json!(Base64::encode_string(&ct.to_vec().unwrap()[..10]));
+
+        let mut extra = e.clone();
+        extra["note"] = serde_json::json!("hi");
+
+        for bad in [
+            "{}".to_string(),
+            "not json".to_string(),
+            future.to_string(),
+            not_base64.to_string(),
+            truncated.to_string(),
+            extra.to_string(),
+        ] {
+            let err = ore::CipherText::<4, 256>::from_json_envelope(&bad, "k1").unwrap_err();
+            assert_eq!(ErrorKind::Parse, err.kind(), "{bad}");
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! Deterministic digests of values, for building hash indexes over encrypted data.
+//!
+
+/// A keyed, deterministic digest of a value, produced by
+/// [`Cipher::equality_token()`](crate::Cipher::equality_token)
+///
+/// Two tokens are equal if, and only if, they were made from the same value, by ciphers with the
+/// same key and parameters.  That makes them just the thing for the key of a `HashMap`, or a hash
+/// index in a database, where a left ciphertext would do the same job but give away a great deal
+/// more: a left ciphertext can be compared against every stored right ciphertext, whereas a token
+/// can't be compared against anything except another token.
+///
+/// Tokens are still deterministic, though, so anyone who can see a collection of them can tell
+/// which values are repeated, and how often.  Keeping them next to IND-CPA secure right
+/// ciphertexts throws away much of the point of those; only store tokens where revealing the
+/// frequency of each value is acceptable.
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::aes128v1::ere;
+/// use cretrit::EqualityToken;
+/// use std::collections::HashMap;
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// # let key = [0u8; 32];
+/// let cipher = ere::Cipher::<4, 256>::new(&key)?;
+/// let mut index: HashMap<EqualityToken, Vec<usize>> = HashMap::new();
+///
+/// for (row, v) in [42u32, 9001, 42].into_iter().enumerate() {
+///     index.entry(cipher.equality_token(&v.try_into()?)?).or_default().push(row);
+/// }
+///
+/// // Tokens can be stored, and loaded again later
+/// let stored = *cipher.equality_token(&42u32.try_into()?)?.as_bytes();
+///
+/// assert_eq!(Some(&vec![0, 2]), index.get(&EqualityToken::from(stored)));
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EqualityToken([u8; 32]);
+
+impl EqualityToken {
+    /// The token's bytes, for storing somewhere
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl From<[u8; 32]> for EqualityToken {
+    /// Turn bytes from [`as_bytes()`](Self::as_bytes) back into a token
+    fn from(bytes: [u8; 32]) -> Self {
+        EqualityToken(bytes)
+    }
+}
+
+impl AsRef<[u8]> for EqualityToken {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aes128v1::{ere, ore};
+    use crate::{Error, SerializableCipherText};
+
+    #[test]
+    fn tokens_identify_values() {
+        let c1 = ore::Cipher::<4, 256>::new(&[1u8; 32]).unwrap();
+        let c2 = ore::Cipher::<4, 256>::new(&[1u8; 32]).unwrap();
+        let other = ore::Cipher::<4, 256>::new(&[2u8; 32]).unwrap();
+        let value = 42u32.try_into().unwrap();
+
+        let t = c1.equality_token(&value).unwrap();
+
+        assert_eq!(t, c2.equality_token(&value).unwrap());
+        assert_ne!(t, c1.equality_token(&43u32.try_into().unwrap()).unwrap());
+        assert_ne!(t, other.equality_token(&value).unwrap());
+    }
+
+    #[test]
+    fn tokens_are_not_query_keys_or_left_ciphertexts() {
+        let cipher = ere::Cipher::<1, 256>::new(&[1u8; 32]).unwrap();
+        let value = 42u8.try_into().unwrap();
+        let t = cipher.equality_token(&value).unwrap();
+        let left = cipher.full_encrypt(&value).unwrap().to_vec().unwrap();
+        let q = cipher.query_key(&value).unwrap();
+
+        assert!(!left
+            .windows(8)
+            .any(|w| t.as_ref().windows(8).any(|tw| tw == w)));
+        assert!(!t
+            .as_ref()
+            .windows(8)
+            .any(|w| q.windows(8).any(|qw| qw == w)));
+    }
+
+    #[test]
+    fn right_only_ciphers_cannot_make_tokens() {
+        let cipher = ere::Cipher::<4, 256>::new_right_only(&[1u8; 32]).unwrap();
+
+        assert!(matches!(
+            cipher.equality_token(&42u32.try_into().unwrap()),
+            Err(Error::NotPermitted(_))
+        ));
+    }
+}
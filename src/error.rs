@@ -2,6 +2,73 @@
 
 use thiserror::Error;
 
+/// A compact, allocation-free description of what kind of [`Error`](enum@Error) occurred
+///
+/// Every `Error` carries a human-readable message, which is great for logs, but it's a `String`,
+/// and so not something that can be stored, compared, or passed across an FFI boundary cheaply.
+/// The `ErrorKind` of an error is a plain `Copy` value, with a numeric [`code()`](Self::code) that
+/// is guaranteed never to change for a given kind, so it's safe to persist or hand to code in
+/// other languages.
+///
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u16)]
+pub enum ErrorKind {
+    /// See [`Error::KeyError`]
+    Key = 1,
+    /// See [`Error::CryptoError`]
+    Crypto = 2,
+    /// See [`Error::ComparisonError`]
+    Comparison = 3,
+    /// See [`Error::Incomparable`]
+    Incomparable = 4,
+    /// See [`Error::ParseError`]
+    Parse = 5,
+    /// See [`Error::RangeError`]
+    Range = 6,
+    /// See [`Error::ParamsError`]
+    Params = 7,
+    /// See [`Error::NumberError`]
+    Number = 8,
+    /// See [`Error::OverflowError`]
+    Overflow = 9,
+    /// See [`Error::NotPermitted`]
+    NotPermitted = 10,
+    /// See [`Error::UnknownCipherTextType`]
+    UnknownCipherTextType = 12,
+    /// See [`Error::InternalError`]
+    Internal = 11,
+}
+
+impl ErrorKind {
+    /// The stable numeric code for this kind of error
+    #[must_use]
+    pub fn code(self) -> u16 {
+        self as u16
+    }
+
+    /// Find the kind of error with the given numeric code, if there is one
+    #[must_use]
+    pub fn from_code(code: u16) -> Option<Self> {
+        [
+            Self::Key,
+            Self::Crypto,
+            Self::Comparison,
+            Self::Incomparable,
+            Self::Parse,
+            Self::Range,
+            Self::Params,
+            Self::Number,
+            Self::Overflow,
+            Self::NotPermitted,
+            Self::Internal,
+            Self::UnknownCipherTextType,
+        ]
+        .into_iter()
+        .find(|k| k.code() == code)
+    }
+}
+
 /// Error type for all Cretrit operations
 #[non_exhaustive]
 #[derive(Debug, Error)]
@@ -15,9 +82,26 @@ pub enum Error {
     CryptoError(String),
 
     /// A comparison operation could not be completed
+    ///
+    /// Comparisons that can't be done because there's no left part to do them with are
+    /// [`Error::Incomparable`], not this.
+    ///
     #[error("a problem occurred during comparison: {0}")]
     ComparisonError(String),
 
+    /// A comparison couldn't be done, because a ciphertext that needed a left part didn't have one
+    ///
+    /// Every comparison needs at least one left part, and some (such as comparing two left parts
+    /// directly, or using a ciphertext as a sort key) need particular ciphertexts to have one.
+    /// Ciphertexts loaded from storage are usually right-only, so this is the error to look out
+    /// for when comparing those.
+    ///
+    #[error("ciphertexts are incomparable: {reason}")]
+    Incomparable {
+        /// Which ciphertext was missing a left part, and why it needed one
+        reason: String,
+    },
+
     /// The serialized data provided as a ciphertext was not valid
     #[error("could not parse ciphertext: {0}")]
     ParseError(String),
@@ -26,11 +110,79 @@ pub enum Error {
     #[error("{0}")]
     RangeError(String),
 
+    /// A set of cipher parameters couldn't be parsed, or didn't match the cipher (or ciphertext)
+    /// they were used with
+    #[error("invalid cipher parameters: {0}")]
+    ParamsError(String),
+
+    /// A string that was supposed to be a number wasn't
+    #[error("not a valid number: {0}")]
+    NumberError(String),
+
     /// Arithmetic overflow (or underflow)
     #[error("{0}")]
     OverflowError(String),
 
+    /// The cipher has been set up so that it isn't allowed to do what was asked of it
+    #[error("operation not permitted: {0}")]
+    NotPermitted(String),
+
+    /// A serialized ciphertext started with a type byte that this version of cretrit doesn't
+    /// know how to handle; see [`CipherTextType`](crate::CipherTextType) for the ones it does
+    #[error("unsupported ciphertext type byte {0:#04x}")]
+    UnknownCipherTextType(u8),
+
     /// Congratulations, you've found a bug!
     #[error("Internal error: {0} (please report as a bug)")]
     InternalError(String),
 }
+
+impl Error {
+    /// The kind of error this is, without the message
+    #[must_use]
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::KeyError(_) => ErrorKind::Key,
+            Error::CryptoError(_) => ErrorKind::Crypto,
+            Error::ComparisonError(_) => ErrorKind::Comparison,
+            Error::Incomparable { .. } => ErrorKind::Incomparable,
+            Error::ParseError(_) => ErrorKind::Parse,
+            Error::RangeError(_) => ErrorKind::Range,
+            Error::ParamsError(_) => ErrorKind::Params,
+            Error::NumberError(_) => ErrorKind::Number,
+            Error::OverflowError(_) => ErrorKind::Overflow,
+            Error::NotPermitted(_) => ErrorKind::NotPermitted,
+            Error::UnknownCipherTextType(_) => ErrorKind::UnknownCipherTextType,
+            Error::InternalError(_) => ErrorKind::Internal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_roundtrip() {
+        for code in 0..=u16::from(u8::MAX) {
+            if let Some(kind) = ErrorKind::from_code(code) {
+                assert_eq!(code, kind.code());
+            }
+        }
+
+        assert_eq!(Some(ErrorKind::Params), ErrorKind::from_code(7));
+        assert_eq!(None, ErrorKind::from_code(0));
+    }
+
+    #[test]
+    fn kind_of_error() {
+        assert_eq!(
+            ErrorKind::Overflow,
+            Error::OverflowError("oops".to_string()).kind()
+        );
+        assert_eq!(
+            ErrorKind::Internal.code(),
+            Error::InternalError("bug".to_string()).kind().code()
+        );
+    }
+}
@@ -0,0 +1,65 @@
+//! Equality-Revealing Encryption with a [Feistel](super) ciphersuite.
+//!
+//! Everything here works just like it does for an ordinary ciphersuite, such as
+//! [`aes128v1::ere`](crate::aes128v1::ere); the only difference is that each type takes the
+//! ciphersuite `S` that the Feistel ciphersuite is based on, as well as the usual block count and
+//! width.  `S` needs to be given the same block width, and an `M` of 2.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use cretrit::aes128v1;
+//! use cretrit::feistel::ere;
+//!
+//! # fn main() -> Result<(), cretrit::Error> {
+//! # let key = [0u8; 32];
+//! type Suite = aes128v1::CipherSuite<256, 2>;
+//!
+//! let cipher = ere::Cipher::<Suite, 4, 256>::new(&key)?;
+//! let forty_two = cipher.full_encrypt(&42u32.try_into()?)?;
+//!
+//! assert!(forty_two.try_eq(&cipher.right_encrypt(&42u32.try_into()?)?)?);
+//! assert!(!forty_two.try_eq(&cipher.right_encrypt(&9001u32.try_into()?)?)?);
+//! # Ok(())
+//! # }
+//! ```
+
+use super::Feistel;
+use crate::ciphersuite::CipherSuite;
+
+crate::suite_modules::ere_items!("a [Feistel](super) ciphersuite", Feistel<S>, [S: CipherSuite<W, 2>], []);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes256v1;
+    use crate::util::random_key;
+
+    type Aes256 = aes256v1::CipherSuite<1000, 2>;
+
+    #[test]
+    fn left_comparisons() {
+        let cipher = Cipher::<Aes256, 3, 1000>::new(&random_key()).unwrap();
+        let left = cipher.left_encrypt(&PlainText::new([4, 999, 2])).unwrap();
+
+        assert!(left
+            .compare_with_right(&cipher.right_encrypt(&PlainText::new([4, 999, 2])).unwrap())
+            .unwrap());
+        assert!(!left
+            .compare_with_right(&cipher.right_encrypt(&PlainText::new([4, 998, 2])).unwrap())
+            .unwrap());
+    }
+
+    quickcheck! {
+        fn pair_eq(a: (u8, u16), b: (u8, u16)) -> bool {
+            let cipher = Cipher::<Aes256, 2, 1000>::new(&random_key()).unwrap();
+            let pa = PlainText::new([u16::from(a.0), a.1.checked_rem(1000).unwrap()]);
+            let pb = PlainText::new([u16::from(b.0), b.1.checked_rem(1000).unwrap()]);
+
+            let ca = cipher.full_encrypt_to_vec(&pa).unwrap();
+            let cb = cipher.right_encrypt_to_vec(&pb).unwrap();
+
+            (pa == pb) == cipher.compare_serialized(&ca, &cb).unwrap()
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Ciphersuites that work out the block permutation on the fly, instead of keeping it in memory.
+//!
+//! Every cipher permutes the values of each block, and the ordinary ciphersuites do that with a
+//! pair of lookup tables, `W` entries each, built when the cipher is created.  For the usual block
+//! width of 256 that's only a kilobyte, but it's four times the block width in bytes, so wide
+//! blocks get expensive, as do programs that create lots of ciphers (one per tenant, say).
+//! [`Feistel<S>`](Feistel) is the ciphersuite `S`, except that its permutation is a small keyed
+//! Feistel network, using `S`'s own PRF as the round function, so creating a cipher takes no
+//! time, and the permutation takes no memory.
+//!
+//! The price is paid at encryption time: looking up a permuted value takes a few dozen PRF calls,
+//! rather than a memory access.  Creating a right ciphertext looks up every value of every block,
+//! so it gets several times slower; left ciphertexts, comparisons, and everything else are
+//! unaffected.
+//!
+//! # Compatibility
+//!
+//! A Feistel ciphersuite produces a different permutation to the one it's based on, so their
+//! ciphertexts can't be compared with each other, even with the same key.  Its name is the name
+//! of the ciphersuite it's based on, followed by `f`, such as `aes128v1f`, and that is what
+//! appears in its JSON envelopes and wire headers.  As with [truncated](crate::truncated)
+//! ciphersuites, they aren't listed in [`suites()`](crate::suites), so their names can't be parsed
+//! into a [`Params`](crate::Params), and their ciphers don't have a
+//! [`params()`](crate::Cipher::params) or [`from_params()`](crate::Cipher::from_params).
+//!
+//! # Examples
+//!
+//! ```rust
+//! use cretrit::aes128v1;
+//! use cretrit::feistel::ore;
+//! use std::cmp::Ordering;
+//!
+//! # fn main() -> Result<(), cretrit::Error> {
+//! # let key = [0u8; 32];
+//! type Suite = aes128v1::CipherSuite<0xFFFF, 3>;
+//!
+//! // No 256 KiB of permutation tables here
+//! let cipher = ore::Cipher::<Suite, 2, 0xFFFF>::new(&key)?;
+//! let forty_two = cipher.full_encrypt(&42u32.try_into()?)?;
+//! let over_nine_thousand = cipher.right_encrypt(&9001u32.try_into()?)?;
+//!
+//! assert_eq!(Ordering::Less, forty_two.try_cmp(&over_nine_thousand)?);
+//! # Ok(())
+//! # }
+//! ```
+//!
+
+pub mod ere;
+pub mod ore;
+
+use std::marker::PhantomData;
+
+use crate::ciphersuite::CipherSuite;
+use crate::prp::FeistelPRP;
+
+/// The ciphersuite `S`, with its permutation worked out on the fly by a Feistel network
+///
+/// See [the module documentation](self) for the details.
+///
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Feistel<S> {
+    /// Compiler pacification
+    _mark: PhantomData<S>,
+}
+
+/// How much space there is for the name of a Feistel ciphersuite
+const NAME_CAPACITY: usize = 32;
+
+/// Put together the name of a Feistel ciphersuite, as bytes, along with how many of them there
+/// are
+///
+/// As with truncated ciphersuites, this has to be done the hard way, because it's needed in a
+/// `const`.  Names that don't fit cause a compile-time panic.
+///
+#[allow(clippy::indexing_slicing, clippy::arithmetic_side_effects)] // Anything out of range fails to compile
+const fn feistel_name(base_name: &str) -> ([u8; NAME_CAPACITY], usize) {
+    let base = base_name.as_bytes();
+    let mut name = [0u8; NAME_CAPACITY];
+    let mut len = 0;
+
+    while len < base.len() {
+        name[len] = base[len];
+        len += 1;
+    }
+
+    name[len] = b'f';
+    len += 1;
+
+    (name, len)
+}
+
+/// Somewhere to keep the name of a Feistel ciphersuite while it's being turned into a `str`
+///
+/// It has to be a `const` of its own, so that `NAME` can refer to it.
+///
+struct FeistelName<S, const W: u16, const M: u8> {
+    /// Compiler pacification
+    _mark: PhantomData<S>,
+}
+
+impl<S: CipherSuite<W, M>, const W: u16, const M: u8> FeistelName<S, W, M> {
+    /// The name, and how much of the buffer it occupies
+    const BYTES: ([u8; NAME_CAPACITY], usize) = feistel_name(S::NAME);
+}
+
+impl<S: CipherSuite<W, M>, const W: u16, const M: u8> CipherSuite<W, M> for Feistel<S> {
+    const NAME: &'static str = {
+        let (bytes, _) = FeistelName::<S, W, M>::BYTES
+            .0
+            .split_at(FeistelName::<S, W, M>::BYTES.1);
+
+        match core::str::from_utf8(bytes) {
+            Ok(name) => name,
+            Err(_) => "(invalid Feistel ciphersuite name)",
+        }
+    };
+
+    type RNG = S::RNG;
+    type PRF = S::PRF;
+    type HF = S::HF;
+    type PRP = FeistelPRP<S::PRF, W>;
+    type KBKDF = S::KBKDF;
+}
@@ -0,0 +1,121 @@
+//! Order-Revealing Encryption with a [Feistel](super) ciphersuite.
+//!
+//! Everything here works just like it does for an ordinary ciphersuite, such as
+//! [`aes128v1::ore`](crate::aes128v1::ore); the only difference is that each type takes the
+//! ciphersuite `S` that the Feistel ciphersuite is based on, as well as the usual block count and
+//! width.  `S` needs to be given the same block width, and an `M` of 3.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use cretrit::aes256v1;
+//! use cretrit::feistel::ore;
+//! use cretrit::SerializableCipherText;
+//! use std::cmp::Ordering;
+//!
+//! # fn main() -> Result<(), cretrit::Error> {
+//! # let key = [0u8; 32];
+//! type Suite = aes256v1::CipherSuite<4096, 3>;
+//!
+//! let cipher = ore::Cipher::<Suite, 3, 4096>::new(&key)?;
+//! let forty_two = cipher.full_encrypt(&42u32.try_into()?)?.to_vec()?;
+//!
+//! let deserialized = ore::CipherText::<Suite, 3, 4096>::from_slice(&forty_two)?;
+//! assert_eq!(
+//!     Ordering::Less,
+//!     deserialized.try_cmp(&cipher.right_encrypt(&9001u32.try_into()?)?)?
+//! );
+//! # Ok(())
+//! # }
+//! ```
+
+use super::Feistel;
+use crate::ciphersuite::CipherSuite;
+
+crate::suite_modules::ore_items!("a [Feistel](super) ciphersuite", Feistel<S>, [S: CipherSuite<W, 3>], []);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::random_key;
+    use crate::{aes128v1, chacha20v1};
+
+    type Aes128 = aes128v1::CipherSuite<256, 3>;
+
+    #[test]
+    fn names() {
+        assert_eq!("aes128v1f", <Feistel<Aes128> as CipherSuite<256, 3>>::NAME);
+        assert_eq!(
+            "chacha20v1f",
+            <Feistel<chacha20v1::CipherSuite<16, 3>> as CipherSuite<16, 3>>::NAME
+        );
+    }
+
+    #[test]
+    fn table_permuted_ciphertexts_are_incompatible() {
+        let cipher = Cipher::<Aes128, 4, 256>::new(&[1u8; 32]).unwrap();
+        let plain = aes128v1::ore::Cipher::<4, 256>::new(&[1u8; 32]).unwrap();
+
+        let ct = cipher.full_encrypt(&42u32.try_into().unwrap()).unwrap();
+        let plain_ct = plain.full_encrypt(&42u32.try_into().unwrap()).unwrap();
+
+        // The PRF is the same, so it's the permuted values that give the game away
+        assert_ne!(
+            ct.left().unwrap().to_vec().unwrap(),
+            plain_ct.left().unwrap().to_vec().unwrap()
+        );
+    }
+
+    #[test]
+    fn left_comparisons() {
+        let cipher = Cipher::<Aes128, 4, 256>::new(&random_key()).unwrap();
+        let left = cipher.left_encrypt(&1000u32.try_into().unwrap()).unwrap();
+        let query = PreparedQuery::<Aes128, 4, 256>::new(&left).unwrap();
+
+        for v in [0u32, 999, 1000, 1001, u32::MAX] {
+            let right = cipher.right_encrypt(&v.try_into().unwrap()).unwrap();
+
+            assert_eq!(1000.cmp(&v), left.compare_with_right(&right).unwrap());
+            assert_eq!(1000.cmp(&v), query.compare_with_right(&right).unwrap());
+        }
+    }
+
+    #[test]
+    fn decryption() {
+        let cipher = Cipher::<Aes128, 8, 256>::new(&random_key()).unwrap();
+        let value: PlainText<8, 256> = 9001u64.try_into().unwrap();
+        let ct = cipher.full_encrypt(&value).unwrap();
+
+        assert_eq!(value, cipher.decrypt(&ct).unwrap());
+    }
+
+    #[test]
+    fn right_only_ciphertexts_are_incomparable() {
+        let cipher = Cipher::<Aes128, 4, 256>::new(&random_key()).unwrap();
+        let ct = cipher.right_encrypt(&42u32.try_into().unwrap()).unwrap();
+
+        assert!(matches!(ct.try_cmp(&ct), Err(Error::Incomparable { .. })));
+        #[cfg(feature = "comparison-traits")]
+        assert_eq!(None, ct.partial_cmp(&ct));
+    }
+
+    quickcheck! {
+        fn u32_cmp(a: u32, b: u32) -> bool {
+            let cipher = Cipher::<Aes128, 4, 256>::new(&random_key()).unwrap();
+
+            let ca = cipher.full_encrypt(&a.try_into().unwrap()).unwrap();
+            let cb = cipher.right_encrypt(&b.try_into().unwrap()).unwrap();
+
+            a.cmp(&b) == ca.try_cmp(&cb).unwrap()
+        }
+
+        fn u32_cmp_chacha20v1(a: u32, b: u32) -> bool {
+            let cipher = Cipher::<chacha20v1::CipherSuite<16, 3>, 8, 16>::new(&random_key()).unwrap();
+
+            let ca = cipher.full_encrypt(&a.try_into().unwrap()).unwrap();
+            let cb = cipher.right_encrypt(&b.try_into().unwrap()).unwrap();
+
+            a.cmp(&b) == ca.try_cmp(&cb).unwrap()
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Declaring the encryption parameters of a field in a configuration file.
+//!
+
+use serde::{Deserialize, Serialize};
+
+use crate::ciphersuite::{CipherSuite, ListedCipherSuite};
+use crate::cmp::Comparator;
+use crate::kbkdf::KBKDFInit;
+use crate::prf::PseudoRandomFunctionInit;
+use crate::prp::PseudoRandomPermutationInit;
+use crate::{Cipher, Error, Params};
+
+/// Everything needed to set up the cipher for an encrypted field, in a form that can be read from
+/// a configuration file
+///
+/// Deserializing a `FieldConfig` checks that the ciphersuite and comparator exist, and that the
+/// block count and width are sensible, so a `FieldConfig` that has been successfully read is
+/// always valid.  Whether it matches the cipher type the code is expecting is checked when the
+/// cipher is created with [`cipher()`](Self::cipher).
+///
+/// The key itself is never part of the configuration, only the `key_id`, which is for the
+/// application to look up in whatever it uses to manage its keys.
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::aes128v1::ore;
+/// use cretrit::FieldConfig;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let config: FieldConfig = serde_json::from_str(r#"{
+///     "suite": "aes128v1",
+///     "comparator": "ore",
+///     "blocks": 8,
+///     "width": 256,
+///     "key_id": "customers-2024"
+/// }"#)?;
+///
+/// # let lookup_key = |_: &str| [0u8; 32];
+/// let key = lookup_key(config.key_id());
+/// let cipher: ore::Cipher<8, 256> = config.cipher(&key)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "RawFieldConfig", into = "RawFieldConfig")]
+pub struct FieldConfig {
+    /// The parameters of the cipher
+    params: Params,
+
+    /// Which key to use
+    key_id: String,
+}
+
+impl FieldConfig {
+    /// Describe a field's configuration directly, rather than by reading it from somewhere
+    #[must_use]
+    pub fn new(params: Params, key_id: &str) -> Self {
+        FieldConfig {
+            params,
+            key_id: key_id.to_string(),
+        }
+    }
+
+    /// The parameters of the field's cipher
+    #[must_use]
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+
+    /// The identifier of the key that the field is encrypted with
+    #[must_use]
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Create the cipher for this field, using the given key
+    ///
+    /// The type of the cipher must match the configured parameters, so a configuration that says
+    /// `ere` can't be accidentally used to create an ORE cipher, for instance.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::ParamsError`] if the configured parameters don't describe the cipher
+    /// type being created, or any error that [`Cipher::new()`] can return.
+    ///
+    pub fn cipher<S, CMP, const N: usize, const W: u16, const M: u8>(
+        &self,
+        key: &[u8; 32],
+    ) -> Result<Cipher<S, CMP, N, W, M>, Error>
+    where
+        S: ListedCipherSuite<W, M>,
+        CMP: Comparator<M>,
+        <S as CipherSuite<W, M>>::PRF: PseudoRandomFunctionInit,
+        <S as CipherSuite<W, M>>::PRP: PseudoRandomPermutationInit<W>,
+        <S as CipherSuite<W, M>>::KBKDF: KBKDFInit + 'static,
+    {
+        Cipher::from_params(&self.params, key)
+    }
+}
+
+/// What a `FieldConfig` looks like on the outside
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawFieldConfig {
+    /// The name of the ciphersuite
+    suite: String,
+
+    /// The name of the comparator
+    comparator: String,
+
+    /// How many blocks in each plaintext (`N`)
+    blocks: usize,
+
+    /// How many distinct values in each block (`W`)
+    width: u16,
+
+    /// Which key to use
+    key_id: String,
+}
+
+impl TryFrom<RawFieldConfig> for FieldConfig {
+    type Error = Error;
+
+    fn try_from(raw: RawFieldConfig) -> Result<Self, Self::Error> {
+        Ok(FieldConfig {
+            params: Params::new(&raw.suite, &raw.comparator, raw.blocks, raw.width)?,
+            key_id: raw.key_id,
+        })
+    }
+}
+
+impl From<FieldConfig> for RawFieldConfig {
+    fn from(config: FieldConfig) -> Self {
+        RawFieldConfig {
+            suite: config.params.suite().to_string(),
+            comparator: config.params.comparator().to_string(),
+            blocks: config.params.blocks(),
+            width: config.params.width(),
+            key_id: config.key_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes128v1::{ere, ore};
+
+    fn config(json: &str) -> Result<FieldConfig, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    #[test]
+    fn valid_config() {
+        let c = config(
+            r#"{"suite":"aes128v1","comparator":"ere","blocks":4,"width":16,"key_id":"k1"}"#,
+        )
+        .unwrap();
+
+        assert_eq!("aes128v1-ere/4x16", c.params().to_string());
+        assert_eq!("k1", c.key_id());
+
+        let cipher: ere::Cipher<4, 16> = c.cipher(&[0u8; 32]).unwrap();
+        assert_eq!(*c.params(), cipher.params());
+        assert!(matches!(
+            c.cipher::<_, _, 4, 16, 3>(&[0u8; 32])
+                .map(|_: ore::Cipher<4, 16>| ()),
+            Err(Error::ParamsError(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_configs() {
+        for json in [
+            r#"{"suite":"aes512v1","comparator":"ore","blocks":4,"width":16,"key_id":"k"}"#,
+            r#"{"suite":"aes128v1","comparator":"ope","blocks":4,"width":16,"key_id":"k"}"#,
+            r#"{"suite":"aes128v1","comparator":"ore","blocks":0,"width":16,"key_id":"k"}"#,
+            r#"{"suite":"aes128v1","comparator":"ore","blocks":4,"width":1,"key_id":"k"}"#,
+            r#"{"suite":"aes128v1","comparator":"ore","blocks":4,"width":16}"#,
+            r#"{"suite":"aes128v1","comparator":"ore","blocks":4,"width":16,"key_id":"k","x":1}"#,
+        ] {
+            assert!(config(json).is_err(), "{json}");
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let c = FieldConfig::new("aes128v1-ore/8x256".parse().unwrap(), "primary");
+
+        assert_eq!(c, config(&serde_json::to_string(&c).unwrap()).unwrap());
+    }
+}
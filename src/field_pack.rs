@@ -0,0 +1,376 @@
+//! Packing several small equality-revealing fields into one ciphertext.
+//!
+
+use std::marker::PhantomData;
+use std::ops::Range;
+
+use crate::ciphersuite::CipherSuite;
+use crate::cmp::EqualityCMP;
+use crate::{Cipher, CipherText, Error, LeftCipherText, PlainText};
+
+/// A layout for encrypting several small-domain fields (flags, enums, and the like) together, as
+/// a single equality-revealing ciphertext, while still being able to check each field on its own.
+///
+/// Every right ciphertext carries a 16 byte nonce base, which dwarfs the handful of bytes that
+/// the values of a field with only two, three, or four possible values take up.  Encrypting a
+/// record's worth of such fields separately spends most of the storage on nonces.  A `FieldPack`
+/// instead gives each field its own run of blocks within one `N` block ERE ciphertext, so that
+/// they all share the one nonce base.
+///
+/// Equality-revealing comparison works block by block, so each field can still be compared
+/// without involving the others, using [`field_eq()`](Self::field_eq) or
+/// [`left_field_eq()`](Self::left_field_eq).  Comparing the whole ciphertext, as usual, tells you
+/// whether every field is equal.
+///
+/// Each field holds a value less than `W` raised to the number of blocks it's given.  The layout
+/// isn't stored anywhere in the ciphertext; make sure that everything reading and writing a
+/// particular set of ciphertexts uses the same one.
+///
+/// Like the other generic types, this isn't meant to be used directly; the `ere` modules of the
+/// ciphersuites (such as [`aes128v1::ere`](crate::aes128v1::ere)) have a `FieldPack` alias
+/// which fills in all the type parameters.
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::aes128v1::ere;
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// # let key = [0u8; 32];
+/// // A flag, a four-way enum, and a number less than 16, with four possible values per block
+/// let layout = ere::FieldPack::<4, 4>::new(&[1, 1, 2])?;
+/// let cipher = ere::Cipher::<4, 4>::new(&key)?;
+///
+/// let stored = layout.right_encrypt(&cipher, &[1, 3, 12])?;
+/// let query = layout.full_encrypt(&cipher, &[0, 3, 12])?;
+///
+/// assert!(!layout.field_eq(0, &query, &stored)?);
+/// assert!(layout.field_eq(1, &query, &stored)?);
+/// assert!(layout.field_eq(2, &query, &stored)?);
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct FieldPack<S: CipherSuite<W, 2>, const N: usize, const W: u16> {
+    /// The first block of each field, and then `N`
+    offsets: Vec<usize>,
+
+    /// Compiler pacification
+    _suite: PhantomData<S>,
+}
+
+impl<S: CipherSuite<W, 2>, const N: usize, const W: u16> FieldPack<S, N, W> {
+    /// Lay out fields with the given number of blocks apiece, in order
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::ParamsError`] if any field has no blocks, or if the fields don't take
+    /// up exactly `N` blocks between them.
+    ///
+    pub fn new(blocks_per_field: &[usize]) -> Result<Self, Error> {
+        let mut offsets = Vec::with_capacity(blocks_per_field.len().saturating_add(1));
+        let mut next = 0usize;
+
+        for (field, blocks) in blocks_per_field.iter().enumerate() {
+            if *blocks == 0 {
+                return Err(Error::ParamsError(format!(
+                    "field {field} must have at least one block"
+                )));
+            }
+
+            offsets.push(next);
+            next = next.saturating_add(*blocks);
+        }
+
+        if next != N {
+            return Err(Error::ParamsError(format!(
+                "fields take up {next} blocks between them, but there are {N} blocks to fill"
+            )));
+        }
+        offsets.push(N);
+
+        Ok(FieldPack {
+            offsets,
+            _suite: PhantomData,
+        })
+    }
+
+    /// How many fields there are
+    #[must_use]
+    pub fn field_count(&self) -> usize {
+        self.offsets.len().saturating_sub(1)
+    }
+
+    /// The blocks that a field occupies
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::RangeError`] if there's no such field.
+    ///
+    pub fn field_blocks(&self, field: usize) -> Result<Range<usize>, Error> {
+        match (
+            self.offsets.get(field),
+            self.offsets.get(field.saturating_add(1)),
+        ) {
+            (Some(start), Some(end)) => Ok(*start..*end),
+            _ => Err(Error::RangeError(format!(
+                "there is no field {field} (there are {} fields)",
+                self.field_count()
+            ))),
+        }
+    }
+
+    /// Put the values of each field together into one plaintext
+    ///
+    /// Each field's value is spread across its blocks most significant block first, just as
+    /// an integer is spread across the blocks of an ordinary plaintext.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::ParamsError`] if there isn't exactly one value for each field, or
+    /// [`Error::RangeError`] if a value is too big for its field.
+    ///
+    pub fn plaintext(&self, values: &[u64]) -> Result<PlainText<N, W>, Error> {
+        if values.len() != self.field_count() {
+            return Err(Error::ParamsError(format!(
+                "expected {} field values, got {}",
+                self.field_count(),
+                values.len()
+            )));
+        }
+
+        let mut blocks = [0u16; N];
+
+        for (field, value) in values.iter().enumerate() {
+            let range = self.field_blocks(field)?;
+            let mut rest = *value;
+
+            for n in range.rev() {
+                let block = blocks.get_mut(n).ok_or_else(|| {
+                    Error::InternalError(format!("field block {n} is outside the plaintext"))
+                })?;
+                *block = u16::try_from(rest.checked_rem(u64::from(W)).unwrap_or_default())
+                    .map_err(|e| {
+                        Error::InternalError(format!("block value doesn't fit in a u16 ({e})"))
+                    })?;
+                rest = rest.checked_div(u64::from(W)).unwrap_or_default();
+            }
+
+            if rest != 0 {
+                return Err(Error::RangeError(format!(
+                    "value {value} is too big for field {field}"
+                )));
+            }
+        }
+
+        Ok(PlainText::new(blocks))
+    }
+
+    /// Encrypt a set of field values into a right-only ciphertext, suitable for storing
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the values don't fit the layout (see
+    /// [`plaintext()`](Self::plaintext)), or anything that
+    /// [`Cipher::right_encrypt()`] can return.
+    ///
+    pub fn right_encrypt(
+        &self,
+        cipher: &Cipher<S, EqualityCMP, N, W, 2>,
+        values: &[u64],
+    ) -> Result<CipherText<S, EqualityCMP, N, W, 2>, Error> {
+        cipher.right_encrypt(&self.plaintext(values)?)
+    }
+
+    /// Encrypt a set of field values into a full ciphertext, suitable for querying with
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the values don't fit the layout (see
+    /// [`plaintext()`](Self::plaintext)), or anything that
+    /// [`Cipher::full_encrypt()`] can return.
+    ///
+    pub fn full_encrypt(
+        &self,
+        cipher: &Cipher<S, EqualityCMP, N, W, 2>,
+        values: &[u64],
+    ) -> Result<CipherText<S, EqualityCMP, N, W, 2>, Error> {
+        cipher.full_encrypt(&self.plaintext(values)?)
+    }
+
+    /// Determine whether one field is equal in two ciphertexts
+    ///
+    /// Only the blocks that belong to the field are compared.  As with any other comparison, at
+    /// least one of the ciphertexts needs to have a left part.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::RangeError`] if there's no such field, or [`Error::Incomparable`] if
+    /// neither ciphertext has a left part.
+    ///
+    pub fn field_eq(
+        &self,
+        field: usize,
+        a: &CipherText<S, EqualityCMP, N, W, 2>,
+        b: &CipherText<S, EqualityCMP, N, W, 2>,
+    ) -> Result<bool, Error> {
+        Self::all_equal(self.field_blocks(field)?, a.block_comparisons(b)?)
+    }
+
+    /// Determine whether one field in a left ciphertext is equal to the same field in the right
+    /// part of a ciphertext
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::RangeError`] if there's no such field.
+    ///
+    pub fn left_field_eq(
+        &self,
+        field: usize,
+        left: &LeftCipherText<S, EqualityCMP, N, W, 2>,
+        right: &CipherText<S, EqualityCMP, N, W, 2>,
+    ) -> Result<bool, Error> {
+        Self::all_equal(self.field_blocks(field)?, left.block_comparisons(right))
+    }
+
+    /// Whether every block in `range` compared equal
+    ///
+    /// Every block in the field is compared, even once one has turned out to differ, so the time
+    /// taken doesn't give away which part of the field differs.
+    ///
+    fn all_equal(
+        range: Range<usize>,
+        comparisons: impl Iterator<Item = Result<u8, Error>>,
+    ) -> Result<bool, Error> {
+        let mut diff = 0u8;
+
+        for res in comparisons.skip(range.start).take(range.len()) {
+            diff |= res?;
+        }
+
+        Ok(diff == 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes128v1::ere;
+    use crate::ciphertext::Serializable;
+
+    fn cipher() -> ere::Cipher<6, 3> {
+        ere::Cipher::<6, 3>::new(&[0u8; 32]).unwrap()
+    }
+
+    fn layout() -> ere::FieldPack<6, 3> {
+        ere::FieldPack::<6, 3>::new(&[1, 2, 3]).unwrap()
+    }
+
+    #[test]
+    fn layout_must_fill_every_block() {
+        assert!(matches!(
+            ere::FieldPack::<6, 3>::new(&[1, 2]),
+            Err(Error::ParamsError(_))
+        ));
+        assert!(matches!(
+            ere::FieldPack::<6, 3>::new(&[1, 2, 4]),
+            Err(Error::ParamsError(_))
+        ));
+        assert!(matches!(
+            ere::FieldPack::<6, 3>::new(&[1, 0, 5]),
+            Err(Error::ParamsError(_))
+        ));
+    }
+
+    #[test]
+    fn field_blocks() {
+        let layout = layout();
+
+        assert_eq!(3, layout.field_count());
+        assert_eq!(0..1, layout.field_blocks(0).unwrap());
+        assert_eq!(1..3, layout.field_blocks(1).unwrap());
+        assert_eq!(3..6, layout.field_blocks(2).unwrap());
+        assert!(matches!(layout.field_blocks(3), Err(Error::RangeError(_))));
+    }
+
+    #[test]
+    fn values_are_spread_across_their_blocks() {
+        let layout = layout();
+
+        assert_eq!(
+            &[2, 2, 1, 0, 2, 1],
+            layout.plaintext(&[2, 7, 7]).unwrap().blocks()
+        );
+        assert!(matches!(
+            layout.plaintext(&[3, 0, 0]),
+            Err(Error::RangeError(_))
+        ));
+        assert!(matches!(
+            layout.plaintext(&[0, 9, 0]),
+            Err(Error::RangeError(_))
+        ));
+        assert!(matches!(
+            layout.plaintext(&[0, 0]),
+            Err(Error::ParamsError(_))
+        ));
+    }
+
+    #[test]
+    fn fields_compare_independently() {
+        let cipher = cipher();
+        let layout = layout();
+
+        let stored = layout.right_encrypt(&cipher, &[1, 8, 20]).unwrap();
+
+        for (query, expected) in [
+            ([1, 8, 20], [true, true, true]),
+            ([0, 8, 20], [false, true, true]),
+            ([1, 7, 20], [true, false, true]),
+            ([1, 8, 26], [true, true, false]),
+            ([2, 0, 0], [false, false, false]),
+        ] {
+            let full = layout.full_encrypt(&cipher, &query).unwrap();
+            let left = cipher
+                .left_encrypt(&layout.plaintext(&query).unwrap())
+                .unwrap();
+
+            for (field, eq) in expected.iter().enumerate() {
+                assert_eq!(*eq, layout.field_eq(field, &full, &stored).unwrap());
+                assert_eq!(*eq, layout.field_eq(field, &stored, &full).unwrap());
+                assert_eq!(*eq, layout.left_field_eq(field, &left, &stored).unwrap());
+            }
+
+            assert_eq!(expected == [true; 3], full.try_eq(&stored).unwrap());
+        }
+    }
+
+    #[test]
+    fn fields_share_one_nonce() {
+        let cipher = cipher();
+        let packed = layout().right_encrypt(&cipher, &[1, 8, 20]).unwrap();
+
+        let separate = ere::Cipher::<1, 3>::new(&[0u8; 32])
+            .unwrap()
+            .right_encrypt(&1u8.try_into().unwrap())
+            .unwrap();
+
+        // One field's worth of ciphertext is nearly all nonce, so three of them would take
+        // up a good deal more room than all three packed together
+        assert!(packed.to_vec().unwrap().len() < 3 * separate.to_vec().unwrap().len());
+    }
+
+    #[test]
+    fn right_only_ciphertexts_are_incomparable() {
+        let cipher = cipher();
+        let layout = layout();
+
+        let a = layout.right_encrypt(&cipher, &[1, 8, 20]).unwrap();
+        let b = layout.right_encrypt(&cipher, &[1, 8, 20]).unwrap();
+
+        assert!(matches!(
+            layout.field_eq(0, &a, &b),
+            Err(Error::Incomparable { .. })
+        ));
+    }
+}
@@ -0,0 +1,325 @@
+//! Cheaply ruling out stored values that can't be equal to a query.
+//!
+
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use zeroize::Zeroizing;
+
+use crate::ciphersuite::CipherSuite;
+use crate::ciphertext::split_serialized;
+use crate::cmp::Comparator;
+use crate::hash::HashFunction;
+use crate::prf::PseudoRandomFunction;
+use crate::{Cipher, CipherText, Error, LeftCipherText, PlainText};
+
+/// The input used to derive the per-value filter key, so that it can never be mistaken for a
+/// comparison hash (which only ever hashes 16 byte nonces)
+const FILTER_KEY_LABEL: &[u8] = b"cretrit.EqualityFilter.key";
+
+/// A quick test for whether a stored ciphertext *might* be equal to a query value, which gets
+/// rid of most of the ones that aren't without doing a full comparison.
+///
+/// Scanning lots of stored ciphertexts for the ones equal to a query means doing a full
+/// comparison against every one of them, which involves deriving a nonce and calculating a hash
+/// for every block of every stored value.  To avoid most of that, each stored ciphertext can have
+/// a one-byte *filter tag* kept alongside it, produced at encryption time by
+/// [`Cipher::right_encrypt_with_filter_tag()`](crate::Cipher::right_encrypt_with_filter_tag).
+/// When scanning, an `EqualityFilter` made from the query's left ciphertext checks each stored
+/// ciphertext's tag with a single hash of its nonce base.  If the tag doesn't match, the values
+/// are definitely not equal; if it does, the values are equal, or it's one of the 1 in 256
+/// unequal values whose tag happens to match anyway, so a full comparison is still needed to be
+/// sure.
+///
+/// The tag is a keyed hash of the ciphertext's (random) nonce base, using a key derived from
+/// the left ciphertext of the value that was encrypted.  Since that key is only known to someone
+/// who can produce the value's left ciphertext, and each ciphertext has a different nonce base,
+/// stored tags look like random noise: two stored ciphertexts of the same value are no more
+/// likely to have the same tag than any other two, and so the tags don't weaken the IND-CPA
+/// security of right-only ciphertexts.  Someone with a query's left ciphertext learns, from a
+/// tag that doesn't match, that the values aren't equal, which they could have found out with a
+/// full comparison anyway.
+///
+/// A tag is only meaningful for the ciphertext it was produced with; storing it alongside the
+/// wrong ciphertext will cause equal values to be filtered out.
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::aes128v1::ere;
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// # let key = [0u8; 32];
+/// let cipher = ere::Cipher::<4, 256>::new(&key)?;
+/// let stored = (0..100u32)
+///     .map(|i| cipher.right_encrypt_with_filter_tag(&i.try_into()?))
+///     .collect::<Result<Vec<_>, _>>()?;
+///
+/// let query = cipher.left_encrypt(&42u32.try_into()?)?;
+/// let filter = ere::EqualityFilter::<4, 256>::new(&query)?;
+///
+/// let mut found = vec![];
+/// for (i, (ciphertext, tag)) in stored.iter().enumerate() {
+///     if filter.may_equal(ciphertext, *tag)? && query.compare_with_right(ciphertext)? {
+///         found.push(i);
+///     }
+/// }
+/// assert_eq!(vec![42], found);
+/// # Ok(())
+/// # }
+/// ```
+///
+pub struct EqualityFilter<
+    S: CipherSuite<W, M>,
+    CMP: Comparator<M>,
+    const N: usize,
+    const W: u16,
+    const M: u8,
+> {
+    /// The hash function, keyed with the key derived from the value's left ciphertext
+    key: <<S as CipherSuite<W, M>>::HF as HashFunction<M>>::Keyed,
+
+    /// Compiler pacification
+    _mark: PhantomData<CMP>,
+}
+
+impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, const M: u8> Debug
+    for EqualityFilter<S, CMP, N, W, M>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        // The key would let anyone check stored tags against this value, so it doesn't get shown
+        f.debug_struct("EqualityFilter").finish_non_exhaustive()
+    }
+}
+
+impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, const M: u8>
+    EqualityFilter<S, CMP, N, W, M>
+{
+    /// Make a filter for checking stored ciphertexts against the value in a left ciphertext
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the hash function can't be set up, which should only happen if
+    /// there's a bug somewhere.
+    ///
+    pub fn new(left: &LeftCipherText<S, CMP, N, W, M>) -> Result<Self, Error> {
+        let mut f = Zeroizing::new([Default::default(); N]);
+
+        for (n, f_n) in f.iter_mut().enumerate() {
+            *f_n = left.f(n)?;
+        }
+
+        Self::from_f(&f)
+    }
+
+    /// Make a filter for a plaintext value, without going via a left ciphertext
+    ///
+    /// This is what encryption uses to produce tags, and so it works for right-only ciphers, which
+    /// aren't allowed to make left ciphertexts.  The filter key can't be turned back into a left
+    /// ciphertext, so this doesn't give right-only ciphers a way around that.
+    ///
+    pub(crate) fn for_value(
+        cipher: &Cipher<S, CMP, N, W, M>,
+        value: &PlainText<N, W>,
+    ) -> Result<Self, Error> {
+        let mut f = Zeroizing::new([Default::default(); N]);
+
+        for (n, f_n) in f.iter_mut().enumerate() {
+            cipher.pseudorandomise(cipher.permuted_value(value.block(n)?)?, f_n);
+        }
+
+        Self::from_f(&f)
+    }
+
+    /// Derive the filter key from the F(k, p(x)) of every block
+    ///
+    /// The first block's F(k, p(x)) keys a hash of the rest of them, so that the filter key
+    /// depends on the whole value.  The hash is run once for every 16 bytes of key the hash
+    /// function needs, with a counter to tell the runs apart.
+    ///
+    fn from_f(
+        f: &[<<S as CipherSuite<W, M>>::PRF as PseudoRandomFunction>::BlockType; N],
+    ) -> Result<Self, Error> {
+        let (first, rest) = f.split_first().ok_or_else(|| {
+            Error::RangeError("cannot make an equality filter for zero blocks".to_string())
+        })?;
+        let hasher = S::HF::keyed(first.as_ref())?;
+
+        let mut input = Zeroizing::new(FILTER_KEY_LABEL.to_vec());
+        input.push(0);
+        for f_n in rest {
+            input.extend_from_slice(f_n.as_ref());
+        }
+
+        let mut key = Zeroizing::new(vec![0u8; S::HF::KEY_SIZE]);
+        for (i, chunk) in key.chunks_mut(16).enumerate() {
+            let counter = input.get_mut(FILTER_KEY_LABEL.len()).ok_or_else(|| {
+                Error::InternalError("filter key input is missing its counter".to_string())
+            })?;
+            *counter = u8::try_from(i)
+                .map_err(|e| Error::InternalError(format!("filter key is far too long ({e})")))?;
+
+            let digest = Zeroizing::new(S::HF::digest_keyed(&hasher, &input)?);
+            chunk.copy_from_slice(digest.get(..chunk.len()).ok_or_else(|| {
+                Error::InternalError("hash output shorter than a key chunk".to_string())
+            })?);
+        }
+
+        Ok(EqualityFilter {
+            key: S::HF::keyed(&key)?,
+            _mark: PhantomData,
+        })
+    }
+
+    /// Work out the filter tag for a ciphertext, assuming that it contains this filter's value
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the hash function fails, which should only happen if there's a bug
+    /// somewhere.
+    ///
+    pub fn tag(&self, ciphertext: &CipherText<S, CMP, N, W, M>) -> Result<u8, Error> {
+        self.tag_for_nonce_base(ciphertext.right.nonce_base())
+    }
+
+    /// Check whether a ciphertext, with the filter tag that was stored alongside it, might
+    /// contain this filter's value
+    ///
+    /// A result of `false` means the values are definitely not equal; `true` means that they
+    /// might be, and a full comparison is needed to find out for sure.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the hash function fails, which should only happen if there's a bug
+    /// somewhere.
+    ///
+    pub fn may_equal(
+        &self,
+        ciphertext: &CipherText<S, CMP, N, W, M>,
+        tag: u8,
+    ) -> Result<bool, Error> {
+        Ok(self.tag(ciphertext)? == tag)
+    }
+
+    /// Check whether a serialized ciphertext, with the filter tag that was stored alongside it,
+    /// might contain this filter's value
+    ///
+    /// Only the framing and the nonce base of the ciphertext are looked at, so this is much
+    /// cheaper than deserializing it, but it also doesn't check that the rest of the ciphertext
+    /// is valid.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the data isn't framed like a serialized ciphertext, or is too short
+    /// to have a nonce base.
+    ///
+    pub fn may_equal_serialized(&self, bytes: &[u8], tag: u8) -> Result<bool, Error> {
+        let (_, right) = split_serialized(bytes)?;
+        let nonce_base: &[u8; 16] = right
+            .get(0..16)
+            .and_then(|n| n.try_into().ok())
+            .ok_or_else(|| {
+                Error::ParseError("end-of-data found while looking for nonce base".to_string())
+            })?;
+
+        Ok(self.tag_for_nonce_base(nonce_base)? == tag)
+    }
+
+    /// The filter tag is the first byte of the keyed hash of the nonce base
+    fn tag_for_nonce_base(&self, nonce_base: &[u8; 16]) -> Result<u8, Error> {
+        S::HF::digest_keyed(&self.key, nonce_base)?
+            .first()
+            .copied()
+            .ok_or_else(|| Error::InternalError("hash function returned no data?!?".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aes128v1::{ere, ore};
+    use crate::util::random_key;
+    use crate::SerializableCipherText;
+
+    #[test]
+    fn equal_values_always_pass() {
+        let cipher = ere::Cipher::<4, 256>::new(&random_key()).unwrap();
+        let filter =
+            ere::EqualityFilter::new(&cipher.left_encrypt(&42u32.try_into().unwrap()).unwrap())
+                .unwrap();
+
+        for _ in 0..100 {
+            let (ct, tag) = cipher
+                .right_encrypt_with_filter_tag(&42u32.try_into().unwrap())
+                .unwrap();
+
+            assert!(filter.may_equal(&ct, tag).unwrap());
+            assert!(filter
+                .may_equal_serialized(&ct.to_vec().unwrap(), tag)
+                .unwrap());
+        }
+    }
+
+    #[test]
+    fn most_unequal_values_are_rejected() {
+        let cipher = ore::Cipher::<4, 256>::new(&random_key()).unwrap();
+        let filter =
+            ore::EqualityFilter::new(&cipher.left_encrypt(&42u32.try_into().unwrap()).unwrap())
+                .unwrap();
+
+        let passed = (0..1000u32)
+            .filter(|i| *i != 42)
+            .filter(|i| {
+                let (ct, tag) = cipher
+                    .right_encrypt_with_filter_tag(&(*i).try_into().unwrap())
+                    .unwrap();
+                filter.may_equal(&ct, tag).unwrap()
+            })
+            .count();
+
+        // Expected value is about 4; this should basically never fail
+        assert!(passed < 30, "{passed} unequal values passed the filter");
+    }
+
+    #[test]
+    fn stored_tags_of_equal_values_differ() {
+        let cipher = ere::Cipher::<4, 256>::new_right_only(&random_key()).unwrap();
+
+        let tags: std::collections::HashSet<u8> = (0..100)
+            .map(|_| {
+                cipher
+                    .right_encrypt_with_filter_tag(&42u32.try_into().unwrap())
+                    .unwrap()
+                    .1
+            })
+            .collect();
+
+        // 100 random bytes with fewer than 40 distinct values is vanishingly unlikely
+        assert!(tags.len() > 40, "only {} distinct tags", tags.len());
+    }
+
+    #[test]
+    fn tags_match_filter_from_left_ciphertext() {
+        let cipher = ere::Cipher::<2, 16>::new(&random_key()).unwrap();
+
+        for v in 0..=255u8 {
+            let pt = v.try_into().unwrap();
+            let (ct, tag) = cipher.right_encrypt_with_filter_tag(&pt).unwrap();
+            let filter = ere::EqualityFilter::new(&cipher.left_encrypt(&pt).unwrap()).unwrap();
+
+            assert_eq!(tag, filter.tag(&ct).unwrap());
+        }
+    }
+
+    #[test]
+    fn truncated_serialized_ciphertext() {
+        let cipher = ere::Cipher::<4, 256>::new(&random_key()).unwrap();
+        let filter =
+            ere::EqualityFilter::new(&cipher.left_encrypt(&42u32.try_into().unwrap()).unwrap())
+                .unwrap();
+
+        assert!(filter
+            .may_equal_serialized(&[0, 0, 4, 1, 2, 3, 4], 0)
+            .is_err());
+        assert!(filter.may_equal_serialized(&[], 0).is_err());
+    }
+}
@@ -6,18 +6,114 @@
 //!
 //! (dum dum)
 
-use aes::Aes128;
+use aes::{Aes128, Aes256};
+use blake2::Blake2sMac256;
 use cmac::{Cmac, Mac};
+use std::marker::PhantomData;
+use zeroize::Zeroizing;
 
 use crate::Error;
 
+/// An unsigned integer type that the output of a hash function can be reduced to
+///
+/// Comparators whose values all fit in a `u8` (which is all of them, for now) get their hashes via
+/// [`HashFunction::hash_keyed()`], but one with a bigger alphabet can ask for a wider value with
+/// [`HashFunction::hash_keyed_into()`].
+///
+#[allow(unreachable_pub)] // I think this is a bug in the lint; see also https://github.com/rust-lang/rust/issues/110923
+pub trait HashOutput: Copy {
+    /// Build a value from the leading bytes of a digest, most significant byte first
+    fn from_digest(digest: &[u8; 16]) -> Result<Self, Error>;
+
+    /// The remainder after dividing by `modulus`, or `None` if `modulus` is zero
+    fn checked_rem(self, modulus: Self) -> Option<Self>;
+}
+
+/// Implement [`HashOutput`] for the unsigned integer types
+macro_rules! hash_output {
+    ($($ty:ty),+) => {
+        $(
+            impl HashOutput for $ty {
+                fn from_digest(digest: &[u8; 16]) -> Result<Self, Error> {
+                    let bytes = digest
+                        .get(..std::mem::size_of::<$ty>())
+                        .and_then(|b| b.try_into().ok())
+                        .ok_or_else(|| {
+                            Error::InternalError(format!(
+                                "digest is too short to make a {}",
+                                stringify!($ty)
+                            ))
+                        })?;
+
+                    Ok(<$ty>::from_be_bytes(bytes))
+                }
+
+                fn checked_rem(self, modulus: Self) -> Option<Self> {
+                    <$ty>::checked_rem(self, modulus)
+                }
+            }
+        )+
+    };
+}
+
+hash_output!(u8, u16, u32, u64);
+
 /// Defines what you need to do in order to be a hash function
 #[allow(unreachable_pub)]
 // I can't help thinking this is a bug in the lint; see https://github.com/rust-lang/rust/issues/110923
 #[allow(clippy::module_name_repetitions)] // it's a trait, get over it
 pub trait HashFunction<const M: u8>: Sized {
+    /// The hash function with a key already loaded into it
+    ///
+    /// Setting up the key is often a significant part of the cost of a hash, so when the same key
+    /// is going to be used for many hashes, it's worth only doing that once.
+    type Keyed;
+
+    /// How many bytes of key the hash function needs
+    const KEY_SIZE: usize;
+
+    /// Refuse to compile a hash function that can't produce at least two different values
+    const VALID_M: () = assert!(M >= 2, "hash function output range (M) must be at least 2");
+
+    /// Set up the hash function to use the given key
+    fn keyed(key: &[u8]) -> Result<Self::Keyed, Error>;
+
+    /// Produce the full output of a previously-keyed hash function
+    ///
+    /// This is for the (few) places that need more than a smol value, such as deriving a key from
+    /// a left ciphertext.
+    ///
+    fn digest_keyed(keyed: &Self::Keyed, input: &[u8]) -> Result<[u8; 16], Error>;
+
+    /// Turns a nonce into a smol value (between 0 and M-1 inclusive, as it happens) using a
+    /// previously-keyed hash function
+    fn hash_keyed(keyed: &Self::Keyed, nonce: &[u8]) -> Result<u8, Error> {
+        #[allow(clippy::let_unit_value)] // Referring to it is what makes the compiler check it
+        let () = Self::VALID_M;
+
+        Self::hash_keyed_into(keyed, nonce, M)
+    }
+
+    /// Turns a nonce into a value between 0 and `modulus - 1` inclusive, of whichever width is
+    /// needed, using a previously-keyed hash function
+    ///
+    /// The value is taken from the leading bytes of the digest, so a `u8` gets exactly what
+    /// [`hash_keyed()`](Self::hash_keyed) would give for a `modulus` of `M`.
+    ///
+    fn hash_keyed_into<T: HashOutput>(
+        keyed: &Self::Keyed,
+        nonce: &[u8],
+        modulus: T,
+    ) -> Result<T, Error> {
+        T::from_digest(&Self::digest_keyed(keyed, nonce)?)?
+            .checked_rem(modulus)
+            .ok_or_else(|| Error::RangeError("hash modulus cannot be 0".to_string()))
+    }
+
     /// Turns a nonce and a key into a smol value (between 0 and M-1 inclusive, as it happens)
-    fn hash(key: &[u8], nonce: &[u8]) -> Result<u8, Error>;
+    fn hash(key: &[u8], nonce: &[u8]) -> Result<u8, Error> {
+        Self::hash_keyed(&Self::keyed(key)?, nonce)
+    }
 }
 
 /// A "hash" function based on CMAC with AES128.
@@ -29,18 +125,158 @@ pub trait HashFunction<const M: u8>: Sized {
 pub struct CMACAES128HF<const M: u8> {}
 
 impl<const M: u8> HashFunction<M> for CMACAES128HF<M> {
-    fn hash(key: &[u8], input: &[u8]) -> Result<u8, Error> {
-        let mut mac = Cmac::<Aes128>::new_from_slice(key).map_err(|e| {
+    type Keyed = Cmac<Aes128>;
+    const KEY_SIZE: usize = 16;
+
+    fn keyed(key: &[u8]) -> Result<Self::Keyed, Error> {
+        Cmac::<Aes128>::new_from_slice(key).map_err(|e| {
             Error::KeyError(format!(
                 "CMACAES128HF received a key of invalid length ({e})"
             ))
-        })?;
+        })
+    }
+
+    fn digest_keyed(keyed: &Self::Keyed, input: &[u8]) -> Result<[u8; 16], Error> {
+        let mut mac = keyed.clone();
+        mac.update(input);
+        Ok(mac.finalize().into_bytes().into())
+    }
+}
+
+/// A "hash" function based on CMAC with AES256.
+///
+/// Exactly the same as [`CMACAES128HF`], except for the size of the key.
+#[allow(unreachable_pub)] // I think this is a bug in the lint; see also https://github.com/rust-lang/rust/issues/110923
+#[derive(Debug)]
+pub struct CMACAES256HF<const M: u8> {}
+
+impl<const M: u8> HashFunction<M> for CMACAES256HF<M> {
+    type Keyed = Cmac<Aes256>;
+    const KEY_SIZE: usize = 32;
+
+    fn keyed(key: &[u8]) -> Result<Self::Keyed, Error> {
+        Cmac::<Aes256>::new_from_slice(key).map_err(|e| {
+            Error::KeyError(format!(
+                "CMACAES256HF received a key of invalid length ({e})"
+            ))
+        })
+    }
+
+    fn digest_keyed(keyed: &Self::Keyed, input: &[u8]) -> Result<[u8; 16], Error> {
+        let mut mac = keyed.clone();
+        mac.update(input);
+        Ok(mac.finalize().into_bytes().into())
+    }
+}
+
+/// A keyed hash function based on BLAKE2s.
+///
+/// BLAKE2s is built for 32 bit CPUs, and doesn't need any hardware acceleration to be quick.  The
+/// 256 bit output is truncated to 128 bits, to match the other hash functions.
+#[allow(unreachable_pub)] // I think this is a bug in the lint; see also https://github.com/rust-lang/rust/issues/110923
+#[derive(Debug)]
+pub struct BLAKE2SHF<const M: u8> {}
+
+impl<const M: u8> HashFunction<M> for BLAKE2SHF<M> {
+    type Keyed = Blake2sMac256;
+    const KEY_SIZE: usize = 32;
+
+    fn keyed(key: &[u8]) -> Result<Self::Keyed, Error> {
+        <Blake2sMac256 as Mac>::new_from_slice(key).map_err(|e| {
+            Error::KeyError(format!("BLAKE2SHF received a key of invalid length ({e})"))
+        })
+    }
+
+    fn digest_keyed(keyed: &Self::Keyed, input: &[u8]) -> Result<[u8; 16], Error> {
+        let mut mac = keyed.clone();
         mac.update(input);
+
         mac.finalize()
             .into_bytes()
-            .first()
-            .ok_or_else(|| Error::InternalError("CMACAES128HF returned no data?!?".to_string()))?
-            .checked_rem(M)
-            .ok_or_else(|| Error::RangeError("M cannot be 0".to_string()))
+            .get(..16)
+            .and_then(|o| o.try_into().ok())
+            .ok_or_else(|| Error::InternalError("BLAKE2SHF output too short?!?".to_string()))
+    }
+}
+
+/// A hash function which accepts keys shorter than the one it wraps needs, by padding them out
+/// with zeroes
+///
+/// This is for the left ciphertext blocks of a [truncated](crate::truncated) ciphersuite, which
+/// are used as hash keys, but are shorter than the hash function's key.  The padding adds nothing
+/// to the strength of the key, of course; a `T` byte key is only as good as its `T` bytes.
+///
+#[allow(unreachable_pub)] // I think this is a bug in the lint; see also https://github.com/rust-lang/rust/issues/110923
+#[derive(Debug)]
+pub struct ZeroPaddedHF<H, const M: u8> {
+    /// Compiler pacification
+    _mark: PhantomData<H>,
+}
+
+impl<H: HashFunction<M>, const M: u8> HashFunction<M> for ZeroPaddedHF<H, M> {
+    type Keyed = H::Keyed;
+    const KEY_SIZE: usize = H::KEY_SIZE;
+
+    fn keyed(key: &[u8]) -> Result<Self::Keyed, Error> {
+        if key.len() >= H::KEY_SIZE {
+            return H::keyed(key);
+        }
+
+        let mut padded = Zeroizing::new(vec![0u8; H::KEY_SIZE]);
+        padded
+            .get_mut(..key.len())
+            .ok_or_else(|| Error::InternalError("padded key is shorter than the key".to_string()))?
+            .copy_from_slice(key);
+
+        H::keyed(&padded)
+    }
+
+    fn digest_keyed(keyed: &Self::Keyed, input: &[u8]) -> Result<[u8; 16], Error> {
+        H::digest_keyed(keyed, input)
+    }
+
+    fn hash_keyed(keyed: &Self::Keyed, nonce: &[u8]) -> Result<u8, Error> {
+        H::hash_keyed(keyed, nonce)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn narrow_output_is_the_smol_value() {
+        let keyed = CMACAES128HF::<3>::keyed(&[0u8; 16]).unwrap();
+
+        for nonce in 0u8..=255 {
+            assert_eq!(
+                CMACAES128HF::<3>::hash_keyed(&keyed, &[nonce]).unwrap(),
+                CMACAES128HF::<3>::hash_keyed_into(&keyed, &[nonce], 3u8).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn wide_output_uses_the_whole_range() {
+        let keyed = CMACAES128HF::<3>::keyed(&[0u8; 16]).unwrap();
+
+        let values = (0u16..1_000)
+            .map(|n| {
+                CMACAES128HF::<3>::hash_keyed_into(&keyed, &n.to_be_bytes(), 1_000u16).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        assert!(values.iter().all(|v| *v < 1_000));
+        assert!(values.iter().any(|v| *v > 255));
+    }
+
+    #[test]
+    fn zero_modulus_is_an_error() {
+        let keyed = CMACAES128HF::<3>::keyed(&[0u8; 16]).unwrap();
+
+        assert!(matches!(
+            CMACAES128HF::<3>::hash_keyed_into(&keyed, b"nonce", 0u32),
+            Err(Error::RangeError(_))
+        ));
     }
 }
@@ -0,0 +1,243 @@
+//! Bucketing of order-revealing ciphertexts.
+//!
+//! Counting how many values fall into each of a set of ranges is the bread and butter of
+//! dashboards and reports.  With order-revealing encryption, this can be done without ever
+//! decrypting the values being counted, by comparing them against encrypted bucket boundaries.
+//!
+
+use std::cmp::Ordering;
+
+use crate::ciphersuite::CipherSuite;
+use crate::cmp::OrderingCMP;
+use crate::{CipherText, Error};
+
+/// A set of buckets, defined by their (encrypted) boundaries, into which order-revealing
+/// ciphertexts can be sorted.
+///
+/// A histogram with `k` boundaries has `k + 1` buckets.  Bucket `0` contains all values less than
+/// the smallest boundary, bucket `i` contains all values greater than or equal to the `i`th
+/// smallest boundary, but less than the `i+1`th, and bucket `k` contains all values greater than
+/// or equal to the largest boundary.
+///
+/// Boundaries must be full ciphertexts (that is, they must have a "left" part), as they are
+/// compared against the values being bucketed, which are typically right-only ciphertexts.  The
+/// boundaries are sorted when the histogram is created, so that each lookup only has to do
+/// `log2(k)` comparisons, rather than comparing against every boundary.
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::aes128v1::ore;
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// # let key = [0u8; 32];
+/// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+/// let histogram = ore::Histogram::<4, 256>::new(vec![
+///     cipher.full_encrypt(&100u32.try_into()?)?,
+///     cipher.full_encrypt(&10u32.try_into()?)?,
+/// ])?;
+///
+/// let values = vec![
+///     cipher.right_encrypt(&1u32.try_into()?)?,
+///     cipher.right_encrypt(&42u32.try_into()?)?,
+///     cipher.right_encrypt(&9001u32.try_into()?)?,
+///     cipher.right_encrypt(&31_337u32.try_into()?)?,
+/// ];
+///
+/// assert_eq!(vec![1, 1, 2], histogram.counts(&values)?);
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct Histogram<S: CipherSuite<W, 3>, const N: usize, const W: u16> {
+    /// The bucket boundaries, in ascending order
+    boundaries: Vec<CipherText<S, OrderingCMP, N, W, 3>>,
+}
+
+impl<S: CipherSuite<W, 3>, const N: usize, const W: u16> Histogram<S, N, W> {
+    /// Create a new histogram from a set of bucket boundaries.
+    ///
+    /// The boundaries can be given in any order.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::Incomparable`] if any of the boundaries doesn't have a "left" part,
+    /// or another error if a
+    /// comparison between boundaries fails.
+    ///
+    pub fn new(mut boundaries: Vec<CipherText<S, OrderingCMP, N, W, 3>>) -> Result<Self, Error> {
+        if boundaries.iter().any(|b| !b.has_left()) {
+            return Err(Error::Incomparable {
+                reason: "all histogram boundaries must have a left part".to_string(),
+            });
+        }
+
+        let mut sort_error: Option<Error> = None;
+
+        boundaries.sort_by(|a, b| match a.compare(b).and_then(OrderingCMP::invert) {
+            Ok(o) => o,
+            Err(e) => {
+                sort_error.get_or_insert(e);
+                Ordering::Equal
+            }
+        });
+
+        match sort_error {
+            Some(e) => Err(e),
+            None => Ok(Histogram { boundaries }),
+        }
+    }
+
+    /// The bucket boundaries, in ascending order
+    pub(crate) fn boundaries(&self) -> &[CipherText<S, OrderingCMP, N, W, 3>] {
+        &self.boundaries
+    }
+
+    /// The number of buckets in the histogram
+    ///
+    /// This is always one more than the number of boundaries.
+    ///
+    #[must_use]
+    pub fn bucket_count(&self) -> usize {
+        self.boundaries.len().saturating_add(1)
+    }
+
+    /// Determine which bucket the given ciphertext belongs in
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if a comparison fails, which should only happen if there's a bug
+    /// somewhere.
+    ///
+    pub fn bucket_of(&self, value: &CipherText<S, OrderingCMP, N, W, 3>) -> Result<usize, Error> {
+        // Find the number of boundaries that are less than or equal to the value, which is
+        // conveniently also the index of the bucket it belongs in
+        let mut lo: usize = 0;
+        let mut hi: usize = self.boundaries.len();
+
+        while lo < hi {
+            let mid = lo.saturating_add(num::Integer::div_floor(&hi.saturating_sub(lo), &2));
+            let boundary = self.boundaries.get(mid).ok_or_else(|| {
+                Error::InternalError(format!(
+                    "binary search went looking for boundary {mid} of {}",
+                    self.boundaries.len()
+                ))
+            })?;
+
+            if OrderingCMP::invert(boundary.compare(value)?)? == Ordering::Greater {
+                hi = mid;
+            } else {
+                lo = mid.saturating_add(1);
+            }
+        }
+
+        Ok(lo)
+    }
+
+    /// Count the number of ciphertexts that fall into each bucket
+    ///
+    /// The returned vector has one entry per bucket, as per [`bucket_count()`](Self::bucket_count).
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if a comparison fails, which should only happen if there's a bug
+    /// somewhere.
+    ///
+    pub fn counts<'a, I>(&self, values: I) -> Result<Vec<usize>, Error>
+    where
+        I: IntoIterator<Item = &'a CipherText<S, OrderingCMP, N, W, 3>>,
+        S: 'a,
+    {
+        let mut counts = vec![0usize; self.bucket_count()];
+
+        for value in values {
+            let bucket = self.bucket_of(value)?;
+            let count = counts.get_mut(bucket).ok_or_else(|| {
+                Error::InternalError(format!(
+                    "bucket_of returned bucket {bucket} of {}",
+                    self.bucket_count()
+                ))
+            })?;
+            *count = count.saturating_add(1);
+        }
+
+        Ok(counts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aes128v1::ore;
+    use crate::util::random_key;
+
+    fn histogram(cipher: &ore::Cipher<2, 256>, boundaries: &[u16]) -> ore::Histogram<2, 256> {
+        ore::Histogram::new(
+            boundaries
+                .iter()
+                .map(|b| cipher.full_encrypt(&(*b).try_into().unwrap()).unwrap())
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn no_boundaries_means_one_bucket() {
+        let cipher = ore::Cipher::<2, 256>::new(&random_key()).unwrap();
+        let h = histogram(&cipher, &[]);
+
+        assert_eq!(1, h.bucket_count());
+        assert_eq!(
+            0,
+            h.bucket_of(&cipher.right_encrypt(&42u16.try_into().unwrap()).unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn values_land_in_the_right_buckets() {
+        let cipher = ore::Cipher::<2, 256>::new(&random_key()).unwrap();
+        let h = histogram(&cipher, &[300, 10, 1000, 20]);
+
+        assert_eq!(5, h.bucket_count());
+
+        for (v, b) in [
+            (0u16, 0usize),
+            (9, 0),
+            (10, 1),
+            (19, 1),
+            (20, 2),
+            (299, 2),
+            (300, 3),
+            (999, 3),
+            (1000, 4),
+            (u16::MAX, 4),
+        ] {
+            let ct = cipher.right_encrypt(&v.try_into().unwrap()).unwrap();
+            assert_eq!(b, h.bucket_of(&ct).unwrap(), "value {v}");
+        }
+    }
+
+    #[test]
+    fn bulk_counting() {
+        let cipher = ore::Cipher::<2, 256>::new(&random_key()).unwrap();
+        let h = histogram(&cipher, &[10, 20]);
+
+        let values: Vec<_> = [1u16, 5, 12, 25, 30, 35]
+            .iter()
+            .map(|v| cipher.right_encrypt(&(*v).try_into().unwrap()).unwrap())
+            .collect();
+
+        assert_eq!(vec![2, 1, 3], h.counts(&values).unwrap());
+    }
+
+    #[test]
+    fn right_only_boundaries_are_rejected() {
+        let cipher = ore::Cipher::<2, 256>::new(&random_key()).unwrap();
+
+        assert!(ore::Histogram::<2, 256>::new(vec![cipher
+            .right_encrypt(&42u16.try_into().unwrap())
+            .unwrap()])
+        .is_err());
+    }
+}
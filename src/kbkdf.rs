@@ -4,27 +4,60 @@
 //! existing key, as many as one might like.  Very handy stuff when you need a lot of keys, and
 //! would rather not spend a fortune on KMS.
 //!
+//! This is the same derivation that cretrit uses internally to turn the key given to a
+//! [`Cipher`](crate::Cipher) into all the subkeys it needs, and it's available for deriving
+//! subkeys of your own.  The output of [`CMACAES256`] for a given root key and `id` is part of
+//! the stable API: it will not change without a major version bump, so subkeys derived today can
+//! be re-derived by any future 0.x or 1.x release.
+//!
+//! Root keys that aren't 32 bytes long, such as secrets that come from some other system, can be
+//! used with [`HKDFSHA256`], which takes key material of any length.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use cretrit::kbkdf::{KBKDFInit, KBKDF, CMACAES256};
+//!
+//! # fn main() -> Result<(), cretrit::Error> {
+//! # let root_key = [0u8; 32];
+//! let kdf = CMACAES256::new(&root_key)?;
+//!
+//! let mut mac_key = [0u8; 32];
+//! kdf.derive_labelled_key(&mut mac_key, b"myapp.mac", b"users.email")?;
+//!
+//! let mut other_mac_key = [0u8; 32];
+//! kdf.derive_labelled_key(&mut other_mac_key, b"myapp.mac", b"users.name")?;
+//!
+//! assert_ne!(mac_key, other_mac_key);
+//! # Ok(())
+//! # }
+//! ```
+//!
 
 use aes::Aes256;
+use blake2::Blake2sMac256;
+use cmac::digest::{FixedOutput, FixedOutputReset};
 use cmac::{Cmac, Mac};
+use hkdf::Hkdf;
+use sha2::Sha256;
 use std::fmt;
-use zeroize::ZeroizeOnDrop;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
 use crate::{util::check_overflow, Error};
 
 /// Initialisation of a KBKDF
 ///
 #[allow(clippy::upper_case_acronyms)]
-#[doc(hidden)]
 pub trait KBKDFInit: KBKDF {
     /// Create a KBKDF instance
     ///
     /// # Errors
     ///
     /// Different KBKDFs have different requirements for the size of their key, which will be the
-    /// most likely cause of errors.
+    /// most likely cause of errors.  [`CMACAES256`] and [`BLAKE2S256`] need exactly 32 bytes,
+    /// while [`HKDFSHA256`] will take any (non-empty) amount of key material.
     ///
-    fn new(root_key: &[u8; 32]) -> Result<Box<Self>, Error>;
+    fn new(root_key: &[u8]) -> Result<Box<Self>, Error>;
 }
 
 /// The key-generating functionality of a KBKDF
@@ -42,6 +75,91 @@ pub trait KBKDF {
     /// Will fail if an underlying cryptographic operation fails.
     ///
     fn derive_key(&self, subkey: &mut [u8], id: &[u8]) -> Result<(), Error>;
+
+    /// Generate several new keys at once
+    ///
+    /// Each `(subkey, id)` pair gets exactly the same subkey that
+    /// [`derive_key()`](Self::derive_key) would have produced for it.  The difference is that
+    /// KBKDFs which have to set up some keyed state before they can derive anything (such as
+    /// [`CMACAES256`], which has to run the AES key schedule) only have to do that once for the
+    /// whole batch, rather than once per subkey.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::kbkdf::{KBKDFInit, KBKDF, CMACAES256};
+    ///
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let root_key = [0u8; 32];
+    /// let kdf = CMACAES256::new(&root_key)?;
+    ///
+    /// let mut mac_key = [0u8; 32];
+    /// let mut enc_key = [0u8; 16];
+    /// kdf.derive_keys(&mut [(&mut mac_key, b"myapp.mac"), (&mut enc_key, b"myapp.enc")])?;
+    ///
+    /// let mut expected = [0u8; 16];
+    /// kdf.derive_key(&mut expected, b"myapp.enc")?;
+    /// assert_eq!(expected, enc_key);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will fail if an underlying cryptographic operation fails.  If that happens part way through
+    /// the batch, some of the subkeys will have been written and others won't.
+    ///
+    fn derive_keys(&self, keys: &mut [(&mut [u8], &[u8])]) -> Result<(), Error> {
+        for (subkey, id) in keys.iter_mut() {
+            self.derive_key(subkey, id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Generate a new key for a particular purpose (`label`) and situation (`context`)
+    ///
+    /// This follows the NIST SP800-108 convention of separating what a key is for from where it's
+    /// being used.  The label and context are length-delimited before being passed to
+    /// [`derive_key()`](Self::derive_key), so no two different `(label, context)` pairs can ever
+    /// produce the same subkey.  The `id`s that cretrit uses internally are all printable ASCII,
+    /// and so can't collide with a labelled key either.
+    ///
+    /// # Errors
+    ///
+    /// Will fail if the label is longer than 65,535 bytes, or if an underlying cryptographic
+    /// operation fails.
+    ///
+    fn derive_labelled_key(
+        &self,
+        subkey: &mut [u8],
+        label: &[u8],
+        context: &[u8],
+    ) -> Result<(), Error> {
+        let label_len = u16::try_from(label.len())
+            .map_err(|e| Error::KeyError(format!("KBKDF label is too long ({e})")))?;
+
+        let mut id =
+            Vec::with_capacity(label.len().saturating_add(context.len()).saturating_add(3));
+        id.push(0);
+        id.extend_from_slice(&label_len.to_be_bytes());
+        id.extend_from_slice(label);
+        id.extend_from_slice(context);
+
+        self.derive_key(subkey, &id)
+    }
+}
+
+/// Make sure a root key is the 32 bytes that the fixed-size KBKDFs need
+fn check_root_key_length(kbkdf: &str, root_key: &[u8]) -> Result<(), Error> {
+    if root_key.len() == 32 {
+        Ok(())
+    } else {
+        Err(Error::KeyError(format!(
+            "{kbkdf} requires a 32 byte root key (got {} bytes)",
+            root_key.len()
+        )))
+    }
 }
 
 /// A KBKDF based on CMACAES256
@@ -60,21 +178,21 @@ pub struct CMACAES256 {
 impl CMACAES256 {
     /// The number of bytes that the underlying cryptographic primitive generates on each call
     const BLOCK_SIZE: usize = 16;
-}
 
-impl KBKDFInit for CMACAES256 {
-    fn new(root_key: &[u8; 32]) -> Result<Box<Self>, Error> {
-        let mut kbkdf = Self {
-            root_key: Default::default(),
-        };
-        kbkdf.root_key.copy_from_slice(root_key);
-
-        Ok(Box::new(kbkdf))
+    /// Set up the CMAC state that every derivation starts from
+    fn keygen(&self) -> Result<Cmac<Aes256>, Error> {
+        Cmac::<Aes256>::new_from_slice(&self.root_key).map_err(|e| {
+            Error::KeyError(format!(
+                "CAN'T HAPPEN: KBKDF key is of invalid length ({e})"
+            ))
+        })
     }
-}
 
-impl KBKDF for CMACAES256 {
-    fn derive_key(&self, subkey: &mut [u8], id: &[u8]) -> Result<(), Error> {
+    /// Derive one subkey using an already-keyed CMAC
+    ///
+    /// The CMAC is left reset, ready for the next derivation.
+    ///
+    fn derive_with(keygen: &mut Cmac<Aes256>, subkey: &mut [u8], id: &[u8]) -> Result<(), Error> {
         let subkey_len = subkey.len();
         let count: u16 = num::Integer::div_ceil(&subkey_len, &CMACAES256::BLOCK_SIZE)
             .try_into()
@@ -83,29 +201,26 @@ impl KBKDF for CMACAES256 {
                     "Attempted to derive key greater than maximum supported size ({e})"
                 ))
             })?;
-        let mut keygen = Cmac::<Aes256>::new_from_slice(&self.root_key).map_err(|e| {
-            Error::KeyError(format!(
-                "CAN'T HAPPEN: KBKDF key is of invalid length ({e})"
-            ))
-        })?;
 
         let mut key_len_remaining = subkey_len;
+        // Wiped however the loop ends, since the ? operators can bail out part way through
+        let mut key_block = Zeroizing::new([0u8; CMACAES256::BLOCK_SIZE]);
 
         for i in 0..count {
             keygen.update(&i.to_be_bytes());
             keygen.update(b"\0");
             keygen.update(id);
 
-            let key_block = keygen.finalize_reset().into_bytes();
+            keygen.finalize_into_reset((&mut *key_block).into());
             let key_segment_len = std::cmp::min(key_len_remaining, CMACAES256::BLOCK_SIZE);
             let key_segment = key_block.get(..key_segment_len).ok_or_else(|| Error::InternalError(format!("key_block did not have bytes in range 0..{key_segment_len} in KBKDF.derive_key")))?;
 
-            let subkey_start = check_overflow(usize::from(i).overflowing_mul(CMACAES256::BLOCK_SIZE), &format!("overflow while attempting to determine subkey_start of block {i} (BLOCK_SIZE = {})", CMACAES256::BLOCK_SIZE))?;
-            let subkey_end = std::cmp::min(subkey_len, check_overflow(subkey_start.overflowing_add(CMACAES256::BLOCK_SIZE), &format!("overflow while attempting to determine subkey_end of block {i} (BLOCK_SIZE = {})", CMACAES256::BLOCK_SIZE))?);
+            let subkey_start = check_overflow(usize::from(i).overflowing_mul(CMACAES256::BLOCK_SIZE), format_args!("overflow while attempting to determine subkey_start of block {i} (BLOCK_SIZE = {})", CMACAES256::BLOCK_SIZE))?;
+            let subkey_end = std::cmp::min(subkey_len, check_overflow(subkey_start.overflowing_add(CMACAES256::BLOCK_SIZE), format_args!("overflow while attempting to determine subkey_end of block {i} (BLOCK_SIZE = {})", CMACAES256::BLOCK_SIZE))?);
 
             let subkey_seg: &mut [u8] = subkey.get_mut(subkey_start..subkey_end).ok_or_else(|| Error::InternalError(format!("subkey did not have bytes in range {subkey_start}..{subkey_end} in KBKDF.derive_key")))?;
             (*subkey_seg).copy_from_slice(key_segment);
-            key_len_remaining = check_overflow(key_len_remaining.overflowing_sub(key_segment_len), &format!("key_len_remaining ({key_len_remaining}) < key_segment_len ({key_segment_len}) in KBKDF.derive_key"))?;
+            key_len_remaining = check_overflow(key_len_remaining.overflowing_sub(key_segment_len), format_args!("key_len_remaining ({key_len_remaining}) < key_segment_len ({key_segment_len}) in KBKDF.derive_key"))?;
         }
 
         if key_len_remaining == 0 {
@@ -118,6 +233,37 @@ impl KBKDF for CMACAES256 {
     }
 }
 
+impl KBKDFInit for CMACAES256 {
+    fn new(root_key: &[u8]) -> Result<Box<Self>, Error> {
+        check_root_key_length("CMACAES256", root_key)?;
+
+        // Box first, then copy the key in, so that there's never a copy of the key sitting in a
+        // stack frame that won't get zeroized
+        let mut kbkdf = Box::new(Self {
+            root_key: Default::default(),
+        });
+        kbkdf.root_key.copy_from_slice(root_key);
+
+        Ok(kbkdf)
+    }
+}
+
+impl KBKDF for CMACAES256 {
+    fn derive_key(&self, subkey: &mut [u8], id: &[u8]) -> Result<(), Error> {
+        Self::derive_with(&mut self.keygen()?, subkey, id)
+    }
+
+    fn derive_keys(&self, keys: &mut [(&mut [u8], &[u8])]) -> Result<(), Error> {
+        let mut keygen = self.keygen()?;
+
+        for (subkey, id) in keys.iter_mut() {
+            Self::derive_with(&mut keygen, subkey, id)?;
+        }
+
+        Ok(())
+    }
+}
+
 impl fmt::Debug for CMACAES256 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("KBKDF")
@@ -126,6 +272,201 @@ impl fmt::Debug for CMACAES256 {
     }
 }
 
+/// A KBKDF based on keyed BLAKE2s
+///
+/// This is the same SP800-108 counter-mode construction as [`CMACAES256`], with BLAKE2s-256 in
+/// keyed mode as the PRF, for CPUs where AES is slow.  Each round of the PRF produces 32 bytes of
+/// subkey, rather than 16.
+///
+#[derive(ZeroizeOnDrop, Clone)]
+#[allow(clippy::upper_case_acronyms)]
+pub struct BLAKE2S256 {
+    /// The key from which all our new keys are derived
+    root_key: [u8; 32],
+}
+
+impl BLAKE2S256 {
+    /// The number of bytes that the underlying cryptographic primitive generates on each call
+    const BLOCK_SIZE: usize = 32;
+
+    /// Set up the keyed hash that every derivation starts from
+    fn keygen(&self) -> Result<Blake2sMac256, Error> {
+        <Blake2sMac256 as Mac>::new_from_slice(&self.root_key).map_err(|e| {
+            Error::KeyError(format!(
+                "CAN'T HAPPEN: KBKDF key is of invalid length ({e})"
+            ))
+        })
+    }
+
+    /// Derive one subkey using an already-keyed hash
+    fn derive_with(keygen: &Blake2sMac256, subkey: &mut [u8], id: &[u8]) -> Result<(), Error> {
+        for (i, segment) in subkey.chunks_mut(BLAKE2S256::BLOCK_SIZE).enumerate() {
+            let counter = u16::try_from(i).map_err(|e| {
+                Error::KeyError(format!(
+                    "Attempted to derive key greater than maximum supported size ({e})"
+                ))
+            })?;
+
+            let mut mac = keygen.clone();
+            mac.update(&counter.to_be_bytes());
+            mac.update(b"\0");
+            mac.update(id);
+            let mut key_block = Zeroizing::new([0u8; BLAKE2S256::BLOCK_SIZE]);
+            mac.finalize_into((&mut *key_block).into());
+
+            let key_segment = key_block.get(..segment.len()).ok_or_else(|| {
+                Error::InternalError(format!(
+                    "key_block did not have bytes in range 0..{} in KBKDF.derive_key",
+                    segment.len()
+                ))
+            })?;
+            segment.copy_from_slice(key_segment);
+        }
+
+        Ok(())
+    }
+}
+
+impl KBKDFInit for BLAKE2S256 {
+    fn new(root_key: &[u8]) -> Result<Box<Self>, Error> {
+        check_root_key_length("BLAKE2S256", root_key)?;
+
+        // Box first, then copy the key in, so that there's never a copy of the key sitting in a
+        // stack frame that won't get zeroized
+        let mut kbkdf = Box::new(Self {
+            root_key: Default::default(),
+        });
+        kbkdf.root_key.copy_from_slice(root_key);
+
+        Ok(kbkdf)
+    }
+}
+
+impl KBKDF for BLAKE2S256 {
+    fn derive_key(&self, subkey: &mut [u8], id: &[u8]) -> Result<(), Error> {
+        Self::derive_with(&self.keygen()?, subkey, id)
+    }
+
+    fn derive_keys(&self, keys: &mut [(&mut [u8], &[u8])]) -> Result<(), Error> {
+        let keygen = self.keygen()?;
+
+        for (subkey, id) in keys.iter_mut() {
+            Self::derive_with(&keygen, subkey, id)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for BLAKE2S256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KBKDF")
+            .field("key", &"**REDACTED**")
+            .finish()
+    }
+}
+
+/// A KBKDF based on HKDF-SHA256
+///
+/// Unlike [`CMACAES256`] and [`BLAKE2S256`], the root key can be any length, so key material that
+/// comes from somewhere else (a key agreement, another system's key hierarchy, a 64 byte master
+/// secret) can be used as it is, without being truncated or padded to fit.  The root key goes
+/// through HKDF-Extract (RFC 5869) once, with no salt, when the KBKDF is created, and each subkey
+/// is then HKDF-Expand of the extracted key, with the `id` as the `info`.  That means the subkeys
+/// are exactly what any other RFC 5869 implementation would produce for the same key material and
+/// `info`, which makes it possible to derive matching keys outside of cretrit.
+///
+/// A single subkey can be at most 8160 bytes (255 rounds of SHA-256) long.
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::kbkdf::{KBKDFInit, KBKDF, HKDFSHA256};
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// # let master_secret = [0u8; 64];
+/// let kdf = HKDFSHA256::new(&master_secret)?;
+///
+/// // A key for a cipher, derived from a secret that's the wrong size to be one
+/// let mut cipher_key = [0u8; 32];
+/// kdf.derive_labelled_key(&mut cipher_key, b"myapp.cipher", b"users.email")?;
+///
+/// let cipher = cretrit::aes128v1::ere::Cipher::<8, 256>::new(&cipher_key)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(ZeroizeOnDrop, Clone)]
+#[allow(clippy::upper_case_acronyms)]
+pub struct HKDFSHA256 {
+    /// The pseudorandom key extracted from the root key, from which all our new keys are derived
+    prk: [u8; 32],
+}
+
+impl HKDFSHA256 {
+    /// Set up the HMAC state that every derivation starts from
+    fn keygen(&self) -> Result<Hkdf<Sha256>, Error> {
+        Hkdf::<Sha256>::from_prk(&self.prk).map_err(|e| {
+            Error::KeyError(format!(
+                "CAN'T HAPPEN: KBKDF key is of invalid length ({e})"
+            ))
+        })
+    }
+
+    /// Derive one subkey using an already-keyed HKDF
+    fn derive_with(keygen: &Hkdf<Sha256>, subkey: &mut [u8], id: &[u8]) -> Result<(), Error> {
+        keygen.expand(id, subkey).map_err(|e| {
+            Error::KeyError(format!(
+                "Attempted to derive key greater than maximum supported size ({e})"
+            ))
+        })
+    }
+}
+
+impl KBKDFInit for HKDFSHA256 {
+    fn new(root_key: &[u8]) -> Result<Box<Self>, Error> {
+        if root_key.is_empty() {
+            return Err(Error::KeyError(
+                "HKDFSHA256 requires at least one byte of root key".to_string(),
+            ));
+        }
+
+        let (mut prk, _) = Hkdf::<Sha256>::extract(None, root_key);
+
+        let mut kbkdf = Box::new(Self {
+            prk: Default::default(),
+        });
+        kbkdf.prk.copy_from_slice(&prk);
+        prk.as_mut_slice().zeroize();
+
+        Ok(kbkdf)
+    }
+}
+
+impl KBKDF for HKDFSHA256 {
+    fn derive_key(&self, subkey: &mut [u8], id: &[u8]) -> Result<(), Error> {
+        Self::derive_with(&self.keygen()?, subkey, id)
+    }
+
+    fn derive_keys(&self, keys: &mut [(&mut [u8], &[u8])]) -> Result<(), Error> {
+        let keygen = self.keygen()?;
+
+        for (subkey, id) in keys.iter_mut() {
+            Self::derive_with(&keygen, subkey, id)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for HKDFSHA256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KBKDF")
+            .field("key", &"**REDACTED**")
+            .finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -229,5 +570,228 @@ mod tests {
             assert_ne!(k2, sk1);
             assert_ne!(k2, sk2);
         }
+
+        #[test]
+        fn derive_labelled_subkey() {
+            let key =
+                hex!["d742ccd1 686b7bce af5d4183 06efe6d6 fe6e4a1d c73a7ef4 3c8f16fb c07c8999"];
+            let kdf = CMACAES256::new(&key).unwrap();
+
+            let mut subkey = [0u8; 16];
+            kdf.derive_labelled_key(&mut subkey, b"label", b"context")
+                .unwrap();
+
+            let mut expected = [0u8; 16];
+            kdf.derive_key(&mut expected, b"\0\0\x05labelcontext")
+                .unwrap();
+
+            assert_eq!(expected, subkey);
+        }
+
+        #[test]
+        fn labels_and_contexts_are_separated() {
+            let kdf = CMACAES256::new(&[0u8; 32]).unwrap();
+
+            let mut sk1 = [0u8; 16];
+            let mut sk2 = [0u8; 16];
+
+            kdf.derive_labelled_key(&mut sk1, b"ab", b"c").unwrap();
+            kdf.derive_labelled_key(&mut sk2, b"a", b"bc").unwrap();
+
+            assert_ne!(sk1, sk2);
+        }
+
+        #[test]
+        fn batch_derivation_matches_individual_derivation() {
+            let key =
+                hex!["d742ccd1 686b7bce af5d4183 06efe6d6 fe6e4a1d c73a7ef4 3c8f16fb c07c8999"];
+            let kdf = CMACAES256::new(&key).unwrap();
+
+            let mut short = [0u8; 4];
+            let mut block = [0u8; 16];
+            let mut odd = [0u8; 39];
+            kdf.derive_keys(&mut [
+                (&mut short, b"testing"),
+                (&mut block, b"blocktest"),
+                (&mut odd, b"oddbod"),
+            ])
+            .unwrap();
+
+            assert_eq!(hex!["3ba5490a"], short);
+            assert_eq!(hex!["58844a69 d2e3d790 86770ea1 2fe70c0e"], block);
+            assert_eq!(
+                hex!["cbec9336 64d9230e 975be577 8cc185ec d359e69e 7c4f7020 368146da 154f15e8 630c7d44 720d61"],
+                odd
+            );
+        }
+
+        #[test]
+        fn wrong_length_root_key_is_rejected() {
+            assert!(matches!(
+                CMACAES256::new(&[0u8; 16]),
+                Err(Error::KeyError(_))
+            ));
+            assert!(matches!(
+                CMACAES256::new(&[0u8; 33]),
+                Err(Error::KeyError(_))
+            ));
+        }
+
+        #[test]
+        fn empty_batch() {
+            let kdf = CMACAES256::new(&[0u8; 32]).unwrap();
+
+            assert!(kdf.derive_keys(&mut []).is_ok());
+        }
+
+        #[test]
+        fn overlong_label() {
+            let kdf = CMACAES256::new(&[0u8; 32]).unwrap();
+            let mut subkey = [0u8; 16];
+
+            assert!(matches!(
+                kdf.derive_labelled_key(&mut subkey, &vec![0u8; usize::from(u16::MAX) + 1], b""),
+                Err(Error::KeyError(_))
+            ));
+        }
+    }
+
+    mod blake2s256 {
+        use super::*;
+
+        const KEY: [u8; 32] =
+            hex!["d742ccd1 686b7bce af5d4183 06efe6d6 fe6e4a1d c73a7ef4 3c8f16fb c07c8999"];
+
+        #[test]
+        fn derive_short_subkey() {
+            let mut subkey = [0u8; 4];
+
+            BLAKE2S256::new(&KEY)
+                .unwrap()
+                .derive_key(&mut subkey, b"testing")
+                .unwrap();
+
+            assert_eq!(hex!["dcd9f796"], subkey);
+        }
+
+        #[test]
+        fn derive_one_block_subkey() {
+            let mut subkey = [0u8; 32];
+
+            BLAKE2S256::new(&KEY)
+                .unwrap()
+                .derive_key(&mut subkey, b"blocktest")
+                .unwrap();
+
+            assert_eq!(
+                hex!["bc0f6c81 95abb48e 7bf31b34 4c256885 e1bd03c1 ccf3f764 180d146a aa3b2dd8"],
+                subkey
+            );
+        }
+
+        #[test]
+        fn derive_odd_sized_multiblock_subkey() {
+            let mut subkey = [0u8; 71];
+
+            BLAKE2S256::new(&KEY)
+                .unwrap()
+                .derive_key(&mut subkey, b"oddbod")
+                .unwrap();
+
+            assert_eq!(
+                hex![
+                    "59deb715 f09b40b3 4f21f3e8 86241b09 b043a1a9 323e8fda 614db0b6 f680b985
+                     d757cbfe 75d6f061 d6a61f66 2964acc0 2d6da651 4a323299 2b03ee16 a8913fac
+                     bc9ce76e ae7c59"
+                ],
+                subkey
+            );
+        }
+
+        #[test]
+        fn batch_derivation_matches_individual_derivation() {
+            let kdf = BLAKE2S256::new(&KEY).unwrap();
+
+            let mut short = [0u8; 4];
+            let mut block = [0u8; 32];
+            kdf.derive_keys(&mut [(&mut short, b"testing"), (&mut block, b"blocktest")])
+                .unwrap();
+
+            assert_eq!(hex!["dcd9f796"], short);
+            assert_eq!(
+                hex!["bc0f6c81 95abb48e 7bf31b34 4c256885 e1bd03c1 ccf3f764 180d146a aa3b2dd8"],
+                block
+            );
+        }
+    }
+
+    mod hkdfsha256 {
+        use super::*;
+
+        #[test]
+        fn rfc5869_test_case_3() {
+            let mut okm = [0u8; 42];
+
+            HKDFSHA256::new(&[0x0bu8; 22])
+                .unwrap()
+                .derive_key(&mut okm, b"")
+                .unwrap();
+
+            assert_eq!(
+                hex![
+                    "8da4e775 a563c18f 715f802a 063c5a31 b8a11f5c 5ee1879e c3454e5f 3c738d2d
+                     9d201395 faa4b61a 96c8"
+                ],
+                okm
+            );
+        }
+
+        #[test]
+        fn long_root_keys_are_accepted() {
+            let kdf = HKDFSHA256::new(&[42u8; 100]).unwrap();
+            let mut subkey = [0u8; 32];
+
+            kdf.derive_key(&mut subkey, b"testing").unwrap();
+
+            assert_ne!([0u8; 32], subkey);
+        }
+
+        #[test]
+        fn empty_root_key_is_rejected() {
+            assert!(matches!(HKDFSHA256::new(&[]), Err(Error::KeyError(_))));
+        }
+
+        #[test]
+        fn overlong_subkey_is_rejected() {
+            let kdf = HKDFSHA256::new(&[0u8; 32]).unwrap();
+
+            assert!(kdf.derive_key(&mut [0u8; 255 * 32], b"max").is_ok());
+            assert!(matches!(
+                kdf.derive_key(&mut [0u8; 255 * 32 + 1], b"toomuch"),
+                Err(Error::KeyError(_))
+            ));
+        }
+
+        #[test]
+        fn batch_derivation_matches_individual_derivation() {
+            let kdf = HKDFSHA256::new(&[0x0bu8; 22]).unwrap();
+
+            let mut short = [0u8; 4];
+            let mut long = [0u8; 42];
+            kdf.derive_keys(&mut [(&mut short, b"testing"), (&mut long, b"")])
+                .unwrap();
+
+            let mut expected = [0u8; 4];
+            kdf.derive_key(&mut expected, b"testing").unwrap();
+
+            assert_eq!(expected, short);
+            assert_eq!(
+                hex![
+                    "8da4e775 a563c18f 715f802a 063c5a31 b8a11f5c 5ee1879e c3454e5f 3c738d2d
+                     9d201395 faa4b61a 96c8"
+                ],
+                long
+            );
+        }
     }
 }
@@ -1,40 +1,109 @@
 #![doc = include_str!("../README.md")]
+// Test code gets to be a little more relaxed about things than the library proper
+#![cfg_attr(
+    test,
+    allow(clippy::assertions_on_result_states, clippy::default_numeric_fallback)
+)]
 #[cfg(doctest)]
 pub struct ReadmeDoctests;
 
 mod cipher;
 mod ciphersuite;
 mod ciphertext;
+mod costs;
+mod encryptor;
+mod equality_token;
 mod error;
+mod field_pack;
+mod filter;
+mod histogram;
+mod matrix;
+mod migrate;
+mod ord_key;
+mod params;
+mod partition;
 mod plaintext;
+mod pool;
+mod query;
+mod range;
+mod rekey;
+mod rng;
+mod sortable;
+mod stats;
+mod suite_modules;
+mod suites;
+mod type_byte;
 mod util;
+mod wire_header;
 
 #[doc(inline)]
 pub use {
-    cipher::Cipher, ciphertext::CipherText, ciphertext::Serializable as SerializableCipherText,
-    error::Error, plaintext::PlainText,
+    cipher::Cipher, ciphertext::sanitize_serialized, ciphertext::CipherText,
+    ciphertext::LeftCipherText, ciphertext::Serializable as SerializableCipherText,
+    ciphertext::FORMAT_VERSION, costs::CostEstimate, encryptor::ComparisonEncryptor,
+    equality_token::EqualityToken, error::Error, error::ErrorKind, field_pack::FieldPack,
+    filter::EqualityFilter, histogram::Histogram, matrix::compare_matrix, matrix::Matrix,
+    migrate::MigrationProgress, migrate::Migrator, ord_key::OrdBytesKey, params::Params,
+    partition::Partition, plaintext::PlainText, pool::CipherTextPool, query::PreparedQuery,
+    range::RangeIndex, range::RangeQuery, rekey::rekey, sortable::SortableBytes,
+    stats::CipherTextStats, suites::suites, suites::ComparatorDescriptor, suites::SuiteDescriptor,
+    type_byte::CipherTextType,
 };
 
 #[doc(hidden)]
-// For some reason, every *other* trait gets exported automatically, but this trait isn't.
-// But it's really an implementation detail, and shouldn't be part of the public API, so let's at
-// least hide it from the crate docs.
+// Kept for compatibility with code written before the kbkdf module was documented; the canonical
+// path is now cretrit::kbkdf::KBKDFInit.
 pub use kbkdf::KBKDFInit;
 
 pub mod aes128v1;
+pub mod aes256v1;
+pub mod audit;
+pub mod chacha20v1;
+pub mod feistel;
+pub mod truncated;
 
 mod bitlist;
 mod cmp;
+mod encoding;
 mod hash;
+mod packed;
 mod prf;
 mod prp;
 
-#[doc(hidden)]
 pub mod kbkdf;
 
+#[cfg(feature = "serde")]
+mod envelope;
 #[cfg(feature = "serde")]
 mod serde;
 
+#[cfg(feature = "serde")]
+mod field_config;
+#[cfg(feature = "serde")]
+#[doc(inline)]
+pub use field_config::FieldConfig;
+
+#[cfg(feature = "rand_core_09")]
+#[doc(inline)]
+pub use rng::Rand09Compat;
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+mod datetime;
+
+#[cfg(test)]
+mod auto_traits;
+
+#[cfg(feature = "alloc-tracking")]
+pub mod alloc_tracking;
+
+#[cfg(feature = "passphrase")]
+pub mod passphrase;
+
+#[cfg(all(test, feature = "alloc-tracking"))]
+#[global_allocator]
+/// Keep tabs on what the test suite allocates, so that the allocation tests have something to go on
+static ALLOCATOR: alloc_tracking::CountingAllocator = alloc_tracking::CountingAllocator::new();
+
 #[cfg(test)]
 #[macro_use]
 extern crate quickcheck;
@@ -43,4 +112,8 @@ extern crate quickcheck;
 #[cfg(test)]
 use criterion as _;
 #[cfg(test)]
+use postgres as _;
+#[cfg(test)]
+use rand_chacha_09 as _;
+#[cfg(test)]
 use serde_json as _;
@@ -0,0 +1,240 @@
+//! Comparing lots of ciphertexts against lots of other ciphertexts.
+//!
+//! Some jobs, like deduplicating a batch of values, need to compare every ciphertext in one set
+//! against every ciphertext in another.  Doing that with `try_cmp()` works, but it throws away a lot of
+//! work that can be shared between comparisons, and only uses one CPU.
+//!
+
+use std::cmp::Ordering;
+#[cfg(not(feature = "rayon"))]
+use std::num::NonZeroUsize;
+#[cfg(not(feature = "rayon"))]
+use std::thread;
+
+use crate::ciphersuite::CipherSuite;
+use crate::ciphertext::PreparedLeft;
+use crate::cmp::OrderingCMP;
+use crate::{CipherText, Error};
+
+/// A rectangular grid of values, stored row by row.
+///
+/// Produced by [`compare_matrix`], where each row corresponds to one of the "left" ciphertexts,
+/// and each column to one of the "right" ciphertexts.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matrix<T> {
+    /// How many rows there are
+    rows: usize,
+    /// How many columns there are
+    cols: usize,
+    /// The values themselves, `cols` at a time
+    cells: Vec<T>,
+}
+
+impl<T> Matrix<T> {
+    /// The number of rows in the matrix
+    #[must_use]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// The number of columns in the matrix
+    #[must_use]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Retrieve the value at the given location, or `None` if the location is outside the matrix
+    #[must_use]
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if col < self.cols {
+            self.cells
+                .get(row.checked_mul(self.cols)?.checked_add(col)?)
+        } else {
+            None
+        }
+    }
+
+    /// Retrieve all the values in the given row, or `None` if there is no such row
+    #[must_use]
+    pub fn row(&self, row: usize) -> Option<&[T]> {
+        let start = row.checked_mul(self.cols)?;
+
+        if row < self.rows {
+            self.cells.get(start..start.checked_add(self.cols)?)
+        } else {
+            None
+        }
+    }
+}
+
+/// Compare every ciphertext in `lefts` against every ciphertext in `rights`.
+///
+/// The result has one row for each of the `lefts`, and one column for each of the `rights`, with
+/// the value at `(i, j)` being the result of `lefts[i].cmp(&rights[j])`.
+///
+/// All of the `lefts` must have a "left" part, while the `rights` can be (and typically will be)
+/// right-only ciphertexts.  The work that only depends on each left ciphertext is done just once
+/// for each row, rather than once for every comparison, and the rows are spread out over as many
+/// threads as the system says it can usefully run.  With the `rayon` feature enabled, the rows are
+/// handed to rayon instead, so they share whatever thread pool the caller has set up.
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::aes128v1::ore;
+/// use std::cmp::Ordering;
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// # let key = [0u8; 32];
+/// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+///
+/// let lefts = vec![cipher.full_encrypt(&42u32.try_into()?)?];
+/// let rights = vec![
+///     cipher.right_encrypt(&1u32.try_into()?)?,
+///     cipher.right_encrypt(&42u32.try_into()?)?,
+///     cipher.right_encrypt(&9001u32.try_into()?)?,
+/// ];
+///
+/// let m = cretrit::compare_matrix(&lefts, &rights)?;
+///
+/// assert_eq!(
+///     Some(&[Ordering::Greater, Ordering::Equal, Ordering::Less][..]),
+///     m.row(0)
+/// );
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Will return [`Error::Incomparable`] if any of the `lefts` doesn't have a left part, or another
+/// error if a comparison fails.
+///
+pub fn compare_matrix<S: CipherSuite<W, 3>, const N: usize, const W: u16>(
+    lefts: &[CipherText<S, OrderingCMP, N, W, 3>],
+    rights: &[CipherText<S, OrderingCMP, N, W, 3>],
+) -> Result<Matrix<Ordering>, Error> {
+    #[cfg(feature = "rayon")]
+    let chunks: Vec<Result<Vec<Ordering>, Error>> = {
+        use rayon::prelude::*;
+
+        lefts
+            .par_chunks(1)
+            .map(|row| compare_rows(row, rights))
+            .collect()
+    };
+
+    #[cfg(not(feature = "rayon"))]
+    let chunks: Vec<Result<Vec<Ordering>, Error>> = {
+        let threads = thread::available_parallelism()
+            .map_or(1, NonZeroUsize::get)
+            .min(lefts.len())
+            .max(1);
+        let chunk_size = num::Integer::div_ceil(&lefts.len(), &threads).max(1);
+
+        thread::scope(|scope| {
+            let handles: Vec<_> = lefts
+                .chunks(chunk_size)
+                .map(|chunk| scope.spawn(move || compare_rows(chunk, rights)))
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+                .collect()
+        })
+    };
+
+    let mut cells = Vec::with_capacity(lefts.len().saturating_mul(rights.len()));
+
+    for chunk in chunks {
+        cells.extend(chunk?);
+    }
+
+    Ok(Matrix {
+        rows: lefts.len(),
+        cols: rights.len(),
+        cells,
+    })
+}
+
+/// Compare each of the given left ciphertexts against all of the right ciphertexts, returning
+/// the results one row after another
+fn compare_rows<S: CipherSuite<W, 3>, const N: usize, const W: u16>(
+    lefts: &[CipherText<S, OrderingCMP, N, W, 3>],
+    rights: &[CipherText<S, OrderingCMP, N, W, 3>],
+) -> Result<Vec<Ordering>, Error> {
+    let mut cells = Vec::with_capacity(lefts.len().saturating_mul(rights.len()));
+
+    for ct in lefts {
+        let left = PreparedLeft::new(ct.left.as_ref().ok_or_else(|| Error::Incomparable {
+            reason: "No left part in this ciphertext".to_string(),
+        })?)?;
+
+        for r in rights {
+            cells.push(OrderingCMP::invert(left.compare(&r.right)?)?);
+        }
+    }
+
+    Ok(cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes128v1::ore;
+    use crate::util::random_key;
+
+    #[test]
+    fn matrix_matches_ord() {
+        let cipher = ore::Cipher::<4, 256>::new(&random_key()).unwrap();
+        let values = [0u32, 42, 42, 9001, 31_337, u32::MAX, 7];
+
+        let lefts: Vec<_> = values
+            .iter()
+            .map(|v| cipher.full_encrypt(&(*v).try_into().unwrap()).unwrap())
+            .collect();
+        let rights: Vec<_> = values
+            .iter()
+            .map(|v| cipher.right_encrypt(&(*v).try_into().unwrap()).unwrap())
+            .collect();
+
+        let m = compare_matrix(&lefts, &rights).unwrap();
+
+        assert_eq!(values.len(), m.rows());
+        assert_eq!(values.len(), m.cols());
+
+        for (i, a) in values.iter().enumerate() {
+            for (j, b) in values.iter().enumerate() {
+                assert_eq!(Some(&a.cmp(b)), m.get(i, j), "{a} <=> {b}");
+                assert_eq!(lefts[i].try_cmp(&rights[j]).unwrap(), *m.get(i, j).unwrap());
+            }
+        }
+
+        assert_eq!(None, m.get(values.len(), 0));
+        assert_eq!(None, m.get(0, values.len()));
+        assert_eq!(None, m.row(values.len()));
+    }
+
+    #[test]
+    fn empty_inputs() {
+        let cipher = ore::Cipher::<4, 256>::new(&random_key()).unwrap();
+        let cts = vec![cipher.full_encrypt(&42u32.try_into().unwrap()).unwrap()];
+
+        let no_lefts = compare_matrix::<_, 4, 256>(&[], &cts).unwrap();
+        assert_eq!((0, 1), (no_lefts.rows(), no_lefts.cols()));
+
+        let no_rights = compare_matrix(&cts, &[]).unwrap();
+        assert_eq!((1, 0), (no_rights.rows(), no_rights.cols()));
+        assert_eq!(Some(&[][..]), no_rights.row(0));
+    }
+
+    #[test]
+    fn lefts_must_have_left_parts() {
+        let cipher = ore::Cipher::<4, 256>::new(&random_key()).unwrap();
+        let cts = vec![cipher.right_encrypt(&42u32.try_into().unwrap()).unwrap()];
+
+        assert!(compare_matrix(&cts, &cts).is_err());
+    }
+}
@@ -0,0 +1,414 @@
+//! Moving a whole column of ciphertexts to a new set of cipher parameters.
+//!
+//! Changing the block count, block width, or ciphersuite of a column means that every value in it
+//! has to be encrypted again, from its plaintext, since ciphertexts with different parameters
+//! can't be compared with each other.  [`rekey()`](crate::rekey) can only help when the
+//! parameters stay the same, so otherwise the plaintext has to come from somewhere else.  A
+//! [`Migrator`] does the rest of the job: it works through the old ciphertexts, asks the caller
+//! for each one's new plaintext, encrypts it with the new cipher, and hands the results back in
+//! batches, so they can be written out a batch at a time.
+//!
+
+use core::fmt::Debug;
+
+use crate::ciphersuite::CipherSuite;
+use crate::ciphertext::Serializable;
+use crate::cmp::Comparator;
+use crate::{Cipher, CipherText, ComparisonEncryptor, Error, PlainText};
+
+/// Something to tell about a migration's progress
+type ProgressReporter<'a> = Box<dyn FnMut(&MigrationProgress) + 'a>;
+
+/// How far a migration has got
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct MigrationProgress {
+    /// How many values have been re-encrypted and handed to the batch hook
+    pub migrated: usize,
+    /// How many batches have been handed to the batch hook
+    pub batches: usize,
+    /// How many values there are to migrate altogether, if the source of old ciphertexts knows
+    pub total: Option<usize>,
+}
+
+/// Re-encrypts a column of ciphertexts with a new cipher, whose parameters can be completely
+/// different from the old one's
+///
+/// The old cipher is used to check that each old ciphertext is what it's supposed to be, and to
+/// decrypt the ones that have a left part.  The plaintext for the new ciphertext comes from a
+/// callback, which is given the old ciphertext and, if it could be decrypted, its old plaintext.
+/// How the old plaintext turns into the new one (or, for right-only ciphertexts, where the
+/// plaintext comes from) is up to the caller, because only the caller knows how the values were
+/// encoded in the first place.
+///
+/// New ciphertexts are right-only, as stored ciphertexts should be, unless
+/// [`with_full_ciphertexts()`](Self::with_full_ciphertexts) says otherwise.
+///
+/// # Examples
+///
+/// Widening a column of 32 bit values to 64 bits:
+///
+/// ```rust
+/// use cretrit::aes128v1::ore;
+/// use cretrit::{Migrator, PlainText, SerializableCipherText};
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// let old = ore::Cipher::<4, 256>::new(&[1u8; 32])?;
+/// let new = ore::Cipher::<8, 256>::new(&[2u8; 32])?;
+///
+/// let column = vec![
+///     old.full_encrypt(&1u32.try_into()?)?.to_vec()?,
+///     old.full_encrypt(&42u32.try_into()?)?.to_vec()?,
+///     old.full_encrypt(&9001u32.try_into()?)?.to_vec()?,
+/// ];
+///
+/// let mut migrated = Vec::new();
+/// let progress = Migrator::new(&old, &new).with_batch_size(2).run(
+///     column,
+///     |_, old_value| {
+///         let value = u32::try_from(&old_value.expect("all the old ciphertexts are full"))?;
+///         PlainText::try_from(u64::from(value))
+///     },
+///     |batch| {
+///         migrated.extend(batch);
+///         Ok(())
+///     },
+/// )?;
+///
+/// assert_eq!(3, progress.migrated);
+/// assert_eq!(2, progress.batches);
+///
+/// let query = new.full_encrypt(&42u64.try_into()?)?;
+/// assert!(query.try_eq(&ore::CipherText::<8, 256>::from_slice(&migrated[1])?)?);
+/// # Ok(())
+/// # }
+/// ```
+///
+pub struct Migrator<
+    'a,
+    S: CipherSuite<W, M>,
+    CMP: Comparator<M>,
+    E: ComparisonEncryptor<NN, NW>,
+    const N: usize,
+    const W: u16,
+    const M: u8,
+    const NN: usize,
+    const NW: u16,
+> {
+    /// The cipher the old ciphertexts were made with
+    old: &'a Cipher<S, CMP, N, W, M>,
+    /// What to make the new ciphertexts with
+    new: &'a E,
+    /// How many new ciphertexts to hand to the batch hook at once
+    batch_size: usize,
+    /// Whether to make full ciphertexts, rather than right-only ones
+    full: bool,
+    /// Who to tell after each batch
+    progress: Option<ProgressReporter<'a>>,
+}
+
+impl<
+        S: CipherSuite<W, M>,
+        CMP: Comparator<M>,
+        E: ComparisonEncryptor<NN, NW>,
+        const N: usize,
+        const W: u16,
+        const M: u8,
+        const NN: usize,
+        const NW: u16,
+    > Debug for Migrator<'_, S, CMP, E, N, W, M, NN, NW>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Migrator")
+            .field("batch_size", &self.batch_size)
+            .field("full", &self.full)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<
+        'a,
+        S: CipherSuite<W, M>,
+        CMP: Comparator<M>,
+        E: ComparisonEncryptor<NN, NW>,
+        const N: usize,
+        const W: u16,
+        const M: u8,
+        const NN: usize,
+        const NW: u16,
+    > Migrator<'a, S, CMP, E, N, W, M, NN, NW>
+{
+    /// The batch size used unless [`with_batch_size()`](Self::with_batch_size) says otherwise
+    pub const DEFAULT_BATCH_SIZE: usize = 1000;
+
+    /// Get ready to move ciphertexts from the `old` cipher to the `new` one
+    ///
+    #[must_use]
+    pub fn new(old: &'a Cipher<S, CMP, N, W, M>, new: &'a E) -> Self {
+        Migrator {
+            old,
+            new,
+            batch_size: Self::DEFAULT_BATCH_SIZE,
+            full: false,
+            progress: None,
+        }
+    }
+
+    /// Hand new ciphertexts to the batch hook `batch_size` at a time
+    ///
+    /// A batch size of zero is treated as one.
+    ///
+    #[must_use]
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Make full ciphertexts, rather than right-only ones
+    ///
+    /// Full ciphertexts are deterministic, and so shouldn't be stored unless they have to be;
+    /// see the documentation for [`CipherText`](crate::CipherText).
+    ///
+    #[must_use]
+    pub fn with_full_ciphertexts(mut self, full: bool) -> Self {
+        self.full = full;
+        self
+    }
+
+    /// Call `progress` after each batch has been handed to the batch hook
+    ///
+    #[must_use]
+    pub fn with_progress(mut self, progress: impl FnMut(&MigrationProgress) + 'a) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Migrate every ciphertext in `old_ciphertexts`
+    ///
+    /// For each serialized old ciphertext, `source` is called with the ciphertext and its old
+    /// plaintext (if it has a left part to decrypt), and returns the plaintext to encrypt with the
+    /// new cipher.  The new ciphertexts are passed to `batch`, in the same order as the old ones,
+    /// whenever there's a full batch of them, and once more at the end for any left over.
+    ///
+    /// The first error from anywhere stops the migration.  Every batch handed to `batch` before
+    /// then is complete, so a migration can be picked up again by skipping the
+    /// [`migrated`](MigrationProgress::migrated) count from the last progress report.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if an old ciphertext isn't a valid serialized ciphertext for the old
+    /// cipher, if one has a left part that wasn't made with the old cipher's key, or if the new
+    /// cipher can't encrypt a value.  Any error returned by `source` or `batch` is passed on as-is.
+    ///
+    pub fn run<B: AsRef<[u8]>>(
+        mut self,
+        old_ciphertexts: impl IntoIterator<Item = B>,
+        mut source: impl FnMut(&[u8], Option<PlainText<N, W>>) -> Result<PlainText<NN, NW>, Error>,
+        mut batch: impl FnMut(Vec<Vec<u8>>) -> Result<(), Error>,
+    ) -> Result<MigrationProgress, Error> {
+        let rows = old_ciphertexts.into_iter();
+        let mut progress = MigrationProgress {
+            total: match rows.size_hint() {
+                (lower, Some(upper)) if lower == upper => Some(upper),
+                _ => None,
+            },
+            ..MigrationProgress::default()
+        };
+        let mut pending = Vec::with_capacity(self.batch_size);
+
+        for bytes in rows {
+            let old_ct = CipherText::<S, CMP, N, W, M>::from_slice(bytes.as_ref())?;
+            let old_value = if old_ct.has_left() {
+                Some(self.old.decrypt(&old_ct)?)
+            } else {
+                None
+            };
+
+            let value = source(bytes.as_ref(), old_value)?;
+            pending.push(if self.full {
+                self.new.full_encrypt_to_vec(&value)?
+            } else {
+                self.new.right_encrypt_to_vec(&value)?
+            });
+
+            if pending.len() >= self.batch_size {
+                self.flush(&mut pending, &mut batch, &mut progress)?;
+            }
+        }
+
+        if !pending.is_empty() {
+            self.flush(&mut pending, &mut batch, &mut progress)?;
+        }
+
+        Ok(progress)
+    }
+
+    /// Hand the pending ciphertexts to the batch hook, and report progress
+    fn flush(
+        &mut self,
+        pending: &mut Vec<Vec<u8>>,
+        batch: &mut impl FnMut(Vec<Vec<u8>>) -> Result<(), Error>,
+        progress: &mut MigrationProgress,
+    ) -> Result<(), Error> {
+        let count = pending.len();
+        batch(std::mem::replace(
+            pending,
+            Vec::with_capacity(self.batch_size),
+        ))?;
+
+        progress.migrated = progress.migrated.saturating_add(count);
+        progress.batches = progress.batches.saturating_add(1);
+
+        if let Some(report) = &mut self.progress {
+            report(progress);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{aes128v1, aes256v1};
+    use std::cell::RefCell;
+
+    fn widen(_: &[u8], old_value: Option<PlainText<4, 256>>) -> Result<PlainText<8, 256>, Error> {
+        let value = u32::try_from(&old_value.unwrap())?;
+        PlainText::try_from(u64::from(value))
+    }
+
+    #[test]
+    fn batches_and_progress() {
+        let old = aes128v1::ore::Cipher::<4, 256>::new(&[1u8; 32]).unwrap();
+        let new = aes256v1::ore::Cipher::<8, 256>::new(&[2u8; 32]).unwrap();
+
+        let column = (0u32..7)
+            .map(|v| {
+                old.full_encrypt(&v.try_into().unwrap())
+                    .unwrap()
+                    .to_vec()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let reports = RefCell::new(Vec::new());
+        let mut batch_sizes = Vec::new();
+        let mut migrated = Vec::new();
+
+        let progress = Migrator::new(&old, &new)
+            .with_batch_size(3)
+            .with_progress(|p| reports.borrow_mut().push(*p))
+            .run(&column, widen, |b| {
+                batch_sizes.push(b.len());
+                migrated.extend(b);
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(vec![3, 3, 1], batch_sizes);
+        assert_eq!(7, progress.migrated);
+        assert_eq!(3, progress.batches);
+        assert_eq!(Some(7), progress.total);
+        assert_eq!(
+            vec![3, 6, 7],
+            reports
+                .borrow()
+                .iter()
+                .map(|p| p.migrated)
+                .collect::<Vec<_>>()
+        );
+
+        for (v, bytes) in migrated.iter().enumerate() {
+            let ct = aes256v1::ore::CipherText::<8, 256>::from_slice(bytes).unwrap();
+            assert!(!ct.has_left());
+            assert!(new
+                .full_encrypt(&u64::try_from(v).unwrap().try_into().unwrap())
+                .unwrap()
+                .try_eq(&ct)
+                .unwrap());
+        }
+    }
+
+    #[test]
+    fn right_only_ciphertexts_need_the_source() {
+        let old = aes128v1::ere::Cipher::<4, 256>::new(&[1u8; 32]).unwrap();
+        let new = aes128v1::ere::Cipher::<2, 16>::new(&[2u8; 32]).unwrap();
+
+        let column = vec![old
+            .right_encrypt(&7u32.try_into().unwrap())
+            .unwrap()
+            .to_vec()
+            .unwrap()];
+        let mut migrated = Vec::new();
+
+        Migrator::new(&old, &new)
+            .with_full_ciphertexts(true)
+            .run(
+                &column,
+                |bytes, old_value| {
+                    assert_eq!(column[0], bytes);
+                    assert!(old_value.is_none());
+                    PlainText::try_from(7u8)
+                },
+                |b| {
+                    migrated.extend(b);
+                    Ok(())
+                },
+            )
+            .unwrap();
+
+        let ct = aes128v1::ere::CipherText::<2, 16>::from_slice(&migrated[0]).unwrap();
+        assert!(ct.has_left());
+        assert_eq!(7, u8::try_from(&new.decrypt(&ct).unwrap()).unwrap());
+    }
+
+    #[test]
+    fn errors_stop_the_migration_after_the_last_complete_batch() {
+        let old = aes128v1::ore::Cipher::<4, 256>::new(&[1u8; 32]).unwrap();
+        let new = aes128v1::ore::Cipher::<8, 256>::new(&[2u8; 32]).unwrap();
+
+        let mut column = (0u32..5)
+            .map(|v| {
+                old.full_encrypt(&v.try_into().unwrap())
+                    .unwrap()
+                    .to_vec()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        column[3] = vec![0xff];
+
+        let mut last = MigrationProgress::default();
+        let mut batches = 0;
+
+        let result = Migrator::new(&old, &new)
+            .with_batch_size(2)
+            .with_progress(|p| last = *p)
+            .run(&column, widen, |_| {
+                batches += 1;
+                Ok(())
+            });
+
+        assert!(result.is_err());
+        assert_eq!(1, batches);
+        assert_eq!(2, last.migrated);
+    }
+
+    #[test]
+    fn ciphertexts_from_another_key_are_rejected() {
+        let old = aes128v1::ore::Cipher::<4, 256>::new(&[1u8; 32]).unwrap();
+        let other = aes128v1::ore::Cipher::<4, 256>::new(&[3u8; 32]).unwrap();
+        let new = aes128v1::ore::Cipher::<8, 256>::new(&[2u8; 32]).unwrap();
+
+        let column = vec![other
+            .full_encrypt(&1u32.try_into().unwrap())
+            .unwrap()
+            .to_vec()
+            .unwrap()];
+
+        assert!(matches!(
+            Migrator::new(&old, &new).run(&column, widen, |_| Ok(())),
+            Err(Error::CryptoError(_))
+        ));
+    }
+}
@@ -0,0 +1,206 @@
+//! Using encrypted values as the keys of standard library collections.
+//!
+
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+use crate::ciphersuite::CipherSuite;
+use crate::ciphertext::Serializable;
+use crate::cmp::OrderingCMP;
+use crate::{CipherText, Error};
+
+/// A serialized order-revealing ciphertext that has been parsed once, up front, so that it can be
+/// used as the key of a `BTreeMap`, `BinaryHeap`, `HashMap`, or any other collection that needs
+/// its keys to be `Ord` or `Hash`.
+///
+/// Unlike [`SortableBytes`](crate::SortableBytes), which only parses the bits of each ciphertext
+/// that a comparison needs, every time it needs them, an `OrdBytesKey` parses (and checks) the
+/// whole ciphertext when it's created, and keeps the result.  That costs more memory, but each
+/// comparison after that is as cheap as comparing two [`CipherText`]s, and a corrupted
+/// ciphertext is caught straight away, rather than causing a panic somewhere in the middle of a
+/// collection operation.
+///
+/// An `OrdBytesKey` doesn't need the [`Cipher`](crate::Cipher) that produced it.  Two keys are
+/// equal if their left parts are identical, and hashing uses the left part as a blind index: left
+/// ciphertexts are deterministic, so equal values always hash the same, without the plaintext
+/// being involved.  That does mean that only full ciphertexts (those with a "left" part) can be
+/// made into an `OrdBytesKey`, with all the correlation risk that storing left ciphertexts
+/// entails.  Keys from ciphers with different keys will compare and hash as nonsense, so don't
+/// mix them in the one collection.
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::aes128v1::ore;
+/// use cretrit::SerializableCipherText;
+/// use std::collections::{BinaryHeap, HashSet};
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// # let key = [0u8; 32];
+/// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+/// let mut heap = BinaryHeap::new();
+/// let mut seen = HashSet::new();
+///
+/// for v in [42u32, 9001, 1, 42] {
+///     let bytes = cipher.full_encrypt(&v.try_into()?)?.to_vec()?;
+///     let key = ore::OrdBytesKey::<4, 256>::new(bytes)?;
+///
+///     seen.insert(key.clone());
+///     heap.push((key, v));
+/// }
+///
+/// assert_eq!(3, seen.len());
+/// assert_eq!(Some(9001), heap.pop().map(|(_, v)| v));
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct OrdBytesKey<S: CipherSuite<W, 3>, const N: usize, const W: u16> {
+    /// The serialized ciphertext
+    bytes: Vec<u8>,
+
+    /// The parsed ciphertext, which is what actually gets compared
+    ciphertext: CipherText<S, OrderingCMP, N, W, 3>,
+}
+
+impl<S: CipherSuite<W, 3>, const N: usize, const W: u16> OrdBytesKey<S, N, W> {
+    /// Parse a serialized ciphertext, so it can be used as a key.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::Incomparable`] if the ciphertext doesn't have a left part, or another
+    /// error if the bytes aren't a valid serialized ciphertext.
+    ///
+    pub fn new(bytes: Vec<u8>) -> Result<Self, Error> {
+        let ciphertext = CipherText::from_slice(&bytes)?;
+
+        if !ciphertext.has_left() {
+            return Err(Error::Incomparable {
+                reason: "ciphertexts used as keys must have a left part".to_string(),
+            });
+        }
+
+        Ok(OrdBytesKey { bytes, ciphertext })
+    }
+
+    /// The parsed ciphertext
+    #[must_use]
+    pub fn ciphertext(&self) -> &CipherText<S, OrderingCMP, N, W, 3> {
+        &self.ciphertext
+    }
+
+    /// The serialized ciphertext
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Unwrap the serialized ciphertext
+    #[must_use]
+    pub fn into_vec(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl<S: CipherSuite<W, 3>, const N: usize, const W: u16> AsRef<[u8]> for OrdBytesKey<S, N, W> {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl<S: CipherSuite<W, 3>, const N: usize, const W: u16> Ord for OrdBytesKey<S, N, W> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        #[allow(clippy::expect_used)] // No way to return an error when implementing Ord
+        self.ciphertext
+            .compare(&other.ciphertext)
+            .and_then(OrderingCMP::invert)
+            .expect("comparison of ciphertexts failed")
+    }
+}
+
+impl<S: CipherSuite<W, 3>, const N: usize, const W: u16> PartialOrd for OrdBytesKey<S, N, W> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S: CipherSuite<W, 3>, const N: usize, const W: u16> PartialEq for OrdBytesKey<S, N, W> {
+    fn eq(&self, other: &Self) -> bool {
+        // Both of them are known to have a left part, so this can't fail
+        self.ciphertext.left_eq(&other.ciphertext).unwrap_or(false)
+    }
+}
+
+impl<S: CipherSuite<W, 3>, const N: usize, const W: u16> Eq for OrdBytesKey<S, N, W> {}
+
+impl<S: CipherSuite<W, 3>, const N: usize, const W: u16> Hash for OrdBytesKey<S, N, W> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        if let Some(left) = self.ciphertext.left() {
+            left.hash_into(state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aes128v1::ore;
+    use crate::util::random_key;
+    use crate::SerializableCipherText;
+    use std::collections::{BTreeMap, HashMap};
+
+    fn ord_key(cipher: &ore::Cipher<4, 256>, v: u32) -> ore::OrdBytesKey<4, 256> {
+        ore::OrdBytesKey::new(
+            cipher
+                .full_encrypt(&v.try_into().unwrap())
+                .unwrap()
+                .to_vec()
+                .unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn right_only_ciphertexts_are_rejected() {
+        let cipher = ore::Cipher::<4, 256>::new(&random_key()).unwrap();
+        let bytes = cipher
+            .right_encrypt(&42u32.try_into().unwrap())
+            .unwrap()
+            .to_vec()
+            .unwrap();
+
+        assert!(ore::OrdBytesKey::<4, 256>::new(bytes).is_err());
+    }
+
+    #[test]
+    fn garbage_is_rejected() {
+        let cipher = ore::Cipher::<4, 256>::new(&random_key()).unwrap();
+        let mut bytes = ord_key(&cipher, 42).into_vec();
+        bytes.push(0);
+
+        assert!(ore::OrdBytesKey::<4, 256>::new(vec![]).is_err());
+        assert!(ore::OrdBytesKey::<4, 256>::new(bytes).is_err());
+    }
+
+    #[test]
+    fn btreemap_and_hashmap() {
+        let cipher = ore::Cipher::<4, 256>::new(&random_key()).unwrap();
+        let mut tree = BTreeMap::new();
+        let mut hash = HashMap::new();
+
+        for v in [31_337u32, 0, 9001, 42, u32::MAX, 1, 42] {
+            // A fresh encryption of an existing key is a different ciphertext, but the same key
+            tree.insert(ord_key(&cipher, v), v);
+            hash.insert(ord_key(&cipher, v), v);
+        }
+
+        assert_eq!(
+            vec![0u32, 1, 42, 9001, 31_337, u32::MAX],
+            tree.values().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(6, hash.len());
+        assert_eq!(Some(&9001), tree.get(&ord_key(&cipher, 9001)));
+        assert_eq!(Some(&9001), hash.get(&ord_key(&cipher, 9001)));
+        assert_eq!(None, hash.get(&ord_key(&cipher, 9002)));
+    }
+}
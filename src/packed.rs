@@ -0,0 +1,337 @@
+//! Compact in-memory storage for lots of small values
+//!
+//! A right ciphertext holds `N * W` values, each of which is less than `M` (which, in practice,
+//! is either 2 or 3).  Giving each of those its own byte wastes most of the memory they take up,
+//! so instead they're packed into 64-bit words, using as few bits per value as will do the job.
+//!
+
+use crate::Error;
+
+/// A fixed-length sequence of values, each less than `M`, packed tightly together
+///
+/// Each value takes up the smallest power-of-two number of bits that can hold `M - 1`, so that
+/// values never straddle a word boundary.  Values are stored least-significant bit first, which
+/// means that when each value only needs one bit, the bytes of the packed words are exactly the
+/// "binary" serialization format of a right ciphertext.
+///
+#[allow(unreachable_pub)] // I think this is a bug in the lint; see also https://github.com/rust-lang/rust/issues/110923
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackedValues<const M: u8> {
+    /// How many values are stored
+    len: usize,
+    /// The values themselves
+    words: Vec<u64>,
+}
+
+impl<const M: u8> PackedValues<M> {
+    /// How many bits each value takes up
+    const BITS: u32 = {
+        let needed = u8::BITS - (M.saturating_sub(1)).leading_zeros();
+
+        if needed == 0 {
+            1
+        } else {
+            needed.next_power_of_two()
+        }
+    };
+
+    /// A mask for a single value's worth of bits
+    const MASK: u64 = (1u64 << Self::BITS) - 1;
+
+    /// Create a sequence of `len` values, all of which are zero
+    pub(crate) fn new(len: usize) -> Self {
+        Self {
+            len,
+            words: vec![0u64; num::Integer::div_ceil(&Self::bit_len(len), &64)],
+        }
+    }
+
+    /// Create a sequence of `len` values, all of which are zero, reusing the allocation of
+    /// `storage` (which may previously have held some other values) if it's big enough
+    pub(crate) fn new_in(len: usize, mut storage: Vec<u64>) -> Self {
+        storage.clear();
+        storage.resize(num::Integer::div_ceil(&Self::bit_len(len), &64), 0);
+
+        Self {
+            len,
+            words: storage,
+        }
+    }
+
+    /// Hand over the storage that the values are kept in, for reuse by
+    /// [`new_in()`](Self::new_in) or [`from_le_bytes_in()`](Self::from_le_bytes_in), leaving
+    /// an empty sequence behind
+    pub(crate) fn take_storage(&mut self) -> Vec<u64> {
+        self.len = 0;
+        std::mem::take(&mut self.words)
+    }
+
+    /// How many values are stored
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Retrieve the value at the given index, or `None` if the index is beyond the end
+    pub(crate) fn get(&self, i: usize) -> Option<u8> {
+        if i >= self.len {
+            return None;
+        }
+
+        let (word, shift) = Self::locate(i);
+
+        u8::try_from(self.words.get(word)?.checked_shr(shift).unwrap_or(0) & Self::MASK).ok()
+    }
+
+    /// Store a value at the given index
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the index is beyond the end of the sequence, or if the value is
+    /// not less than `M`.
+    ///
+    pub(crate) fn set(&mut self, i: usize, v: u8) -> Result<(), Error> {
+        if v >= M {
+            return Err(Error::RangeError(format!(
+                "cannot store value {v} when all values must be less than {M}"
+            )));
+        }
+        if i >= self.len {
+            return Err(Error::RangeError(format!(
+                "cannot store value at index {i} of {}",
+                self.len
+            )));
+        }
+
+        let (word, shift) = Self::locate(i);
+        let w = self.words.get_mut(word).ok_or_else(|| {
+            Error::InternalError(format!("value {i} should be in word {word}, but isn't"))
+        })?;
+
+        *w = (*w & !Self::MASK.checked_shl(shift).unwrap_or(0))
+            | u64::from(v).checked_shl(shift).unwrap_or(0);
+
+        Ok(())
+    }
+
+    /// Iterate over all the values, in order
+    pub(crate) fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..self.len).filter_map(|i| self.get(i))
+    }
+
+    /// A copy of the first `len` values, or `None` if there aren't that many
+    pub(crate) fn prefix(&self, len: usize) -> Option<Self> {
+        if len > self.len {
+            return None;
+        }
+
+        let mut p = Self {
+            len,
+            words: self
+                .words
+                .get(..num::Integer::div_ceil(&Self::bit_len(len), &64))?
+                .to_vec(),
+        };
+        p.clear_padding();
+
+        Some(p)
+    }
+
+    /// Reconstitute a sequence of `len` values from the bytes produced by
+    /// [`to_le_bytes()`](Self::to_le_bytes)
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `bytes` isn't exactly the right length to hold `len` values, or
+    /// if any of the values in it is not less than `M`.
+    ///
+    #[cfg(test)] // Parsing always has storage to offer, but this is handy for testing
+    pub(crate) fn from_le_bytes(bytes: &[u8], len: usize) -> Result<Self, Error> {
+        Self::from_le_bytes_in(bytes, len, Vec::new())
+    }
+
+    /// Reconstitute a sequence of `len` values from the bytes produced by
+    /// [`to_le_bytes()`](Self::to_le_bytes), reusing the allocation of `storage` if it's big
+    /// enough
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `bytes` isn't exactly the right length to hold `len` values, or
+    /// if any of the values in it is not less than `M`.
+    ///
+    pub(crate) fn from_le_bytes_in(
+        bytes: &[u8],
+        len: usize,
+        mut storage: Vec<u64>,
+    ) -> Result<Self, Error> {
+        let expected = num::Integer::div_ceil(&Self::bit_len(len), &8);
+
+        if bytes.len() != expected {
+            return Err(Error::ParseError(format!(
+                "expected {expected} bytes of packed values, got {}",
+                bytes.len()
+            )));
+        }
+
+        storage.clear();
+        storage.extend(bytes.chunks(8).map(|chunk| {
+            let mut word = [0u8; 8];
+            for (dst, src) in word.iter_mut().zip(chunk) {
+                *dst = *src;
+            }
+            u64::from_le_bytes(word)
+        }));
+
+        let mut p = Self {
+            len,
+            words: storage,
+        };
+        p.clear_padding();
+
+        if p.iter().any(|v| v >= M) {
+            return Err(Error::ParseError(format!(
+                "packed values contain a value not less than {M}"
+            )));
+        }
+
+        Ok(p)
+    }
+
+    /// The packed values, as a little-endian sequence of bytes
+    ///
+    /// Only as many bytes as are needed to hold all the values are returned, so the last byte
+    /// may be only partially used, in which case the unused bits are zero.
+    ///
+    pub(crate) fn to_le_bytes(&self) -> Vec<u8> {
+        self.words
+            .iter()
+            .flat_map(|w| w.to_le_bytes())
+            .take(num::Integer::div_ceil(&Self::bit_len(self.len), &8))
+            .collect()
+    }
+
+    /// How many bits it takes to store `len` values
+    fn bit_len(len: usize) -> usize {
+        len.saturating_mul(Self::BITS as usize)
+    }
+
+    /// Which word the `i`th value lives in, and how far up that word it is
+    fn locate(i: usize) -> (usize, u32) {
+        let (word, bit) = num::Integer::div_rem(&Self::bit_len(i), &64);
+
+        (word, u32::try_from(bit).unwrap_or(0))
+    }
+
+    /// Zero out any bits in the last word that don't belong to a value, so that equal sequences
+    /// always have equal words
+    fn clear_padding(&mut self) {
+        let used = num::Integer::mod_floor(&Self::bit_len(self.len), &64);
+
+        if used > 0 {
+            if let Some(last) = self.words.last_mut() {
+                *last &= u64::MAX
+                    .checked_shr(64u32.saturating_sub(u32::try_from(used).unwrap_or(64)))
+                    .unwrap_or(u64::MAX);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bits_per_value() {
+        assert_eq!(1, PackedValues::<1>::BITS);
+        assert_eq!(1, PackedValues::<2>::BITS);
+        assert_eq!(2, PackedValues::<3>::BITS);
+        assert_eq!(2, PackedValues::<4>::BITS);
+        assert_eq!(4, PackedValues::<5>::BITS);
+        assert_eq!(8, PackedValues::<255>::BITS);
+    }
+
+    #[test]
+    fn set_and_get() {
+        let mut p = PackedValues::<3>::new(100);
+
+        assert_eq!(100, p.len());
+        assert!(p.iter().all(|v| v == 0));
+
+        for i in 0..100 {
+            p.set(i, u8::try_from(i % 3).unwrap()).unwrap();
+        }
+
+        for i in 0..100 {
+            assert_eq!(Some(u8::try_from(i % 3).unwrap()), p.get(i));
+        }
+
+        p.set(31, 0).unwrap();
+        assert_eq!(Some(0), p.get(31));
+        assert_eq!(Some(2), p.get(32));
+        assert_eq!(None, p.get(100));
+    }
+
+    #[test]
+    fn set_rejects_bad_values() {
+        let mut p = PackedValues::<3>::new(10);
+
+        assert!(p.set(0, 3).is_err());
+        assert!(p.set(10, 0).is_err());
+    }
+
+    #[test]
+    fn binary_bytes_are_lsb_first() {
+        let mut p = PackedValues::<2>::new(10);
+
+        p.set(0, 1).unwrap();
+        p.set(2, 1).unwrap();
+        p.set(9, 1).unwrap();
+
+        assert_eq!(vec![0x05u8, 0x02], p.to_le_bytes());
+    }
+
+    #[test]
+    fn from_le_bytes_checks_length_and_values() {
+        assert!(PackedValues::<2>::from_le_bytes(&[0x05, 0x02], 10).is_ok());
+        assert!(PackedValues::<2>::from_le_bytes(&[0x05], 10).is_err());
+        assert!(PackedValues::<2>::from_le_bytes(&[0x05, 0x02, 0x00], 10).is_err());
+        assert!(PackedValues::<3>::from_le_bytes(&[0x03], 4).is_err());
+    }
+
+    #[test]
+    fn from_le_bytes_ignores_padding() {
+        assert_eq!(
+            PackedValues::<2>::from_le_bytes(&[0x05, 0x02], 10).unwrap(),
+            PackedValues::<2>::from_le_bytes(&[0x05, 0xfe], 10).unwrap()
+        );
+    }
+
+    #[test]
+    fn prefix() {
+        let mut p = PackedValues::<2>::new(130);
+
+        for i in 0..130 {
+            p.set(i, 1).unwrap();
+        }
+
+        let short = p.prefix(65).unwrap();
+        assert_eq!(65, short.len());
+        assert!(short.iter().all(|v| v == 1));
+        assert_eq!(65, short.iter().count());
+        assert!(p.prefix(131).is_none());
+    }
+
+    quickcheck! {
+        fn roundtrip(vals: Vec<u8>) -> bool {
+            let mut p = PackedValues::<3>::new(vals.len());
+
+            for (i, v) in vals.iter().enumerate() {
+                p.set(i, v % 3).unwrap();
+            }
+
+            let q = PackedValues::<3>::from_le_bytes(&p.to_le_bytes(), vals.len()).unwrap();
+
+            p == q && vals.iter().zip(q.iter()).all(|(a, b)| a % 3 == b)
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//! A textual name for each cipher configuration.
+//!
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::ciphersuite::ListedCipherSuite;
+use crate::cmp::Comparator;
+use crate::suites;
+use crate::Error;
+
+/// A description of everything that determines whether two ciphertexts can be compared: the
+/// ciphersuite, the kind of comparison, the number of blocks (`N`) and the block width (`W`).
+///
+/// Everything that is described by a `Params` is also baked into the type of a
+/// [`Cipher`](crate::Cipher), which is great for catching mistakes at compile time, but not so
+/// great for putting in a configuration file.  A `Params` has a canonical textual form, such as
+/// `aes128v1-ore/8x256` (for an `aes128v1::ore::Cipher::<8, 256>`), which will never change, and
+/// so is safe to store alongside ciphertexts, or write in a configuration file.
+///
+/// Once the configuration has been parsed, it can be checked against the cipher type that the
+/// code expects with [`Cipher::from_params`](crate::Cipher::from_params).
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::aes128v1::ore;
+/// use cretrit::Params;
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// # let key = [0u8; 32];
+/// let params: Params = "aes128v1-ore/8x256".parse()?;
+///
+/// let cipher = ore::Cipher::<8, 256>::from_params(&params, &key)?;
+/// assert_eq!("aes128v1-ore/8x256", cipher.params().to_string());
+///
+/// // Wrong number of blocks
+/// assert!(ore::Cipher::<4, 256>::from_params(&params, &key).is_err());
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Params {
+    /// The name of the ciphersuite
+    suite: &'static str,
+
+    /// The name of the comparator
+    comparator: &'static str,
+
+    /// How many blocks in each plaintext (`N`)
+    blocks: usize,
+
+    /// How many distinct values in each block (`W`)
+    width: u16,
+}
+
+impl Params {
+    /// Put together a set of parameters from its parts
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::ParamsError`] if the ciphersuite or comparator isn't one that this
+    /// build of cretrit knows about (see [`suites()`](crate::suites)), if there are no blocks, or
+    /// if the block width is less than two.
+    ///
+    pub fn new(suite: &str, comparator: &str, blocks: usize, width: u16) -> Result<Self, Error> {
+        let invalid = |why: String| Error::ParamsError(why);
+
+        let descriptor = suites::find(suite)
+            .ok_or_else(|| invalid(format!("{suite:?} does not name a known ciphersuite")))?;
+        let cmp_descriptor = descriptor
+            .comparator(comparator)
+            .ok_or_else(|| invalid(format!("{comparator:?} does not name a known comparator")))?;
+
+        if blocks == 0 {
+            return Err(invalid("there must be at least one block".to_string()));
+        }
+
+        if width < 2 {
+            return Err(invalid(format!(
+                "block width must be at least two (got {width})"
+            )));
+        }
+
+        Ok(Params {
+            suite: descriptor.name,
+            comparator: cmp_descriptor.name,
+            blocks,
+            width,
+        })
+    }
+
+    /// The parameters of a cipher with the given types
+    pub(crate) fn of<
+        S: ListedCipherSuite<W, M>,
+        CMP: Comparator<M>,
+        const N: usize,
+        const W: u16,
+        const M: u8,
+    >() -> Self {
+        Params {
+            suite: S::NAME,
+            comparator: CMP::NAME,
+            blocks: N,
+            width: W,
+        }
+    }
+
+    /// Make sure these parameters describe a cipher with the given types
+    pub(crate) fn check<
+        S: ListedCipherSuite<W, M>,
+        CMP: Comparator<M>,
+        const N: usize,
+        const W: u16,
+        const M: u8,
+    >(
+        &self,
+    ) -> Result<(), Error> {
+        let expected = Self::of::<S, CMP, N, W, M>();
+
+        if *self == expected {
+            Ok(())
+        } else {
+            Err(Error::ParamsError(format!(
+                "{self} cannot be used with a cipher expecting {expected}"
+            )))
+        }
+    }
+
+    /// The name of the ciphersuite, such as `aes128v1`
+    #[must_use]
+    pub fn suite(&self) -> &'static str {
+        self.suite
+    }
+
+    /// The name of the kind of comparison, such as `ore` (order-revealing) or `ere`
+    /// (equality-revealing)
+    #[must_use]
+    pub fn comparator(&self) -> &'static str {
+        self.comparator
+    }
+
+    /// The number of blocks in each plaintext (`N`)
+    #[must_use]
+    pub fn blocks(&self) -> usize {
+        self.blocks
+    }
+
+    /// The number of distinct values in each block (`W`)
+    #[must_use]
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+}
+
+impl fmt::Display for Params {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}/{}x{}",
+            self.suite, self.comparator, self.blocks, self.width
+        )
+    }
+}
+
+impl FromStr for Params {
+    type Err = Error;
+
+    /// Parse the canonical form of a set of parameters
+    ///
+    /// Only the exact form produced by `Display` is accepted, so there's never more than one way
+    /// to write the same parameters.
+    ///
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |why: &str| Error::ParamsError(format!("{s:?} {why}"));
+
+        let (scheme, shape) = s
+            .split_once('/')
+            .ok_or_else(|| invalid("is not of the form <suite>-<comparator>/<N>x<W>"))?;
+        let (suite, comparator) = scheme
+            .split_once('-')
+            .ok_or_else(|| invalid("is not of the form <suite>-<comparator>/<N>x<W>"))?;
+        let (blocks, width) = shape
+            .split_once('x')
+            .ok_or_else(|| invalid("is not of the form <suite>-<comparator>/<N>x<W>"))?;
+
+        let params = Params::new(
+            suite,
+            comparator,
+            blocks
+                .parse()
+                .map_err(|e| invalid(&format!("has an invalid block count ({e})")))?,
+            width
+                .parse()
+                .map_err(|e| invalid(&format!("has an invalid block width ({e})")))?,
+        )?;
+
+        // Catches things like leading zeroes or a leading "+", which the integer parsers are
+        // quite happy to accept
+        if params.to_string() != s {
+            return Err(invalid("is not in canonical form"));
+        }
+
+        Ok(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes128v1::{ere, ore};
+    use crate::util::random_key;
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            "aes128v1-ore/8x256",
+            ore::Cipher::<8, 256>::new(&random_key())
+                .unwrap()
+                .params()
+                .to_string()
+        );
+        assert_eq!(
+            "aes128v1-ere/16x16",
+            ere::Cipher::<16, 16>::new(&random_key())
+                .unwrap()
+                .params()
+                .to_string()
+        );
+        assert_eq!(
+            "aes256v1-ore/4x16",
+            crate::aes256v1::ore::Cipher::<4, 16>::new(&random_key())
+                .unwrap()
+                .params()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn parse() {
+        let p: Params = "aes128v1-ere/4x256".parse().unwrap();
+
+        assert_eq!("aes128v1", p.suite());
+        assert_eq!("ere", p.comparator());
+        assert_eq!(4, p.blocks());
+        assert_eq!(256, p.width());
+    }
+
+    #[test]
+    fn parse_garbage() {
+        for s in [
+            "",
+            "aes128v1-ore",
+            "aes128v1-ore/8",
+            "aes128v1/8x256",
+            "aes512v1-ore/8x256",
+            "aes128v1-ope/8x256",
+            "aes128v1-ore/0x256",
+            "aes128v1-ore/8x1",
+            "aes128v1-ore/8x65536",
+            "aes128v1-ore/08x256",
+            "aes128v1-ore/+8x256",
+            "aes128v1-ore/8x256 ",
+            "AES128v1-ORE/8x256",
+        ] {
+            assert!(
+                matches!(s.parse::<Params>(), Err(Error::ParamsError(_))),
+                "{s:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn from_params() {
+        let params: Params = "aes128v1-ore/4x256".parse().unwrap();
+
+        assert!(ore::Cipher::<4, 256>::from_params(&params, &random_key()).is_ok());
+        assert!(ore::Cipher::<4, 16>::from_params(&params, &random_key()).is_err());
+        assert!(ore::Cipher::<8, 256>::from_params(&params, &random_key()).is_err());
+        assert!(ere::Cipher::<4, 256>::from_params(&params, &random_key()).is_err());
+    }
+
+    quickcheck! {
+        fn roundtrip(ore: bool, blocks: usize, width: u16) -> bool {
+            let p = Params {
+                suite: "aes128v1",
+                comparator: if ore { "ore" } else { "ere" },
+                blocks: blocks.saturating_add(1),
+                width: width.max(2),
+            };
+
+            p.to_string().parse::<Params>().unwrap() == p
+        }
+    }
+}
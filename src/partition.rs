@@ -0,0 +1,315 @@
+//! Splitting the domain of order-revealing ciphertexts into shards.
+//!
+//! When a table is sharded by an encrypted key, every writer needs to be able to tell which
+//! shard a value belongs in, without being able to decrypt it.  With order-revealing encryption,
+//! this can be done by comparing the value against a set of encrypted boundaries, which can be
+//! generated once, serialized, and handed out to everyone who needs to route values.
+//!
+
+use crate::ciphersuite::CipherSuite;
+use crate::ciphertext::Serializable;
+use crate::cmp::OrderingCMP;
+use crate::histogram::Histogram;
+use crate::{Cipher, CipherText, Error, PlainText};
+
+/// A division of the plaintext domain into contiguous shards, defined by their (encrypted)
+/// boundaries.
+///
+/// A partition with `k` boundaries has `k + 1` shards.  Shard `0` holds all values less than the
+/// smallest boundary, shard `i` holds all values greater than or equal to the `i`th smallest
+/// boundary, but less than the `i+1`th, and shard `k` holds all values greater than or equal to
+/// the largest boundary.
+///
+/// The boundaries are full ciphertexts, and so (like those in a [`RangeQuery`](crate::RangeQuery))
+/// they reveal the order of the values they are compared against.  Anyone holding the boundary
+/// tokens can tell which shard a value is in, which is the whole point, but that also means they
+/// shouldn't be handed out to anyone who doesn't need to do the routing.
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::aes128v1::ore;
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// # let key = [0u8; 32];
+/// let cipher = ore::Cipher::<2, 256>::new(&key)?;
+///
+/// // Four equally-sized shards, covering the whole range of a u16
+/// let partition = ore::Partition::<2, 256>::uniform(&cipher, 4)?;
+/// let tokens = partition.tokens()?;
+///
+/// // ... send the tokens off to wherever the routing happens ...
+///
+/// let router = ore::Partition::<2, 256>::from_tokens(&tokens)?;
+/// let value = cipher.right_encrypt(&40_000u16.try_into()?)?;
+///
+/// assert_eq!(2, router.shard_of(&value)?);
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct Partition<S: CipherSuite<W, 3>, const N: usize, const W: u16> {
+    /// The boundaries, which are really just histogram bucket boundaries by another name
+    boundaries: Histogram<S, N, W>,
+}
+
+impl<S: CipherSuite<W, 3>, const N: usize, const W: u16> Partition<S, N, W> {
+    /// Create a partition with the given plaintext boundaries.
+    ///
+    /// The boundaries can be given in any order.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if any of the underlying cryptographic operations can't complete, or if
+    /// there's a bug somewhere.
+    ///
+    pub fn new(
+        cipher: &Cipher<S, OrderingCMP, N, W, 3>,
+        boundaries: &[PlainText<N, W>],
+    ) -> Result<Self, Error> {
+        Ok(Partition {
+            boundaries: Histogram::new(
+                boundaries
+                    .iter()
+                    .map(|b| cipher.full_encrypt(b))
+                    .collect::<Result<Vec<_>, _>>()?,
+            )?,
+        })
+    }
+
+    /// Create a partition that splits the entire plaintext domain into `shards` equally-sized
+    /// shards.
+    ///
+    /// The `i`th boundary is `floor(i * W^N / shards)`, so when the domain doesn't divide evenly,
+    /// the earlier shards are the ones that come up short.  Asking for more shards than there are
+    /// values in the domain will produce some duplicate boundaries, and hence some shards that can
+    /// never have anything in them.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if `shards` is zero, or if any of the underlying cryptographic
+    /// operations can't complete.
+    ///
+    pub fn uniform(cipher: &Cipher<S, OrderingCMP, N, W, 3>, shards: usize) -> Result<Self, Error> {
+        if shards == 0 {
+            return Err(Error::RangeError(
+                "cannot partition a domain into zero shards".to_string(),
+            ));
+        }
+
+        let boundaries = (1..shards)
+            .map(|i| uniform_boundary::<N, W>(i, shards))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::new(cipher, &boundaries)
+    }
+
+    /// Reconstitute a partition from the tokens produced by [`tokens()`](Self::tokens).
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if any of the tokens can't be parsed, or isn't a full ciphertext.
+    ///
+    pub fn from_tokens<T: AsRef<[u8]>>(tokens: &[T]) -> Result<Self, Error> {
+        Ok(Partition {
+            boundaries: Histogram::new(
+                tokens
+                    .iter()
+                    .map(|t| CipherText::<S, OrderingCMP, N, W, 3>::from_slice(t.as_ref()))
+                    .collect::<Result<Vec<_>, _>>()?,
+            )?,
+        })
+    }
+
+    /// The serialized boundaries of the partition, in ascending order.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if serialization fails, which should only happen if there's a bug
+    /// somewhere.
+    ///
+    pub fn tokens(&self) -> Result<Vec<Vec<u8>>, Error> {
+        self.boundaries
+            .boundaries()
+            .iter()
+            .map(Serializable::to_vec)
+            .collect()
+    }
+
+    /// The number of shards in the partition
+    ///
+    /// This is always one more than the number of boundaries.
+    ///
+    #[must_use]
+    pub fn shard_count(&self) -> usize {
+        self.boundaries.bucket_count()
+    }
+
+    /// Determine which shard the given ciphertext belongs in
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if a comparison fails, which should only happen if there's a bug
+    /// somewhere.
+    ///
+    pub fn shard_of(&self, value: &CipherText<S, OrderingCMP, N, W, 3>) -> Result<usize, Error> {
+        self.boundaries.bucket_of(value)
+    }
+}
+
+/// Calculate `floor(i * W^N / shards)`, as a plaintext
+///
+/// `W^N` can easily be too big for any integer type we've got, so this is done as long division,
+/// one block at a time, with `i` (which is always less than `shards`) as the starting remainder.
+///
+fn uniform_boundary<const N: usize, const W: u16>(
+    i: usize,
+    shards: usize,
+) -> Result<PlainText<N, W>, Error> {
+    let divisor = u128::try_from(shards)
+        .map_err(|e| Error::RangeError(format!("cannot represent {shards} as u128 ({e})")))?;
+    let mut remainder = u128::try_from(i)
+        .map_err(|e| Error::RangeError(format!("cannot represent {i} as u128 ({e})")))?;
+    let mut blocks = [0u16; N];
+
+    for block in &mut blocks {
+        let (digit, rem) = num::Integer::div_rem(
+            &remainder.checked_mul(u128::from(W)).ok_or_else(|| {
+                Error::InternalError(format!("overflow calculating boundary {i} of {shards}"))
+            })?,
+            &divisor,
+        );
+        *block = u16::try_from(digit).map_err(|e| {
+            Error::InternalError(format!(
+                "boundary {i} of {shards} has a block value of {digit} ({e})"
+            ))
+        })?;
+        remainder = rem;
+    }
+
+    Ok(PlainText::new(blocks))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes128v1::ore;
+    use crate::util::random_key;
+
+    fn shard(cipher: &ore::Cipher<2, 256>, p: &ore::Partition<2, 256>, v: u16) -> usize {
+        p.shard_of(&cipher.right_encrypt(&v.try_into().unwrap()).unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn uniform_boundaries() {
+        let b = |i, k| {
+            uniform_boundary::<2, 256>(i, k).unwrap().block(0).unwrap() << 8
+                | uniform_boundary::<2, 256>(i, k).unwrap().block(1).unwrap()
+        };
+
+        assert_eq!(0x4000, b(1, 4));
+        assert_eq!(0x8000, b(2, 4));
+        assert_eq!(0xc000, b(3, 4));
+        assert_eq!(21_845, b(1, 3));
+        assert_eq!(43_690, b(2, 3));
+    }
+
+    #[test]
+    fn uniform_boundaries_for_huge_domains() {
+        let p = uniform_boundary::<16, 256>(1, 2).unwrap();
+
+        assert_eq!(128, p.block(0).unwrap());
+        assert!((1..16).all(|n| p.block(n).unwrap() == 0));
+    }
+
+    #[test]
+    fn uniform_partition() {
+        let cipher = ore::Cipher::<2, 256>::new(&random_key()).unwrap();
+        let p = ore::Partition::<2, 256>::uniform(&cipher, 4).unwrap();
+
+        assert_eq!(4, p.shard_count());
+        assert_eq!(0, shard(&cipher, &p, 0));
+        assert_eq!(0, shard(&cipher, &p, 0x3fff));
+        assert_eq!(1, shard(&cipher, &p, 0x4000));
+        assert_eq!(2, shard(&cipher, &p, 0xbfff));
+        assert_eq!(3, shard(&cipher, &p, 0xc000));
+        assert_eq!(3, shard(&cipher, &p, u16::MAX));
+    }
+
+    #[test]
+    fn one_shard_has_no_boundaries() {
+        let cipher = ore::Cipher::<2, 256>::new(&random_key()).unwrap();
+        let p = ore::Partition::<2, 256>::uniform(&cipher, 1).unwrap();
+
+        assert_eq!(1, p.shard_count());
+        assert!(p.tokens().unwrap().is_empty());
+        assert_eq!(0, shard(&cipher, &p, 42));
+    }
+
+    #[test]
+    fn zero_shards_is_an_error() {
+        let cipher = ore::Cipher::<2, 256>::new(&random_key()).unwrap();
+
+        assert!(ore::Partition::<2, 256>::uniform(&cipher, 0).is_err());
+    }
+
+    #[test]
+    fn explicit_boundaries_in_any_order() {
+        let cipher = ore::Cipher::<2, 256>::new(&random_key()).unwrap();
+        let p = ore::Partition::<2, 256>::new(
+            &cipher,
+            &[1000u16.try_into().unwrap(), 10u16.try_into().unwrap()],
+        )
+        .unwrap();
+
+        assert_eq!(3, p.shard_count());
+        assert_eq!(0, shard(&cipher, &p, 9));
+        assert_eq!(1, shard(&cipher, &p, 10));
+        assert_eq!(1, shard(&cipher, &p, 999));
+        assert_eq!(2, shard(&cipher, &p, 1000));
+    }
+
+    #[test]
+    fn tokens_are_ordered_and_roundtrip() {
+        let cipher = ore::Cipher::<2, 256>::new(&random_key()).unwrap();
+        let p = ore::Partition::<2, 256>::new(
+            &cipher,
+            &[
+                300u16.try_into().unwrap(),
+                10u16.try_into().unwrap(),
+                20u16.try_into().unwrap(),
+            ],
+        )
+        .unwrap();
+
+        let tokens = p.tokens().unwrap();
+        let cts: Vec<_> = tokens
+            .iter()
+            .map(|t| ore::CipherText::<2, 256>::from_slice(t).unwrap())
+            .collect();
+
+        assert!(cts
+            .windows(2)
+            .all(|w| w[0].try_cmp(&w[1]).unwrap() == std::cmp::Ordering::Less));
+
+        let q = ore::Partition::<2, 256>::from_tokens(&tokens).unwrap();
+
+        for v in [0u16, 10, 15, 20, 299, 300, u16::MAX] {
+            assert_eq!(shard(&cipher, &p, v), shard(&cipher, &q, v), "value {v}");
+        }
+    }
+
+    #[test]
+    fn right_only_tokens_are_rejected() {
+        let cipher = ore::Cipher::<2, 256>::new(&random_key()).unwrap();
+        let token = cipher
+            .right_encrypt(&42u16.try_into().unwrap())
+            .unwrap()
+            .to_vec()
+            .unwrap();
+
+        assert!(ore::Partition::<2, 256>::from_tokens(&[token]).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+//! Deriving cipher keys from passphrases.
+//!
+//! A passphrase is a lousy key: it's too short, too predictable, and too easy to guess at scale.
+//! Argon2id turns it into something usable, by making every guess expensive (in both time and
+//! memory), and by mixing in a salt so that the same passphrase used in two places doesn't produce
+//! the same key.
+//!
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use zeroize::Zeroizing;
+
+use crate::Error;
+
+/// The shortest salt we're willing to accept
+///
+/// 16 bytes is the length recommended by the Argon2 RFC (RFC9106, section 3.1).
+///
+pub const MIN_SALT_LENGTH: usize = 16;
+
+/// How much work Argon2id should do when turning a passphrase into a key
+///
+/// The defaults are the minimum recommended by OWASP for Argon2id (19 MiB of memory, 2
+/// iterations, 1 degree of parallelism).  If you can afford to make key derivation slower, you
+/// should increase them; the only downside is that every party that needs to derive the key will
+/// have to wait longer to do so.
+///
+/// Changing any of these parameters changes the derived key, so whatever values you use need to be
+/// stored alongside the salt.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PassphraseParams {
+    /// How much memory to use, in KiB
+    pub memory_kib: u32,
+    /// How many passes to make over that memory
+    pub iterations: u32,
+    /// How many lanes of computation to use
+    pub parallelism: u32,
+}
+
+impl PassphraseParams {
+    /// Create a set of parameters with the given memory size (in KiB), iteration count, and
+    /// degree of parallelism
+    #[must_use]
+    pub fn new(memory_kib: u32, iterations: u32, parallelism: u32) -> Self {
+        PassphraseParams {
+            memory_kib,
+            iterations,
+            parallelism,
+        }
+    }
+}
+
+impl Default for PassphraseParams {
+    fn default() -> Self {
+        PassphraseParams::new(19 * 1024, 2, 1)
+    }
+}
+
+/// Turn a passphrase and salt into a root key for a [`Cipher`](crate::Cipher)
+///
+/// # Errors
+///
+/// Will return an error if the salt is shorter than [`MIN_SALT_LENGTH`], if the parameters aren't
+/// acceptable to Argon2, or if the derivation itself fails.
+///
+pub(crate) fn derive_key(
+    passphrase: &[u8],
+    salt: &[u8],
+    params: &PassphraseParams,
+) -> Result<Zeroizing<[u8; 32]>, Error> {
+    if salt.len() < MIN_SALT_LENGTH {
+        return Err(Error::KeyError(format!(
+            "passphrase salt must be at least {MIN_SALT_LENGTH} bytes long (got {})",
+            salt.len()
+        )));
+    }
+
+    let argon_params = Params::new(
+        params.memory_kib,
+        params.iterations,
+        params.parallelism,
+        Some(32),
+    )
+    .map_err(|e| Error::KeyError(format!("invalid passphrase parameters ({e})")))?;
+
+    let mut key = Zeroizing::new([0u8; 32]);
+
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, argon_params)
+        .hash_password_into(passphrase, salt, &mut *key)
+        .map_err(|e| Error::KeyError(format!("failed to derive key from passphrase ({e})")))?;
+
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes128v1::ore;
+
+    /// Real parameters take too long for a test suite, so we use the smallest ones that'll work
+    fn params() -> PassphraseParams {
+        PassphraseParams::new(8, 1, 1)
+    }
+
+    #[test]
+    fn same_passphrase_and_salt_gives_same_key() {
+        let salt = b"sodium chloride!";
+
+        assert_eq!(
+            derive_key(b"hunter2", salt, &params()).unwrap(),
+            derive_key(b"hunter2", salt, &params()).unwrap()
+        );
+    }
+
+    #[test]
+    fn different_salts_give_different_keys() {
+        assert_ne!(
+            derive_key(b"hunter2", b"sodium chloride!", &params()).unwrap(),
+            derive_key(b"hunter2", b"potassium iodide", &params()).unwrap()
+        );
+    }
+
+    #[test]
+    fn different_params_give_different_keys() {
+        let salt = b"sodium chloride!";
+
+        assert_ne!(
+            derive_key(b"hunter2", salt, &params()).unwrap(),
+            derive_key(b"hunter2", salt, &PassphraseParams::new(8, 2, 1)).unwrap()
+        );
+    }
+
+    #[test]
+    fn short_salts_are_rejected() {
+        assert!(derive_key(b"hunter2", b"NaCl", &params()).is_err());
+    }
+
+    #[test]
+    fn silly_params_are_rejected() {
+        assert!(derive_key(
+            b"hunter2",
+            b"sodium chloride!",
+            &PassphraseParams::new(8, 0, 1)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn ciphers_from_the_same_passphrase_can_compare() {
+        let salt = b"sodium chloride!";
+        let c1 = ore::Cipher::<4, 256>::from_passphrase(b"hunter2", salt, &params()).unwrap();
+        let c2 = ore::Cipher::<4, 256>::from_passphrase(b"hunter2", salt, &params()).unwrap();
+
+        let a = c1.full_encrypt(&42u32.try_into().unwrap()).unwrap();
+        let b = c2.right_encrypt(&9001u32.try_into().unwrap()).unwrap();
+
+        assert!(a < b);
+    }
+}
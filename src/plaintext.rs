@@ -30,7 +30,7 @@ use crate::Error;
 /// # }
 /// ```
 ///
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PlainText<const N: usize, const W: u16>([u16; N]);
 
 impl<const N: usize, const W: u16> PlainText<N, W> {
@@ -40,6 +40,370 @@ impl<const N: usize, const W: u16> PlainText<N, W> {
         PlainText(a)
     }
 
+    /// Whether every `bits`-bit unsigned integer can be represented in this plaintext; that is,
+    /// whether `W^N >= 2^bits`
+    ///
+    /// Any out-of-range arithmetic in here happens at compile time, and so is a compile error,
+    /// rather than a runtime surprise.
+    ///
+    #[allow(clippy::arithmetic_side_effects)]
+    const fn holds_bits(bits: u32) -> bool {
+        let mut capacity: u128 = 1;
+        let mut n = 0;
+
+        while n < N {
+            match capacity.checked_mul(W as u128) {
+                Some(c) => capacity = c,
+                // Too big for a u128, which is big enough for anything
+                None => return true,
+            }
+            n += 1;
+        }
+
+        bits < 128 && capacity >= 1u128 << bits
+    }
+
+    /// Parse a plaintext from a non-negative decimal integer, such as `"42"` or `"+9001"`.
+    ///
+    /// The number is converted straight into blocks, without going through any fixed-width
+    /// integer type along the way, so any value that fits in the plaintext can be parsed, no
+    /// matter how big it is.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::NumberError`] if the string isn't a decimal integer (optionally
+    /// preceded by a `+`), and [`Error::RangeError`] if the value is too large to be represented
+    /// in this plaintext.
+    ///
+    pub fn parse_uint(s: &str) -> Result<Self, Error> {
+        Ok(PlainText(Self::parse_magnitude(
+            s.strip_prefix('+').unwrap_or(s),
+            s,
+        )?))
+    }
+
+    /// Parse a plaintext from a (possibly negative) decimal integer, such as `"-42"`.
+    ///
+    /// So that negative numbers sort before positive ones, the value is stored offset by half the
+    /// size of the plaintext domain, `floor(W^N / 2)`; this means that the range of values which
+    /// can be parsed is `-floor(W^N / 2)` up to `ceil(W^N / 2) - 1`.  When `W^N` is a power of two,
+    /// this is the same encoding as flipping the sign bit of a two's complement integer, so
+    /// `PlainText::<4, 256>::parse_int("-1")` is the same plaintext as `0x7fff_ffff_u32`.
+    ///
+    /// Plaintexts produced by this function are *not* comparable with those produced by
+    /// [`parse_uint()`](Self::parse_uint) or the unsigned integer conversions; pick one encoding
+    /// for a given column of data, and stick with it.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::NumberError`] if the string isn't a decimal integer (optionally
+    /// preceded by a `+` or `-`), and [`Error::RangeError`] if the value is outside the range that
+    /// can be represented in this plaintext.
+    ///
+    pub fn parse_int(s: &str) -> Result<Self, Error> {
+        let out_of_range = || {
+            Error::RangeError(format!(
+                "Could not represent {s} in signed PlainText<{N}, {W}>"
+            ))
+        };
+
+        let offset = Self::half_domain()?;
+
+        let blocks = if let Some(digits) = s.strip_prefix('-') {
+            sub_blocks::<N, W>(&offset, &Self::parse_magnitude(digits, s)?)
+        } else {
+            add_blocks::<N, W>(
+                &offset,
+                &Self::parse_magnitude(s.strip_prefix('+').unwrap_or(s), s)?,
+            )
+        };
+
+        Ok(PlainText(blocks.ok_or_else(out_of_range)?))
+    }
+
+    /// Make a plaintext out of a signed integer, using the same offset encoding as
+    /// [`parse_int()`](Self::parse_int)
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::RangeError`] if the value is outside the range that can be represented
+    /// in this plaintext.
+    ///
+    #[cfg(any(feature = "chrono", feature = "rust_decimal", feature = "time"))]
+    pub(crate) fn from_signed(value: i128) -> Result<Self, Error> {
+        let out_of_range = || {
+            Error::RangeError(format!(
+                "Could not represent {value} in signed PlainText<{N}, {W}>"
+            ))
+        };
+
+        let offset = Self::half_domain()?;
+        let magnitude = PlainText::<N, W>::try_from(value.unsigned_abs())?.0;
+
+        let blocks = if value < 0 {
+            sub_blocks::<N, W>(&offset, &magnitude)
+        } else {
+            add_blocks::<N, W>(&offset, &magnitude)
+        };
+
+        Ok(PlainText(blocks.ok_or_else(out_of_range)?))
+    }
+
+    /// Make a plaintext out of a decimal number, such as an amount of money, with a fixed number
+    /// of decimal places.
+    ///
+    /// The value is multiplied by `10^scale`, so that it becomes an integer number of the smallest
+    /// unit of interest (cents, say, with a `scale` of 2), and that integer is then encoded the same
+    /// way as [`parse_int()`](Self::parse_int) would, so negative amounts sort before positive
+    /// ones.  Every value in a given column has to be converted with the same `scale`, or the
+    /// plaintexts won't compare correctly.
+    ///
+    /// Values with more decimal places than `scale` are rejected, rather than being rounded, since
+    /// quietly losing a fraction of a cent is rarely what anyone wants.  Trailing zeroes don't
+    /// count, so `1.500` can be converted with a `scale` of 1.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::NumberError`] if `value` has more significant decimal places than
+    /// `scale`, and [`Error::RangeError`] if the scaled value is outside the range that can be
+    /// represented in this plaintext.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::ore;
+    /// use cretrit::PlainText;
+    /// use rust_decimal::Decimal;
+    /// use std::cmp::Ordering;
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let key = [0u8; 32];
+    ///
+    /// let cipher = ore::Cipher::<8, 256>::new(&key)?;
+    ///
+    /// let refund = cipher.full_encrypt(&PlainText::from_decimal(Decimal::new(-1250, 2), 2)?)?;
+    /// let coffee = cipher.full_encrypt(&PlainText::from_decimal(Decimal::new(45, 1), 2)?)?;
+    ///
+    /// assert_eq!(Ordering::Less, refund.try_cmp(&coffee)?);
+    ///
+    /// assert!(PlainText::<8, 256>::from_decimal(Decimal::new(1, 3), 2).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    #[cfg(feature = "rust_decimal")]
+    pub fn from_decimal(value: rust_decimal::Decimal, scale: u32) -> Result<Self, Error> {
+        let normalized = value.normalize();
+
+        let extra_places = scale.checked_sub(normalized.scale()).ok_or_else(|| {
+            Error::NumberError(format!("{value} has more than {scale} decimal places"))
+        })?;
+
+        let scaled = 10i128
+            .checked_pow(extra_places)
+            .and_then(|multiplier| normalized.mantissa().checked_mul(multiplier))
+            .ok_or_else(|| {
+                Error::RangeError(format!(
+                    "Could not represent {value} at scale {scale} in PlainText<{N}, {W}>"
+                ))
+            })?;
+
+        Self::from_signed(scaled)
+    }
+
+    /// Make a plaintext out of a short string of bytes (or a `str`), such that plaintexts sort in
+    /// the same order as the byte strings they were made from.
+    ///
+    /// The plaintext holds a fixed number of bytes; values shorter than that are padded out at the
+    /// end with copies of `padding`.  Each byte takes up one block if `W` is 256, or is split,
+    /// most significant bits first, across two blocks if `W` is 16, four if it is 4, or eight if
+    /// it is 2, so a `PlainText<8, 256>` or a `PlainText<16, 16>` both hold eight bytes.
+    ///
+    /// A `padding` of zero gives plain lexicographic ordering (`"ab" < "abc" < "b"`), as long as
+    /// the values don't themselves contain zero bytes, which would be indistinguishable from the
+    /// padding.  A `padding` of `b' '` gives the ordering of an SQL `CHAR` column, where trailing
+    /// spaces are ignored.  Since UTF-8 strings sort bytewise in codepoint order, `str` values can
+    /// be passed straight in, as long as codepoint order is what you want; there's no attempt at
+    /// any sort of locale-aware collation.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::RangeError`] if `bytes` is longer than the plaintext can hold, and
+    /// [`Error::ParamsError`] if `W` isn't one of 2, 4, 16, or 256, or `N` isn't a whole number of
+    /// bytes' worth of blocks.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cretrit::aes128v1::ore;
+    /// use cretrit::PlainText;
+    /// use std::cmp::Ordering;
+    /// # fn main() -> Result<(), cretrit::Error> {
+    /// # let key = [0u8; 32];
+    ///
+    /// let cipher = ore::Cipher::<16, 256>::new(&key)?;
+    ///
+    /// let alice = cipher.full_encrypt(&PlainText::from_bytes("alice", 0)?)?;
+    /// let alicia = cipher.full_encrypt(&PlainText::from_bytes("alicia", 0)?)?;
+    /// let bob = cipher.full_encrypt(&PlainText::from_bytes("bob", 0)?)?;
+    ///
+    /// assert_eq!(Ordering::Less, alice.try_cmp(&alicia)?);
+    /// assert_eq!(Ordering::Less, alicia.try_cmp(&bob)?);
+    ///
+    /// assert!(PlainText::<16, 256>::from_bytes("a_very_long_username", 0).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    pub fn from_bytes(bytes: impl AsRef<[u8]>, padding: u8) -> Result<Self, Error> {
+        let (bits, blocks_per_byte) = Self::byte_layout()?;
+        let value = bytes.as_ref();
+        let capacity = N.checked_div(blocks_per_byte).unwrap_or(0);
+
+        if value.len() > capacity {
+            return Err(Error::RangeError(format!(
+                "Could not fit {} bytes into PlainText<{N}, {W}>, which holds {capacity}",
+                value.len()
+            )));
+        }
+
+        let mask = W.saturating_sub(1);
+        let mut blocks = [0u16; N];
+
+        for (chunk, byte) in blocks
+            .chunks_mut(blocks_per_byte)
+            .zip(value.iter().copied().chain(std::iter::repeat(padding)))
+        {
+            let mut remaining = u16::from(byte);
+
+            for block in chunk.iter_mut().rev() {
+                *block = remaining & mask;
+                remaining = remaining.checked_shr(bits).unwrap_or(0);
+            }
+        }
+
+        Ok(PlainText(blocks))
+    }
+
+    /// Turn a plaintext made by [`from_bytes()`](Self::from_bytes) back into bytes.
+    ///
+    /// All the bytes the plaintext can hold are returned, including any padding; since a padding
+    /// byte looks just like any other byte, it's up to the caller to strip it off, if need be.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::ParamsError`] in the same circumstances as
+    /// [`from_bytes()`](Self::from_bytes), and [`Error::RangeError`] if a block is too big for
+    /// `W`.
+    ///
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let (bits, blocks_per_byte) = Self::byte_layout()?;
+
+        self.0
+            .chunks(blocks_per_byte)
+            .map(|chunk| {
+                let byte = chunk.iter().try_fold(0u16, |acc, block| {
+                    if *block >= W {
+                        return Err(Error::RangeError(format!(
+                            "Block value {block} is out of range for PlainText<{N}, {W}>"
+                        )));
+                    }
+                    Ok(acc.checked_shl(bits).unwrap_or(0) | *block)
+                })?;
+
+                u8::try_from(byte).map_err(|e| {
+                    Error::InternalError(format!("Couldn't represent {byte} as u8 ({e})"))
+                })
+            })
+            .collect()
+    }
+
+    /// How many bits of a byte go in each block, and how many blocks each byte takes up, for
+    /// [`from_bytes()`](Self::from_bytes) and [`to_bytes()`](Self::to_bytes)
+    fn byte_layout() -> Result<(u32, usize), Error> {
+        let (bits, blocks_per_byte) = match W {
+            2 => (1, 8),
+            4 => (2, 4),
+            16 => (4, 2),
+            256 => (8, 1),
+            _ => {
+                return Err(Error::ParamsError(format!(
+                    "PlainText<{N}, {W}> can't hold bytes; W must be 2, 4, 16, or 256"
+                )))
+            }
+        };
+
+        if N.checked_rem(blocks_per_byte) != Some(0) {
+            return Err(Error::ParamsError(format!(
+                "PlainText<{N}, {W}> can't hold bytes; N must be a multiple of {blocks_per_byte}"
+            )));
+        }
+
+        Ok((bits, blocks_per_byte))
+    }
+
+    /// Turn a string of decimal digits into blocks, by repeated multiply-and-add
+    ///
+    /// `original` is only used for error messages.
+    ///
+    fn parse_magnitude(digits: &str, original: &str) -> Result<[u16; N], Error> {
+        if digits.is_empty() {
+            return Err(Error::NumberError(format!("{original:?} has no digits")));
+        }
+
+        let width = u32::from(W);
+        let mut blocks = [0u16; N];
+
+        for c in digits.chars() {
+            let mut carry = c.to_digit(10).ok_or_else(|| {
+                Error::NumberError(format!("{original:?} is not a decimal integer"))
+            })?;
+
+            for block in blocks.iter_mut().rev() {
+                // Can't overflow, since block < 2^16 and carry < 2^16 + 10
+                let v = u32::from(*block).saturating_mul(10).saturating_add(carry);
+                *block = u16::try_from(v.checked_rem(width).ok_or_else(|| {
+                    Error::InternalError(format!("PlainText<{N}, {W}> has zero width"))
+                })?)
+                .map_err(|e| {
+                    Error::InternalError(format!("Couldn't represent {v} % {width} as u16 ({e})"))
+                })?;
+                carry = v.checked_div(width).unwrap_or(0);
+            }
+
+            if carry != 0 {
+                return Err(Error::RangeError(format!(
+                    "Could not represent {original} in PlainText<{N}, {W}>"
+                )));
+            }
+        }
+
+        Ok(blocks)
+    }
+
+    /// Calculate `floor(W^N / 2)`, by long division of `W^N` by two
+    fn half_domain() -> Result<[u16; N], Error> {
+        let width = u32::from(W);
+        // W^N is a one followed by N zero blocks; the leading one goes straight into the remainder
+        let mut remainder = 1u32;
+        let mut blocks = [0u16; N];
+
+        for block in &mut blocks {
+            let (digit, rem) = num::Integer::div_rem(&remainder.saturating_mul(width), &2);
+            *block = u16::try_from(digit).map_err(|e| {
+                Error::InternalError(format!("Couldn't represent {digit} as u16 ({e})"))
+            })?;
+            remainder = rem;
+        }
+
+        Ok(blocks)
+    }
+
+    /// The blocks of the plaintext, most significant first
+    #[must_use]
+    pub fn blocks(&self) -> &[u16; N] {
+        &self.0
+    }
+
     /// Get the `n`th block of the plaintext
     pub(crate) fn block(&self, n: usize) -> Result<u16, Error> {
         self.0
@@ -51,6 +415,55 @@ impl<const N: usize, const W: u16> PlainText<N, W> {
     }
 }
 
+/// Add two sets of blocks together, returning `None` if the result doesn't fit
+fn add_blocks<const N: usize, const W: u16>(a: &[u16; N], b: &[u16; N]) -> Option<[u16; N]> {
+    let width = u32::from(W);
+    let mut carry = 0u32;
+    let mut sum = [0u16; N];
+
+    for ((total, x), y) in sum.iter_mut().zip(a).zip(b).rev() {
+        let value = u32::from(*x)
+            .saturating_add(u32::from(*y))
+            .saturating_add(carry);
+        (carry, *total) = if value >= width {
+            (1, u16::try_from(value.saturating_sub(width)).ok()?)
+        } else {
+            (0, u16::try_from(value).ok()?)
+        };
+    }
+
+    (carry == 0).then_some(sum)
+}
+
+/// Subtract one set of blocks from another, returning `None` if the result would be negative
+fn sub_blocks<const N: usize, const W: u16>(a: &[u16; N], b: &[u16; N]) -> Option<[u16; N]> {
+    let width = u32::from(W);
+    let mut borrow = 0u32;
+    let mut diff = [0u16; N];
+
+    for ((difference, x), y) in diff.iter_mut().zip(a).zip(b).rev() {
+        let subtrahend = u32::from(*y).saturating_add(borrow);
+        (borrow, *difference) = if u32::from(*x) >= subtrahend {
+            (
+                0,
+                u16::try_from(u32::from(*x).saturating_sub(subtrahend)).ok()?,
+            )
+        } else {
+            (
+                1,
+                u16::try_from(
+                    u32::from(*x)
+                        .saturating_add(width)
+                        .saturating_sub(subtrahend),
+                )
+                .ok()?,
+            )
+        };
+    }
+
+    (borrow == 0).then_some(diff)
+}
+
 /// Generate an implementation of [`TryFrom`] for an unsigned integer type
 macro_rules! from_uint_to_plaintext {
     ($ty:ident) => {
@@ -81,12 +494,96 @@ macro_rules! from_uint_to_plaintext {
     };
 }
 
+/// Generate an infallible, compile-time checked, conversion from an unsigned integer type
+macro_rules! from_uint_exact {
+    ($ty:ident, $fn:ident, $check:ident) => {
+        impl<const N: usize, const W: u16> PlainText<N, W> {
+            #[doc = concat!("Proof that every `", stringify!($ty), "` fits in this plaintext")]
+            const $check: () = assert!(
+                Self::holds_bits($ty::BITS),
+                concat!("PlainText<N, W> is too small to hold every ", stringify!($ty))
+            );
+
+            #[doc = concat!("Create a plaintext from a `", stringify!($ty), "`, which can't fail")]
+            ///
+            /// Unlike the `TryFrom` conversion, whether every value of the integer type can be
+            /// represented in the plaintext is checked at compile time, so using this with a plaintext
+            /// that's too small won't compile:
+            ///
+            /// ```rust,compile_fail
+            /// # use cretrit::PlainText;
+            #[doc = concat!("let p = PlainText::<1, 16>::", stringify!($fn), "(", stringify!($ty), "::MAX);")]
+            /// ```
+            ///
+            #[must_use]
+            pub fn $fn(value: $ty) -> Self {
+                #[allow(clippy::let_unit_value)] // Forces the check to be evaluated
+                let () = Self::$check;
+
+                let mut u = u128::from(value);
+                let width = u128::from(W);
+                let mut p = [0u16; N];
+
+                for block in p.iter_mut().rev() {
+                    // Can't fail, because the remainder is always less than W
+                    *block = u16::try_from(u.checked_rem(width).unwrap_or(0)).unwrap_or(0);
+                    u = u.checked_div(width).unwrap_or(0);
+                }
+
+                PlainText::new(p)
+            }
+        }
+    };
+}
+
+from_uint_exact!(u8, from_u8_exact, HOLDS_U8);
+from_uint_exact!(u16, from_u16_exact, HOLDS_U16);
+from_uint_exact!(u32, from_u32_exact, HOLDS_U32);
+from_uint_exact!(u64, from_u64_exact, HOLDS_U64);
+from_uint_exact!(u128, from_u128_exact, HOLDS_U128);
+
+/// Generate an implementation of [`TryFrom`] from a plaintext back to an unsigned integer type
+macro_rules! from_plaintext_to_uint {
+    ($ty:ident) => {
+        impl<const N: usize, const W: u16> TryFrom<&PlainText<N, W>> for $ty {
+            type Error = Error;
+
+            fn try_from(value: &PlainText<N, W>) -> Result<Self, Self::Error> {
+                let out_of_range = || {
+                    Self::Error::RangeError(format!(
+                        "Could not represent PlainText<{N}, {W}> {:?} as {}",
+                        value.0,
+                        stringify!($ty)
+                    ))
+                };
+
+                let u = value
+                    .0
+                    .iter()
+                    .try_fold(0u128, |acc, block| {
+                        acc.checked_mul(u128::from(W))?
+                            .checked_add(u128::from(*block))
+                    })
+                    .ok_or_else(out_of_range)?;
+
+                $ty::try_from(u).map_err(|_| out_of_range())
+            }
+        }
+    };
+}
+
 from_uint_to_plaintext!(u128);
 from_uint_to_plaintext!(u64);
 from_uint_to_plaintext!(u32);
 from_uint_to_plaintext!(u16);
 from_uint_to_plaintext!(u8);
 
+from_plaintext_to_uint!(u128);
+from_plaintext_to_uint!(u64);
+from_plaintext_to_uint!(u32);
+from_plaintext_to_uint!(u16);
+from_plaintext_to_uint!(u8);
+
 impl<const N: usize, const W: u16> TryFrom<bool> for PlainText<N, W> {
     type Error = Error;
 
@@ -95,10 +592,414 @@ impl<const N: usize, const W: u16> TryFrom<bool> for PlainText<N, W> {
     }
 }
 
+#[cfg(feature = "uuid")]
+impl<const N: usize, const W: u16> TryFrom<uuid::Uuid> for PlainText<N, W> {
+    type Error = Error;
+
+    /// Treat the UUID as a 128 bit big-endian unsigned integer, so that a `PlainText<16, 256>`
+    /// holds its bytes in order, one per block
+    fn try_from(value: uuid::Uuid) -> Result<PlainText<N, W>, Self::Error> {
+        PlainText::<N, W>::try_from(value.as_u128())
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl<const N: usize, const W: u16> TryFrom<&PlainText<N, W>> for uuid::Uuid {
+    type Error = Error;
+
+    fn try_from(value: &PlainText<N, W>) -> Result<Self, Self::Error> {
+        u128::try_from(value).map(uuid::Uuid::from_u128)
+    }
+}
+
+impl<const N: usize, const W: u16> TryFrom<&str> for PlainText<N, W> {
+    type Error = Error;
+
+    /// Parse a non-negative decimal integer; see [`PlainText::parse_uint`]
+    fn try_from(value: &str) -> Result<PlainText<N, W>, Self::Error> {
+        PlainText::<N, W>::parse_uint(value)
+    }
+}
+
+/// Make a newtype around an unsigned integer convertible to and from a [`PlainText`]
+///
+/// Wrapping identifiers and the like in a type of their own (such as `struct CustomerId(u64)`)
+/// keeps them from being mixed up with other numbers, but it means unwrapping them every time they
+/// need to be encrypted.  `impl_cretrit_plaintext!(CustomerId => u64)` generates the same
+/// conversions for the newtype that the integer type it wraps has:
+///
+/// * `TryFrom<CustomerId>` and `TryFrom<&CustomerId>` for `PlainText<N, W>`, for encryption; and
+/// * `TryFrom<&PlainText<N, W>>` for `CustomerId`, for getting a decrypted value back out.
+///
+/// The newtype has to be a tuple struct whose only field is the integer, which can be any of `u8`,
+/// `u16`, `u32`, `u64`, or `u128`.  The macro needs to be used somewhere that can see that field;
+/// usually, right after the newtype is defined.
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::aes128v1::ore;
+/// use cretrit::{impl_cretrit_plaintext, PlainText};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct CustomerId(u64);
+///
+/// impl_cretrit_plaintext!(CustomerId => u64);
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// # let key = [0u8; 32];
+/// let cipher = ore::Cipher::<8, 256>::new(&key)?;
+/// let id = CustomerId(42);
+///
+/// let encrypted = cipher.full_encrypt(&(&id).try_into()?)?;
+/// assert_eq!(id, CustomerId::try_from(&cipher.decrypt(&encrypted)?)?);
+/// # Ok(())
+/// # }
+/// ```
+///
+#[macro_export]
+macro_rules! impl_cretrit_plaintext {
+    ($newtype:ty => $inner:ty) => {
+        impl<const N: usize, const W: u16> ::core::convert::TryFrom<$newtype>
+            for $crate::PlainText<N, W>
+        {
+            type Error = $crate::Error;
+
+            fn try_from(value: $newtype) -> ::core::result::Result<Self, Self::Error> {
+                <$crate::PlainText<N, W> as ::core::convert::TryFrom<$inner>>::try_from(value.0)
+            }
+        }
+
+        impl<const N: usize, const W: u16> ::core::convert::TryFrom<&$newtype>
+            for $crate::PlainText<N, W>
+        {
+            type Error = $crate::Error;
+
+            fn try_from(value: &$newtype) -> ::core::result::Result<Self, Self::Error> {
+                <$crate::PlainText<N, W> as ::core::convert::TryFrom<$inner>>::try_from(value.0)
+            }
+        }
+
+        impl<const N: usize, const W: u16> ::core::convert::TryFrom<&$crate::PlainText<N, W>>
+            for $newtype
+        {
+            type Error = $crate::Error;
+
+            fn try_from(
+                value: &$crate::PlainText<N, W>,
+            ) -> ::core::result::Result<Self, Self::Error> {
+                <$inner as ::core::convert::TryFrom<&$crate::PlainText<N, W>>>::try_from(value)
+                    .map(Self)
+            }
+        }
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn holds_bits() {
+        assert!(PlainText::<4, 256>::holds_bits(32));
+        assert!(!PlainText::<4, 256>::holds_bits(33));
+        assert!(PlainText::<2, 65_000>::holds_bits(31));
+        assert!(!PlainText::<2, 65_000>::holds_bits(32));
+        assert!(PlainText::<16, 256>::holds_bits(128));
+        assert!(!PlainText::<15, 256>::holds_bits(128));
+        assert!(PlainText::<20, 100>::holds_bits(64));
+    }
+
+    #[test]
+    fn exact_conversions_match_fallible_ones() {
+        for v in [0u32, 1, 42, 23_338, 67_305_985, u32::MAX] {
+            assert_eq!(
+                PlainText::<4, 256>::try_from(v).unwrap().0,
+                PlainText::<4, 256>::from_u32_exact(v).0
+            );
+            assert_eq!(
+                PlainText::<5, 100>::try_from(v).unwrap().0,
+                PlainText::<5, 100>::from_u32_exact(v).0
+            );
+        }
+
+        assert_eq!(
+            PlainText::<16, 256>::try_from(u128::MAX).unwrap().0,
+            PlainText::<16, 256>::from_u128_exact(u128::MAX).0
+        );
+        assert_eq!([0u16, 7], PlainText::<2, 256>::from_u8_exact(7).0);
+    }
+
+    #[test]
+    fn parse_uint_matches_integer_conversions() {
+        for v in [0u32, 1, 42, 23_338, 67_305_985, u32::MAX] {
+            assert_eq!(
+                PlainText::<4, 256>::try_from(v).unwrap().0,
+                PlainText::<4, 256>::parse_uint(&v.to_string()).unwrap().0
+            );
+            assert_eq!(
+                PlainText::<5, 100>::try_from(v).unwrap().0,
+                PlainText::<5, 100>::parse_uint(&v.to_string()).unwrap().0
+            );
+        }
+
+        assert_eq!(
+            [0u16, 0, 0, 42],
+            PlainText::<4, 256>::try_from("+42").unwrap().0
+        );
+        assert_eq!(
+            [0u16, 0, 0, 42],
+            PlainText::<4, 256>::parse_uint("0000042").unwrap().0
+        );
+    }
+
+    #[test]
+    fn parse_uint_bigger_than_u128() {
+        let p =
+            PlainText::<17, 256>::parse_uint("340282366920938463463374607431768211456").unwrap();
+
+        assert_eq!(1, p.0[0]);
+        assert!(p.0[1..].iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn parse_uint_out_of_range() {
+        assert!(PlainText::<4, 256>::parse_uint("4294967295").is_ok());
+        assert!(matches!(
+            PlainText::<4, 256>::parse_uint("4294967296"),
+            Err(Error::RangeError(_))
+        ));
+        assert!(matches!(
+            PlainText::<2, 10>::parse_uint("100"),
+            Err(Error::RangeError(_))
+        ));
+    }
+
+    #[test]
+    fn parse_garbage() {
+        for s in [
+            "", "+", "-", "-1", "1.5", " 1", "1 ", "0x10", "++1", "\u{661}",
+        ] {
+            assert!(
+                matches!(
+                    PlainText::<4, 256>::parse_uint(s),
+                    Err(Error::NumberError(_))
+                ),
+                "{s:?}"
+            );
+        }
+
+        for s in ["", "+", "-", "--1", "-+1", "1e3"] {
+            assert!(
+                matches!(
+                    PlainText::<4, 256>::parse_int(s),
+                    Err(Error::NumberError(_))
+                ),
+                "{s:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_int_range() {
+        assert_eq!(
+            [0u16; 4],
+            PlainText::<4, 256>::parse_int("-2147483648").unwrap().0
+        );
+        assert_eq!(
+            [255u16; 4],
+            PlainText::<4, 256>::parse_int("2147483647").unwrap().0
+        );
+        assert!(matches!(
+            PlainText::<4, 256>::parse_int("-2147483649"),
+            Err(Error::RangeError(_))
+        ));
+        assert!(matches!(
+            PlainText::<4, 256>::parse_int("2147483648"),
+            Err(Error::RangeError(_))
+        ));
+
+        // W^N = 125, so the range is -62..=62
+        assert_eq!([0u16; 3], PlainText::<3, 5>::parse_int("-62").unwrap().0);
+        assert_eq!([4u16; 3], PlainText::<3, 5>::parse_int("62").unwrap().0);
+        assert!(PlainText::<3, 5>::parse_int("-63").is_err());
+        assert!(PlainText::<3, 5>::parse_int("63").is_err());
+        assert_eq!(
+            PlainText::<3, 5>::parse_int("0").unwrap().0,
+            PlainText::<3, 5>::parse_int("-0").unwrap().0
+        );
+    }
+
+    #[test]
+    fn integer_conversions_out_of_range() {
+        let p = PlainText::<3, 100>::try_from(999_999u32).unwrap();
+
+        assert_eq!(999_999, u32::try_from(&p).unwrap());
+        assert!(matches!(u16::try_from(&p), Err(Error::RangeError(_))));
+        assert!(matches!(
+            u128::try_from(&PlainText::<17, 256>::new([255; 17])),
+            Err(Error::RangeError(_))
+        ));
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct CustomerId(u64);
+
+    impl_cretrit_plaintext!(CustomerId => u64);
+
+    #[derive(Debug, PartialEq)]
+    struct Tiny(u8);
+
+    impl_cretrit_plaintext!(Tiny => u8);
+
+    #[test]
+    fn newtype_conversions() {
+        let id = CustomerId(9001);
+
+        assert_eq!(
+            PlainText::<8, 256>::try_from(9001u64).unwrap(),
+            PlainText::<8, 256>::try_from(id).unwrap()
+        );
+        assert_eq!(
+            PlainText::<8, 256>::try_from(9001u64).unwrap(),
+            PlainText::<8, 256>::try_from(&id).unwrap()
+        );
+        assert_eq!(
+            id,
+            CustomerId::try_from(&PlainText::<8, 256>::try_from(id).unwrap()).unwrap()
+        );
+
+        assert!(matches!(
+            PlainText::<1, 16>::try_from(id),
+            Err(Error::RangeError(_))
+        ));
+        assert!(matches!(
+            Tiny::try_from(&PlainText::<2, 256>::try_from(id).unwrap()),
+            Err(Error::RangeError(_))
+        ));
+        assert_eq!(
+            Tiny(42),
+            Tiny::try_from(&PlainText::<2, 256>::try_from(&Tiny(42)).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn byte_strings() {
+        assert_eq!(
+            [u16::from(b'a'), u16::from(b'b'), 0, 0],
+            PlainText::<4, 256>::from_bytes("ab", 0).unwrap().0
+        );
+        assert_eq!(
+            [6u16, 1, 6, 2, 2, 0, 2, 0],
+            PlainText::<8, 16>::from_bytes(b"ab", b' ').unwrap().0
+        );
+        assert_eq!(
+            [0u16, 1, 1, 0, 1, 1, 1, 1],
+            PlainText::<8, 2>::from_bytes([0x6fu8], 0).unwrap().0
+        );
+        assert_eq!(
+            b"ab  ".to_vec(),
+            PlainText::<8, 16>::from_bytes("ab", b' ')
+                .unwrap()
+                .to_bytes()
+                .unwrap()
+        );
+
+        assert!(matches!(
+            PlainText::<4, 256>::from_bytes("abcde", 0),
+            Err(Error::RangeError(_))
+        ));
+        assert!(matches!(
+            PlainText::<4, 100>::from_bytes("ab", 0),
+            Err(Error::ParamsError(_))
+        ));
+        assert!(matches!(
+            PlainText::<3, 16>::from_bytes("a", 0),
+            Err(Error::ParamsError(_))
+        ));
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    #[test]
+    fn decimals() {
+        use rust_decimal::Decimal;
+
+        assert_eq!(
+            PlainText::<4, 256>::parse_int("1234").unwrap(),
+            PlainText::from_decimal(Decimal::new(1234, 2), 2).unwrap()
+        );
+        assert_eq!(
+            PlainText::<4, 256>::parse_int("-1200").unwrap(),
+            PlainText::from_decimal(Decimal::new(-12, 0), 2).unwrap()
+        );
+        assert_eq!(
+            PlainText::<4, 256>::parse_int("15").unwrap(),
+            PlainText::from_decimal(Decimal::new(1500, 3), 1).unwrap()
+        );
+
+        assert!(matches!(
+            PlainText::<4, 256>::from_decimal(Decimal::new(1234, 3), 2),
+            Err(Error::NumberError(_))
+        ));
+        assert!(matches!(
+            PlainText::<4, 256>::from_decimal(Decimal::new(2_500_000_000, 2), 2),
+            Err(Error::RangeError(_))
+        ));
+        assert!(matches!(
+            PlainText::<16, 256>::from_decimal(Decimal::MAX, 40),
+            Err(Error::RangeError(_))
+        ));
+        assert!(PlainText::<16, 256>::from_decimal(Decimal::MIN, 2).is_ok());
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn uuids() {
+        let id = uuid::Uuid::from_u128(0x0011_2233_4455_6677_8899_aabb_ccdd_eeff);
+        let p = PlainText::<16, 256>::try_from(id).unwrap();
+
+        assert_eq!(id.as_bytes().map(u16::from), *p.blocks());
+        assert_eq!(id, uuid::Uuid::try_from(&p).unwrap());
+        assert!(matches!(
+            PlainText::<8, 256>::try_from(id),
+            Err(Error::RangeError(_))
+        ));
+    }
+
+    quickcheck! {
+        fn integer_conversions_roundtrip(v: u64) -> bool {
+            u64::try_from(&PlainText::<7, 1000>::try_from(v).unwrap()).unwrap() == v
+                && u64::try_from(&PlainText::<8, 256>::from_u64_exact(v)).unwrap() == v
+        }
+
+        fn parse_int_flips_the_sign_bit(v: i32) -> bool {
+            PlainText::<4, 256>::parse_int(&v.to_string()).unwrap().0
+                == PlainText::<4, 256>::try_from(u32::from_be_bytes(v.to_be_bytes()) ^ 0x8000_0000).unwrap().0
+        }
+
+        fn parse_uint_roundtrips_u64(v: u64) -> bool {
+            PlainText::<8, 256>::parse_uint(&v.to_string()).unwrap().0
+                == PlainText::<8, 256>::try_from(v).unwrap().0
+        }
+
+        fn byte_strings_sort_lexicographically(a: Vec<u8>, b: Vec<u8>) -> bool {
+            // Zero bytes are the padding, and can't be told apart from it
+            let a: Vec<u8> = a.into_iter().filter(|&c| c != 0).take(8).collect();
+            let b: Vec<u8> = b.into_iter().filter(|&c| c != 0).take(8).collect();
+
+            a.cmp(&b) == PlainText::<16, 16>::from_bytes(&a, 0).unwrap().0
+                .cmp(&PlainText::<16, 16>::from_bytes(&b, 0).unwrap().0)
+        }
+
+        fn byte_strings_roundtrip(v: Vec<u8>, padding: u8) -> bool {
+            let v: Vec<u8> = v.into_iter().take(4).collect();
+            let mut padded = v.clone();
+            padded.resize(4, padding);
+
+            PlainText::<32, 2>::from_bytes(&v, padding).unwrap().to_bytes().unwrap() == padded
+        }
+    }
+
     mod pt_4_256 {
         use super::*;
 
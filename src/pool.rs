@@ -0,0 +1,190 @@
+//! Reuse of ciphertext memory, for code that parses lots of ciphertexts one after another.
+//!
+//! Parsing a ciphertext allocates exactly once, for the (packed) values of its right part, and
+//! dropping it frees that allocation again.  That's nothing to worry about for a handful of
+//! ciphertexts, but a scan that parses, compares, and drops millions of them spends a noticeable
+//! amount of its time in the allocator, doing the same thing over and over.  A [`CipherTextPool`]
+//! holds on to the memory of ciphertexts that are finished with, and hands it out again when the
+//! next one is parsed.
+//!
+//! Only the allocator's share of the work goes away: every value still has to be decoded, and the
+//! nonces derived, so how much faster a scan gets depends a lot on the allocator in use, and on
+//! how busy it is with other threads.  The `pooled parse and scan 1000` benchmark is a good place
+//! to start finding out.
+//!
+
+use crate::ciphersuite::CipherSuite;
+use crate::cmp::Comparator;
+use crate::CipherText;
+
+/// Memory from finished-with ciphertexts, ready to be used again
+///
+/// Ciphertexts are parsed from the pool with
+/// [`CipherText::from_slice_pooled()`](crate::CipherText::from_slice_pooled), and given back to it
+/// with [`recycle()`](Self::recycle) once they're no longer needed.  In a loop that does both, only
+/// the first few ciphertexts need to allocate anything.  Ciphertexts that aren't given back are
+/// perfectly fine; their memory is simply freed when they're dropped, as usual.
+///
+/// A pool isn't tied to any one type of ciphertext, so the same pool can be used for parsing
+/// ciphertexts with different ciphersuites, comparators, or sizes.  Memory that's too small for a
+/// ciphertext is grown to fit, so a pool works best when most of what's parsed from it is the same
+/// size.
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::aes128v1::ore;
+/// use cretrit::{CipherTextPool, SerializableCipherText};
+/// use std::cmp::Ordering;
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// # let key = [0u8; 32];
+/// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+/// let stored = (0..100u32)
+///     .map(|i| cipher.right_encrypt(&(i * 7).try_into()?)?.to_vec())
+///     .collect::<Result<Vec<_>, _>>()?;
+/// let query = cipher.full_encrypt(&350u32.try_into()?)?;
+///
+/// let mut pool = CipherTextPool::new();
+/// let mut matches = 0;
+///
+/// for bytes in &stored {
+///     let ct = ore::CipherText::<4, 256>::from_slice_pooled(bytes, &mut pool)?;
+///
+///     if query.try_cmp(&ct)? == Ordering::Less {
+///         matches += 1;
+///     }
+///
+///     pool.recycle(ct);
+/// }
+///
+/// assert_eq!(49, matches);
+/// // Just the one ciphertext's worth of memory, going round and round
+/// assert_eq!(1, pool.len());
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Debug, Default)]
+pub struct CipherTextPool {
+    /// Storage for right ciphertext values, waiting to be reused
+    spare: Vec<Vec<u64>>,
+}
+
+impl CipherTextPool {
+    /// Create an empty pool
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Give a ciphertext's memory back to the pool, so it can be reused for the next ciphertext
+    /// that's parsed
+    pub fn recycle<
+        S: CipherSuite<W, M>,
+        CMP: Comparator<M>,
+        const N: usize,
+        const W: u16,
+        const M: u8,
+    >(
+        &mut self,
+        mut ct: CipherText<S, CMP, N, W, M>,
+    ) {
+        let storage = ct.take_storage();
+
+        // Nothing to reuse if it never allocated in the first place
+        if storage.capacity() > 0 {
+            self.spare.push(storage);
+        }
+    }
+
+    /// How many ciphertexts' worth of memory the pool is holding on to
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.spare.len()
+    }
+
+    /// Whether the pool has no memory to hand out
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.spare.is_empty()
+    }
+
+    /// Free all the memory the pool is holding on to
+    pub fn clear(&mut self) {
+        self.spare.clear();
+    }
+
+    /// Take some memory out of the pool, or an empty (and therefore unallocated) `Vec` if there
+    /// isn't any
+    pub(crate) fn take(&mut self) -> Vec<u64> {
+        self.spare.pop().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes128v1::{ere, ore};
+    use crate::{PlainText, SerializableCipherText};
+
+    #[test]
+    fn pooled_ciphertexts_are_the_same() {
+        let cipher = ore::Cipher::<8, 256>::new(&[1u8; 32]).unwrap();
+        let mut pool = CipherTextPool::new();
+
+        for v in [0u64, 42, 9001, u64::MAX] {
+            let bytes = cipher
+                .full_encrypt(&v.try_into().unwrap())
+                .unwrap()
+                .to_vec()
+                .unwrap();
+            let ct = ore::CipherText::<8, 256>::from_slice_pooled(&bytes, &mut pool).unwrap();
+
+            assert_eq!(bytes, ct.to_vec().unwrap());
+            pool.recycle(ct);
+        }
+
+        assert_eq!(1, pool.len());
+    }
+
+    #[test]
+    fn memory_is_shared_between_ciphertext_types() {
+        let ore_cipher = ore::Cipher::<8, 256>::new(&[1u8; 32]).unwrap();
+        let ere_cipher = ere::Cipher::<2, 16>::new(&[1u8; 32]).unwrap();
+        let mut pool = CipherTextPool::new();
+
+        let big = ore_cipher
+            .right_encrypt(&42u64.try_into().unwrap())
+            .unwrap()
+            .to_vec()
+            .unwrap();
+        let small = ere_cipher.full_encrypt(&PlainText::new([3, 14])).unwrap();
+        let small_bytes = small.to_vec().unwrap();
+
+        let first = ore::CipherText::<8, 256>::from_slice_pooled(&big, &mut pool).unwrap();
+        pool.recycle(first);
+        let reused = ere::CipherText::<2, 16>::from_slice_pooled(&small_bytes, &mut pool).unwrap();
+
+        assert!(pool.is_empty());
+        assert!(reused.try_eq(&small).unwrap());
+        assert_eq!(small_bytes, reused.to_vec().unwrap());
+
+        // And back again, when the memory has to grow
+        pool.recycle(reused);
+        let grown = ore::CipherText::<8, 256>::from_slice_pooled(&big, &mut pool).unwrap();
+
+        assert_eq!(big, grown.to_vec().unwrap());
+    }
+
+    #[test]
+    fn failed_parses_leave_the_pool_alone() {
+        let mut pool = CipherTextPool::new();
+
+        assert!(ore::CipherText::<8, 256>::from_slice_pooled(&[0u8; 7], &mut pool).is_err());
+        assert!(pool.is_empty());
+
+        pool.clear();
+        assert_eq!(0, pool.len());
+    }
+}
@@ -11,11 +11,13 @@
 
 use aes::cipher::generic_array::GenericArray;
 use aes::cipher::{BlockEncrypt, KeyInit};
-use aes::Aes128;
-use rand::Fill;
-use zeroize::Zeroize;
+use aes::{Aes128, Aes256};
+use rand_chacha::ChaCha20Rng;
+use std::mem;
+use zeroize::{Zeroize, Zeroizing};
 
 use crate::kbkdf::KBKDF;
+use crate::rng::{Fill, RngCore, SeedableRng};
 use crate::Error;
 
 /// Initialisation of a PRF
@@ -31,8 +33,18 @@ pub trait PseudoRandomFunctionInit: Sized + PseudoRandomFunction {
 pub trait PseudoRandomFunction: Sized {
     /// The exact type of the block of data that will be returned by `randomise()`
     ///
-    /// In practice this will always be a u8 array of some size
-    type BlockType: Default + Copy + Fill + core::fmt::Debug + Into<Vec<u8>> + AsMut<[u8]>;
+    /// In practice this will always be a u8 array of some size.  It has to be `Send + Sync`, since
+    /// it's stored in left ciphertexts, which are guaranteed to be both.
+    type BlockType: Default
+        + Send
+        + Sync
+        + Copy
+        + Fill
+        + core::fmt::Debug
+        + Into<Vec<u8>>
+        + AsRef<[u8]>
+        + AsMut<[u8]>
+        + Zeroize;
 
     /// The number of elements in the block returned from `randomise()`
     ///
@@ -57,12 +69,11 @@ pub struct AES128PRF {
 
 impl PseudoRandomFunctionInit for AES128PRF {
     fn new(kdf: &dyn KBKDF) -> Result<Self, Error> {
-        let mut k: [u8; 16] = Default::default();
+        let mut k: Zeroizing<[u8; 16]> = Zeroizing::new(Default::default());
 
-        kdf.derive_key(&mut k, b"AES128PRF.subkey")?;
+        kdf.derive_key(&mut *k, b"AES128PRF.subkey")?;
 
-        let cipher = Aes128::new(&GenericArray::from(k));
-        k.zeroize();
+        let cipher = Aes128::new(GenericArray::from_slice(&*k));
 
         Ok(AES128PRF { cipher })
     }
@@ -80,5 +91,219 @@ impl PseudoRandomFunction for AES128PRF {
         self.cipher
             .encrypt_block(GenericArray::from_mut_slice(&mut a));
         block.copy_from_slice(a.as_slice());
+        a.zeroize();
+    }
+}
+
+/// A PRF based on using AES256
+///
+/// The output is twice the size of an AES block, because the output of the PRF is used as the
+/// key for the ciphersuite's hash function, and for the [`aes256v1`](crate::aes256v1)
+/// ciphersuite that's CMAC-AES256, which needs a 256 bit key.  The two halves are the encryption
+/// of the value with a different final byte, so they're independent of each other.
+///
+#[allow(unreachable_pub)] // I think this is a bug in the lint; see also https://github.com/rust-lang/rust/issues/110923
+#[derive(Debug)]
+pub struct AES256PRF {
+    /// The block cipher, keyed and ready to go
+    cipher: Aes256,
+}
+
+impl PseudoRandomFunctionInit for AES256PRF {
+    fn new(kdf: &dyn KBKDF) -> Result<Self, Error> {
+        let mut k: Zeroizing<[u8; 32]> = Zeroizing::new(Default::default());
+
+        kdf.derive_key(&mut *k, b"AES256PRF.subkey")?;
+
+        let cipher = Aes256::new(GenericArray::from_slice(&*k));
+
+        Ok(AES256PRF { cipher })
+    }
+}
+
+impl PseudoRandomFunction for AES256PRF {
+    type BlockType = [u8; 32];
+    const BLOCK_SIZE: usize = 32;
+
+    fn randomise(&self, value: u16, block: &mut Self::BlockType) {
+        let mut a = [[0u8; 16]; 2];
+        let v = value.to_be_bytes();
+
+        for (i, half) in a.iter_mut().enumerate() {
+            half[0] = v[0];
+            half[1] = v[1];
+            half[15] = u8::try_from(i).unwrap_or(u8::MAX);
+            self.cipher
+                .encrypt_block(GenericArray::from_mut_slice(half));
+        }
+
+        let (lo, hi) = block.split_at_mut(16);
+        lo.copy_from_slice(&a[0]);
+        hi.copy_from_slice(&a[1]);
+        a.zeroize();
+    }
+}
+
+/// A PRF based on the `ChaCha20` stream cipher
+///
+/// The value is used as the `ChaCha20` nonce (or "stream", as `rand_chacha` calls it), and the
+/// output is the first 32 bytes of the resulting keystream.  `ChaCha20` only needs additions,
+/// rotations, and XORs, so it's quick even on CPUs that don't have any AES acceleration.
+///
+#[allow(unreachable_pub)] // I think this is a bug in the lint; see also https://github.com/rust-lang/rust/issues/110923
+#[derive(Debug)]
+pub struct ChaCha20PRF {
+    /// The keystream generator, keyed and positioned at the start of stream zero
+    keystream: ChaCha20Rng,
+}
+
+impl PseudoRandomFunctionInit for ChaCha20PRF {
+    fn new(kdf: &dyn KBKDF) -> Result<Self, Error> {
+        let mut k: Zeroizing<[u8; 32]> = Zeroizing::new(Default::default());
+
+        kdf.derive_key(&mut *k, b"ChaCha20PRF.subkey")?;
+
+        Ok(ChaCha20PRF {
+            keystream: ChaCha20Rng::from_seed(mem::take(&mut *k)),
+        })
+    }
+}
+
+impl PseudoRandomFunction for ChaCha20PRF {
+    type BlockType = [u8; 32];
+    const BLOCK_SIZE: usize = 32;
+
+    fn randomise(&self, value: u16, block: &mut Self::BlockType) {
+        let mut keystream = self.keystream.clone();
+        keystream.set_stream(value.into());
+        keystream.fill_bytes(block);
+    }
+}
+
+/// The output of a [`TruncatedPRF`]
+///
+/// This is just a `[u8; T]`, except that arrays only get a `Default` implementation up to a
+/// fixed size, rather than for any `T`.
+///
+#[allow(unreachable_pub)] // I think this is a bug in the lint; see also https://github.com/rust-lang/rust/issues/110923
+#[derive(Debug, Clone, Copy, Zeroize)]
+pub struct TruncatedBlock<const T: usize>([u8; T]);
+
+impl<const T: usize> Default for TruncatedBlock<T> {
+    fn default() -> Self {
+        TruncatedBlock([0u8; T])
+    }
+}
+
+impl<const T: usize> Fill for TruncatedBlock<T> {
+    fn try_fill<R: rand::Rng + ?Sized>(&mut self, rng: &mut R) -> Result<(), rand::Error> {
+        self.0.as_mut_slice().try_fill(rng)
+    }
+}
+
+impl<const T: usize> AsRef<[u8]> for TruncatedBlock<T> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<const T: usize> AsMut<[u8]> for TruncatedBlock<T> {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+impl<const T: usize> From<TruncatedBlock<T>> for Vec<u8> {
+    fn from(block: TruncatedBlock<T>) -> Vec<u8> {
+        block.0.into()
+    }
+}
+
+/// A PRF whose output is the first `T` bytes of another PRF's output
+///
+/// The wrapped PRF's subkeys are derived under a prefix which includes `T`, so the output isn't
+/// the first `T` bytes of what the wrapped PRF would produce on its own (or truncated to any other
+/// length) with the same root key.  Otherwise, using one key with both truncated and untruncated
+/// ciphers would hand out a chunk of every block of the untruncated cipher's left ciphertexts.
+///
+/// `T` must be at least [`MIN_TRUNCATED_BLOCK_SIZE`], and no more than the wrapped PRF's block
+/// size; anything else fails to compile.
+///
+#[allow(unreachable_pub)] // I think this is a bug in the lint; see also https://github.com/rust-lang/rust/issues/110923
+#[derive(Debug)]
+pub struct TruncatedPRF<P, const T: usize> {
+    /// The PRF doing the actual work
+    prf: P,
+}
+
+/// The shortest truncated PRF output that is allowed
+///
+/// See [`truncated`](crate::truncated) for why.
+///
+pub(crate) const MIN_TRUNCATED_BLOCK_SIZE: usize = 8;
+
+impl<P: PseudoRandomFunction, const T: usize> TruncatedPRF<P, T> {
+    /// Refuse to compile a truncation length that makes no sense
+    const VALID_LENGTH: () = assert!(
+        T >= MIN_TRUNCATED_BLOCK_SIZE && T <= P::BLOCK_SIZE,
+        "truncated PRF output length is out of range"
+    );
+}
+
+/// Derives subkeys from another KBKDF, with a prefix on every `id`
+pub(crate) struct PrefixedKBKDF<'a> {
+    /// Where the subkeys really come from
+    kdf: &'a dyn KBKDF,
+    /// What goes in front of every `id`
+    prefix: Vec<u8>,
+}
+
+impl<'a> PrefixedKBKDF<'a> {
+    /// Derive subkeys from `kdf`, with `prefix` in front of every `id`
+    pub(crate) fn new(kdf: &'a dyn KBKDF, prefix: &[u8]) -> Self {
+        PrefixedKBKDF {
+            kdf,
+            prefix: prefix.to_vec(),
+        }
+    }
+}
+
+impl KBKDF for PrefixedKBKDF<'_> {
+    fn derive_key(&self, subkey: &mut [u8], id: &[u8]) -> Result<(), Error> {
+        let mut prefixed = Vec::with_capacity(self.prefix.len().saturating_add(id.len()));
+        prefixed.extend_from_slice(&self.prefix);
+        prefixed.extend_from_slice(id);
+
+        self.kdf.derive_key(subkey, &prefixed)
+    }
+}
+
+impl<P: PseudoRandomFunctionInit, const T: usize> PseudoRandomFunctionInit for TruncatedPRF<P, T> {
+    fn new(kdf: &dyn KBKDF) -> Result<Self, Error> {
+        #[allow(clippy::let_unit_value)] // Referring to it is what makes the compiler check it
+        let () = Self::VALID_LENGTH;
+
+        let prefixed = PrefixedKBKDF {
+            kdf,
+            prefix: format!("TruncatedPRF.{T}.").into_bytes(),
+        };
+
+        Ok(TruncatedPRF {
+            prf: P::new(&prefixed)?,
+        })
+    }
+}
+
+impl<P: PseudoRandomFunction, const T: usize> PseudoRandomFunction for TruncatedPRF<P, T> {
+    type BlockType = TruncatedBlock<T>;
+    const BLOCK_SIZE: usize = T;
+
+    fn randomise(&self, value: u16, block: &mut Self::BlockType) {
+        let mut full: Zeroizing<P::BlockType> = Zeroizing::new(Default::default());
+        self.prf.randomise(value, &mut full);
+
+        for (b, f) in block.0.iter_mut().zip(full.as_ref()) {
+            *b = *f;
+        }
     }
 }
@@ -4,14 +4,16 @@
 //! value in the same range.  This is the module that contains everything you need to do that.
 //!
 
-use rand::{seq::SliceRandom, SeedableRng};
 use rand_chacha::ChaCha20Rng;
 use std::fmt;
-use zeroize::ZeroizeOnDrop;
+use std::mem;
+use zeroize::{ZeroizeOnDrop, Zeroizing};
 
 use crate::Error;
 
 use crate::kbkdf::KBKDF;
+use crate::prf::{PrefixedKBKDF, PseudoRandomFunction, PseudoRandomFunctionInit};
+use crate::rng::{SeedableRng, SliceRandom};
 
 /// Functionality for an initialising PRP
 pub trait PseudoRandomPermutationInit<const W: u16>: Sized + PseudoRandomPermutation<W> {
@@ -44,9 +46,11 @@ pub struct RandShufflePRP<const W: u16> {
 
 impl<const W: u16> PseudoRandomPermutationInit<W> for RandShufflePRP<W> {
     fn new(kdf: &dyn KBKDF) -> Result<Self, Error> {
-        let mut seed: [u8; 32] = Default::default();
-        kdf.derive_key(&mut seed, b"RandShufflePRP.rngseed")?;
-        let mut rng: ChaCha20Rng = SeedableRng::from_seed(seed);
+        let mut seed: Zeroizing<[u8; 32]> = Zeroizing::new(Default::default());
+        kdf.derive_key(&mut *seed, b"RandShufflePRP.rngseed")?;
+        // Moved out, leaving zeroes behind, rather than copied, so that there's no stray copy of
+        // the seed left over once the RNG has been made from it
+        let mut rng: ChaCha20Rng = SeedableRng::from_seed(mem::take(&mut *seed));
 
         let mut p: Vec<u16> = (0..W).collect();
         let mut p_1 = vec![0u16; W as usize];
@@ -100,10 +104,137 @@ impl<const W: u16> PseudoRandomPermutation<W> for RandShufflePRP<W> {
     }
 }
 
+/// How many rounds a [`FeistelPRP`] puts each value through
+///
+/// Four rounds are enough for a Feistel network over big blocks, but the halves here are at most
+/// eight bits, and networks that small need a lot more rounds before they stop leaking structure.
+///
+const FEISTEL_ROUNDS: u16 = 16;
+
+/// A pseudo-random permutation that works out each value as it's asked for, rather than keeping
+/// a table of them all
+///
+/// [`RandShufflePRP`] holds two `W`-entry tables, which adds up for wide blocks, or when lots of
+/// ciphers are around at once.  This one is a balanced Feistel network, with the PRF `P` as the
+/// round function, over the smallest even number of bits that can hold every value less than
+/// `W`.  Any value the network maps to `W` or more is put through the network again ("cycle
+/// walking"), until it lands back in range, so the result is a permutation of `0..W`.
+///
+/// Each lookup costs [`FEISTEL_ROUNDS`] PRF calls (times however many walks it takes, which
+/// averages less than four), in place of a memory access.  How long a lookup takes depends on how
+/// many walks the value needs, but the only values looked up by anything other than a loop over
+/// every value are the `p(x)`s that end up in left ciphertexts anyway.
+///
+#[allow(unreachable_pub)] // I think this is a bug in the lint; see also https://github.com/rust-lang/rust/issues/110923
+#[doc(hidden)]
+pub struct FeistelPRP<P, const W: u16> {
+    /// The round function, keyed separately from the ciphersuite's own use of the same PRF
+    prf: P,
+}
+
+impl<P, const W: u16> FeistelPRP<P, W> {
+    /// How many bits go in each half of the network
+    const HALF_BITS: u32 = {
+        let bits = u16::BITS - W.saturating_sub(1).leading_zeros();
+        let half = bits.div_ceil(2);
+
+        if half == 0 {
+            1
+        } else {
+            half
+        }
+    };
+
+    /// The mask for a single half
+    const HALF_MASK: u16 = (1u16 << Self::HALF_BITS).wrapping_sub(1);
+
+    /// The most times any value can need to be walked, which is the size of the network's domain
+    const MAX_WALKS: u32 = 1u32 << (Self::HALF_BITS * 2);
+}
+
+impl<P: PseudoRandomFunction, const W: u16> FeistelPRP<P, W> {
+    /// The round function: the first byte of the PRF output for the round number and half-value
+    fn round(&self, round: u16, half: u16) -> u16 {
+        let mut block: Zeroizing<P::BlockType> = Zeroizing::new(Default::default());
+        self.prf
+            .randomise((round << 8) | (half & Self::HALF_MASK), &mut block);
+
+        u16::from(block.as_ref().first().copied().unwrap_or(0)) & Self::HALF_MASK
+    }
+
+    /// Put a value through the network once, forwards or backwards
+    fn network(&self, data: u16, forwards: bool) -> u16 {
+        let mut left = (data >> Self::HALF_BITS) & Self::HALF_MASK;
+        let mut right = data & Self::HALF_MASK;
+
+        for i in 0..FEISTEL_ROUNDS {
+            if forwards {
+                (left, right) = (right, left ^ self.round(i, right));
+            } else {
+                let r = FEISTEL_ROUNDS.saturating_sub(1).saturating_sub(i);
+                (left, right) = (right ^ self.round(r, left), left);
+            }
+        }
+
+        (left << Self::HALF_BITS) | right
+    }
+
+    /// Walk a value through the network until it comes out less than `W`
+    fn walk(&self, data: u16, forwards: bool) -> Result<u16, Error> {
+        if data >= W {
+            return Err(Error::RangeError(format!(
+                "cannot permute {data}, as it isn't less than {W}"
+            )));
+        }
+
+        let mut v = data;
+        for _ in 0..Self::MAX_WALKS {
+            v = self.network(v, forwards);
+            if v < W {
+                return Ok(v);
+            }
+        }
+
+        Err(Error::InternalError(format!(
+            "cycle walk from {data} never came back to a value less than {W}"
+        )))
+    }
+}
+
+impl<P: PseudoRandomFunctionInit, const W: u16> PseudoRandomPermutationInit<W>
+    for FeistelPRP<P, W>
+{
+    fn new(kdf: &dyn KBKDF) -> Result<Self, Error> {
+        let prefixed = PrefixedKBKDF::new(kdf, b"FeistelPRP.");
+
+        Ok(FeistelPRP {
+            prf: P::new(&prefixed)?,
+        })
+    }
+}
+
+impl<P, const W: u16> fmt::Debug for FeistelPRP<P, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(&format!("FeistelPRP<W: {W}>"))
+            .finish_non_exhaustive()
+    }
+}
+
+impl<P: PseudoRandomFunction, const W: u16> PseudoRandomPermutation<W> for FeistelPRP<P, W> {
+    fn value(&self, data: u16) -> Result<u16, Error> {
+        self.walk(data, true)
+    }
+
+    fn inverse(&self, data: u16) -> Result<u16, Error> {
+        self.walk(data, false)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::kbkdf::{KBKDFInit, CMACAES256};
+    use crate::prf::AES128PRF;
 
     fn kdf() -> Box<dyn KBKDF> {
         CMACAES256::new(&[0u8; 32]).unwrap()
@@ -116,6 +247,43 @@ mod tests {
         assert!(!(0..16).all(|i| prp.value(i).unwrap() == i));
     }
 
+    #[test]
+    fn feistel_permutes_every_width() {
+        fn check<const W: u16>() {
+            let prp = FeistelPRP::<AES128PRF, W>::new(&*kdf()).unwrap();
+            let mut seen = vec![false; usize::from(W)];
+
+            for i in 0..W {
+                let v = prp.value(i).unwrap();
+                assert!(!seen[usize::from(v)], "{v} came up twice for W={W}");
+                seen[usize::from(v)] = true;
+                assert_eq!(i, prp.inverse(v).unwrap());
+            }
+            assert!(prp.value(W).is_err());
+            assert!(prp.inverse(W).is_err());
+        }
+
+        check::<2>();
+        check::<3>();
+        check::<16>();
+        check::<17>();
+        check::<256>();
+        check::<1000>();
+        check::<0xFFFF>();
+    }
+
+    #[test]
+    fn feistel_is_keyed() {
+        let a = FeistelPRP::<AES128PRF, 256>::new(&*kdf()).unwrap();
+        let b = FeistelPRP::<AES128PRF, 256>::new(&*CMACAES256::new(&[1u8; 32]).unwrap()).unwrap();
+
+        assert!(!(0..256).all(|i| a.value(i).unwrap() == i));
+        assert!(!(0..256).all(|i| a.value(i).unwrap() == b.value(i).unwrap()));
+        // The same key gives the same permutation
+        let c = FeistelPRP::<AES128PRF, 256>::new(&*kdf()).unwrap();
+        assert!((0..256).all(|i| a.value(i).unwrap() == c.value(i).unwrap()));
+    }
+
     #[test]
     fn small_shuffle_round_trips_correctly() {
         let prp = RandShufflePRP::<16>::new(&*kdf()).unwrap();
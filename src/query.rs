@@ -0,0 +1,289 @@
+//! Comparing one query against many stored values, over and over again.
+//!
+
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Debug;
+
+use crate::ciphersuite::CipherSuite;
+use crate::ciphertext::{LeftCipherText, PreparedLeft};
+use crate::cmp::Comparator;
+use crate::{CipherText, Error};
+
+/// A left ciphertext which has had all of its per-query work done up front, so that it can be
+/// compared against lots of ciphertexts as cheaply as possible.
+///
+/// Every comparison involves hashing each block of the right ciphertext's nonce with a key taken
+/// from the left ciphertext.  Setting up those keys is a significant part of the cost of a
+/// comparison, and since they only depend on the left ciphertext, a `PreparedQuery` does it once,
+/// rather than once per comparison.
+///
+/// If the same query is going to be compared against the same stored values repeatedly (say,
+/// because of overlapping pages of results), a bounded cache of comparison results can be turned
+/// on with [`with_cache()`](Self::with_cache).  The cache is keyed by the nonce base of the right
+/// ciphertext, which is random for every encryption, and so identifies a stored value without
+/// having to look at the rest of it.  That does mean that the cache trusts that two ciphertexts
+/// with the same nonce base are the same ciphertext; if stored values can be tampered with, don't
+/// use the cache.
+///
+/// As with [`Cipher`](crate::Cipher), the cache uses interior mutability, so a `PreparedQuery`
+/// can't be shared between threads.  Prepare a separate one for each thread instead.
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::aes128v1::ore;
+/// use std::cmp::Ordering;
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// # let key = [0u8; 32];
+/// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+/// let stored = vec![
+///     cipher.right_encrypt(&1u32.try_into()?)?,
+///     cipher.right_encrypt(&9001u32.try_into()?)?,
+/// ];
+///
+/// let full = cipher.full_encrypt(&42u32.try_into()?)?;
+/// let query = ore::PreparedQuery::<4, 256>::new(full.left().expect("it's a full ciphertext"))?
+///     .with_cache(1000);
+///
+/// for _page in 0..3 {
+///     assert_eq!(Ordering::Greater, query.compare_with_right(&stored[0])?);
+///     assert_eq!(Ordering::Less, query.compare_with_right(&stored[1])?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+pub struct PreparedQuery<
+    S: CipherSuite<W, M>,
+    CMP: Comparator<M>,
+    const N: usize,
+    const W: u16,
+    const M: u8,
+> {
+    /// The left ciphertext, all ready to go
+    left: PreparedLeft<S, CMP, N, W, M>,
+
+    /// Results of previous comparisons, if we're keeping them
+    cache: Option<RefCell<ComparisonCache>>,
+}
+
+impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, const M: u8> Debug
+    for PreparedQuery<S, CMP, N, W, M>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        // The prepared left ciphertext is as sensitive as the left ciphertext itself, so it
+        // doesn't get shown
+        f.debug_struct("PreparedQuery")
+            .field("cache", &self.cache)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: CipherSuite<W, M>, CMP: Comparator<M>, const N: usize, const W: u16, const M: u8>
+    PreparedQuery<S, CMP, N, W, M>
+{
+    /// Prepare a left ciphertext for comparison against many right ciphertexts
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the hash function can't be set up, which should only happen if
+    /// there's a bug somewhere.
+    ///
+    pub fn new(left: &LeftCipherText<S, CMP, N, W, M>) -> Result<Self, Error> {
+        Ok(PreparedQuery {
+            left: PreparedLeft::new(left)?,
+            cache: None,
+        })
+    }
+
+    /// Remember the results of up to `capacity` comparisons, so that comparing against the same
+    /// ciphertext again doesn't have to redo the work
+    ///
+    /// Once the cache is full, the oldest result is forgotten to make room for each new one.  A
+    /// `capacity` of zero turns the cache off again.
+    ///
+    #[must_use]
+    pub fn with_cache(mut self, capacity: usize) -> Self {
+        self.cache = (capacity > 0).then(|| RefCell::new(ComparisonCache::new(capacity)));
+        self
+    }
+
+    /// The number of comparison results currently being remembered
+    #[must_use]
+    pub fn cached(&self) -> usize {
+        self.cache.as_ref().map_or(0, |c| c.borrow().results.len())
+    }
+
+    /// Compare against the right part of a ciphertext, using the cache if there is one
+    ///
+    /// Returns the numeric comparison value, which needs to be run through the comparator's invert
+    /// function in order to convert that into a "proper" logical comparison value.
+    ///
+    pub(crate) fn compare(&self, other: &CipherText<S, CMP, N, W, M>) -> Result<u8, Error> {
+        // Checked up front, since a cached result for the same nonce base would otherwise skip it
+        self.left.check_key(&other.right)?;
+
+        let Some(cache) = &self.cache else {
+            return self.left.compare(&other.right);
+        };
+
+        let nonce_base = other.right.nonce_base();
+
+        if let Some(result) = cache.borrow().get(nonce_base) {
+            return Ok(result);
+        }
+
+        let result = self.left.compare(&other.right)?;
+        cache.borrow_mut().insert(*nonce_base, result);
+
+        Ok(result)
+    }
+}
+
+/// A fixed-size collection of comparison results, which forgets the oldest result when it needs
+/// room for a new one
+#[derive(Debug)]
+struct ComparisonCache {
+    /// The most results we'll hold on to
+    capacity: usize,
+
+    /// The results themselves, by nonce base
+    results: HashMap<[u8; 16], u8>,
+
+    /// The order in which the results were added, oldest first
+    order: VecDeque<[u8; 16]>,
+}
+
+impl ComparisonCache {
+    /// Create an empty cache
+    fn new(capacity: usize) -> Self {
+        ComparisonCache {
+            capacity,
+            results: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Find a previously-recorded result
+    fn get(&self, nonce_base: &[u8; 16]) -> Option<u8> {
+        self.results.get(nonce_base).copied()
+    }
+
+    /// Record a result, making room for it if need be
+    fn insert(&mut self, nonce_base: [u8; 16], result: u8) {
+        if self.results.insert(nonce_base, result).is_some() {
+            return;
+        }
+
+        self.order.push_back(nonce_base);
+
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.results.remove(&oldest);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aes128v1::{ere, ore};
+    use crate::util::random_key;
+    use std::cmp::Ordering;
+
+    fn query(cipher: &ore::Cipher<4, 256>, v: u32) -> ore::PreparedQuery<4, 256> {
+        ore::PreparedQuery::new(
+            cipher
+                .full_encrypt(&v.try_into().unwrap())
+                .unwrap()
+                .left()
+                .unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn uncached_comparison() {
+        let cipher = ore::Cipher::<4, 256>::new(&random_key()).unwrap();
+        let q = query(&cipher, 42);
+
+        for v in [0u32, 41, 42, 43, u32::MAX] {
+            let r = cipher.right_encrypt(&v.try_into().unwrap()).unwrap();
+            assert_eq!(42.cmp(&v), q.compare_with_right(&r).unwrap(), "{v}");
+        }
+
+        assert_eq!(0, q.cached());
+    }
+
+    #[test]
+    fn cached_comparison() {
+        let cipher = ore::Cipher::<4, 256>::new(&random_key()).unwrap();
+        let q = query(&cipher, 42).with_cache(10);
+        let rights: Vec<_> = [1u32, 42, 9001]
+            .iter()
+            .map(|v| cipher.right_encrypt(&(*v).try_into().unwrap()).unwrap())
+            .collect();
+
+        for _ in 0..3 {
+            assert_eq!(Ordering::Greater, q.compare_with_right(&rights[0]).unwrap());
+            assert_eq!(Ordering::Equal, q.compare_with_right(&rights[1]).unwrap());
+            assert_eq!(Ordering::Less, q.compare_with_right(&rights[2]).unwrap());
+        }
+
+        assert_eq!(3, q.cached());
+    }
+
+    #[test]
+    fn cache_is_bounded() {
+        let cipher = ore::Cipher::<4, 256>::new(&random_key()).unwrap();
+        let q = query(&cipher, 42).with_cache(2);
+        let rights: Vec<_> = (0u32..5)
+            .map(|v| cipher.right_encrypt(&v.try_into().unwrap()).unwrap())
+            .collect();
+
+        for r in &rights {
+            q.compare_with_right(r).unwrap();
+        }
+        assert_eq!(2, q.cached());
+
+        // The oldest results are the ones that got dropped
+        let cache = q.cache.as_ref().unwrap().borrow();
+        assert!(cache.get(rights[0].right.nonce_base()).is_none());
+        assert!(cache.get(rights[4].right.nonce_base()).is_some());
+    }
+
+    #[test]
+    fn zero_capacity_means_no_cache() {
+        let cipher = ore::Cipher::<4, 256>::new(&random_key()).unwrap();
+        let q = query(&cipher, 42).with_cache(0);
+
+        q.compare_with_right(&cipher.right_encrypt(&1u32.try_into().unwrap()).unwrap())
+            .unwrap();
+
+        assert!(q.cache.is_none());
+        assert_eq!(0, q.cached());
+    }
+
+    #[test]
+    fn equality_queries() {
+        let cipher = ere::Cipher::<4, 256>::new(&random_key()).unwrap();
+        let q = ere::PreparedQuery::<4, 256>::new(
+            cipher
+                .full_encrypt(&42u32.try_into().unwrap())
+                .unwrap()
+                .left()
+                .unwrap(),
+        )
+        .unwrap()
+        .with_cache(10);
+
+        assert!(q
+            .compare_with_right(&cipher.right_encrypt(&42u32.try_into().unwrap()).unwrap())
+            .unwrap());
+        assert!(!q
+            .compare_with_right(&cipher.right_encrypt(&9001u32.try_into().unwrap()).unwrap())
+            .unwrap());
+    }
+}
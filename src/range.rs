@@ -0,0 +1,291 @@
+//! Range queries over order-revealing ciphertexts.
+//!
+//! The most common thing to do with order-revealing ciphertexts is to ask "which of these values
+//! lie between X and Y?".  This module provides the machinery to do that, without the caller
+//! having to keep track of which ciphertexts have left parts, and which way around each comparison
+//! needs to go.
+//!
+
+use std::cmp::Ordering;
+use std::ops::Bound;
+
+use crate::ciphersuite::CipherSuite;
+use crate::cmp::OrderingCMP;
+use crate::{Cipher, CipherText, Error, PlainText};
+
+/// The lower and upper bounds of a range query.
+///
+/// Each bound is encrypted as a full ciphertext, so that it can be compared against stored
+/// ciphertexts that only have a "right" part.  Since the bounds contain (deterministic) left
+/// ciphertexts, a `RangeQuery` should be used and then thrown away, not stored alongside the data
+/// being queried.
+///
+#[derive(Debug, Clone)]
+pub struct RangeQuery<S: CipherSuite<W, 3>, const N: usize, const W: u16> {
+    /// Where the range starts
+    lower: Bound<CipherText<S, OrderingCMP, N, W, 3>>,
+    /// Where the range ends
+    upper: Bound<CipherText<S, OrderingCMP, N, W, 3>>,
+}
+
+impl<S: CipherSuite<W, 3>, const N: usize, const W: u16> RangeQuery<S, N, W> {
+    /// Encrypt the bounds of a range, ready for querying.
+    ///
+    /// The bounds work the same way as they do for [`BTreeMap::range`](std::collections::BTreeMap::range);
+    /// use `Bound::Unbounded` for an open-ended range.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if any of the underlying cryptographic operations can't complete, or if
+    /// there's a bug somewhere.
+    ///
+    pub fn new(
+        cipher: &Cipher<S, OrderingCMP, N, W, 3>,
+        lower: Bound<&PlainText<N, W>>,
+        upper: Bound<&PlainText<N, W>>,
+    ) -> Result<Self, Error> {
+        Ok(RangeQuery {
+            lower: encrypt_bound(cipher, lower)?,
+            upper: encrypt_bound(cipher, upper)?,
+        })
+    }
+
+    /// Determine whether the value encrypted in the given ciphertext lies within the range.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if the comparison fails, which should only happen if there's a bug
+    /// somewhere.
+    ///
+    pub fn contains(&self, ct: &CipherText<S, OrderingCMP, N, W, 3>) -> Result<bool, Error> {
+        let above_lower = match &self.lower {
+            Bound::Unbounded => true,
+            Bound::Included(l) => OrderingCMP::invert(l.compare(ct)?)? != Ordering::Greater,
+            Bound::Excluded(l) => OrderingCMP::invert(l.compare(ct)?)? == Ordering::Less,
+        };
+        let below_upper = match &self.upper {
+            Bound::Unbounded => true,
+            Bound::Included(u) => OrderingCMP::invert(u.compare(ct)?)? != Ordering::Less,
+            Bound::Excluded(u) => OrderingCMP::invert(u.compare(ct)?)? == Ordering::Greater,
+        };
+
+        Ok(above_lower && below_upper)
+    }
+}
+
+/// Turn a plaintext bound into a ciphertext bound
+fn encrypt_bound<S: CipherSuite<W, 3>, const N: usize, const W: u16>(
+    cipher: &Cipher<S, OrderingCMP, N, W, 3>,
+    bound: Bound<&PlainText<N, W>>,
+) -> Result<Bound<CipherText<S, OrderingCMP, N, W, 3>>, Error> {
+    Ok(match bound {
+        Bound::Included(v) => Bound::Included(cipher.full_encrypt(v)?),
+        Bound::Excluded(v) => Bound::Excluded(cipher.full_encrypt(v)?),
+        Bound::Unbounded => Bound::Unbounded,
+    })
+}
+
+/// A collection of order-revealing ciphertexts, each with an associated value, which can be
+/// searched with a [`RangeQuery`].
+///
+/// The ciphertexts you insert should be right-only ciphertexts (as produced by
+/// [`Cipher::right_encrypt`](crate::Cipher::right_encrypt)), because that is what you'd be storing
+/// in the real world.  However, right ciphertexts can't be compared against each other, which
+/// means they can't be kept in any sort of order, and so a query has to compare the range bounds
+/// against every entry in the index.  If you need something cleverer than that, you'll need to
+/// store left ciphertexts, with all the correlation risk that entails.
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::aes128v1::ore;
+/// use std::ops::Bound;
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// # let key = [0u8; 32];
+/// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+/// let mut index = ore::RangeIndex::<4, 256, &str>::new();
+///
+/// index.insert(cipher.right_encrypt(&42u32.try_into()?)?, "forty-two");
+/// index.insert(cipher.right_encrypt(&9001u32.try_into()?)?, "over nine thousand");
+///
+/// let query = ore::RangeQuery::<4, 256>::new(
+///     &cipher,
+///     Bound::Included(&0u32.try_into()?),
+///     Bound::Excluded(&100u32.try_into()?),
+/// )?;
+///
+/// assert_eq!(vec![&"forty-two"], index.query(&query)?);
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct RangeIndex<S: CipherSuite<W, 3>, const N: usize, const W: u16, V> {
+    /// Everything we know about
+    entries: Vec<(CipherText<S, OrderingCMP, N, W, 3>, V)>,
+}
+
+impl<S: CipherSuite<W, 3>, const N: usize, const W: u16, V> RangeIndex<S, N, W, V> {
+    /// Create a new, empty, index
+    #[must_use]
+    pub fn new() -> Self {
+        RangeIndex {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Add a ciphertext, and its associated value, to the index
+    pub fn insert(&mut self, ct: CipherText<S, OrderingCMP, N, W, 3>, value: V) {
+        self.entries.push((ct, value));
+    }
+
+    /// The number of entries in the index
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the index is completely empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Find the values associated with all ciphertexts which fall within the query's range
+    ///
+    /// Values are returned in the order in which they were inserted.
+    ///
+    /// # Errors
+    ///
+    /// Can return an error if a comparison fails, which should only happen if there's a bug
+    /// somewhere.
+    ///
+    pub fn query(&self, query: &RangeQuery<S, N, W>) -> Result<Vec<&V>, Error> {
+        let mut results = Vec::new();
+
+        for (ct, value) in &self.entries {
+            if query.contains(ct)? {
+                results.push(value);
+            }
+        }
+
+        Ok(results)
+    }
+}
+
+impl<S: CipherSuite<W, 3>, const N: usize, const W: u16, V> Default for RangeIndex<S, N, W, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes128v1::ore;
+    use crate::util::random_key;
+
+    fn index(cipher: &ore::Cipher<2, 256>) -> ore::RangeIndex<2, 256, u16> {
+        let mut idx = ore::RangeIndex::<2, 256, u16>::new();
+
+        for i in [5u16, 10, 15, 20, 25] {
+            idx.insert(cipher.right_encrypt(&i.try_into().unwrap()).unwrap(), i);
+        }
+
+        idx
+    }
+
+    fn query(
+        cipher: &ore::Cipher<2, 256>,
+        lower: Bound<u16>,
+        upper: Bound<u16>,
+    ) -> ore::RangeQuery<2, 256> {
+        fn pt(b: Bound<u16>) -> Bound<PlainText<2, 256>> {
+            match b {
+                Bound::Included(v) => Bound::Included(v.try_into().unwrap()),
+                Bound::Excluded(v) => Bound::Excluded(v.try_into().unwrap()),
+                Bound::Unbounded => Bound::Unbounded,
+            }
+        }
+
+        ore::RangeQuery::new(cipher, pt(lower).as_ref(), pt(upper).as_ref()).unwrap()
+    }
+
+    #[test]
+    fn empty_index() {
+        let cipher = ore::Cipher::<2, 256>::new(&random_key()).unwrap();
+        let idx = ore::RangeIndex::<2, 256, u16>::new();
+
+        assert!(idx.is_empty());
+        assert!(idx
+            .query(&query(&cipher, Bound::Unbounded, Bound::Unbounded))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn unbounded_query_finds_everything() {
+        let cipher = ore::Cipher::<2, 256>::new(&random_key()).unwrap();
+        let idx = index(&cipher);
+
+        assert_eq!(5, idx.len());
+        assert_eq!(
+            vec![&5, &10, &15, &20, &25],
+            idx.query(&query(&cipher, Bound::Unbounded, Bound::Unbounded))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn inclusive_bounds() {
+        let cipher = ore::Cipher::<2, 256>::new(&random_key()).unwrap();
+        let idx = index(&cipher);
+
+        assert_eq!(
+            vec![&10, &15, &20],
+            idx.query(&query(&cipher, Bound::Included(10), Bound::Included(20)))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn exclusive_bounds() {
+        let cipher = ore::Cipher::<2, 256>::new(&random_key()).unwrap();
+        let idx = index(&cipher);
+
+        assert_eq!(
+            vec![&15],
+            idx.query(&query(&cipher, Bound::Excluded(10), Bound::Excluded(20)))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn half_open_bounds() {
+        let cipher = ore::Cipher::<2, 256>::new(&random_key()).unwrap();
+        let idx = index(&cipher);
+
+        assert_eq!(
+            vec![&20, &25],
+            idx.query(&query(&cipher, Bound::Included(16), Bound::Unbounded))
+                .unwrap()
+        );
+        assert_eq!(
+            vec![&5, &10],
+            idx.query(&query(&cipher, Bound::Unbounded, Bound::Excluded(15)))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn empty_range() {
+        let cipher = ore::Cipher::<2, 256>::new(&random_key()).unwrap();
+        let idx = index(&cipher);
+
+        assert!(idx
+            .query(&query(&cipher, Bound::Included(11), Bound::Included(14)))
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,143 @@
+//! Moving ciphertexts from one key to another.
+//!
+
+use crate::ciphersuite::CipherSuite;
+use crate::ciphertext::Serializable;
+use crate::cmp::Comparator;
+use crate::{Cipher, CipherText, Error};
+
+/// Re-encrypt a serialized full ciphertext under a new key.
+///
+/// The value is recovered from the ciphertext's left part with the `old` cipher (see
+/// [`Cipher::decrypt()`]), and encrypted again, as a full ciphertext, with the `new` one.  This is
+/// what's needed for scheduled key rotation: every stored full ciphertext can be run through here
+/// once, without anyone having to go back to the original data.  Right-only ciphertexts can't be
+/// decrypted, and so can't be rekeyed; values stored that way have to be encrypted afresh from
+/// their plaintexts.
+///
+/// The two ciphers need the same comparator, block count, and block width, but can use different
+/// ciphersuites, so this can also move values from one ciphersuite to another.  The right part of
+/// the new ciphertext has a fresh nonce, so the result can't be linked to the original by anyone
+/// who only has the right parts.
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::aes128v1::ore;
+/// use cretrit::SerializableCipherText;
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// let old = ore::Cipher::<4, 256>::new(&[1u8; 32])?;
+/// let new = ore::Cipher::<4, 256>::new(&[2u8; 32])?;
+///
+/// let stored = old.full_encrypt(&42u32.try_into()?)?.to_vec()?;
+/// let rekeyed = cretrit::rekey(&old, &new, &stored)?;
+///
+/// assert!(new
+///     .full_encrypt(&42u32.try_into()?)?
+///     .try_eq(&ore::CipherText::<4, 256>::from_slice(&rekeyed)?)?);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Will return an error if `bytes` isn't a valid serialized ciphertext for these parameters, if
+/// it has no left part, or if its left part wasn't produced with the `old` cipher's key.  Will
+/// also return [`Error::NotPermitted`] if `new` is a right-only cipher.
+///
+pub fn rekey<
+    OS: CipherSuite<W, M>,
+    NS: CipherSuite<W, M>,
+    CMP: Comparator<M>,
+    const N: usize,
+    const W: u16,
+    const M: u8,
+>(
+    old: &Cipher<OS, CMP, N, W, M>,
+    new: &Cipher<NS, CMP, N, W, M>,
+    bytes: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let plaintext = old.decrypt(&CipherText::<OS, CMP, N, W, M>::from_slice(bytes)?)?;
+
+    new.full_encrypt(&plaintext)?.to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{aes128v1, aes256v1};
+
+    #[test]
+    fn rekeyed_ciphertext_decrypts_with_new_key() {
+        let old = aes128v1::ore::Cipher::<4, 256>::new(&[1u8; 32]).unwrap();
+        let new = aes128v1::ore::Cipher::<4, 256>::new(&[2u8; 32]).unwrap();
+
+        let stored = old
+            .full_encrypt(&42u32.try_into().unwrap())
+            .unwrap()
+            .to_vec()
+            .unwrap();
+        let rekeyed =
+            aes128v1::ore::CipherText::<4, 256>::from_slice(&rekey(&old, &new, &stored).unwrap())
+                .unwrap();
+
+        assert_eq!(42, u32::try_from(&new.decrypt(&rekeyed).unwrap()).unwrap());
+        assert!(old.decrypt(&rekeyed).is_err());
+    }
+
+    #[test]
+    fn rekeying_can_change_ciphersuite() {
+        let old = aes128v1::ere::Cipher::<2, 16>::new(&[1u8; 32]).unwrap();
+        let new = aes256v1::ere::Cipher::<2, 16>::new(&[1u8; 32]).unwrap();
+
+        let stored = old
+            .full_encrypt(&200u8.try_into().unwrap())
+            .unwrap()
+            .to_vec()
+            .unwrap();
+        let rekeyed =
+            aes256v1::ere::CipherText::<2, 16>::from_slice(&rekey(&old, &new, &stored).unwrap())
+                .unwrap();
+
+        assert!(new
+            .full_encrypt(&200u8.try_into().unwrap())
+            .unwrap()
+            .try_eq(&rekeyed)
+            .unwrap());
+    }
+
+    #[test]
+    fn cannot_rekey_without_a_left_part() {
+        let old = aes128v1::ore::Cipher::<4, 256>::new(&[1u8; 32]).unwrap();
+        let new = aes128v1::ore::Cipher::<4, 256>::new(&[2u8; 32]).unwrap();
+
+        let stored = old
+            .right_encrypt(&42u32.try_into().unwrap())
+            .unwrap()
+            .to_vec()
+            .unwrap();
+
+        assert!(matches!(
+            rekey(&old, &new, &stored),
+            Err(Error::CryptoError(_))
+        ));
+    }
+
+    #[test]
+    fn cannot_rekey_with_the_wrong_old_key() {
+        let old = aes128v1::ore::Cipher::<4, 256>::new(&[1u8; 32]).unwrap();
+        let new = aes128v1::ore::Cipher::<4, 256>::new(&[2u8; 32]).unwrap();
+
+        let stored = new
+            .full_encrypt(&42u32.try_into().unwrap())
+            .unwrap()
+            .to_vec()
+            .unwrap();
+
+        assert!(matches!(
+            rekey(&old, &new, &stored),
+            Err(Error::CryptoError(_))
+        ));
+    }
+}
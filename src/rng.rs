@@ -0,0 +1,126 @@
+//! The random number generation traits that everything else is built on.
+//!
+//! Cretrit's own RNGs, including the ones named by each ciphersuite, come from rand 0.8, and always
+//! will for a given ciphersuite, because the PRP is a shuffle driven by a keyed RNG; a different
+//! RNG (or a different shuffle algorithm) would produce different ciphertexts from the same key.
+//! Everything inside the crate gets its RNG traits from here, rather than from `rand` directly, so
+//! that there is only one place to change when that changes.
+//!
+//! Callers who have moved on to newer rand releases can still hand their RNGs to cretrit, via the
+//! adapters in here, each of which is behind its own feature flag.
+//!
+
+pub(crate) use rand::{seq::SliceRandom, CryptoRng, Fill, Rng, RngCore, SeedableRng};
+
+/// Use an RNG from rand 0.9 (or anything else built on `rand_core` 0.9) where cretrit wants one.
+///
+/// Cretrit's RNG bounds (such as on [`Cipher::new_with_rng()`](crate::Cipher::new_with_rng)) are
+/// rand 0.8 traits, which rand 0.9 RNGs don't implement.  Wrapping one of those RNGs in this
+/// makes it usable, without changing what it generates.  The wrapper is only a [`CryptoRng`] if
+/// the RNG it wraps is one.
+///
+/// Only available when the `rand_core_09` feature is enabled.
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::aes128v1::ore;
+/// use cretrit::Rand09Compat;
+/// use rand_chacha_09::rand_core::SeedableRng;
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// # let key = [0u8; 32];
+/// let mut rng = rand_chacha_09::ChaCha20Rng::from_seed([1u8; 32]);
+/// let cipher = ore::Cipher::<4, 256>::new_with_rng(&key, &mut Rand09Compat::new(&mut rng))?;
+/// let forty_two = cipher.full_encrypt(&42u32.try_into()?)?;
+/// # Ok(())
+/// # }
+/// ```
+///
+#[cfg(feature = "rand_core_09")]
+#[derive(Clone, Debug)]
+pub struct Rand09Compat<R>(R);
+
+#[cfg(feature = "rand_core_09")]
+impl<R: rand_core_09::RngCore> Rand09Compat<R> {
+    /// Wrap a rand 0.9 RNG
+    ///
+    pub fn new(rng: R) -> Self {
+        Self(rng)
+    }
+
+    /// Get the wrapped RNG back
+    ///
+    pub fn into_inner(self) -> R {
+        self.0
+    }
+}
+
+#[cfg(feature = "rand_core_09")]
+impl<R: rand_core_09::RngCore> RngCore for Rand09Compat<R> {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        // rand 0.9's infallible RngCore panics, rather than returning an error, if it can't
+        // generate, so there's never anything to pass on here
+        self.0.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rand_core_09")]
+impl<R: rand_core_09::CryptoRng> CryptoRng for Rand09Compat<R> {}
+
+#[cfg(all(test, feature = "rand_core_09"))]
+mod tests {
+    use super::*;
+    use crate::aes128v1::ore;
+    use crate::ciphertext::Serializable;
+    use rand_chacha_09::rand_core::{RngCore as _, SeedableRng as _};
+
+    #[test]
+    fn generates_what_the_wrapped_rng_does() {
+        let mut bare = rand_chacha_09::ChaCha20Rng::from_seed([1u8; 32]);
+        let mut wrapped = Rand09Compat::new(rand_chacha_09::ChaCha20Rng::from_seed([1u8; 32]));
+
+        assert_eq!(bare.next_u64(), wrapped.next_u64());
+
+        let (mut a, mut b) = ([0u8; 37], [0u8; 37]);
+        bare.fill_bytes(&mut a);
+        wrapped.try_fill_bytes(&mut b).unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn seeds_a_cipher() {
+        let key = [7u8; 32];
+        let seed = [3u8; 32];
+
+        let c1 = ore::Cipher::<4, 256>::new_with_rng(
+            &key,
+            &mut Rand09Compat::new(rand_chacha_09::ChaCha20Rng::from_seed(seed)),
+        )
+        .unwrap();
+        let c2 = ore::Cipher::<4, 256>::new_with_rng(
+            &key,
+            &mut Rand09Compat::new(rand_chacha_09::ChaCha20Rng::from_seed(seed)),
+        )
+        .unwrap();
+
+        let v = 42u32.try_into().unwrap();
+        assert_eq!(
+            c1.full_encrypt(&v).unwrap().to_vec().unwrap(),
+            c2.full_encrypt(&v).unwrap().to_vec().unwrap()
+        );
+    }
+}
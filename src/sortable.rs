@@ -0,0 +1,202 @@
+//! Ordering serialized ciphertexts without deserializing them.
+//!
+
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+
+use crate::ciphersuite::CipherSuite;
+use crate::ciphertext::split_serialized;
+use crate::cmp::OrderingCMP;
+use crate::{CipherText, Error};
+
+/// A serialized order-revealing ciphertext, which can be sorted without being deserialized.
+///
+/// Keeping serialized ciphertexts in an ordered collection (such as a `BTreeMap`) would normally
+/// mean either deserializing every ciphertext up front, or deserializing a pair of them for every
+/// single comparison.  A `SortableBytes` instead keeps hold of the serialized form, and each
+/// comparison only parses the bits of the two ciphertexts that the comparison actually needs: the
+/// left part of one, and a handful of values (one per block) from the right part of the other.
+///
+/// Since every ciphertext in an ordered collection has to be comparable with every other, only
+/// full ciphertexts (that is, those with a "left" part) can be made into a `SortableBytes`, with
+/// all the correlation risk that storing left ciphertexts entails.
+///
+/// The framing of the serialized ciphertext is checked when the `SortableBytes` is created, but
+/// its contents aren't, so a ciphertext that has been corrupted may cause a panic when it is
+/// compared, or may just end up in the wrong place.  If you don't trust where the bytes came
+/// from, deserialize them properly first.
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::aes128v1::ore;
+/// use cretrit::SerializableCipherText;
+/// use std::collections::BTreeMap;
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// # let key = [0u8; 32];
+/// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+/// let mut map = BTreeMap::new();
+///
+/// for (v, name) in [(9001u32, "over nine thousand"), (42, "forty-two"), (1, "one")] {
+///     let bytes = cipher.full_encrypt(&v.try_into()?)?.to_vec()?;
+///     map.insert(ore::SortableBytes::<4, 256>::new(bytes)?, name);
+/// }
+///
+/// assert_eq!(
+///     vec![&"one", &"forty-two", &"over nine thousand"],
+///     map.values().collect::<Vec<_>>()
+/// );
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Debug, Clone)]
+pub struct SortableBytes<S: CipherSuite<W, 3>, const N: usize, const W: u16> {
+    /// The serialized ciphertext
+    bytes: Vec<u8>,
+
+    /// Compiler pacification
+    _mark: PhantomData<S>,
+}
+
+impl<S: CipherSuite<W, 3>, const N: usize, const W: u16> SortableBytes<S, N, W> {
+    /// Wrap up a serialized ciphertext, so it can be sorted.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::Incomparable`] if the ciphertext doesn't have a left part, or another
+    /// error if the bytes aren't framed like a serialized ciphertext.
+    ///
+    pub fn new(bytes: Vec<u8>) -> Result<Self, Error> {
+        if split_serialized(&bytes)?.0.is_none() {
+            return Err(Error::Incomparable {
+                reason: "sortable ciphertexts must have a left part".to_string(),
+            });
+        }
+
+        Ok(SortableBytes {
+            bytes,
+            _mark: PhantomData,
+        })
+    }
+
+    /// The serialized ciphertext
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Unwrap the serialized ciphertext
+    #[must_use]
+    pub fn into_vec(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl<S: CipherSuite<W, 3>, const N: usize, const W: u16> AsRef<[u8]> for SortableBytes<S, N, W> {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl<S: CipherSuite<W, 3>, const N: usize, const W: u16> Ord for SortableBytes<S, N, W> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        #[allow(clippy::expect_used)] // No way to return an error when implementing Ord
+        CipherText::<S, OrderingCMP, N, W, 3>::lazy_compare(&self.bytes, &other.bytes)
+            .and_then(OrderingCMP::invert)
+            .expect("comparison of serialized ciphertexts failed")
+    }
+}
+
+impl<S: CipherSuite<W, 3>, const N: usize, const W: u16> PartialOrd for SortableBytes<S, N, W> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S: CipherSuite<W, 3>, const N: usize, const W: u16> PartialEq for SortableBytes<S, N, W> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<S: CipherSuite<W, 3>, const N: usize, const W: u16> Eq for SortableBytes<S, N, W> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::aes128v1::ore;
+    use crate::util::random_key;
+    use crate::SerializableCipherText;
+    use std::collections::BTreeMap;
+
+    fn sortable(cipher: &ore::Cipher<4, 256>, v: u32) -> ore::SortableBytes<4, 256> {
+        ore::SortableBytes::new(
+            cipher
+                .full_encrypt(&v.try_into().unwrap())
+                .unwrap()
+                .to_vec()
+                .unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn right_only_ciphertexts_are_rejected() {
+        let cipher = ore::Cipher::<4, 256>::new(&random_key()).unwrap();
+        let bytes = cipher
+            .right_encrypt(&42u32.try_into().unwrap())
+            .unwrap()
+            .to_vec()
+            .unwrap();
+
+        assert!(ore::SortableBytes::<4, 256>::new(bytes).is_err());
+    }
+
+    #[test]
+    fn garbage_is_rejected() {
+        assert!(ore::SortableBytes::<4, 256>::new(vec![]).is_err());
+        assert!(ore::SortableBytes::<4, 256>::new(vec![1, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn bytes_survive_the_trip() {
+        let cipher = ore::Cipher::<4, 256>::new(&random_key()).unwrap();
+        let s = sortable(&cipher, 42);
+        let bytes = s.as_bytes().to_vec();
+
+        assert_eq!(bytes, s.into_vec());
+    }
+
+    #[test]
+    fn btreemap_sorting() {
+        let cipher = ore::Cipher::<4, 256>::new(&random_key()).unwrap();
+        let mut map = BTreeMap::new();
+
+        for v in [31_337u32, 0, 9001, 42, u32::MAX, 1] {
+            map.insert(sortable(&cipher, v), v);
+        }
+
+        // A fresh encryption of an existing key is a different ciphertext, but the same key
+        map.insert(sortable(&cipher, 42), 42);
+
+        assert_eq!(
+            vec![0u32, 1, 42, 9001, 31_337, u32::MAX],
+            map.values().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(&9001), map.get(&sortable(&cipher, 9001)));
+        assert_eq!(None, map.get(&sortable(&cipher, 9002)));
+    }
+
+    quickcheck! {
+        fn lazy_comparison_matches_full_comparison(a: u32, b: u32) -> bool {
+            let cipher = ore::Cipher::<4, 256>::new(&random_key()).unwrap();
+            let ct_a = cipher.full_encrypt(&a.try_into().unwrap()).unwrap();
+            let ct_b = cipher.full_encrypt(&b.try_into().unwrap()).unwrap();
+            let s_a = ore::SortableBytes::<4, 256>::new(ct_a.to_vec().unwrap()).unwrap();
+            let s_b = ore::SortableBytes::<4, 256>::new(ct_b.to_vec().unwrap()).unwrap();
+
+            s_a.cmp(&s_b) == ct_a.try_cmp(&ct_b).unwrap() && s_a.cmp(&s_b) == a.cmp(&b)
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! Statistics about serialized ciphertexts, for checking that stored data looks the way it should.
+//!
+//! Every value in a right ciphertext is a comparison result offset by the output of a keyed hash,
+//! so the values should be spread evenly across `0..M`, and no two ciphertexts should ever share a
+//! nonce base.  A ciphertext that's been truncated, zeroed, or produced by a broken RNG tends to
+//! stand out a mile on both counts, without anyone needing the key to notice.
+//!
+//! None of the figures collected here say anything about the encrypted value: they're all things
+//! that anyone holding the serialized ciphertext could work out for themselves.
+//!
+
+use crate::packed::PackedValues;
+use crate::CipherTextType;
+
+/// What a serialized ciphertext looks like, in aggregate
+///
+/// Produced by [`CipherText::inspect_serialized()`](crate::CipherText::inspect_serialized).
+///
+/// What counts as "normal" depends on the comparator: right ciphertext values are packed with a
+/// different encoding for each one, so the proportion of set bits in the packed values will be
+/// close to, but not necessarily exactly, a half.  The most reliable approach is to compare the
+/// figures for a ciphertext against those for other ciphertexts of the same type.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::collections::HashSet;
+///
+/// use cretrit::aes128v1::ere;
+/// use cretrit::SerializableCipherText;
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// # let key = [0u8; 32];
+/// let cipher = ere::Cipher::<4, 256>::new(&key)?;
+/// let mut nonces = HashSet::new();
+///
+/// for i in 0..10u32 {
+///     let stored = cipher.right_encrypt(&i.try_into()?)?.to_vec()?;
+///     let stats = ere::CipherText::<4, 256>::inspect_serialized(&stored)?;
+///
+///     // Every nonce base should be unique
+///     assert!(nonces.insert(stats.nonce_base));
+///     // With 1024 values, getting fewer than 400 of either is vanishingly unlikely
+///     assert!(stats.value_counts.iter().all(|c| *c > 400));
+///     assert!(stats.packed_ones > 400 && stats.packed_ones < 624);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CipherTextStats {
+    /// Whether the ciphertext has a left part
+    pub ciphertext_type: CipherTextType,
+    /// The nonce base of the right ciphertext, which should never be repeated
+    pub nonce_base: [u8; 16],
+    /// How many of the right ciphertext's values are `0`, `1`, and so on, up to `M - 1`
+    pub value_counts: Vec<usize>,
+    /// How many bytes the right ciphertext's values take up once they've been packed
+    pub packed_bytes: usize,
+    /// How many of the bits in those bytes are set
+    pub packed_ones: usize,
+}
+
+impl CipherTextStats {
+    /// Gather up the statistics for a ciphertext's right part
+    pub(crate) fn new<const M: u8>(
+        ciphertext_type: CipherTextType,
+        nonce_base: [u8; 16],
+        values: &PackedValues<M>,
+        packed: &[u8],
+    ) -> Self {
+        let mut value_counts = vec![0usize; usize::from(M)];
+
+        for v in values.iter() {
+            if let Some(count) = value_counts.get_mut(usize::from(v)) {
+                *count = count.saturating_add(1);
+            }
+        }
+
+        CipherTextStats {
+            ciphertext_type,
+            nonce_base,
+            value_counts,
+            packed_bytes: packed.len(),
+            packed_ones: packed.iter().fold(0usize, |ones, b| {
+                ones.saturating_add(usize::try_from(b.count_ones()).unwrap_or(0))
+            }),
+        }
+    }
+
+    /// How many values there are in the right ciphertext, all told
+    #[must_use]
+    pub fn values(&self) -> usize {
+        self.value_counts
+            .iter()
+            .fold(0usize, |total, c| total.saturating_add(*c))
+    }
+
+    /// How many bits the packed values take up, including any padding at the end
+    #[must_use]
+    pub fn packed_bits(&self) -> usize {
+        self.packed_bytes.saturating_mul(8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes128v1::{ere, ore};
+    use crate::{Error, SerializableCipherText};
+
+    #[test]
+    fn order_revealing_values_are_evenly_spread() {
+        let cipher = ore::Cipher::<8, 256>::new(&[1u8; 32]).unwrap();
+        let full = cipher.full_encrypt(&42u64.try_into().unwrap()).unwrap();
+        let v = full.to_vec().unwrap();
+
+        let stats = ore::CipherText::<8, 256>::inspect_serialized(&v).unwrap();
+
+        assert_eq!(CipherTextType::Full, stats.ciphertext_type);
+        assert_eq!(3, stats.value_counts.len());
+        assert_eq!(8 * 256, stats.values());
+        // Each value should turn up about 683 times
+        assert!(stats.value_counts.iter().all(|c| *c > 550), "{stats:?}");
+        assert!(stats.packed_ones < stats.packed_bits());
+    }
+
+    #[test]
+    fn fixed_width_values_fill_their_bytes() {
+        let cipher = ere::Cipher::<4, 256>::new(&[1u8; 32]).unwrap();
+        let v = cipher
+            .right_encrypt(&42u32.try_into().unwrap())
+            .unwrap()
+            .to_vec()
+            .unwrap();
+
+        let stats = ere::CipherText::<4, 256>::inspect_serialized(&v).unwrap();
+
+        assert_eq!(CipherTextType::RightOnly, stats.ciphertext_type);
+        assert_eq!(stats.values(), stats.packed_bits());
+        assert_eq!(stats.value_counts[1], stats.packed_ones);
+        assert_eq!(v[3..19], stats.nonce_base);
+    }
+
+    #[test]
+    fn broken_ciphertexts_are_not_inspected() {
+        let cipher = ere::Cipher::<4, 256>::new(&[1u8; 32]).unwrap();
+        let v = cipher
+            .right_encrypt(&42u32.try_into().unwrap())
+            .unwrap()
+            .to_vec()
+            .unwrap();
+
+        assert!(matches!(
+            ere::CipherText::<4, 256>::inspect_serialized(&v[..100]),
+            Err(Error::ParseError(_))
+        ));
+        assert!(ere::CipherText::<4, 257>::inspect_serialized(&v).is_err());
+    }
+}
@@ -0,0 +1,921 @@
+//! The parts of each ciphersuite's comparison modules that are the same for every ciphersuite.
+//!
+//! The `ore`, `ere`, and `pore` modules of every ciphersuite have the same type aliases and
+//! methods, and the only thing that differs between ciphersuites is which `CipherSuite` they're
+//! for.  Rather than keeping three copies of each, the modules invoke one of the macros in here,
+//! with the name of the ciphersuite (for the documentation), and keep their module documentation
+//! and any tests of the ciphersuite itself alongside.  The `*_module` macros expect to be invoked
+//! in a module whose parent defines the ciphersuite's `CipherSuite`.
+//!
+//! Ciphersuites that are built on top of another ciphersuite, such as the
+//! [truncated](crate::truncated) and [Feistel](crate::feistel) ones, have extra generic
+//! parameters, and so their modules can't have the presets, or the examples, that name a concrete
+//! cipher.  They invoke the `*_items` macros instead, which define just the aliases and comparison
+//! methods that every module has; the `*_module` macros use them too.
+//!
+
+/// Define the type aliases and comparison methods that every `ore` (order-revealing) module has
+///
+/// The aliases are for the ciphersuite `$cs`, which can refer to the block width `W`, and to the
+/// extra type parameters `$t` (each of which must implement `$tb`) and const parameters `$c`.  The
+/// extra parameters come before the block count and width in every alias.  `$desc` finishes the
+/// sentence "specialisation for ..." in the aliases' documentation, and any attributes at the end
+/// (such as doc comments with examples) are added to the documentation of `try_cmp()`.
+///
+macro_rules! ore_items {
+    (
+        $desc:literal,
+        $cs:ty,
+        [$($t:ident: $tb:path),*],
+        [$($c:ident: $ct:ty),*]
+        $(, $(#[$try_cmp_doc:meta])+)?
+    ) => {
+        use std::cmp::Ordering;
+
+        use crate::audit::AuditOperation;
+        use crate::cipher::Cipher as C;
+        use crate::ciphertext::{CipherText as CT, LeftCipherText as LCT, Serializable};
+        use crate::cmp::OrderingCMP;
+        use crate::filter::EqualityFilter as EF;
+        use crate::query::PreparedQuery as PQ;
+        use crate::{ComparisonEncryptor, Error, PlainText};
+
+        #[doc = concat!(" [`Cipher`](crate::Cipher) specialisation for ", $desc, ".")]
+        ///
+        /// See the documentation for [`Cipher`](crate::Cipher) for usage information.
+        ///
+        pub type Cipher<$($t,)* $(const $c: $ct,)* const N: usize, const W: u16> =
+            C<$cs, OrderingCMP, N, W, 3>;
+
+        #[doc = concat!(" [`CipherText`](crate::CipherText) specialisation for ", $desc, ".")]
+        ///
+        /// See the documentation for [`CipherText`](crate::CipherText) for usage information.
+        ///
+        pub type CipherText<$($t,)* $(const $c: $ct,)* const N: usize, const W: u16> =
+            CT<$cs, OrderingCMP, N, W, 3>;
+
+        #[doc = concat!(" [`LeftCipherText`](crate::LeftCipherText) specialisation for ", $desc, ".")]
+        ///
+        /// See the documentation for [`LeftCipherText`](crate::LeftCipherText) for usage
+        /// information.
+        ///
+        pub type LeftCipherText<$($t,)* $(const $c: $ct,)* const N: usize, const W: u16> =
+            LCT<$cs, OrderingCMP, N, W, 3>;
+
+        #[doc = concat!(" [`PreparedQuery`](crate::PreparedQuery) specialisation for ", $desc, ".")]
+        ///
+        /// See the documentation for [`PreparedQuery`](crate::PreparedQuery) for usage information.
+        ///
+        pub type PreparedQuery<$($t,)* $(const $c: $ct,)* const N: usize, const W: u16> =
+            PQ<$cs, OrderingCMP, N, W, 3>;
+
+        #[doc = concat!(" [`EqualityFilter`](crate::EqualityFilter) specialisation for ", $desc, ".")]
+        ///
+        /// See the documentation for [`EqualityFilter`](crate::EqualityFilter) for usage
+        /// information.
+        ///
+        pub type EqualityFilter<$($t,)* $(const $c: $ct,)* const N: usize, const W: u16> =
+            EF<$cs, OrderingCMP, N, W, 3>;
+
+        #[cfg(feature = "comparison-traits")]
+        impl<$($t: $tb,)* $(const $c: $ct,)* const N: usize, const W: u16> PartialOrd
+            for CipherText<$($t,)* $($c,)* N, W>
+        {
+            /// Returns `None` if neither ciphertext has a left part, which is why there's no
+            /// [`Ord`] implementation
+            fn partial_cmp(&self, other: &CipherText<$($t,)* $($c,)* N, W>) -> Option<Ordering> {
+                self.try_cmp(other).ok()
+            }
+        }
+
+        #[cfg(feature = "comparison-traits")]
+        impl<$($t: $tb,)* $(const $c: $ct,)* const N: usize, const W: u16> PartialEq
+            for CipherText<$($t,)* $($c,)* N, W>
+        {
+            /// Returns `false` if neither ciphertext has a left part, even when comparing a
+            /// ciphertext with itself, which is why there's no [`Eq`] implementation
+            fn eq(&self, other: &CipherText<$($t,)* $($c,)* N, W>) -> bool {
+                self.partial_cmp(other) == Some(Ordering::Equal)
+            }
+        }
+
+        impl<$($t: $tb,)* $(const $c: $ct,)* const N: usize, const W: u16> ComparisonEncryptor<N, W>
+            for Cipher<$($t,)* $($c,)* N, W>
+        {
+            type Comparison = Ordering;
+
+            fn full_encrypt_to_vec(&self, value: &PlainText<N, W>) -> Result<Vec<u8>, Error> {
+                self.full_encrypt(value)?.to_vec()
+            }
+
+            fn right_encrypt_to_vec(&self, value: &PlainText<N, W>) -> Result<Vec<u8>, Error> {
+                self.right_encrypt(value)?.to_vec()
+            }
+
+            fn compare_serialized(&self, a: &[u8], b: &[u8]) -> Result<Ordering, Error> {
+                self.audited(AuditOperation::Compare, || {
+                    let ct_a = CipherText::<$($t,)* $($c,)* N, W>::from_slice(a)?;
+                    let ct_b = CipherText::<$($t,)* $($c,)* N, W>::from_slice(b)?;
+
+                    ct_a.try_cmp(&ct_b)
+                })
+            }
+        }
+
+        impl<$($t: $tb,)* $(const $c: $ct,)* const N: usize, const W: u16>
+            CipherText<$($t,)* $($c,)* N, W>
+        {
+            /// Compare this ciphertext against another, returning an error (rather than `None`, as
+            /// [`partial_cmp()`](PartialOrd::partial_cmp) does) if they can't be compared
+            ///
+            $($(#[$try_cmp_doc])+)?
+            /// # Errors
+            ///
+            /// Will return [`Error::Incomparable`] if neither ciphertext has a left part, and
+            /// [`Error::ParamsError`] if they're known to have been encrypted with different keys.
+            ///
+            pub fn try_cmp(&self, other: &CipherText<$($t,)* $($c,)* N, W>) -> Result<Ordering, Error> {
+                OrderingCMP::invert(self.compare_either(other)?)
+            }
+
+            /// Determine whether this ciphertext's value is equal to another's, returning an error
+            /// (rather than `false`, as `==` does) if they can't be compared
+            ///
+            /// # Errors
+            ///
+            /// Will return [`Error::Incomparable`] if neither ciphertext has a left part, and
+            /// [`Error::ParamsError`] if they're known to have been encrypted with different keys.
+            ///
+            pub fn try_eq(&self, other: &CipherText<$($t,)* $($c,)* N, W>) -> Result<bool, Error> {
+                Ok(self.try_cmp(other)? == Ordering::Equal)
+            }
+        }
+
+        impl<$($t: $tb,)* $(const $c: $ct,)* const N: usize, const W: u16>
+            LeftCipherText<$($t,)* $($c,)* N, W>
+        {
+            /// Compare the value in this left ciphertext against the value in the right part of a
+            /// ciphertext
+            ///
+            /// The result is from the perspective of the left ciphertext; that is, `Ordering::Less`
+            /// means that the value in the left ciphertext is less than the value in `right`.
+            ///
+            /// # Errors
+            ///
+            /// Will return [`Error::ParamsError`] if `right` is known to have been encrypted with a
+            /// different key.  Can also return an error if something goes wrong during the comparison,
+            /// which should only happen if there's a bug somewhere.
+            ///
+            pub fn compare_with_right(
+                &self,
+                right: &CipherText<$($t,)* $($c,)* N, W>,
+            ) -> Result<Ordering, Error> {
+                OrderingCMP::invert(self.compare(right)?)
+            }
+        }
+
+        impl<$($t: $tb,)* $(const $c: $ct,)* const N: usize, const W: u16>
+            PreparedQuery<$($t,)* $($c,)* N, W>
+        {
+            /// Compare the value in this prepared query against the value in the right part of a
+            /// ciphertext
+            ///
+            /// The result is from the perspective of the query; that is, `Ordering::Less` means
+            /// that the value in the query is less than the value in `right`.
+            ///
+            /// # Errors
+            ///
+            /// Will return [`Error::ParamsError`] if `right` is known to have been encrypted with a
+            /// different key.  Can also return an error if something goes wrong during the comparison,
+            /// which should only happen if there's a bug somewhere.
+            ///
+            pub fn compare_with_right(
+                &self,
+                right: &CipherText<$($t,)* $($c,)* N, W>,
+            ) -> Result<Ordering, Error> {
+                OrderingCMP::invert(self.compare(right)?)
+            }
+        }
+    };
+}
+pub(crate) use ore_items;
+
+/// Define the types and methods of a ciphersuite's `ore` (order-revealing) module
+macro_rules! ore_module {
+    ($suite:literal) => {
+        use super::CipherSuite;
+        use crate::histogram::Histogram as H;
+        use crate::ord_key::OrdBytesKey as OBK;
+        use crate::partition::Partition as P;
+        use crate::range::{RangeIndex as RI, RangeQuery as RQ};
+        use crate::sortable::SortableBytes as SB;
+
+        crate::suite_modules::ore_items!(
+            "this [ciphersuite](super)",
+            CipherSuite<W, 3>,
+            [],
+            [],
+            /// # Examples
+            ///
+            /// ```rust
+            #[doc = concat!(" use cretrit::", $suite, "::ore;")]
+            /// use cretrit::Error;
+            /// use std::cmp::Ordering;
+            ///
+            /// # fn main() -> Result<(), cretrit::Error> {
+            /// # let key = [0u8; 32];
+            /// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+            /// let query = cipher.full_encrypt(&42u32.try_into()?)?;
+            /// let stored = cipher.right_encrypt(&9001u32.try_into()?)?;
+            ///
+            /// assert_eq!(Ordering::Less, query.try_cmp(&stored)?);
+            /// // Two right-only ciphertexts can't be compared
+            /// assert!(matches!(stored.try_cmp(&stored), Err(Error::Incomparable { .. })));
+            /// # Ok(())
+            /// # }
+            /// ```
+            ///
+        );
+
+        /// [`RangeQuery`](crate::RangeQuery) specialisation for this [ciphersuite](super).
+        ///
+        /// See the documentation for [`RangeQuery`](crate::RangeQuery) for usage information.
+        ///
+        pub type RangeQuery<const N: usize, const W: u16> = RQ<CipherSuite<W, 3>, N, W>;
+
+        /// [`RangeIndex`](crate::RangeIndex) specialisation for this [ciphersuite](super).
+        ///
+        /// See the documentation for [`RangeIndex`](crate::RangeIndex) for usage information.
+        ///
+        pub type RangeIndex<const N: usize, const W: u16, V> = RI<CipherSuite<W, 3>, N, W, V>;
+
+        /// [`Histogram`](crate::Histogram) specialisation for this [ciphersuite](super).
+        ///
+        /// See the documentation for [`Histogram`](crate::Histogram) for usage information.
+        ///
+        pub type Histogram<const N: usize, const W: u16> = H<CipherSuite<W, 3>, N, W>;
+
+        /// [`Partition`](crate::Partition) specialisation for this [ciphersuite](super).
+        ///
+        /// See the documentation for [`Partition`](crate::Partition) for usage information.
+        ///
+        pub type Partition<const N: usize, const W: u16> = P<CipherSuite<W, 3>, N, W>;
+
+        /// [`OrdBytesKey`](crate::OrdBytesKey) specialisation for this [ciphersuite](super).
+        ///
+        /// See the documentation for [`OrdBytesKey`](crate::OrdBytesKey) for usage information.
+        ///
+        pub type OrdBytesKey<const N: usize, const W: u16> = OBK<CipherSuite<W, 3>, N, W>;
+
+        /// [`SortableBytes`](crate::SortableBytes) specialisation for this [ciphersuite](super).
+        ///
+        /// See the documentation for [`SortableBytes`](crate::SortableBytes) for usage information.
+        ///
+        pub type SortableBytes<const N: usize, const W: u16> = SB<CipherSuite<W, 3>, N, W>;
+
+        /// A [`Cipher`] for 8 bit values.
+        ///
+        /// The block count and width of a cipher have to be the same everywhere its ciphertexts are
+        /// compared, so services that need to share ciphertexts are best off agreeing on one of
+        /// these presets rather than each picking their own.  A single block of 256 is the cheapest
+        /// way to encrypt a byte, and all the same keys, plaintexts, and ciphertexts work with an
+        /// explicit `Cipher<1, 256>` too.
+        ///
+        /// # Examples
+        ///
+        /// ```rust
+        #[doc = concat!(" use cretrit::", $suite, "::ore;")]
+        /// # fn main() -> Result<(), cretrit::Error> {
+        /// # let key = [0u8; 32];
+        ///
+        /// let cipher = ore::SmallCipher::new(&key)?;
+        /// let forty_two = cipher.full_encrypt(&42u8.try_into()?)?;
+        /// # Ok(())
+        /// # }
+        /// ```
+        ///
+        pub type SmallCipher = Cipher<1, 256>;
+
+        /// The [`CipherText`] produced by a [`SmallCipher`]
+        pub type SmallCipherText = CipherText<1, 256>;
+
+        /// A [`Cipher`] for 32 bit values.
+        ///
+        /// Four blocks of 256, one for each byte.  See [`SmallCipher`] for why presets are worth
+        /// using.
+        ///
+        pub type MediumCipher = Cipher<4, 256>;
+
+        /// The [`CipherText`] produced by a [`MediumCipher`]
+        pub type MediumCipherText = CipherText<4, 256>;
+
+        /// A [`Cipher`] for 64 bit values.
+        ///
+        /// Eight blocks of 256, one for each byte.  See [`SmallCipher`] for why presets are worth
+        /// using.
+        ///
+        pub type LargeCipher = Cipher<8, 256>;
+
+        /// The [`CipherText`] produced by a [`LargeCipher`]
+        pub type LargeCipherText = CipherText<8, 256>;
+
+        impl<const N: usize, const W: u16> CipherText<N, W> {
+            /// Compare this ciphertext against another, breaking ties between equal values by
+            /// comparing the ciphertexts' serialized bytes
+            ///
+            /// Two encryptions of the same value compare as [`Ordering::Equal`], but (because right
+            /// ciphertexts are randomised) they're not the same bytes, so which of them ends up
+            /// first after a sort depends on the order they started in.  Using this as the sort
+            /// order instead means that a given set of ciphertexts always sorts into the same
+            /// order, whatever order they started in, with the ciphertexts of each value kept
+            /// together.
+            ///
+            /// # Examples
+            ///
+            /// ```rust
+            #[doc = concat!(" use cretrit::", $suite, "::ore;")]
+            /// use std::cmp::Ordering;
+            ///
+            /// # fn main() -> Result<(), cretrit::Error> {
+            /// # let key = [0u8; 32];
+            /// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+            /// let a = cipher.full_encrypt(&42u32.try_into()?)?;
+            /// let b = cipher.full_encrypt(&42u32.try_into()?)?;
+            ///
+            /// assert_eq!(Ordering::Equal, a.try_cmp(&b)?);
+            /// assert_ne!(Ordering::Equal, a.stable_cmp(&b)?);
+            /// assert_eq!(a.stable_cmp(&b)?, b.stable_cmp(&a)?.reverse());
+            /// # Ok(())
+            /// # }
+            /// ```
+            ///
+            /// # Errors
+            ///
+            /// Will return [`Error::Incomparable`] if neither ciphertext has a left part, or
+            /// another error if either of them can't be serialized.
+            ///
+            pub fn stable_cmp(&self, other: &CipherText<N, W>) -> Result<Ordering, Error> {
+                let ordering = self.try_cmp(other)?;
+
+                if ordering == Ordering::Equal {
+                    Ok(self.to_vec()?.cmp(&other.to_vec()?))
+                } else {
+                    Ok(ordering)
+                }
+            }
+
+            /// Compare this ciphertext against another one block at a time, most significant block
+            /// first
+            ///
+            /// Each block's comparison is only done when the iterator gets to it, and tells you how
+            /// that block, on its own, compares to the same block of `other`.  Taking the first
+            /// result that isn't `Ordering::Equal` gives the same answer as an ordinary comparison;
+            /// stopping after the first few blocks gives the answer you'd get if the values only
+            /// had that many blocks of precision.  As with an ordinary comparison, at least one of
+            /// the ciphertexts needs to have a left part, and the results are from the perspective
+            /// of this ciphertext.
+            ///
+            /// # Examples
+            ///
+            /// ```rust
+            #[doc = concat!(" use cretrit::", $suite, "::ore;")]
+            /// use std::cmp::Ordering;
+            ///
+            /// # fn main() -> Result<(), cretrit::Error> {
+            /// # let key = [0u8; 32];
+            /// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+            /// let a = cipher.full_encrypt(&0x0102_0304u32.try_into()?)?;
+            /// let b = cipher.right_encrypt(&0x0102_ff00u32.try_into()?)?;
+            ///
+            /// let blocks = a.compare_blocks(&b)?.collect::<Result<Vec<_>, _>>()?;
+            /// assert_eq!(
+            ///     vec![Ordering::Equal, Ordering::Equal, Ordering::Less, Ordering::Greater],
+            ///     blocks
+            /// );
+            ///
+            /// // To 16 bits of precision, the values are the same
+            /// assert!(a.compare_blocks(&b)?.take(2).all(|r| matches!(r, Ok(Ordering::Equal))));
+            /// # Ok(())
+            /// # }
+            /// ```
+            ///
+            /// # Errors
+            ///
+            /// Will return [`Error::Incomparable`] if neither ciphertext has a left part.  Each
+            /// block's result can also be an error, if there's a bug somewhere.
+            ///
+            pub fn compare_blocks<'a>(
+                &'a self,
+                other: &'a CipherText<N, W>,
+            ) -> Result<impl Iterator<Item = Result<Ordering, Error>> + 'a, Error> {
+                Ok(self
+                    .block_comparisons(other)?
+                    .map(|r| r.and_then(OrderingCMP::invert)))
+            }
+        }
+
+        impl<const N: usize, const W: u16> Cipher<N, W> {
+            /// Compare two serialized ciphertexts, returning `-1`, `0`, or `1` if `a` is less than,
+            /// equal to, or greater than `b`, respectively
+            ///
+            /// This is the convention used by the comparison callbacks of most databases (such as
+            /// the support functions of a `PostgreSQL` b-tree operator class), so the result can be
+            /// handed straight back without any further translation.  Otherwise, it's just like
+            /// [`compare_serialized()`](ComparisonEncryptor::compare_serialized).
+            ///
+            /// # Examples
+            ///
+            /// ```rust
+            #[doc = concat!(" use cretrit::", $suite, "::ore;")]
+            /// use cretrit::ComparisonEncryptor;
+            ///
+            /// # fn main() -> Result<(), cretrit::Error> {
+            /// # let key = [0u8; 32];
+            /// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+            /// let forty_two = cipher.full_encrypt_to_vec(&42u32.try_into()?)?;
+            /// let over_nine_thousand = cipher.right_encrypt_to_vec(&9001u32.try_into()?)?;
+            ///
+            /// assert_eq!(-1, cipher.compare_serialized_i32(&forty_two, &over_nine_thousand)?);
+            /// # Ok(())
+            /// # }
+            /// ```
+            ///
+            /// # Errors
+            ///
+            /// Will return [`Error::Incomparable`] if neither ciphertext has a left part, which is
+            /// usually a sign that two stored values are being compared directly, rather than a
+            /// stored value being compared against a query.  Anything else that goes wrong (such as
+            /// either ciphertext being unparseable) produces some other kind of error.
+            ///
+            pub fn compare_serialized_i32(&self, a: &[u8], b: &[u8]) -> Result<i32, Error> {
+                Ok(match self.compare_serialized(a, b)? {
+                    Ordering::Less => -1,
+                    Ordering::Equal => 0,
+                    Ordering::Greater => 1,
+                })
+            }
+        }
+    };
+}
+pub(crate) use ore_module;
+
+/// Define the type aliases and comparison methods that every `ere` (equality-revealing) module has
+///
+/// Takes the same arguments as [`ore_items`], other than the attributes for `try_cmp()`, which
+/// `ere` modules don't have.
+///
+macro_rules! ere_items {
+    ($desc:literal, $cs:ty, [$($t:ident: $tb:path),*], [$($c:ident: $ct:ty),*]) => {
+        use crate::audit::AuditOperation;
+        use crate::cipher::Cipher as C;
+        use crate::ciphertext::{CipherText as CT, LeftCipherText as LCT, Serializable};
+        use crate::cmp::EqualityCMP;
+        use crate::filter::EqualityFilter as EF;
+        use crate::query::PreparedQuery as PQ;
+        use crate::{ComparisonEncryptor, Error, PlainText};
+
+        #[doc = concat!(" [`Cipher`](crate::Cipher) specialisation for ", $desc, ".")]
+        ///
+        /// See the documentation for [`Cipher`](crate::Cipher) for usage information.
+        ///
+        pub type Cipher<$($t,)* $(const $c: $ct,)* const N: usize, const W: u16> =
+            C<$cs, EqualityCMP, N, W, 2>;
+
+        #[doc = concat!(" [`CipherText`](crate::CipherText) specialisation for ", $desc, ".")]
+        ///
+        /// See the documentation for [`CipherText`](crate::CipherText) for usage information.
+        ///
+        pub type CipherText<$($t,)* $(const $c: $ct,)* const N: usize, const W: u16> =
+            CT<$cs, EqualityCMP, N, W, 2>;
+
+        #[doc = concat!(" [`LeftCipherText`](crate::LeftCipherText) specialisation for ", $desc, ".")]
+        ///
+        /// See the documentation for [`LeftCipherText`](crate::LeftCipherText) for usage
+        /// information.
+        ///
+        pub type LeftCipherText<$($t,)* $(const $c: $ct,)* const N: usize, const W: u16> =
+            LCT<$cs, EqualityCMP, N, W, 2>;
+
+        #[doc = concat!(" [`PreparedQuery`](crate::PreparedQuery) specialisation for ", $desc, ".")]
+        ///
+        /// See the documentation for [`PreparedQuery`](crate::PreparedQuery) for usage information.
+        ///
+        pub type PreparedQuery<$($t,)* $(const $c: $ct,)* const N: usize, const W: u16> =
+            PQ<$cs, EqualityCMP, N, W, 2>;
+
+        #[doc = concat!(" [`EqualityFilter`](crate::EqualityFilter) specialisation for ", $desc, ".")]
+        ///
+        /// See the documentation for [`EqualityFilter`](crate::EqualityFilter) for usage
+        /// information.
+        ///
+        pub type EqualityFilter<$($t,)* $(const $c: $ct,)* const N: usize, const W: u16> =
+            EF<$cs, EqualityCMP, N, W, 2>;
+
+        impl<$($t: $tb,)* $(const $c: $ct,)* const N: usize, const W: u16>
+            CipherText<$($t,)* $($c,)* N, W>
+        {
+            /// Determine whether this ciphertext's value is equal to another's, returning an error
+            /// (rather than `false`, as `==` does) if they can't be compared
+            ///
+            /// # Errors
+            ///
+            /// Will return [`Error::Incomparable`] if neither ciphertext has a left part, and
+            /// [`Error::ParamsError`] if they're known to have been encrypted with different keys.
+            ///
+            pub fn try_eq(&self, other: &CipherText<$($t,)* $($c,)* N, W>) -> Result<bool, Error> {
+                EqualityCMP::invert(self.compare_either(other)?)
+            }
+        }
+
+        impl<$($t: $tb,)* $(const $c: $ct,)* const N: usize, const W: u16>
+            LeftCipherText<$($t,)* $($c,)* N, W>
+        {
+            /// Determine whether the value in this left ciphertext is equal to the value in the
+            /// right part of a ciphertext
+            ///
+            /// # Errors
+            ///
+            /// Will return [`Error::ParamsError`] if `right` is known to have been encrypted with a
+            /// different key.  Can also return an error if something goes wrong during the comparison,
+            /// which should only happen if there's a bug somewhere.
+            ///
+            pub fn compare_with_right(
+                &self,
+                right: &CipherText<$($t,)* $($c,)* N, W>,
+            ) -> Result<bool, Error> {
+                EqualityCMP::invert(self.compare(right)?)
+            }
+        }
+
+        impl<$($t: $tb,)* $(const $c: $ct,)* const N: usize, const W: u16>
+            PreparedQuery<$($t,)* $($c,)* N, W>
+        {
+            /// Determine whether the value in this prepared query is equal to the value in the
+            /// right part of a ciphertext
+            ///
+            /// # Errors
+            ///
+            /// Will return [`Error::ParamsError`] if `right` is known to have been encrypted with a
+            /// different key.  Can also return an error if something goes wrong during the comparison,
+            /// which should only happen if there's a bug somewhere.
+            ///
+            pub fn compare_with_right(
+                &self,
+                right: &CipherText<$($t,)* $($c,)* N, W>,
+            ) -> Result<bool, Error> {
+                EqualityCMP::invert(self.compare(right)?)
+            }
+        }
+
+        #[cfg(feature = "comparison-traits")]
+        impl<$($t: $tb,)* $(const $c: $ct,)* const N: usize, const W: u16> PartialEq
+            for CipherText<$($t,)* $($c,)* N, W>
+        {
+            /// Returns `false` if neither ciphertext has a left part, even when comparing a
+            /// ciphertext with itself, which is why there's no [`Eq`] implementation
+            fn eq(&self, other: &CipherText<$($t,)* $($c,)* N, W>) -> bool {
+                self.try_eq(other).unwrap_or(false)
+            }
+        }
+
+        impl<$($t: $tb,)* $(const $c: $ct,)* const N: usize, const W: u16> ComparisonEncryptor<N, W>
+            for Cipher<$($t,)* $($c,)* N, W>
+        {
+            type Comparison = bool;
+
+            fn full_encrypt_to_vec(&self, value: &PlainText<N, W>) -> Result<Vec<u8>, Error> {
+                self.full_encrypt(value)?.to_vec()
+            }
+
+            fn right_encrypt_to_vec(&self, value: &PlainText<N, W>) -> Result<Vec<u8>, Error> {
+                self.right_encrypt(value)?.to_vec()
+            }
+
+            fn compare_serialized(&self, a: &[u8], b: &[u8]) -> Result<bool, Error> {
+                self.audited(AuditOperation::Compare, || {
+                    let ct_a = CipherText::<$($t,)* $($c,)* N, W>::from_slice(a)?;
+                    let ct_b = CipherText::<$($t,)* $($c,)* N, W>::from_slice(b)?;
+
+                    ct_a.try_eq(&ct_b)
+                })
+            }
+        }
+    };
+}
+pub(crate) use ere_items;
+
+/// Define the types and methods of a ciphersuite's `ere` (equality-revealing) module
+macro_rules! ere_module {
+    ($suite:literal) => {
+        use super::CipherSuite;
+        use crate::field_pack::FieldPack as FP;
+
+        crate::suite_modules::ere_items!("this [ciphersuite](super)", CipherSuite<W, 2>, [], []);
+
+        /// [`FieldPack`](crate::FieldPack) specialisation for this [ciphersuite](super).
+        ///
+        /// See the documentation for [`FieldPack`](crate::FieldPack) for usage information.
+        ///
+        pub type FieldPack<const N: usize, const W: u16> = FP<CipherSuite<W, 2>, N, W>;
+
+        /// A [`Cipher`] for UUIDs (and any other 128 bit identifiers).
+        ///
+        /// Sixteen blocks of 256 holds exactly one byte of a UUID in each block.  With the `uuid`
+        /// feature enabled, a [`uuid::Uuid`](https://docs.rs/uuid) can be converted straight into a
+        /// [`PlainText`] for this cipher, and a decrypted one converted back again.
+        ///
+        /// # Examples
+        ///
+        /// ```rust
+        #[doc = concat!(" use cretrit::", $suite, "::ere;")]
+        /// # fn main() -> Result<(), cretrit::Error> {
+        /// # let key = [0u8; 32];
+        ///
+        /// let cipher = ere::UuidCipher::new(&key)?;
+        /// let uuid = 0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8u128;
+        /// let id = cipher.full_encrypt(&uuid.try_into()?)?;
+        /// # Ok(())
+        /// # }
+        /// ```
+        ///
+        pub type UuidCipher = Cipher<16, 256>;
+
+        /// The [`CipherText`] produced by a [`UuidCipher`]
+        pub type UuidCipherText = CipherText<16, 256>;
+
+        /// A [`Cipher`] for 8 bit values.
+        ///
+        /// The block count and width of a cipher have to be the same everywhere its ciphertexts are
+        /// compared, so services that need to share ciphertexts are best off agreeing on one of
+        /// these presets rather than each picking their own.  A single block of 256 is the cheapest
+        /// way to encrypt a byte, and all the same keys, plaintexts, and ciphertexts work with an
+        /// explicit `Cipher<1, 256>` too.
+        ///
+        /// # Examples
+        ///
+        /// ```rust
+        #[doc = concat!(" use cretrit::", $suite, "::ere;")]
+        /// # fn main() -> Result<(), cretrit::Error> {
+        /// # let key = [0u8; 32];
+        ///
+        /// let cipher = ere::SmallCipher::new(&key)?;
+        /// let forty_two = cipher.full_encrypt(&42u8.try_into()?)?;
+        /// # Ok(())
+        /// # }
+        /// ```
+        ///
+        pub type SmallCipher = Cipher<1, 256>;
+
+        /// The [`CipherText`] produced by a [`SmallCipher`]
+        pub type SmallCipherText = CipherText<1, 256>;
+
+        /// A [`Cipher`] for 32 bit values.
+        ///
+        /// Four blocks of 256, one for each byte.  See [`SmallCipher`] for why presets are worth
+        /// using.
+        ///
+        pub type MediumCipher = Cipher<4, 256>;
+
+        /// The [`CipherText`] produced by a [`MediumCipher`]
+        pub type MediumCipherText = CipherText<4, 256>;
+
+        /// A [`Cipher`] for 64 bit values.
+        ///
+        /// Eight blocks of 256, one for each byte.  See [`SmallCipher`] for why presets are worth
+        /// using.
+        ///
+        pub type LargeCipher = Cipher<8, 256>;
+
+        /// The [`CipherText`] produced by a [`LargeCipher`]
+        pub type LargeCipherText = CipherText<8, 256>;
+
+        impl<const N: usize, const W: u16> CipherText<N, W> {
+            /// Compare this ciphertext against another one block at a time, most significant block
+            /// first
+            ///
+            /// Each block's comparison is only done when the iterator gets to it, and is `true` if
+            /// that block is equal to the same block of `other`.  The values are equal if every
+            /// block is; stopping after the first few blocks tells you whether the values are equal
+            /// to that many blocks of precision.  As with an ordinary comparison, at least one of
+            /// the ciphertexts needs to have a left part.
+            ///
+            /// # Examples
+            ///
+            /// ```rust
+            #[doc = concat!(" use cretrit::", $suite, "::ere;")]
+            ///
+            /// # fn main() -> Result<(), cretrit::Error> {
+            /// # let key = [0u8; 32];
+            /// let cipher = ere::Cipher::<4, 256>::new(&key)?;
+            /// let a = cipher.full_encrypt(&0x0102_0304u32.try_into()?)?;
+            /// let b = cipher.right_encrypt(&0x0102_ff04u32.try_into()?)?;
+            ///
+            /// let blocks = a.compare_blocks(&b)?.collect::<Result<Vec<_>, _>>()?;
+            /// assert_eq!(vec![true, true, false, true], blocks);
+            /// # Ok(())
+            /// # }
+            /// ```
+            ///
+            /// # Errors
+            ///
+            /// Will return [`Error::Incomparable`] if neither ciphertext has a left part.  Each
+            /// block's result can also be an error, if there's a bug somewhere.
+            ///
+            pub fn compare_blocks<'a>(
+                &'a self,
+                other: &'a CipherText<N, W>,
+            ) -> Result<impl Iterator<Item = Result<bool, Error>> + 'a, Error> {
+                Ok(self
+                    .block_comparisons(other)?
+                    .map(|r| r.and_then(EqualityCMP::invert)))
+            }
+
+            /// Determine whether the first `blocks` blocks of this ciphertext's value match those
+            /// of another ciphertext's value
+            ///
+            /// As with any other comparison, at least one of the ciphertexts needs to have a left
+            /// part.
+            ///
+            /// # Errors
+            ///
+            /// Will return [`Error::RangeError`] if `blocks` is more than `N`, or
+            /// [`Error::Incomparable`] if neither ciphertext has a left part.
+            ///
+            pub fn prefix_matches(
+                &self,
+                other: &CipherText<N, W>,
+                blocks: usize,
+            ) -> Result<bool, Error> {
+                EqualityCMP::prefix_matches(blocks, self.block_comparisons(other)?)
+            }
+        }
+
+        impl<const N: usize, const W: u16> LeftCipherText<N, W> {
+            /// Determine whether the first `blocks` blocks of the value in this left ciphertext
+            /// match those of the value in the right part of a ciphertext
+            ///
+            /// # Errors
+            ///
+            /// Will return [`Error::RangeError`] if `blocks` is more than `N`.
+            ///
+            pub fn prefix_matches(
+                &self,
+                right: &CipherText<N, W>,
+                blocks: usize,
+            ) -> Result<bool, Error> {
+                EqualityCMP::prefix_matches(blocks, self.block_comparisons(right))
+            }
+        }
+    };
+}
+pub(crate) use ere_module;
+
+/// Define the types and methods of a ciphersuite's `pore` (partial-order-revealing) module
+macro_rules! pore_module {
+    ($suite:literal) => {
+        use std::cmp::Ordering;
+
+        use super::CipherSuite;
+        use crate::audit::AuditOperation;
+        use crate::cipher::Cipher as C;
+        use crate::ciphertext::{CipherText as CT, LeftCipherText as LCT, Serializable};
+        use crate::cmp::PartialOrderCMP;
+        use crate::query::PreparedQuery as PQ;
+        use crate::{ComparisonEncryptor, Error, PlainText};
+
+        /// [`Cipher`](crate::Cipher) specialisation for this [ciphersuite](super).
+        ///
+        /// See the documentation for [`Cipher`](crate::Cipher) for usage information.
+        ///
+        pub type Cipher<const N: usize, const W: u16> =
+            C<CipherSuite<W, 4>, PartialOrderCMP, N, W, 4>;
+
+        /// [`CipherText`](crate::CipherText) specialisation for this [ciphersuite](super).
+        ///
+        /// See the documentation for [`CipherText`](crate::CipherText) for usage information.
+        ///
+        pub type CipherText<const N: usize, const W: u16> =
+            CT<CipherSuite<W, 4>, PartialOrderCMP, N, W, 4>;
+
+        /// [`LeftCipherText`](crate::LeftCipherText) specialisation for this [ciphersuite](super).
+        ///
+        /// See the documentation for [`LeftCipherText`](crate::LeftCipherText) for usage
+        /// information.
+        ///
+        pub type LeftCipherText<const N: usize, const W: u16> =
+            LCT<CipherSuite<W, 4>, PartialOrderCMP, N, W, 4>;
+
+        /// [`PreparedQuery`](crate::PreparedQuery) specialisation for this [ciphersuite](super).
+        ///
+        /// See the documentation for [`PreparedQuery`](crate::PreparedQuery) for usage information.
+        ///
+        pub type PreparedQuery<const N: usize, const W: u16> =
+            PQ<CipherSuite<W, 4>, PartialOrderCMP, N, W, 4>;
+
+        impl<const N: usize, const W: u16> LeftCipherText<N, W> {
+            /// Compare the value in this left ciphertext against the value in the right part of a
+            /// ciphertext, returning `None` if the two values are incomparable
+            ///
+            /// # Errors
+            ///
+            /// Will return [`Error::ParamsError`] if `right` is known to have been encrypted with a
+            /// different key.  Can also return an error if something goes wrong during the comparison,
+            /// which should only happen if there's a bug somewhere.
+            ///
+            pub fn compare_with_right(
+                &self,
+                right: &CipherText<N, W>,
+            ) -> Result<Option<Ordering>, Error> {
+                PartialOrderCMP::invert(self.compare(right)?)
+            }
+        }
+
+        impl<const N: usize, const W: u16> PreparedQuery<N, W> {
+            /// Compare the value in this prepared query against the value in the right part of a
+            /// ciphertext, returning `None` if the two values are incomparable
+            ///
+            /// # Errors
+            ///
+            /// Will return [`Error::ParamsError`] if `right` is known to have been encrypted with a
+            /// different key.  Can also return an error if something goes wrong during the comparison,
+            /// which should only happen if there's a bug somewhere.
+            ///
+            pub fn compare_with_right(
+                &self,
+                right: &CipherText<N, W>,
+            ) -> Result<Option<Ordering>, Error> {
+                PartialOrderCMP::invert(self.compare(right)?)
+            }
+        }
+
+        impl<const N: usize, const W: u16> CipherText<N, W> {
+            /// Compare this ciphertext against another, returning `None` if the two values are
+            /// incomparable, and an error (where [`partial_cmp()`](PartialOrd::partial_cmp) returns
+            /// `None` for both) if the ciphertexts themselves can't be compared
+            ///
+            /// # Errors
+            ///
+            /// Will return [`Error::Incomparable`] if neither ciphertext has a left part, and
+            /// [`Error::ParamsError`] if they're known to have been encrypted with different keys.
+            ///
+            pub fn try_partial_cmp(
+                &self,
+                other: &CipherText<N, W>,
+            ) -> Result<Option<Ordering>, Error> {
+                PartialOrderCMP::invert(self.compare_either(other)?)
+            }
+
+            /// Determine whether this ciphertext's value is equal to another's, returning an error
+            /// (rather than `false`, as `==` does) if they can't be compared
+            ///
+            /// # Errors
+            ///
+            /// Will return [`Error::Incomparable`] if neither ciphertext has a left part, and
+            /// [`Error::ParamsError`] if they're known to have been encrypted with different keys.
+            ///
+            pub fn try_eq(&self, other: &CipherText<N, W>) -> Result<bool, Error> {
+                Ok(self.try_partial_cmp(other)? == Some(Ordering::Equal))
+            }
+        }
+
+        #[cfg(feature = "comparison-traits")]
+        impl<const N: usize, const W: u16> PartialOrd for CipherText<N, W> {
+            /// Returns `None` if the values are incomparable, or if neither ciphertext has a left
+            /// part
+            fn partial_cmp(&self, other: &CipherText<N, W>) -> Option<Ordering> {
+                self.try_partial_cmp(other).ok().flatten()
+            }
+        }
+
+        #[cfg(feature = "comparison-traits")]
+        impl<const N: usize, const W: u16> PartialEq for CipherText<N, W> {
+            /// Returns `false` if neither ciphertext has a left part, even when comparing a
+            /// ciphertext with itself, which is why there's no [`Eq`] implementation
+            fn eq(&self, other: &CipherText<N, W>) -> bool {
+                self.partial_cmp(other) == Some(Ordering::Equal)
+            }
+        }
+
+        impl<const N: usize, const W: u16> ComparisonEncryptor<N, W> for Cipher<N, W> {
+            type Comparison = Option<Ordering>;
+
+            fn full_encrypt_to_vec(&self, value: &PlainText<N, W>) -> Result<Vec<u8>, Error> {
+                self.full_encrypt(value)?.to_vec()
+            }
+
+            fn right_encrypt_to_vec(&self, value: &PlainText<N, W>) -> Result<Vec<u8>, Error> {
+                self.right_encrypt(value)?.to_vec()
+            }
+
+            fn compare_serialized(&self, a: &[u8], b: &[u8]) -> Result<Option<Ordering>, Error> {
+                self.audited(AuditOperation::Compare, || {
+                    let ct_a = CipherText::<N, W>::from_slice(a)?;
+                    let ct_b = CipherText::<N, W>::from_slice(b)?;
+
+                    PartialOrderCMP::invert(ct_a.compare_either(&ct_b)?)
+                })
+            }
+        }
+    };
+}
+pub(crate) use pore_module;
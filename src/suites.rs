@@ -0,0 +1,172 @@
+//! What ciphersuites this build of cretrit knows about.
+//!
+
+use crate::ciphersuite::CipherSuite;
+use crate::cmp::{Comparator, EqualityCMP, OrderingCMP, PartialOrderCMP};
+use crate::{aes128v1, aes256v1, chacha20v1};
+
+/// A description of a ciphersuite, for tools that need to find out at runtime what's available
+///
+/// The `name` is the identifier for the ciphersuite everywhere else, such as in the textual form
+/// of [`Params`](crate::Params), and it will never be reused for a different set of primitives.
+/// The primitive identifiers are informational; they describe what each part of the ciphersuite
+/// is built from, but there's no way to mix and match them.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct SuiteDescriptor {
+    /// The name of the ciphersuite, such as `aes128v1`
+    pub name: &'static str,
+
+    /// The random number generator used for nonces and key-seeded shuffling
+    pub rng: &'static str,
+
+    /// The pseudo-random function used to produce the left ciphertext blocks
+    pub prf: &'static str,
+
+    /// The keyed hash function used to mask the right ciphertext values
+    pub hash: &'static str,
+
+    /// The pseudo-random permutation applied to each block's values
+    pub prp: &'static str,
+
+    /// The key derivation function used to derive every subkey from the root key
+    pub kbkdf: &'static str,
+
+    /// The kinds of comparison that this ciphersuite supports
+    pub comparators: &'static [ComparatorDescriptor],
+}
+
+/// A description of a kind of comparison that a ciphersuite supports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ComparatorDescriptor {
+    /// The name of the comparator, as used in [`Params`](crate::Params): `ore`, `ere`, or `pore`
+    pub name: &'static str,
+
+    /// How many distinct comparison results the comparator can produce (`M`)
+    pub m: u8,
+}
+
+/// The comparators supported by every ciphersuite that exists at present
+const ALL_COMPARATORS: [ComparatorDescriptor; 3] = [
+    ComparatorDescriptor {
+        name: OrderingCMP::NAME,
+        m: 3,
+    },
+    ComparatorDescriptor {
+        name: EqualityCMP::NAME,
+        m: 2,
+    },
+    ComparatorDescriptor {
+        name: PartialOrderCMP::NAME,
+        m: 4,
+    },
+];
+
+/// Every ciphersuite in this build
+///
+/// The block width and comparison count parameters don't affect the name, so any old values will
+/// do.
+///
+const SUITES: [SuiteDescriptor; 3] = [
+    SuiteDescriptor {
+        name: <aes128v1::CipherSuite<2, 2> as CipherSuite<2, 2>>::NAME,
+        rng: "chacha20",
+        prf: "aes128",
+        hash: "cmac-aes128",
+        prp: "chacha20-shuffle",
+        kbkdf: "sp800-108-cmac-aes256",
+        comparators: &ALL_COMPARATORS,
+    },
+    SuiteDescriptor {
+        name: <aes256v1::CipherSuite<2, 2> as CipherSuite<2, 2>>::NAME,
+        rng: "chacha20",
+        prf: "aes256",
+        hash: "cmac-aes256",
+        prp: "chacha20-shuffle",
+        kbkdf: "sp800-108-cmac-aes256",
+        comparators: &ALL_COMPARATORS,
+    },
+    SuiteDescriptor {
+        name: <chacha20v1::CipherSuite<2, 2> as CipherSuite<2, 2>>::NAME,
+        rng: "chacha20",
+        prf: "chacha20",
+        hash: "blake2s-mac",
+        prp: "chacha20-shuffle",
+        kbkdf: "sp800-108-blake2s",
+        comparators: &ALL_COMPARATORS,
+    },
+];
+
+/// All of the ciphersuites supported by this build of cretrit
+///
+/// # Examples
+///
+/// ```rust
+/// for suite in cretrit::suites() {
+///     for cmp in suite.comparators {
+///         println!("{}-{} (M={})", suite.name, cmp.name, cmp.m);
+///     }
+/// }
+///
+/// assert!(cretrit::suites().iter().any(|s| s.name == "aes128v1"));
+/// ```
+///
+#[must_use]
+pub fn suites() -> &'static [SuiteDescriptor] {
+    &SUITES
+}
+
+/// Find a ciphersuite by name
+pub(crate) fn find(name: &str) -> Option<&'static SuiteDescriptor> {
+    SUITES.iter().find(|s| s.name == name)
+}
+
+impl SuiteDescriptor {
+    /// Find one of this ciphersuite's comparators by name
+    #[must_use]
+    pub fn comparator(&self, name: &str) -> Option<&'static ComparatorDescriptor> {
+        self.comparators.iter().find(|c| c.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aes128v1_is_supported() {
+        let suite = find("aes128v1").unwrap();
+
+        assert_eq!(3, suite.comparator("ore").unwrap().m);
+        assert_eq!(2, suite.comparator("ere").unwrap().m);
+        assert_eq!(4, suite.comparator("pore").unwrap().m);
+        assert!(suite.comparator("ope").is_none());
+    }
+
+    #[test]
+    fn aes256v1_is_supported() {
+        let suite = find("aes256v1").unwrap();
+
+        assert_eq!("cmac-aes256", suite.hash);
+        assert_eq!(3, suite.comparator("ore").unwrap().m);
+        assert_eq!(2, suite.comparator("ere").unwrap().m);
+        assert_eq!(4, suite.comparator("pore").unwrap().m);
+    }
+
+    #[test]
+    fn chacha20v1_is_supported() {
+        let suite = find("chacha20v1").unwrap();
+
+        assert_eq!("blake2s-mac", suite.hash);
+        assert_eq!(3, suite.comparator("ore").unwrap().m);
+        assert_eq!(2, suite.comparator("ere").unwrap().m);
+        assert_eq!(4, suite.comparator("pore").unwrap().m);
+    }
+
+    #[test]
+    fn unknown_suite() {
+        assert!(find("aes512v1").is_none());
+    }
+}
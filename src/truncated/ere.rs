@@ -0,0 +1,69 @@
+//! Equality-Revealing Encryption with a [truncated](super) ciphersuite.
+//!
+//! Everything here works just like it does for an untruncated ciphersuite, such as
+//! [`aes128v1::ere`](crate::aes128v1::ere); the only difference is that each type takes the
+//! ciphersuite `S` that's being truncated, and the truncated length `T`, as well as the usual block
+//! count and width.  `S` needs to be given the same block width, and an `M` of 2.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use cretrit::aes128v1;
+//! use cretrit::truncated::ere;
+//!
+//! # fn main() -> Result<(), cretrit::Error> {
+//! # let key = [0u8; 32];
+//! type Suite = aes128v1::CipherSuite<256, 2>;
+//!
+//! let cipher = ere::Cipher::<Suite, 12, 4, 256>::new(&key)?;
+//! let forty_two = cipher.full_encrypt(&42u32.try_into()?)?;
+//!
+//! assert!(forty_two.try_eq(&cipher.right_encrypt(&42u32.try_into()?)?)?);
+//! assert!(!forty_two.try_eq(&cipher.right_encrypt(&9001u32.try_into()?)?)?);
+//! # Ok(())
+//! # }
+//! ```
+
+use super::Truncated;
+use crate::ciphersuite::CipherSuite;
+
+crate::suite_modules::ere_items!(
+    "a [truncated](super) ciphersuite",
+    Truncated<S, T>,
+    [S: CipherSuite<W, 2>],
+    [T: usize]
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes256v1;
+    use crate::util::random_key;
+
+    type Aes256 = aes256v1::CipherSuite<16, 2>;
+
+    #[test]
+    fn left_comparisons() {
+        let cipher = Cipher::<Aes256, 10, 8, 16>::new(&random_key()).unwrap();
+        let left = cipher.left_encrypt(&42u32.try_into().unwrap()).unwrap();
+
+        assert_eq!(88, left.to_vec().unwrap().len());
+        assert!(left
+            .compare_with_right(&cipher.right_encrypt(&42u32.try_into().unwrap()).unwrap())
+            .unwrap());
+        assert!(!left
+            .compare_with_right(&cipher.right_encrypt(&43u32.try_into().unwrap()).unwrap())
+            .unwrap());
+    }
+
+    quickcheck! {
+        fn u32_eq(a: u32, b: u32) -> bool {
+            let cipher = Cipher::<Aes256, 8, 8, 16>::new(&random_key()).unwrap();
+
+            let ca = cipher.full_encrypt_to_vec(&a.try_into().unwrap()).unwrap();
+            let cb = cipher.right_encrypt_to_vec(&b.try_into().unwrap()).unwrap();
+
+            (a == b) == cipher.compare_serialized(&ca, &cb).unwrap()
+        }
+    }
+}
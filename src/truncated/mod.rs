@@ -0,0 +1,195 @@
+//! Ciphersuites with shorter left ciphertexts, in exchange for a smaller security margin.
+//!
+//! Each block of a left ciphertext is F(k, p(x)), the output of the ciphersuite's pseudo-random
+//! function, followed by p(x), the permuted block value.  For the usual block widths of 256 or
+//! less, p(x) is a single byte, and F(k, p(x)) is 16 bytes (for [`aes128v1`](crate::aes128v1))
+//! or 32 bytes (for [`aes256v1`](crate::aes256v1) and [`chacha20v1`](crate::chacha20v1)), so the
+//! PRF output is nearly all of the left ciphertext.  [`Truncated<S, T>`](Truncated) is the
+//! ciphersuite `S`, except that the PRF output is cut down to its first `T` bytes, making each
+//! left block `T + 1` bytes long.  Right ciphertexts are the same size as they were.
+//!
+//! # Security
+//!
+//! Truncation doesn't make comparisons any less accurate.  The F(k, p(x)) in each left block is
+//! the key for the hash function that masks the values in the right ciphertext, and both the left
+//! and right ciphertexts of a truncated ciphersuite use the truncated value as that key (padded
+//! out with zeroes to the size the hash function needs).  So every comparison gives exactly the
+//! answer it would have without truncation; nor can two different values share a left ciphertext,
+//! since p(x) is a permutation.
+//!
+//! What truncation costs is the strength of those hash keys.  There is one for each of the `W`
+//! possible values of p(x) (it doesn't depend on which block it's in), and knowing the key for a
+//! value `j` unmasks the comparison of `j` against the corresponding block of *every* right
+//! ciphertext produced with the cipher key, just as though the attacker had the left ciphertext of
+//! a value containing `j`.  With `T` bytes of key, an attacker holding some right ciphertexts can
+//! simply try every possible key: the right guess produces comparison results that hang together
+//! (for ORE, each block looks like a run of "less than", then "equal", then "greater than", across
+//! the values of p(x)), while wrong guesses produce noise.  Finding the key for one value takes
+//! about `2^(8T)` hash operations, and finding them all takes `W` times that; without truncation,
+//! the same attack is up against the full 128 or 256 bit hash key.
+//!
+//! Put another way, a truncated ciphersuite keeps the right ciphertexts' security only against
+//! attackers who can't do `2^(8T)` work.  Some reasonable choices are:
+//!
+//! * `T = 16`: with [`aes256v1`](crate::aes256v1) or [`chacha20v1`](crate::chacha20v1), 128 bit
+//!   keys, as strong as [`aes128v1`](crate::aes128v1), while nearly halving the size of their
+//!   left ciphertexts.
+//! * `T = 12`: 96 bit keys, which is well beyond any known brute force effort, for left
+//!   ciphertexts about a quarter smaller than [`aes128v1`](crate::aes128v1)'s, or 60% smaller
+//!   than those of the 256 bit ciphersuites.
+//! * `T = 8`: 64 bit keys.  Searches of this size have been done in public, so this is only for
+//!   data whose value to an attacker is well below the cost of a large amount of computing time.
+//!   This is the smallest `T` that will compile.
+//!
+//! The left ciphertexts themselves are deterministic, and reveal exactly what they did before
+//! truncation.
+//!
+//! # Compatibility
+//!
+//! A truncated ciphersuite is a different ciphersuite, and its ciphertexts can't be compared with
+//! those of the ciphersuite it is based on, or with ones truncated to a different length, even if
+//! they were produced with the same key.  Its PRF's subkeys are derived separately for each `T`,
+//! so using the same key for both is safe, if somewhat pointless; in particular, the left
+//! ciphertexts of a truncated cipher don't give away any part of those of any other cipher.
+//!
+//! The name of a truncated ciphersuite is the name of the one it's based on, followed by `t` and
+//! the value of `T`, such as `aes128v1t12`, and that is what appears in its JSON envelopes and
+//! wire headers.  They aren't listed in [`suites()`](crate::suites), though, so their names can't
+//! be parsed into a [`Params`](crate::Params), and their ciphers don't have a
+//! [`params()`](crate::Cipher::params) or [`from_params()`](crate::Cipher::from_params) (and so
+//! can't be used with a `FieldConfig` either).
+//!
+//! # Examples
+//!
+//! ```rust
+//! use cretrit::aes128v1;
+//! use cretrit::truncated::ore;
+//! use std::cmp::Ordering;
+//!
+//! # fn main() -> Result<(), cretrit::Error> {
+//! # let key = [0u8; 32];
+//! type Suite = aes128v1::CipherSuite<256, 3>;
+//!
+//! let cipher = ore::Cipher::<Suite, 12, 4, 256>::new(&key)?;
+//! let forty_two = cipher.full_encrypt(&42u32.try_into()?)?;
+//! let over_nine_thousand = cipher.right_encrypt(&9001u32.try_into()?)?;
+//!
+//! assert_eq!(Ordering::Less, forty_two.try_cmp(&over_nine_thousand)?);
+//!
+//! // Four blocks of 12 + 1 bytes, rather than 16 + 1
+//! assert_eq!(52, ore::LeftCipherText::<Suite, 12, 4, 256>::serialized_len());
+//! assert_eq!(68, aes128v1::ore::LeftCipherText::<4, 256>::serialized_len());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Lengths outside the allowed range are caught when the cipher is compiled:
+//!
+//! ```rust,compile_fail
+//! use cretrit::aes128v1;
+//! use cretrit::truncated::ore;
+//!
+//! # fn main() -> Result<(), cretrit::Error> {
+//! # let key = [0u8; 32];
+//! let cipher = ore::Cipher::<aes128v1::CipherSuite<256, 3>, 4, 4, 256>::new(&key)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+
+pub mod ere;
+pub mod ore;
+
+use std::marker::PhantomData;
+
+use crate::ciphersuite::CipherSuite;
+use crate::hash::ZeroPaddedHF;
+use crate::prf::TruncatedPRF;
+
+/// The ciphersuite `S`, with its PRF output truncated to `T` bytes
+///
+/// See [the module documentation](self) for the details, and what `T` to choose.
+///
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Truncated<S, const T: usize> {
+    /// Compiler pacification
+    _mark: PhantomData<S>,
+}
+
+/// How much space there is for the name of a truncated ciphersuite
+const NAME_CAPACITY: usize = 32;
+
+/// Put together the name of a truncated ciphersuite, as bytes, along with how many of them there
+/// are
+///
+/// This has to be done the hard way, because it's needed in a `const`.  Names that don't fit cause
+/// a compile-time panic.
+///
+#[allow(
+    clippy::indexing_slicing,
+    clippy::arithmetic_side_effects,
+    clippy::integer_division
+)] // Anything out of range fails to compile, and the division is meant to truncate
+const fn truncated_name(base_name: &str, t: usize) -> ([u8; NAME_CAPACITY], usize) {
+    let base = base_name.as_bytes();
+    let mut name = [0u8; NAME_CAPACITY];
+    let mut len = 0;
+
+    while len < base.len() {
+        name[len] = base[len];
+        len += 1;
+    }
+
+    name[len] = b't';
+    len += 1;
+
+    let mut scale = 1;
+    while scale * 10 <= t {
+        scale *= 10;
+    }
+    while scale > 0 {
+        #[allow(clippy::cast_possible_truncation)] // It's a single digit
+        let digit = (t / scale % 10) as u8;
+        name[len] = b'0' + digit;
+        len += 1;
+        scale /= 10;
+    }
+
+    (name, len)
+}
+
+/// Somewhere to keep the name of a truncated ciphersuite while it's being turned into a `str`
+///
+/// It has to be a `const` of its own, so that `NAME` can refer to it.
+///
+struct TruncatedName<S, const W: u16, const M: u8, const T: usize> {
+    /// Compiler pacification
+    _mark: PhantomData<S>,
+}
+
+impl<S: CipherSuite<W, M>, const W: u16, const M: u8, const T: usize> TruncatedName<S, W, M, T> {
+    /// The name, and how much of the buffer it occupies
+    const BYTES: ([u8; NAME_CAPACITY], usize) = truncated_name(S::NAME, T);
+}
+
+impl<S: CipherSuite<W, M>, const W: u16, const M: u8, const T: usize> CipherSuite<W, M>
+    for Truncated<S, T>
+{
+    const NAME: &'static str = {
+        let (bytes, _) = TruncatedName::<S, W, M, T>::BYTES
+            .0
+            .split_at(TruncatedName::<S, W, M, T>::BYTES.1);
+
+        match core::str::from_utf8(bytes) {
+            Ok(name) => name,
+            Err(_) => "(invalid truncated ciphersuite name)",
+        }
+    };
+
+    type RNG = S::RNG;
+    type PRF = TruncatedPRF<S::PRF, T>;
+    type HF = ZeroPaddedHF<S::HF, M>;
+    type PRP = S::PRP;
+    type KBKDF = S::KBKDF;
+}
@@ -0,0 +1,183 @@
+//! Order-Revealing Encryption with a [truncated](super) ciphersuite.
+//!
+//! Everything here works just like it does for an untruncated ciphersuite, such as
+//! [`aes128v1::ore`](crate::aes128v1::ore); the only difference is that each type takes the
+//! ciphersuite `S` that's being truncated, and the truncated length `T`, as well as the usual block
+//! count and width.  `S` needs to be given the same block width, and an `M` of 3.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use cretrit::aes256v1;
+//! use cretrit::truncated::ore;
+//! use cretrit::SerializableCipherText;
+//! use std::cmp::Ordering;
+//!
+//! # fn main() -> Result<(), cretrit::Error> {
+//! # let key = [0u8; 32];
+//! type Suite = aes256v1::CipherSuite<16, 3>;
+//!
+//! let cipher = ore::Cipher::<Suite, 16, 8, 16>::new(&key)?;
+//! let forty_two = cipher.full_encrypt(&42u32.try_into()?)?.to_vec()?;
+//!
+//! let deserialized = ore::CipherText::<Suite, 16, 8, 16>::from_slice(&forty_two)?;
+//! assert_eq!(
+//!     Ordering::Less,
+//!     deserialized.try_cmp(&cipher.right_encrypt(&9001u32.try_into()?)?)?
+//! );
+//! # Ok(())
+//! # }
+//! ```
+
+use super::Truncated;
+use crate::ciphersuite::CipherSuite;
+
+crate::suite_modules::ore_items!(
+    "a [truncated](super) ciphersuite",
+    Truncated<S, T>,
+    [S: CipherSuite<W, 3>],
+    [T: usize]
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::random_key;
+    use crate::{aes128v1, aes256v1, chacha20v1};
+
+    type Aes128 = aes128v1::CipherSuite<256, 3>;
+
+    #[test]
+    fn names() {
+        assert_eq!(
+            "aes128v1t12",
+            <Truncated<Aes128, 12> as CipherSuite<256, 3>>::NAME
+        );
+        assert_eq!(
+            "aes256v1t8",
+            <Truncated<aes256v1::CipherSuite<16, 3>, 8> as CipherSuite<16, 3>>::NAME
+        );
+        assert_eq!(
+            "chacha20v1t32",
+            <Truncated<chacha20v1::CipherSuite<256, 3>, 32> as CipherSuite<256, 3>>::NAME
+        );
+    }
+
+    #[test]
+    fn left_blocks_are_truncated() {
+        let cipher = Cipher::<Aes128, 9, 4, 256>::new(&random_key()).unwrap();
+        let left = cipher.left_encrypt(&42u32.try_into().unwrap()).unwrap();
+
+        assert_eq!(40, left.to_vec().unwrap().len());
+        assert_eq!(40, LeftCipherText::<Aes128, 9, 4, 256>::serialized_len());
+    }
+
+    #[test]
+    fn untruncated_ciphertexts_are_incompatible() {
+        let k = random_key();
+        let cipher = Cipher::<Aes128, 16, 4, 256>::new(&k).unwrap();
+        let plain = aes128v1::ore::Cipher::<4, 256>::new(&k).unwrap();
+
+        // Same length, even, but nothing in common
+        let ct = cipher.full_encrypt(&42u32.try_into().unwrap()).unwrap();
+        let plain_ct = plain.full_encrypt(&42u32.try_into().unwrap()).unwrap();
+        assert_ne!(ct.to_vec().unwrap(), plain_ct.to_vec().unwrap());
+
+        let left = plain_ct.left().unwrap().to_vec().unwrap();
+        assert!(
+            !ct.left()
+                .unwrap()
+                .to_vec()
+                .unwrap()
+                .windows(16)
+                .any(|w| left.windows(16).any(|l| l == w)),
+            "truncated left ciphertext shares a block with an untruncated one"
+        );
+    }
+
+    #[test]
+    fn different_truncations_are_unrelated() {
+        let k = random_key();
+        let short = Cipher::<Aes128, 8, 1, 256>::new(&k).unwrap();
+        let long = Cipher::<Aes128, 12, 1, 256>::new(&k).unwrap();
+
+        let s = short.left_encrypt(&PlainText::new([42])).unwrap();
+        let l = long.left_encrypt(&PlainText::new([42])).unwrap();
+
+        assert_ne!(s.to_vec().unwrap().get(..8), l.to_vec().unwrap().get(..8));
+    }
+
+    #[test]
+    fn left_comparisons() {
+        let cipher = Cipher::<Aes128, 8, 4, 256>::new(&random_key()).unwrap();
+        let left = cipher.left_encrypt(&1000u32.try_into().unwrap()).unwrap();
+        let query = PreparedQuery::<Aes128, 8, 4, 256>::new(&left).unwrap();
+
+        for v in [0u32, 999, 1000, 1001, u32::MAX] {
+            let right = cipher.right_encrypt(&v.try_into().unwrap()).unwrap();
+
+            assert_eq!(1000.cmp(&v), left.compare_with_right(&right).unwrap());
+            assert_eq!(1000.cmp(&v), query.compare_with_right(&right).unwrap());
+        }
+    }
+
+    #[test]
+    fn serialized_comparisons() {
+        let cipher = Cipher::<Aes128, 8, 4, 256>::new(&random_key()).unwrap();
+        let a = cipher
+            .full_encrypt_to_vec(&42u32.try_into().unwrap())
+            .unwrap();
+        let b = cipher
+            .right_encrypt_to_vec(&9001u32.try_into().unwrap())
+            .unwrap();
+
+        assert_eq!(Ordering::Less, cipher.compare_serialized(&a, &b).unwrap());
+        assert_eq!(
+            Ordering::Greater,
+            cipher.compare_serialized(&b, &a).unwrap()
+        );
+    }
+
+    #[test]
+    fn decryption() {
+        let cipher = Cipher::<Aes128, 12, 8, 256>::new(&random_key()).unwrap();
+        let value: PlainText<8, 256> = 9001u64.try_into().unwrap();
+        let ct = cipher.full_encrypt(&value).unwrap();
+
+        assert_eq!(value, cipher.decrypt(&ct).unwrap());
+    }
+
+    #[test]
+    fn equality_filter() {
+        let cipher = Cipher::<Aes128, 8, 4, 256>::new(&random_key()).unwrap();
+        let (ct, tag) = cipher
+            .right_encrypt_with_filter_tag(&42u32.try_into().unwrap())
+            .unwrap();
+        let filter = EqualityFilter::<Aes128, 8, 4, 256>::new(
+            &cipher.left_encrypt(&42u32.try_into().unwrap()).unwrap(),
+        )
+        .unwrap();
+
+        assert!(filter.may_equal(&ct, tag).unwrap());
+    }
+
+    quickcheck! {
+        fn u32_cmp_t8(a: u32, b: u32) -> bool {
+            let cipher = Cipher::<Aes128, 8, 4, 256>::new(&random_key()).unwrap();
+
+            let ca = cipher.full_encrypt(&a.try_into().unwrap()).unwrap();
+            let cb = cipher.right_encrypt(&b.try_into().unwrap()).unwrap();
+
+            a.cmp(&b) == ca.try_cmp(&cb).unwrap()
+        }
+
+        fn u32_cmp_chacha20v1_t12(a: u32, b: u32) -> bool {
+            let cipher = Cipher::<chacha20v1::CipherSuite<16, 3>, 12, 8, 16>::new(&random_key()).unwrap();
+
+            let ca = cipher.full_encrypt(&a.try_into().unwrap()).unwrap();
+            let cb = cipher.right_encrypt(&b.try_into().unwrap()).unwrap();
+
+            a.cmp(&b) == ca.try_cmp(&cb).unwrap()
+        }
+    }
+}
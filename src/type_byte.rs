@@ -0,0 +1,137 @@
+//! The first byte of every serialized ciphertext, and what it means.
+//!
+
+use crate::Error;
+
+/// What sort of serialized ciphertext is this?
+///
+/// Every serialized [`CipherText`](crate::CipherText) starts with a single "type byte", which
+/// says how the rest of the ciphertext is laid out.  Only some of the 256 possible values mean
+/// anything at the moment, but ranges have been set aside for the kinds of ciphertext that are
+/// likely to turn up in future, so that adding them won't mean reassigning anything:
+///
+/// | Type byte     | Meaning                                                          |
+/// |---------------|------------------------------------------------------------------|
+/// | `0x00`        | Right ciphertext only ([`RightOnly`](Self::RightOnly))           |
+/// | `0x01`        | Left and right ciphertexts ([`Full`](Self::Full))                |
+/// | `0x02`        | *Reserved*: left ciphertext only                                 |
+/// | `0x03`-`0x0f` | *Reserved*: other basic layouts                                  |
+/// | `0x10`-`0x1f` | *Reserved*: padded ciphertexts (to hide the parameters in use)   |
+/// | `0x20`-`0x2f` | *Reserved*: ciphertexts with an authentication tag               |
+/// | `0x30`-`0x3f` | *Reserved*: compressed ciphertexts                               |
+/// | `0x40`        | A [wire header](crate::CipherText::to_vec_with_header)           |
+/// | `0x41`-`0xef` | Unassigned                                                       |
+/// | `0xf0`-`0xff` | Private use; cretrit will never assign these                     |
+///
+/// Anything other than a type byte that cretrit knows how to parse is rejected with
+/// [`Error::UnknownCipherTextType`].  That includes the wire header marker, because a headered
+/// ciphertext has to be read with
+/// [`CipherText::from_slice_with_header()`](crate::CipherText::from_slice_with_header).
+///
+/// # Examples
+///
+/// ```rust
+/// use cretrit::aes128v1::ore;
+/// use cretrit::{CipherTextType, SerializableCipherText};
+///
+/// # fn main() -> Result<(), cretrit::Error> {
+/// # let key = [0u8; 32];
+/// let cipher = ore::Cipher::<4, 256>::new(&key)?;
+/// let stored = cipher.right_encrypt(&42u32.try_into()?)?.to_vec()?;
+///
+/// assert_eq!(CipherTextType::RightOnly, CipherTextType::of_serialized(&stored)?);
+/// # Ok(())
+/// # }
+/// ```
+///
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CipherTextType {
+    /// Just the right ciphertext; the kind of ciphertext that gets stored
+    RightOnly,
+
+    /// Both the left and right ciphertexts; the kind of ciphertext that gets used for querying
+    Full,
+}
+
+impl CipherTextType {
+    /// The type byte for this type of ciphertext
+    #[must_use]
+    pub fn byte(self) -> u8 {
+        match self {
+            CipherTextType::RightOnly => 0x00,
+            CipherTextType::Full => 0x01,
+        }
+    }
+
+    /// Figure out the type of ciphertext from its type byte
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::UnknownCipherTextType`] if the type byte isn't one that this version
+    /// of cretrit knows how to handle, including all of the reserved values.
+    ///
+    pub fn from_byte(byte: u8) -> Result<Self, Error> {
+        match byte {
+            0x00 => Ok(CipherTextType::RightOnly),
+            0x01 => Ok(CipherTextType::Full),
+            b => Err(Error::UnknownCipherTextType(b)),
+        }
+    }
+
+    /// Figure out the type of a serialized ciphertext, without parsing the rest of it
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::ParseError`] if there isn't a type byte at all, and
+    /// [`Error::UnknownCipherTextType`] if the type byte isn't one that this version of cretrit
+    /// knows how to handle.
+    ///
+    pub fn of_serialized(bytes: &[u8]) -> Result<Self, Error> {
+        Self::from_byte(*bytes.first().ok_or_else(|| {
+            Error::ParseError("end-of-data while looking for ciphertext type marker".to_string())
+        })?)
+    }
+
+    /// Whether ciphertexts of this type have a left part
+    #[must_use]
+    pub fn has_left(self) -> bool {
+        match self {
+            CipherTextType::RightOnly => false,
+            CipherTextType::Full => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_roundtrip() {
+        for t in [CipherTextType::RightOnly, CipherTextType::Full] {
+            assert_eq!(t, CipherTextType::from_byte(t.byte()).unwrap());
+        }
+    }
+
+    #[test]
+    fn unknown_bytes_are_rejected() {
+        for b in 0x02..=0xff {
+            assert!(
+                matches!(
+                    CipherTextType::from_byte(b),
+                    Err(Error::UnknownCipherTextType(v)) if v == b
+                ),
+                "{b:#04x}"
+            );
+        }
+    }
+
+    #[test]
+    fn empty_input() {
+        assert!(matches!(
+            CipherTextType::of_serialized(&[]),
+            Err(Error::ParseError(_))
+        ));
+    }
+}
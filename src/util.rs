@@ -1,12 +1,59 @@
 //! Snippets that are used in various places that we don't have a more sensible home for
 //!
 
+use std::fmt::Arguments;
+
 use crate::Error;
 
 /// Simple wrapper to grab overflowing arithmetic and return an error
-pub(crate) fn check_overflow<T>(v: (T, bool), e: &str) -> Result<T, Error> {
+///
+/// The error message is taken as `format_args!()`, rather than a `String`, so that it only gets
+/// formatted if there actually is an overflow; these checks are often in loops, and the success
+/// path shouldn't pay for building an error message that gets thrown away.
+///
+pub(crate) fn check_overflow<T>(v: (T, bool), e: Arguments<'_>) -> Result<T, Error> {
     match v {
         (u, false) => Ok(u),
         (_, true) => Err(Error::OverflowError(e.to_string())),
     }
 }
+
+/// The 64-bit FNV-1a hash of some bytes
+///
+/// This is *not* a cryptographic hash; it's here for cheap, stable fingerprints of data that's
+/// already safe to reveal, where `std`'s hashers (whose output can change between releases) won't
+/// do.
+///
+pub(crate) fn fnv1a_64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |h, b| {
+        (h ^ u64::from(*b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// A fresh random key, for tests that don't care what key they use
+#[cfg(test)]
+pub(crate) fn random_key() -> [u8; 32] {
+    use rand::Rng;
+
+    let mut k: [u8; 32] = Default::default();
+
+    // Yes, using a potentially-weak RNG would normally be terribad, but
+    // for testing purposes, it's not going to break anything
+    let mut rng = rand::thread_rng();
+
+    rng.try_fill(&mut k).unwrap();
+
+    k
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fnv1a_64_matches_reference_values() {
+        assert_eq!(0xcbf2_9ce4_8422_2325, fnv1a_64(b""));
+        assert_eq!(0xaf63_dc4c_8601_ec8c, fnv1a_64(b"a"));
+        assert_eq!(0x8594_4171_f739_67e8, fnv1a_64(b"foobar"));
+    }
+}
@@ -0,0 +1,335 @@
+//! A self-describing binary header in front of a serialized ciphertext.
+//!
+//! The ordinary serialized form of a ciphertext says nothing about what sort of ciphertext it is,
+//! beyond whether it has a left part, so reading one with the wrong cipher type produces, at
+//! best, a confusing parse error, and at worst a ciphertext that parses fine and compares as
+//! nonsense.  The header records everything needed to tell the difference up front:
+//!
+//! | Bytes | Contents                                                               |
+//! |-------|------------------------------------------------------------------------|
+//! | 1     | [`HEADER_MARKER`], which is never a valid ciphertext type byte         |
+//! | 1     | The header format version; currently always `1`                        |
+//! | 1     | The length of the ciphersuite name                                     |
+//! | *n*   | The ciphersuite name, such as `aes128v1`                               |
+//! | 4     | The number of blocks (`N`), big-endian                                 |
+//! | 2     | The block width (`W`), big-endian                                      |
+//! | 1     | The number of possible comparison results (`M`), identifying the comparator |
+//! | 3     | The [key check value](crate::Cipher::key_check_value) of the encrypting key |
+//!
+//! The ordinary serialized ciphertext follows, unchanged.
+//!
+
+use crate::ciphersuite::CipherSuite;
+use crate::ciphertext::{CipherText, Serializable};
+use crate::cmp::Comparator;
+use crate::Error;
+
+/// The first byte of every headered ciphertext
+///
+/// This is set aside in the [type byte table](crate::CipherTextType), so a headered ciphertext
+/// given to [`from_slice()`](Serializable::from_slice) is rejected as an unknown ciphertext type,
+/// rather than being misread.
+///
+pub(crate) const HEADER_MARKER: u8 = 0x40;
+
+/// The only version of the header format that currently exists
+const HEADER_VERSION: u8 = 1;
+
+/// What a header says about the ciphertext that follows it
+#[derive(Debug, PartialEq, Eq)]
+struct Header<'a> {
+    /// The name of the ciphersuite
+    suite: &'a [u8],
+    /// Number of blocks
+    n: u32,
+    /// Block width
+    w: u16,
+    /// Number of possible comparison results, which identifies the comparator
+    m: u8,
+    /// Key check value of the cipher that did the encrypting
+    kcv: [u8; 3],
+}
+
+impl<'a> Header<'a> {
+    /// The header for a ciphertext of the given type, encrypted with a key that has the given KCV
+    fn of<S: CipherSuite<W, M>, const N: usize, const W: u16, const M: u8>(
+        kcv: [u8; 3],
+    ) -> Result<Header<'static>, Error> {
+        Ok(Header {
+            suite: S::NAME.as_bytes(),
+            n: u32::try_from(N).map_err(|e| {
+                Error::RangeError(format!("{N} blocks is too many for a wire header ({e})"))
+            })?,
+            w: W,
+            m: M,
+            kcv,
+        })
+    }
+
+    /// Pull a header off the front of `bytes`, and return it along with whatever follows it
+    fn parse(bytes: &'a [u8]) -> Result<(Self, &'a [u8]), Error> {
+        let eod = |what: &str| Error::ParseError(format!("end-of-data while looking for {what}"));
+
+        let (&marker, after_marker) = bytes.split_first().ok_or_else(|| eod("header marker"))?;
+        if marker != HEADER_MARKER {
+            return Err(Error::ParseError(format!(
+                "expected a wire header marker ({HEADER_MARKER:#04x}), found {marker:#04x}"
+            )));
+        }
+
+        let (&version, after_version) = after_marker
+            .split_first()
+            .ok_or_else(|| eod("header version"))?;
+        if version != HEADER_VERSION {
+            return Err(Error::ParseError(format!(
+                "unsupported wire header version {version} (expected {HEADER_VERSION})"
+            )));
+        }
+
+        let (&suite_len, after_len) = after_version
+            .split_first()
+            .ok_or_else(|| eod("ciphersuite name length"))?;
+        let suite_end = usize::from(suite_len);
+        let suite = after_len
+            .get(..suite_end)
+            .ok_or_else(|| eod("ciphersuite name"))?;
+        let rest = after_len.get(suite_end..).unwrap_or_default();
+
+        let n = rest.get(..4).ok_or_else(|| eod("block count"))?;
+        let w = rest.get(4..6).ok_or_else(|| eod("block width"))?;
+        let m = rest.get(6).ok_or_else(|| eod("comparison result count"))?;
+        let kcv = rest.get(7..10).ok_or_else(|| eod("key check value"))?;
+
+        Ok((
+            Header {
+                suite,
+                n: u32::from_be_bytes(n.try_into().map_err(|e| {
+                    Error::ParseError(format!("failed to convert {n:?} into block count ({e})"))
+                })?),
+                w: u16::from_be_bytes(w.try_into().map_err(|e| {
+                    Error::ParseError(format!("failed to convert {w:?} into block width ({e})"))
+                })?),
+                m: *m,
+                kcv: kcv.try_into().map_err(|e| {
+                    Error::ParseError(format!(
+                        "failed to convert {kcv:?} into key check value ({e})"
+                    ))
+                })?,
+            },
+            rest.get(10..).unwrap_or_default(),
+        ))
+    }
+
+    /// Append the serialized header to `out`
+    fn write(&self, out: &mut Vec<u8>) -> Result<(), Error> {
+        out.push(HEADER_MARKER);
+        out.push(HEADER_VERSION);
+        out.push(u8::try_from(self.suite.len()).map_err(|e| {
+            Error::InternalError(format!(
+                "ciphersuite name is {} bytes long, which doesn't fit in a wire header ({e})",
+                self.suite.len()
+            ))
+        })?);
+        out.extend_from_slice(self.suite);
+        out.extend_from_slice(&self.n.to_be_bytes());
+        out.extend_from_slice(&self.w.to_be_bytes());
+        out.push(self.m);
+        out.extend_from_slice(&self.kcv);
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Header<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/{}x{} ciphertext with m={}",
+            String::from_utf8_lossy(self.suite),
+            self.n,
+            self.w,
+            self.m
+        )
+    }
+}
+
+/// Serialize a ciphertext with a header in front of it, saying it was encrypted with a key with
+/// the given KCV
+pub(crate) fn wrap<
+    S: CipherSuite<W, M>,
+    CMP: Comparator<M>,
+    const N: usize,
+    const W: u16,
+    const M: u8,
+>(
+    ct: &CipherText<S, CMP, N, W, M>,
+    kcv: [u8; 3],
+) -> Result<Vec<u8>, Error> {
+    let body = ct.to_vec()?;
+    let header = Header::of::<S, N, W, M>(kcv)?;
+
+    let mut v = Vec::with_capacity(
+        body.len()
+            .saturating_add(header.suite.len())
+            .saturating_add(13),
+    );
+    header.write(&mut v)?;
+    v.extend_from_slice(&body);
+
+    Ok(v)
+}
+
+/// Deserialize a headered ciphertext, as long as the header says it's the right sort of
+/// ciphertext, encrypted with a key with the given KCV
+pub(crate) fn unwrap<
+    S: CipherSuite<W, M>,
+    CMP: Comparator<M>,
+    const N: usize,
+    const W: u16,
+    const M: u8,
+>(
+    bytes: &[u8],
+    kcv: [u8; 3],
+) -> Result<CipherText<S, CMP, N, W, M>, Error> {
+    let (header, body) = Header::parse(bytes)?;
+    let expected = Header::of::<S, N, W, M>(kcv)?;
+
+    // A different ciphersuite means a different KCV too, so the rest of the header gets checked
+    // first, to give the more useful error
+    if (Header { kcv, ..header }) != expected {
+        return Err(Error::ParamsError(format!(
+            "wire header describes a {header}, but a {expected} was expected"
+        )));
+    }
+    if header.kcv != kcv {
+        return Err(Error::ParamsError(format!(
+            "wire header has key check value {:02x?}, but {kcv:02x?} was expected",
+            header.kcv
+        )));
+    }
+
+    Ok(CipherText::<S, CMP, N, W, M>::from_slice(body)?.with_key_check_value(kcv))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes128v1::{ere, ore};
+    use crate::{aes256v1, ErrorKind};
+
+    fn cipher() -> ore::Cipher<4, 256> {
+        ore::Cipher::<4, 256>::new(&[0u8; 32]).unwrap()
+    }
+
+    fn kcv() -> [u8; 3] {
+        cipher().key_check_value()
+    }
+
+    #[test]
+    fn header_has_the_agreed_layout() {
+        let ct = cipher().right_encrypt(&42u32.try_into().unwrap()).unwrap();
+        let v = ct.to_vec_with_header(kcv()).unwrap();
+
+        assert_eq!(
+            [
+                &[0x40, 1, 8][..],
+                b"aes128v1",
+                &[0, 0, 0, 4, 1, 0, 3],
+                &cipher().key_check_value()
+            ]
+            .concat(),
+            v[..21]
+        );
+        assert_eq!(ct.to_vec().unwrap(), v[21..]);
+    }
+
+    #[test]
+    fn roundtrip() {
+        let cipher = cipher();
+        let ct = cipher.full_encrypt(&42u32.try_into().unwrap()).unwrap();
+
+        let v = ct.to_vec_with_header(kcv()).unwrap();
+        let back = ore::CipherText::<4, 256>::from_slice_with_header(&v, kcv()).unwrap();
+
+        assert_eq!(ct.to_vec().unwrap(), back.to_vec().unwrap());
+    }
+
+    #[test]
+    fn mismatched_parameters_are_rejected() {
+        let v = cipher()
+            .right_encrypt(&42u32.try_into().unwrap())
+            .unwrap()
+            .to_vec_with_header(kcv())
+            .unwrap();
+
+        for e in [
+            ore::CipherText::<8, 256>::from_slice_with_header(&v, kcv()).unwrap_err(),
+            ere::CipherText::<4, 256>::from_slice_with_header(&v, kcv()).unwrap_err(),
+            aes256v1::ore::CipherText::<4, 256>::from_slice_with_header(&v, kcv()).unwrap_err(),
+        ] {
+            assert_eq!(ErrorKind::Params, e.kind(), "{e}");
+        }
+    }
+
+    #[test]
+    fn other_keys_are_rejected() {
+        let v = cipher()
+            .right_encrypt(&42u32.try_into().unwrap())
+            .unwrap()
+            .to_vec_with_header(kcv())
+            .unwrap();
+        let other_kcv = ore::Cipher::<4, 256>::new(&[1u8; 32])
+            .unwrap()
+            .key_check_value();
+
+        let e = ore::CipherText::<4, 256>::from_slice_with_header(&v, other_kcv).unwrap_err();
+        assert_eq!(ErrorKind::Params, e.kind(), "{e}");
+        assert!(e.to_string().contains("key check value"), "{e}");
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        let mut v = cipher()
+            .right_encrypt(&42u32.try_into().unwrap())
+            .unwrap()
+            .to_vec_with_header(kcv())
+            .unwrap();
+        v[1] = 2;
+
+        let e = ore::CipherText::<4, 256>::from_slice_with_header(&v, kcv()).unwrap_err();
+        assert!(e.to_string().contains("version 2"), "{e}");
+    }
+
+    #[test]
+    fn headerless_ciphertexts_are_rejected() {
+        let ct = cipher().right_encrypt(&42u32.try_into().unwrap()).unwrap();
+
+        assert!(matches!(
+            ore::CipherText::<4, 256>::from_slice_with_header(&ct.to_vec().unwrap(), kcv()),
+            Err(Error::ParseError(_))
+        ));
+        assert!(matches!(
+            ore::CipherText::<4, 256>::from_slice(&ct.to_vec_with_header(kcv()).unwrap()),
+            Err(Error::UnknownCipherTextType(HEADER_MARKER))
+        ));
+    }
+
+    #[test]
+    fn truncated_headers_are_rejected() {
+        let v = cipher()
+            .right_encrypt(&42u32.try_into().unwrap())
+            .unwrap()
+            .to_vec_with_header(kcv())
+            .unwrap();
+
+        for len in 0..21 {
+            assert!(
+                matches!(
+                    ore::CipherText::<4, 256>::from_slice_with_header(&v[..len], kcv()),
+                    Err(Error::ParseError(_))
+                ),
+                "{len}"
+            );
+        }
+    }
+}